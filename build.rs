@@ -0,0 +1,22 @@
+//! ビルド時のメタデータを埋め込む
+//!
+//! `safe-rm version` で表示するため、コンパイルに使った rustc のバージョンを
+//! `SAFE_RM_RUSTC_VERSION` として渡す（取得できない場合は設定しない）。
+
+use std::process::Command;
+
+fn main() {
+    println!("cargo:rerun-if-env-changed=RUSTC");
+    let rustc = std::env::var("RUSTC").unwrap_or_else(|_| "rustc".to_string());
+    let version = Command::new(rustc)
+        .arg("--version")
+        .output()
+        .ok()
+        .filter(|output| output.status.success())
+        .and_then(|output| String::from_utf8(output.stdout).ok());
+    if let Some(version) = version {
+        let version = version.trim();
+        let version = version.strip_prefix("rustc ").unwrap_or(version);
+        println!("cargo:rustc-env=SAFE_RM_RUSTC_VERSION={}", version);
+    }
+}
@@ -0,0 +1,193 @@
+//! safe-rm の監査ログ
+//!
+//! 削除（およびブロック）ごとに1行を追記するフォレンジック用ログ。
+//! 複数の safe-rm が同時に書き込んでも行が混ざらないよう、O_APPEND で開いた
+//! ファイルに1行を1回の write で書き込む。
+
+use crate::error::FileStatus;
+use std::fs::{File, OpenOptions};
+use std::io::{self, Write};
+use std::path::Path;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// 監査ログに記録するアクション
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AuditAction {
+    /// 削除成功
+    Removed,
+    /// ドライランで削除対象と判定
+    WouldRemove,
+    /// 安全チェックによりブロック（Exit 2 相当）
+    Blocked,
+    /// 操作エラー（Exit 1 相当）
+    Failed,
+}
+
+impl AuditAction {
+    fn as_str(self) -> &'static str {
+        match self {
+            Self::Removed => "removed",
+            Self::WouldRemove => "would_remove",
+            Self::Blocked => "blocked",
+            Self::Failed => "failed",
+        }
+    }
+}
+
+/// 監査ログの1エントリ
+#[derive(Debug)]
+pub struct AuditEntry<'a> {
+    /// 対象の絶対パス
+    pub path: &'a Path,
+    /// 実行結果
+    pub action: AuditAction,
+    /// 削除前の Git ステータス（判定できない場合は None）
+    pub status: Option<FileStatus>,
+    /// ブロック・失敗時の理由
+    pub reason: Option<String>,
+    /// safe-rm を実行したカレントディレクトリ
+    pub cwd: &'a Path,
+}
+
+/// 追記専用の監査ログ
+pub struct AuditLog {
+    file: File,
+}
+
+impl AuditLog {
+    /// 監査ログを追記モードで開く（存在しなければ作成）
+    pub fn open(path: &Path) -> io::Result<Self> {
+        let file = OpenOptions::new().create(true).append(true).open(path)?;
+        Ok(Self { file })
+    }
+
+    /// 1エントリを記録
+    ///
+    /// 行は TAB 区切り: `timestamp action path status reason cwd`。
+    /// 不明な値は `-` で埋める。
+    pub fn record(&mut self, entry: &AuditEntry) -> io::Result<()> {
+        let line = Self::format_line(SystemTime::now(), entry);
+        // 1行を1回の write で書き込み、O_APPEND の原子性に依存する
+        self.file.write_all(line.as_bytes())
+    }
+
+    fn format_line(now: SystemTime, entry: &AuditEntry) -> String {
+        let status = entry
+            .status
+            .map(|s| s.to_string())
+            .unwrap_or_else(|| "-".to_string());
+        let reason = entry.reason.as_deref().unwrap_or("-");
+        format!(
+            "{}\t{}\t{}\t{}\t{}\t{}\n",
+            format_utc(now),
+            entry.action.as_str(),
+            sanitize(&entry.path.to_string_lossy()),
+            status,
+            sanitize(reason),
+            sanitize(&entry.cwd.to_string_lossy()),
+        )
+    }
+}
+
+/// TAB/改行を含む値が行構造を壊さないようエスケープ
+fn sanitize(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace('\t', "\\t")
+        .replace('\n', "\\n")
+        .replace('\r', "\\r")
+}
+
+/// SystemTime を RFC 3339 形式の UTC 文字列に変換（例: 2026-01-02T03:04:05Z）
+pub(crate) fn format_utc(time: SystemTime) -> String {
+    let secs = time
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0);
+    let days = (secs / 86_400) as i64;
+    let rem = secs % 86_400;
+
+    // Howard Hinnant の civil_from_days
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z.rem_euclid(146_097);
+    let yoe = (doe - doe / 1_460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + i64::from(month <= 2);
+
+    format!(
+        "{:04}-{:02}-{:02}T{:02}:{:02}:{:02}Z",
+        year,
+        month,
+        day,
+        rem / 3_600,
+        (rem % 3_600) / 60,
+        rem % 60
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::PathBuf;
+    use std::time::Duration;
+
+    #[test]
+    fn test_format_utc_epoch() {
+        assert_eq!(format_utc(UNIX_EPOCH), "1970-01-01T00:00:00Z");
+    }
+
+    #[test]
+    fn test_format_utc_known_date() {
+        // 2024-02-29T12:34:56Z（うるう日）
+        let time = UNIX_EPOCH + Duration::from_secs(1_709_210_096);
+        assert_eq!(format_utc(time), "2024-02-29T12:34:56Z");
+    }
+
+    #[test]
+    fn test_format_line_fields() {
+        let path = PathBuf::from("/repo/a.txt");
+        let cwd = PathBuf::from("/repo");
+        let entry = AuditEntry {
+            path: &path,
+            action: AuditAction::Blocked,
+            status: Some(FileStatus::Modified),
+            reason: Some("line1\nline2".to_string()),
+            cwd: &cwd,
+        };
+        let line = AuditLog::format_line(UNIX_EPOCH, &entry);
+        assert_eq!(
+            line,
+            "1970-01-01T00:00:00Z\tblocked\t/repo/a.txt\tModified\tline1\\nline2\t/repo\n"
+        );
+    }
+
+    #[test]
+    fn test_record_appends() {
+        let dir = tempfile::tempdir().unwrap();
+        let log_path = dir.path().join("audit.log");
+        let path = PathBuf::from("/repo/a.txt");
+        let cwd = PathBuf::from("/repo");
+        let entry = AuditEntry {
+            path: &path,
+            action: AuditAction::Removed,
+            status: None,
+            reason: None,
+            cwd: &cwd,
+        };
+
+        AuditLog::open(&log_path).unwrap().record(&entry).unwrap();
+        AuditLog::open(&log_path).unwrap().record(&entry).unwrap();
+
+        let content = std::fs::read_to_string(&log_path).unwrap();
+        assert_eq!(content.lines().count(), 2);
+        assert!(
+            content
+                .lines()
+                .all(|l| l.contains("\tremoved\t/repo/a.txt\t-\t-\t/repo"))
+        );
+    }
+}
@@ -0,0 +1,348 @@
+//! safe-rm のバックアップ
+//!
+//! `--backup DIR` 指定時、削除対象を DIR 配下の実行ごとのディレクトリへ移動し、
+//! 元のパスへ戻すための manifest.json と restore.sh を書き出す。
+//! `--verify-backup` 指定時は移動後に退避先の内容を確認し、復元できない場合は失敗として報告する。
+
+use crate::audit::format_utc;
+use serde::Serialize;
+use std::fs;
+use std::io;
+use std::path::{Component, Path, PathBuf};
+use std::time::SystemTime;
+
+/// manifest.json のファイル名
+pub const MANIFEST_FILE: &str = "manifest.json";
+/// restore.sh のファイル名
+pub const RESTORE_SCRIPT: &str = "restore.sh";
+
+/// バックアップ1件（元のパス → 退避先）
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct BackupEntry {
+    /// 元の絶対パス
+    pub original: PathBuf,
+    /// 退避先の絶対パス
+    pub backup: PathBuf,
+}
+
+/// 復元可能かの確認に使う内容の要約（種類・ファイル数・合計バイト数）
+///
+/// シンボリックリンクは辿らず、リンク自体を1ファイルとして数える。
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Fingerprint {
+    is_dir: bool,
+    files: u64,
+    bytes: u64,
+}
+
+impl Fingerprint {
+    /// `path` 配下を走査して要約を作る（読み取れないエントリはエラー）
+    pub fn of(path: &Path) -> io::Result<Self> {
+        let metadata = fs::symlink_metadata(path)?;
+        let mut fingerprint = Self {
+            is_dir: metadata.is_dir(),
+            files: 0,
+            bytes: 0,
+        };
+        fingerprint.add(path, &metadata)?;
+        Ok(fingerprint)
+    }
+
+    fn add(&mut self, path: &Path, metadata: &fs::Metadata) -> io::Result<()> {
+        if !metadata.is_dir() {
+            self.files += 1;
+            self.bytes += metadata.len();
+            return Ok(());
+        }
+        for entry in fs::read_dir(path)? {
+            let entry = entry?;
+            self.add(&entry.path(), &fs::symlink_metadata(entry.path())?)?;
+        }
+        Ok(())
+    }
+}
+
+/// 退避先が移動前の要約と一致し、復元できる状態か確認
+pub fn verify_restorable(backup: &Path, expected: Fingerprint) -> io::Result<()> {
+    let actual = match Fingerprint::of(backup) {
+        Ok(actual) => actual,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => {
+            return Err(io::Error::other(format!(
+                "backup verification failed: {} is missing",
+                backup.display()
+            )));
+        }
+        Err(e) => return Err(e),
+    };
+    if actual != expected {
+        return Err(io::Error::other(format!(
+            "backup verification failed: {} has {} file(s) / {} bytes, expected {} file(s) / {} bytes",
+            backup.display(),
+            actual.files,
+            actual.bytes,
+            expected.files,
+            expected.bytes
+        )));
+    }
+    Ok(())
+}
+
+/// manifest.json の内容
+#[derive(Debug, Serialize)]
+struct Manifest<'a> {
+    created: &'a str,
+    entries: &'a [BackupEntry],
+}
+
+/// 1回の実行分のバックアップ
+#[derive(Debug)]
+pub struct BackupSession {
+    run_dir: PathBuf,
+    created: String,
+    entries: Vec<BackupEntry>,
+}
+
+impl BackupSession {
+    /// `root` 配下に実行ごとのディレクトリ（`<UTC時刻>-<pid>`）を作成
+    pub fn create(root: &Path) -> io::Result<Self> {
+        let created = format_utc(SystemTime::now());
+        let stamp: String = created.chars().filter(|c| *c != '-' && *c != ':').collect();
+        let root = root.canonicalize().or_else(|_| {
+            fs::create_dir_all(root)?;
+            root.canonicalize()
+        })?;
+        let run_dir = root.join(format!("{}-{}", stamp, std::process::id()));
+        fs::create_dir_all(run_dir.join("files"))?;
+
+        Ok(Self {
+            run_dir,
+            created,
+            entries: Vec::new(),
+        })
+    }
+
+    /// 実行ごとのディレクトリ
+    pub fn run_dir(&self) -> &Path {
+        &self.run_dir
+    }
+
+    /// 記録済みのエントリ（削除順）
+    pub fn entries(&self) -> &[BackupEntry] {
+        &self.entries
+    }
+
+    /// 対象を退避先へ移動し、manifest と restore スクリプトを更新
+    ///
+    /// `original` は絶対パス。シンボリックリンクはリンク自体を移動する。
+    /// 別ファイルシステムへの移動はコピー後に元を削除する。
+    pub fn stash(&mut self, original: &Path) -> io::Result<PathBuf> {
+        let backup = self.backup_path(original);
+        if let Some(parent) = backup.parent() {
+            fs::create_dir_all(parent)?;
+        }
+
+        match fs::rename(original, &backup) {
+            Ok(()) => {}
+            Err(e) if e.kind() == io::ErrorKind::CrossesDevices => {
+                copy_recursive(original, &backup)?;
+                let metadata = fs::symlink_metadata(original)?;
+                if metadata.is_dir() {
+                    fs::remove_dir_all(original)?;
+                } else {
+                    fs::remove_file(original)?;
+                }
+            }
+            Err(e) => return Err(e),
+        }
+
+        self.entries.push(BackupEntry {
+            original: original.to_path_buf(),
+            backup: backup.clone(),
+        });
+        // 途中で中断しても復元できるよう、移動のたびに書き出す
+        self.write_manifest()?;
+        Ok(backup)
+    }
+
+    /// 対象を退避先へ移動し、退避先から復元できることを確認（`--verify-backup`）
+    ///
+    /// 移動前の内容の要約と退避先を比較し、一致しなければエラーを返す。
+    /// 移動自体は完了しているため、manifest と restore スクリプトには記録済み。
+    pub fn stash_verified(&mut self, original: &Path) -> io::Result<PathBuf> {
+        let expected = Fingerprint::of(original)?;
+        let backup = self.stash(original)?;
+        verify_restorable(&backup, expected)?;
+        Ok(backup)
+    }
+
+    /// 元の絶対パスを `files/` 配下へ写像した退避先
+    fn backup_path(&self, original: &Path) -> PathBuf {
+        let mut backup = self.run_dir.join("files");
+        for component in original.components() {
+            if let Component::Normal(name) = component {
+                backup.push(name);
+            }
+        }
+        backup
+    }
+
+    fn write_manifest(&self) -> io::Result<()> {
+        let manifest = Manifest {
+            created: &self.created,
+            entries: &self.entries,
+        };
+        let json = serde_json::to_string_pretty(&manifest).map_err(io::Error::other)?;
+        fs::write(self.run_dir.join(MANIFEST_FILE), json + "\n")?;
+        fs::write(self.run_dir.join(RESTORE_SCRIPT), self.restore_script())
+    }
+
+    /// 削除と逆順に元の場所へ戻すシェルスクリプト
+    fn restore_script(&self) -> String {
+        let mut script = String::from("#!/bin/sh\n# Restore files removed by safe-rm\nset -e\n");
+        for entry in self.entries.iter().rev() {
+            let parent = entry.original.parent().unwrap_or(Path::new("/"));
+            script.push_str(&format!(
+                "mkdir -p {} && mv {} {}\n",
+                shell_quote(parent),
+                shell_quote(&entry.backup),
+                shell_quote(&entry.original)
+            ));
+        }
+        script
+    }
+}
+
+/// POSIX シェル用のシングルクォート
+pub fn shell_quote(path: &Path) -> String {
+    format!("'{}'", path.to_string_lossy().replace('\'', "'\\''"))
+}
+
+/// シンボリックリンクを辿らずに再帰コピー
+fn copy_recursive(src: &Path, dst: &Path) -> io::Result<()> {
+    let metadata = fs::symlink_metadata(src)?;
+    if metadata.file_type().is_symlink() {
+        copy_symlink(src, dst)
+    } else if metadata.is_dir() {
+        fs::create_dir_all(dst)?;
+        for entry in fs::read_dir(src)? {
+            let entry = entry?;
+            copy_recursive(&entry.path(), &dst.join(entry.file_name()))?;
+        }
+        Ok(())
+    } else {
+        fs::copy(src, dst).map(|_| ())
+    }
+}
+
+#[cfg(unix)]
+fn copy_symlink(src: &Path, dst: &Path) -> io::Result<()> {
+    std::os::unix::fs::symlink(fs::read_link(src)?, dst)
+}
+
+#[cfg(not(unix))]
+fn copy_symlink(src: &Path, dst: &Path) -> io::Result<()> {
+    fs::copy(src, dst).map(|_| ())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_stash_moves_file_and_writes_manifest() {
+        let work = TempDir::new().unwrap();
+        let backup_root = TempDir::new().unwrap();
+        let dir = work.path().canonicalize().unwrap();
+        let file = dir.join("a.txt");
+        fs::write(&file, "content").unwrap();
+
+        let mut session = BackupSession::create(backup_root.path()).unwrap();
+        let backup = session.stash(&file).unwrap();
+
+        assert!(!file.exists());
+        assert_eq!(fs::read_to_string(&backup).unwrap(), "content");
+        assert!(backup.starts_with(session.run_dir()));
+
+        let manifest: serde_json::Value = serde_json::from_str(
+            &fs::read_to_string(session.run_dir().join(MANIFEST_FILE)).unwrap(),
+        )
+        .unwrap();
+        assert_eq!(manifest["entries"][0]["original"], file.to_str().unwrap());
+        assert_eq!(manifest["entries"][0]["backup"], backup.to_str().unwrap());
+    }
+
+    #[test]
+    fn test_restore_script_reverses_order_and_quotes() {
+        let work = TempDir::new().unwrap();
+        let backup_root = TempDir::new().unwrap();
+        let dir = work.path().canonicalize().unwrap();
+        let first = dir.join("it's.txt");
+        let second = dir.join("b.txt");
+        fs::write(&first, "1").unwrap();
+        fs::write(&second, "2").unwrap();
+
+        let mut session = BackupSession::create(backup_root.path()).unwrap();
+        session.stash(&first).unwrap();
+        session.stash(&second).unwrap();
+
+        let script = session.restore_script();
+        let b_pos = script.find("b.txt").unwrap();
+        let first_pos = script.find("it'\\''s.txt").unwrap();
+        assert!(b_pos < first_pos, "restore must run in reverse order");
+    }
+
+    #[test]
+    fn test_stash_verified_accepts_complete_backup() {
+        let work = TempDir::new().unwrap();
+        let backup_root = TempDir::new().unwrap();
+        let dir = work.path().canonicalize().unwrap().join("build");
+        fs::create_dir_all(dir.join("nested")).unwrap();
+        fs::write(dir.join("a.o"), "aaa").unwrap();
+        fs::write(dir.join("nested").join("b.o"), "b").unwrap();
+
+        let mut session = BackupSession::create(backup_root.path()).unwrap();
+        let backup = session.stash_verified(&dir).unwrap();
+        assert!(!dir.exists());
+        assert_eq!(
+            Fingerprint::of(&backup).unwrap(),
+            Fingerprint {
+                is_dir: true,
+                files: 2,
+                bytes: 4,
+            }
+        );
+    }
+
+    #[test]
+    fn test_verify_restorable_detects_lost_or_truncated_backup() {
+        let work = TempDir::new().unwrap();
+        let file = work.path().join("a.txt");
+        fs::write(&file, "content").unwrap();
+        let expected = Fingerprint::of(&file).unwrap();
+        assert!(verify_restorable(&file, expected).is_ok());
+
+        fs::write(&file, "cont").unwrap();
+        let err = verify_restorable(&file, expected).unwrap_err();
+        assert!(err.to_string().contains("expected 1 file(s) / 7 bytes"));
+
+        fs::remove_file(&file).unwrap();
+        let err = verify_restorable(&file, expected).unwrap_err();
+        assert!(err.to_string().contains("is missing"));
+    }
+
+    #[test]
+    fn test_copy_recursive_copies_tree() {
+        let work = TempDir::new().unwrap();
+        let src = work.path().join("src");
+        fs::create_dir_all(src.join("nested")).unwrap();
+        fs::write(src.join("nested").join("f.txt"), "x").unwrap();
+
+        let dst = work.path().join("dst");
+        copy_recursive(&src, &dst).unwrap();
+        assert_eq!(
+            fs::read_to_string(dst.join("nested").join("f.txt")).unwrap(),
+            "x"
+        );
+    }
+}
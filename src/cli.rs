@@ -2,11 +2,13 @@
 //!
 //! clap derive による型安全な引数パースを提供する。
 
-use clap::{Parser, Subcommand};
+use clap::builder::{OsStringValueParser, TypedValueParser};
+use clap::{CommandFactory, Parser, Subcommand, ValueEnum};
 use std::path::PathBuf;
+use std::time::Duration;
 
 /// safe-rm の CLI 引数
-#[derive(Parser, Debug)]
+#[derive(Parser, Debug, Default)]
 #[command(
     name = "safe-rm",
     version,
@@ -22,20 +24,273 @@ pub struct CliArgs {
     pub command: Option<Commands>,
 
     /// 削除対象のファイルまたはディレクトリ
-    #[arg(required = true, value_name = "PATH")]
+    ///
+    /// 空文字列は clap で弾かず、`SafeRmError::InvalidPath` として明示的に拒否する。
+    #[arg(
+        required_unless_present = "files_from",
+        value_name = "PATH",
+        value_parser = OsStringValueParser::new().map(PathBuf::from)
+    )]
     pub paths: Vec<PathBuf>,
 
     /// 再帰削除（ディレクトリとその内容を削除）
     #[arg(short, long)]
     pub recursive: bool,
 
+    /// 空のディレクトリを削除（-r なし。空でない場合はエラー）
+    #[arg(short = 'd', long = "dir")]
+    pub dir: bool,
+
     /// 強制削除（存在しないファイルを無視）
     #[arg(short, long)]
     pub force: bool,
 
+    /// `-f` と併用した場合のみ、safe-rm 自身の設定ファイル（またはそれを含むディレクトリ）の削除を許可
+    #[arg(long, requires = "force")]
+    pub allow_self_config: bool,
+
     /// ドライランモード（実際には削除せず、削除対象を表示）
     #[arg(short = 'n', long)]
     pub dry_run: bool,
+
+    /// パスごとの結果と集計を `--format json` と同じ形式の JSON 行でファイルに書き出す
+    /// （端末の出力は変えない。部分的な失敗でも書き出し、一時ファイル経由で置き換える）
+    #[arg(long, value_name = "FILE")]
+    pub report: Option<PathBuf>,
+
+    /// 最初のブロック・エラーで中断し、残りのパスを処理しない
+    #[arg(long, overrides_with = "keep_going")]
+    pub fail_fast: bool,
+
+    /// ブロック・エラーがあっても残りのパスを処理して最後に集計（デフォルト）
+    #[arg(long, overrides_with = "fail_fast")]
+    pub keep_going: bool,
+
+    /// 厳格モードで削除直前に Git ステータスを再取得（事前キャッシュを使わない）
+    #[arg(long)]
+    pub recheck: bool,
+
+    /// 厳格モードでディレクトリがブロックされたとき、削除できないファイルをすべて表示
+    #[arg(long)]
+    pub list_dirty: bool,
+
+    /// 厳格モードで `-r` の対象に削除できないファイルがあっても中止せず、それらと親ディレクトリを
+    /// 残して他のファイルを削除し、残したファイルを表示
+    #[arg(long, requires = "recursive", conflicts_with_all = ["backup", "emit_script"])]
+    pub partial_dir: bool,
+
+    /// パス毎に最寄りの Git リポジトリを検出し、そのルートを境界として検証
+    #[arg(long, conflicts_with = "root")]
+    pub per_repo: bool,
+
+    /// Git リポジトリを開かず、Git ステータスのチェックを一切行わない（包含検証のみ）
+    #[arg(long, conflicts_with_all = ["per_repo", "recheck", "since", "pathspec"])]
+    pub no_git: bool,
+
+    /// 環境変数 `SAFE_RM_DISABLE=1` と併用した場合のみ、包含検証と Git チェックを無効化する
+    /// 緊急用のスイッチ（誤って有効にならないようヘルプには表示しない）
+    #[arg(long, hide = true)]
+    pub i_understand_the_risk: bool,
+
+    /// 引数（と `--files-from` の各行）を Git の pathspec（`:(glob)**/*.tmp`、`:/logs` 等）として
+    /// 解釈し、一致する追跡中・未追跡のファイルをそれぞれ通常どおりチェックして削除
+    #[arg(long, conflicts_with_all = ["per_repo", "contents_only"])]
+    pub pathspec: bool,
+
+    /// 指定した Git 参照（ブランチ・タグ・コミット）から内容が変わったファイルもブロック
+    #[arg(long, value_name = "REF", conflicts_with = "per_repo")]
+    pub since: Option<String>,
+
+    /// プロジェクト境界を明示的に指定（カレントディレクトリの祖先である必要あり）
+    #[arg(long, value_name = "DIR")]
+    pub root: Option<PathBuf>,
+
+    /// 削除対象を改行区切りで列挙したファイル（`#` 始まりの行はコメント）
+    #[arg(long, value_name = "FILE")]
+    pub files_from: Option<PathBuf>,
+
+    /// 削除（またはドライラン）対象をファイル種別ごとに集計して表示
+    #[arg(long)]
+    pub by_type: bool,
+
+    /// ブロックされたパスの理由別の件数を最後に stderr へ1行で出力（strict モードでは常に出力）
+    #[arg(long)]
+    pub summary: bool,
+
+    /// 削除せず DIR 配下へ移動し、復元用の manifest.json と restore.sh を書き出す
+    #[arg(long, value_name = "DIR")]
+    pub backup: Option<PathBuf>,
+
+    /// `--backup` の移動後、退避先の内容（ファイル数・サイズ）を確認してから成功として報告
+    #[arg(long, requires = "backup")]
+    pub verify_backup: bool,
+
+    /// `-r` の削除時に残すエントリの glob（複数指定可。`/` を含まなければファイル名と照合）
+    #[arg(long, value_name = "PATTERN", conflicts_with = "backup")]
+    pub exclude: Vec<String>,
+
+    /// ドライランで `would remove:` の代わりに同等の `rm` コマンドをシェルスクリプトとして出力
+    /// （ブロックされたパスは stderr にコメントとして出力）
+    #[arg(
+        long,
+        requires = "dry_run",
+        conflicts_with_all = ["exclude", "older_than", "one_file_system", "depth"]
+    )]
+    pub emit_script: bool,
+
+    /// 削除（ドライランでは削除対象）のパスを NUL 区切りで stdout へ出力し、`removed:` 等の
+    /// 表示を省く（`xargs -0` 向け。`--format json` とは併用不可）
+    #[arg(long, conflicts_with_all = ["emit_script", "by_type"])]
+    pub print0: bool,
+
+    /// ドライランと同じ判定を行い、削除対象のファイル数（ディレクトリ配下を含む）だけを stdout へ
+    /// 出力（ブロックされたパスは stderr に表示して件数に含めず、終了コードは 0）
+    #[arg(
+        long,
+        conflicts_with_all = ["emit_script", "print0", "by_type", "report", "backup"]
+    )]
+    pub count: bool,
+
+    /// `-r` の削除中、対象と異なるファイルシステム（マウントポイント）へ入らずに残す（Unix のみ）
+    #[arg(long, conflicts_with = "backup")]
+    pub one_file_system: bool,
+
+    /// `-r` の削除で対象ディレクトリから N 階層までのエントリのみ削除し、より深いエントリは残す
+    #[arg(
+        long,
+        value_name = "N",
+        requires = "recursive",
+        conflicts_with = "backup"
+    )]
+    pub depth: Option<usize>,
+
+    /// ディレクトリ自体は残し、直下のエントリだけを削除（各エントリを通常どおりチェック）
+    #[arg(long)]
+    pub contents_only: bool,
+
+    /// `-r` の対象にネストした Git リポジトリ（サブモジュール等）が含まれていても削除を許可
+    #[arg(long)]
+    pub allow_nested_repos: bool,
+
+    /// `-r` の削除中、削除したファイル数を定期的に stderr へ出力
+    #[arg(long)]
+    pub progress: bool,
+
+    /// リポジトリのオープン、Git ステータス取得、パス毎のチェックと削除の所要時間を stderr へ出力
+    #[arg(long)]
+    pub trace: bool,
+
+    /// 削除・ブロックの結果を1行ずつ追記する監査ログ（設定の audit_log より優先）
+    #[arg(long, value_name = "FILE")]
+    pub audit_log: Option<PathBuf>,
+
+    /// 件数に関係なく、削除前に件数と対象の一部を表示して一度だけ確認
+    #[arg(long)]
+    pub confirm: bool,
+
+    /// 再帰削除、または3件を超えるパスの削除前に一度だけ確認（GNU rm の `-I`）
+    ///
+    /// stdin が端末でなくても1行読み取り、`y` / `yes` 以外なら何も削除しない。
+    #[arg(short = 'I', long)]
+    pub interactive_once: bool,
+
+    /// 確認プロンプト（`--confirm` / confirm_threshold）にすべて yes と答えて続行
+    ///
+    /// 確認を省くだけで安全チェックは緩めない（`-f` や厳格モードの解除とは別）。
+    #[arg(short = 'y', long)]
+    pub yes: bool,
+
+    /// 最終更新からこの秒数が経過していない対象をブロック（設定の min_age_seconds より優先）
+    #[arg(long, value_name = "SECONDS")]
+    pub min_age: Option<u64>,
+
+    /// 最終更新から DURATION（例: `7d`, `24h`）以上経過したファイルのみ削除し、
+    /// それより新しいものはエラーにせず残す（`-r` ではディレクトリ配下も同様）
+    #[arg(
+        long,
+        value_name = "DURATION",
+        value_parser = parse_duration,
+        conflicts_with = "backup"
+    )]
+    pub older_than: Option<Duration>,
+
+    /// 1回の実行で受け付けるパス数の上限（設定の max_paths より優先）
+    #[arg(long, value_name = "N")]
+    pub max_paths: Option<usize>,
+
+    /// `removed:` / `would remove:` / エラー行のパスをこのディレクトリからの相対パスで表示
+    /// （値を省略すると cwd。基準の外にあるパスは絶対パスで表示）
+    #[arg(
+        long,
+        value_name = "DIR",
+        num_args = 0..=1,
+        default_missing_value = "."
+    )]
+    pub relative_to: Option<PathBuf>,
+
+    /// 設定ファイルのパス（SAFE_RM_CONFIG 環境変数とデフォルトパスより優先）
+    #[arg(long, global = true, value_name = "PATH")]
+    pub config: Option<PathBuf>,
+
+    /// 出力形式（`status` サブコマンドの結果、設定警告、エラー出力に適用）
+    #[arg(
+        long,
+        global = true,
+        value_enum,
+        value_name = "FORMAT",
+        default_value_t = OutputFormat::Human
+    )]
+    pub format: OutputFormat,
+
+    /// 設定読み込み時の追加の警告を表示（存在しない allowed_paths エントリなど）
+    #[arg(short, long, global = true)]
+    pub verbose: bool,
+
+    /// 他プロセスが開いているファイルを検出（Unix のみ。`--check-open=block` でブロック）
+    #[arg(
+        long,
+        value_enum,
+        value_name = "MODE",
+        num_args = 0..=1,
+        require_equals = true,
+        default_missing_value = "warn"
+    )]
+    pub check_open: Option<OpenCheckMode>,
+
+    /// 出力の色付け（NO_COLOR 環境変数が設定されていれば常に無効）
+    #[arg(long, value_enum, value_name = "WHEN", default_value_t = ColorChoice::Auto)]
+    pub color: ColorChoice,
+}
+
+/// `--color` の指定値
+#[derive(ValueEnum, Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ColorChoice {
+    /// 出力先が TTY の場合のみ色付け
+    #[default]
+    Auto,
+    /// 常に色付け
+    Always,
+    /// 色付けしない
+    Never,
+}
+
+/// `--check-open` の指定値
+#[derive(ValueEnum, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OpenCheckMode {
+    /// 警告を表示して削除を続行
+    Warn,
+    /// 削除をブロック（終了コード 2）
+    Block,
+}
+
+/// `--format` の指定値
+#[derive(ValueEnum, Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum OutputFormat {
+    /// 人間向けのテキスト出力
+    #[default]
+    Human,
+    /// JSON 出力
+    Json,
 }
 
 /// サブコマンド
@@ -43,15 +298,109 @@ pub struct CliArgs {
 pub enum Commands {
     /// 設定ファイルを初期化（~/.config/safe-rm/config.toml）
     Init,
+    /// 削除せずに各パスの Git ステータスと削除可否を表示
+    Status {
+        /// 判定対象のファイルまたはディレクトリ
+        #[arg(required = true, value_name = "PATH")]
+        paths: Vec<PathBuf>,
+    },
+    /// 設定ファイル・Git リポジトリ・allowed_paths の状態を診断
+    Verify,
+    /// 実効設定（設定ファイルのパス・パース結果・allowed_paths の解決結果）を表示
+    Config {
+        /// allowed_paths の各エントリ（解決後のパス・再帰の有無・存在有無）のみを1行ずつ表示
+        #[arg(long)]
+        list_allowed: bool,
+    },
+    /// init で生成した設定ファイルを削除（確認あり）
+    Reset {
+        /// テンプレートから編集された設定ファイルも削除
+        #[arg(short, long)]
+        force: bool,
+        /// 確認を省略して削除
+        #[arg(short = 'y', long)]
+        yes: bool,
+        /// `--force --yes` で、テンプレートから編集された設定ファイルも確認なしで削除
+        #[arg(long, requires = "force")]
+        allow_self_config: bool,
+    },
+    /// バージョンとビルド情報（libgit2・rustc のバージョン、有効なフィーチャ）を表示
+    Version {
+        /// `{"version":...,"git2":...,"rustc":...,"features":[...]}` の1行の JSON で出力
+        #[arg(long)]
+        json: bool,
+    },
 }
 
 impl CliArgs {
     /// コマンドライン引数をパース
     pub fn parse_args() -> Self {
-        Self::parse()
+        let args = Self::parse();
+        if let Err(e) = args.validate() {
+            e.exit();
+        }
+        args
+    }
+
+    /// clap の宣言では表せない組み合わせを検証（`--format` は既定値を持つため）
+    pub fn validate(&self) -> Result<(), clap::Error> {
+        if self.print0 && self.format == OutputFormat::Json {
+            return Err(Self::command().error(
+                clap::error::ErrorKind::ArgumentConflict,
+                "--print0 cannot be used with --format json",
+            ));
+        }
+        Ok(())
     }
 }
 
+/// `--files-from` の内容をパスのリストに変換
+///
+/// 1行1パス。空行と `#` で始まる行（先頭空白は無視）はスキップする。
+/// パス中の空白を保持するため、行末の `\r` 以外はトリムしない。
+pub fn parse_file_list(content: &str) -> Vec<PathBuf> {
+    content
+        .lines()
+        .map(|line| line.strip_suffix('\r').unwrap_or(line))
+        .filter(|line| {
+            let trimmed = line.trim_start();
+            !trimmed.is_empty() && !trimmed.starts_with('#')
+        })
+        .map(PathBuf::from)
+        .collect()
+}
+
+/// `--older-than` の期間をパース
+///
+/// 整数と単位（`s` 秒、`m` 分、`h` 時間、`d` 日、`w` 週）の組。単位を省略すると秒。
+pub fn parse_duration(value: &str) -> Result<Duration, String> {
+    let value = value.trim();
+    let split = value
+        .find(|c: char| !c.is_ascii_digit())
+        .unwrap_or(value.len());
+    let (number, unit) = value.split_at(split);
+    let number: u64 = number
+        .parse()
+        .map_err(|_| format!("invalid duration '{}' (e.g. 30m, 24h, 7d)", value))?;
+    let seconds = match unit {
+        "" | "s" => 1,
+        "m" => 60,
+        "h" => 60 * 60,
+        "d" => 24 * 60 * 60,
+        "w" => 7 * 24 * 60 * 60,
+        _ => {
+            return Err(format!(
+                "unknown duration unit '{}' (use s, m, h, d or w)",
+                unit
+            ));
+        }
+    };
+    number
+        .checked_mul(seconds)
+        .map(Duration::from_secs)
+        .ok_or_else(|| format!("duration '{}' is too large", value))
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -63,9 +412,81 @@ mod tests {
             recursive,
             force,
             dry_run,
+            ..Default::default()
         }
     }
 
+    #[test]
+    fn test_cli_args_dir_flag() {
+        let args = CliArgs::try_parse_from(["safe-rm", "-d", "empty"]).unwrap();
+        assert!(args.dir);
+        assert!(!args.recursive);
+        let args = CliArgs::try_parse_from(["safe-rm", "--dir", "empty"]).unwrap();
+        assert!(args.dir);
+    }
+
+    #[test]
+    fn test_cli_args_check_open() {
+        let args = CliArgs::try_parse_from(["safe-rm", "--check-open", "a.txt"]).unwrap();
+        assert_eq!(args.check_open, Some(OpenCheckMode::Warn));
+        assert_eq!(args.paths, vec![PathBuf::from("a.txt")]);
+
+        let args = CliArgs::try_parse_from(["safe-rm", "--check-open=block", "a.txt"]).unwrap();
+        assert_eq!(args.check_open, Some(OpenCheckMode::Block));
+
+        let args = CliArgs::try_parse_from(["safe-rm", "a.txt"]).unwrap();
+        assert_eq!(args.check_open, None);
+    }
+
+    #[test]
+    fn test_cli_args_config_flag_is_global() {
+        let args =
+            CliArgs::try_parse_from(["safe-rm", "--config", "/tmp/c.toml", "a.txt"]).unwrap();
+        assert_eq!(args.config, Some(PathBuf::from("/tmp/c.toml")));
+
+        let args =
+            CliArgs::try_parse_from(["safe-rm", "config", "--config", "/tmp/c.toml"]).unwrap();
+        assert!(matches!(
+            args.command,
+            Some(Commands::Config {
+                list_allowed: false
+            })
+        ));
+        assert_eq!(args.config, Some(PathBuf::from("/tmp/c.toml")));
+    }
+
+    #[test]
+    fn test_cli_args_version_subcommand() {
+        let args = CliArgs::try_parse_from(["safe-rm", "version", "--json"]).unwrap();
+        assert!(matches!(
+            args.command,
+            Some(Commands::Version { json: true })
+        ));
+        let args = CliArgs::try_parse_from(["safe-rm", "version"]).unwrap();
+        assert!(matches!(
+            args.command,
+            Some(Commands::Version { json: false })
+        ));
+    }
+
+    #[test]
+    fn test_parse_duration() {
+        assert_eq!(parse_duration("90"), Ok(Duration::from_secs(90)));
+        assert_eq!(parse_duration("30m"), Ok(Duration::from_secs(1800)));
+        assert_eq!(parse_duration("24h"), Ok(Duration::from_secs(86_400)));
+        assert_eq!(parse_duration("7d"), Ok(Duration::from_secs(604_800)));
+        assert_eq!(parse_duration("2w"), Ok(Duration::from_secs(1_209_600)));
+        assert!(parse_duration("").is_err());
+        assert!(parse_duration("d").is_err());
+        assert!(parse_duration("7y").is_err());
+        assert!(parse_duration("1.5h").is_err());
+        assert!(parse_duration("99999999999999999999w").is_err());
+
+        let args = CliArgs::try_parse_from(["safe-rm", "--older-than", "7d", "a.log"]).unwrap();
+        assert_eq!(args.older_than, Some(Duration::from_secs(604_800)));
+        assert!(CliArgs::try_parse_from(["safe-rm", "--older-than", "soon", "a.log"]).is_err());
+    }
+
     #[test]
     fn test_cli_args_debug() {
         let args = make_args(vec!["file.txt"], false, false, false);
@@ -121,15 +542,110 @@ mod tests {
         assert!(args.dry_run);
     }
 
+    #[test]
+    fn test_parse_file_list_skips_comments_and_blank_lines() {
+        let content = "# cleanup list\nbuild/out.o\n\n  # indented comment\nlogs/run.log\r\n";
+        let paths = parse_file_list(content);
+        assert_eq!(
+            paths,
+            vec![PathBuf::from("build/out.o"), PathBuf::from("logs/run.log")]
+        );
+    }
+
+    #[test]
+    fn test_parse_file_list_keeps_spaces_in_names() {
+        let paths = parse_file_list("my file.txt\n");
+        assert_eq!(paths, vec![PathBuf::from("my file.txt")]);
+    }
+
+    #[test]
+    fn test_cli_args_files_from_without_paths() {
+        let args = CliArgs::try_parse_from(["safe-rm", "--files-from", "list.txt"]).unwrap();
+        assert!(args.paths.is_empty());
+        assert_eq!(args.files_from, Some(PathBuf::from("list.txt")));
+    }
+
+    #[test]
+    fn test_cli_args_requires_path_or_files_from() {
+        assert!(CliArgs::try_parse_from(["safe-rm"]).is_err());
+    }
+
+    #[test]
+    fn test_cli_args_color_default_auto() {
+        let args = CliArgs::try_parse_from(["safe-rm", "file.txt"]).unwrap();
+        assert_eq!(args.color, ColorChoice::Auto);
+
+        let args = CliArgs::try_parse_from(["safe-rm", "--color", "never", "file.txt"]).unwrap();
+        assert_eq!(args.color, ColorChoice::Never);
+    }
+
     #[test]
     fn test_cli_args_init_subcommand() {
         let args = CliArgs {
             command: Some(Commands::Init),
-            paths: vec![],
-            recursive: false,
-            force: false,
-            dry_run: false,
+            ..Default::default()
         };
         assert!(matches!(args.command, Some(Commands::Init)));
     }
+
+    #[test]
+    fn test_cli_args_status_subcommand_with_format() {
+        let args = CliArgs::try_parse_from(["safe-rm", "status", "--format", "json", "a.txt", "b"])
+            .unwrap();
+        assert_eq!(args.format, OutputFormat::Json);
+        match args.command {
+            Some(Commands::Status { paths }) => {
+                assert_eq!(paths, vec![PathBuf::from("a.txt"), PathBuf::from("b")]);
+            }
+            other => panic!("unexpected command: {other:?}"),
+        }
+    }
+
+    #[test]
+    fn test_cli_args_root_conflicts_with_per_repo() {
+        let args = CliArgs::try_parse_from(["safe-rm", "--root", "/repo", "a.txt"]).unwrap();
+        assert_eq!(args.root, Some(PathBuf::from("/repo")));
+        assert!(
+            CliArgs::try_parse_from(["safe-rm", "--root", "/repo", "--per-repo", "a.txt"]).is_err()
+        );
+    }
+
+    #[test]
+    fn test_cli_args_no_git_conflicts_with_per_repo() {
+        let args = CliArgs::try_parse_from(["safe-rm", "--no-git", "a.txt"]).unwrap();
+        assert!(args.no_git);
+        assert!(CliArgs::try_parse_from(["safe-rm", "--no-git", "--per-repo", "a.txt"]).is_err());
+    }
+
+    #[test]
+    fn test_cli_args_print0_rejects_json_format() {
+        let args = CliArgs::try_parse_from(["safe-rm", "--print0", "a.txt"]).unwrap();
+        assert!(args.print0);
+        assert!(args.validate().is_ok());
+
+        let args =
+            CliArgs::try_parse_from(["safe-rm", "--print0", "--format", "json", "a.txt"]).unwrap();
+        assert!(args.validate().is_err());
+    }
+
+    #[test]
+    fn test_cli_args_count_conflicts_with_other_outputs() {
+        let args = CliArgs::try_parse_from(["safe-rm", "--count", "-r", "dir"]).unwrap();
+        assert!(args.count);
+        assert!(CliArgs::try_parse_from(["safe-rm", "--count", "--print0", "a.txt"]).is_err());
+        assert!(CliArgs::try_parse_from(["safe-rm", "--count", "--emit-script", "a.txt"]).is_err());
+    }
+
+    #[test]
+    fn test_cli_args_allow_self_config_requires_force() {
+        assert!(CliArgs::try_parse_from(["safe-rm", "--allow-self-config", "a.txt"]).is_err());
+        let args =
+            CliArgs::try_parse_from(["safe-rm", "-f", "--allow-self-config", "a.txt"]).unwrap();
+        assert!(args.allow_self_config);
+    }
+
+    #[test]
+    fn test_cli_args_status_requires_path() {
+        assert!(CliArgs::try_parse_from(["safe-rm", "status"]).is_err());
+    }
 }
@@ -0,0 +1,136 @@
+//! safe-rm の一括削除の確認（`--confirm` / confirm_threshold）
+//!
+//! 削除予定のファイル数と対象の一部を表示し、削除を始める前に一度だけ確認する。
+//! ファイルごとに確認するより負担が少なく、大量削除の安全弁として働く。
+
+use std::io::{self, BufRead, Write};
+use std::path::{Path, PathBuf};
+
+/// 確認時に表示する対象パスの最大数
+const SAMPLE_SIZE: usize = 5;
+
+/// `-I` で確認せずに削除できるパス数の上限（GNU rm と同じく3件まで）
+pub const INTERACTIVE_ONCE_MAX_PATHS: usize = 3;
+
+/// `-I`（`--interactive-once`）で確認が必要か
+///
+/// GNU rm の `-I` と同じく、再帰削除、または3件を超えるパスの削除で一度だけ確認する。
+pub fn interactive_once_applies(path_count: usize, recursive: bool) -> bool {
+    recursive || path_count > INTERACTIVE_ONCE_MAX_PATHS
+}
+
+/// 削除予定のファイル数を数える
+///
+/// ディレクトリは配下のファイル（ディレクトリ以外）を数え、シンボリックリンクは辿らず
+/// リンク自体を1件とする。存在しないパスや読み取れないエントリは数えない
+/// （表示専用のため。安全チェックは削除時に別途 fail-closed で行う）。
+pub fn count_files(paths: &[PathBuf]) -> usize {
+    paths.iter().map(|path| count_path(path)).sum()
+}
+
+fn count_path(path: &Path) -> usize {
+    let Ok(metadata) = std::fs::symlink_metadata(path) else {
+        return 0;
+    };
+    if !metadata.is_dir() {
+        return 1;
+    }
+    std::fs::read_dir(path)
+        .map(|entries| {
+            entries
+                .flatten()
+                .map(|entry| count_path(&entry.path()))
+                .sum()
+        })
+        .unwrap_or(0)
+}
+
+/// 件数と対象の一部を `output` へ表示し、`input` の回答で続行するか判定
+///
+/// `y` / `yes`（大文字小文字は無視）のみ続行とし、空行や入力終了は中止とする。
+pub fn prompt(
+    mut input: impl BufRead,
+    mut output: impl Write,
+    count: usize,
+    targets: &[PathBuf],
+) -> io::Result<bool> {
+    writeln!(
+        output,
+        "safe-rm: about to delete {} file(s) from {} path(s):",
+        count,
+        targets.len()
+    )?;
+    for target in targets.iter().take(SAMPLE_SIZE) {
+        writeln!(output, "  {}", target.display())?;
+    }
+    if targets.len() > SAMPLE_SIZE {
+        writeln!(output, "  ... and {} more", targets.len() - SAMPLE_SIZE)?;
+    }
+    write!(output, "Proceed? [y/N] ")?;
+    output.flush()?;
+
+    let mut answer = String::new();
+    input.read_line(&mut answer)?;
+    let answer = answer.trim();
+    Ok(answer.eq_ignore_ascii_case("y") || answer.eq_ignore_ascii_case("yes"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use std::io::Cursor;
+
+    fn ask(answer: &str, targets: &[PathBuf]) -> (bool, String) {
+        let mut output = Vec::new();
+        let proceed = prompt(Cursor::new(answer), &mut output, 42, targets).unwrap();
+        (proceed, String::from_utf8(output).unwrap())
+    }
+
+    #[test]
+    fn test_interactive_once_applies() {
+        assert!(!interactive_once_applies(3, false));
+        assert!(interactive_once_applies(4, false));
+        assert!(interactive_once_applies(1, true));
+    }
+
+    #[test]
+    fn test_prompt_accepts_only_yes() {
+        let targets = vec![PathBuf::from("build")];
+        assert!(ask("y\n", &targets).0);
+        assert!(ask("YES\n", &targets).0);
+        assert!(!ask("n\n", &targets).0);
+        assert!(!ask("\n", &targets).0);
+        assert!(!ask("", &targets).0);
+    }
+
+    #[test]
+    fn test_prompt_shows_count_and_sample() {
+        let targets: Vec<PathBuf> = (0..8)
+            .map(|i| PathBuf::from(format!("f{}.txt", i)))
+            .collect();
+        let (_, output) = ask("n\n", &targets);
+        assert!(output.starts_with("safe-rm: about to delete 42 file(s) from 8 path(s):"));
+        assert!(output.contains("  f4.txt\n"));
+        assert!(!output.contains("f5.txt"));
+        assert!(output.contains("... and 3 more"));
+        assert!(output.ends_with("Proceed? [y/N] "));
+    }
+
+    #[test]
+    fn test_count_files_includes_directory_contents() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let dir = temp_dir.path().join("dir");
+        fs::create_dir_all(dir.join("sub")).unwrap();
+        fs::write(dir.join("a.txt"), "a").unwrap();
+        fs::write(dir.join("sub").join("b.txt"), "b").unwrap();
+        fs::write(temp_dir.path().join("c.txt"), "c").unwrap();
+
+        let paths = vec![
+            dir,
+            temp_dir.path().join("c.txt"),
+            temp_dir.path().join("missing.txt"),
+        ];
+        assert_eq!(count_files(&paths), 3);
+    }
+}
@@ -0,0 +1,577 @@
+//! safe-rm のライブラリ向け削除 API
+//!
+//! CLI と同じ安全チェック（空パス拒否 → allowed_paths / 包含検証・deletion_scope →
+//! 特殊ファイル → protected_patterns → ネストしたリポジトリ → Git ステータス → pre_delete_hook）を適用して削除し、
+//! 結果を `DeleteReport` として返す。
+//! 出力は行わないため、組み込み側で表示や取り消し（undo）を実装できる。
+
+use crate::config::Config;
+use crate::error::{FileStatus, SafeRmError};
+use crate::git_checker::GitChecker;
+use crate::hook;
+use crate::lock::RepoLock;
+use crate::path_checker::PathChecker;
+use crate::retry::{self, FsRemover, RemoveKind, RetryPolicy};
+use path_clean::PathClean;
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// `safe_delete` のオプション
+#[derive(Debug, Clone, Default)]
+pub struct DeleteOptions {
+    /// ディレクトリを再帰的に削除
+    pub recursive: bool,
+    /// 存在しないパスを無視
+    pub force: bool,
+    /// 削除せずに判定のみ行う
+    pub dry_run: bool,
+    /// 指定時、このバイト数以下の通常ファイルの内容を undo バッファに保持
+    pub undo_capture_limit: Option<u64>,
+    /// 再帰削除の対象にネストした Git リポジトリが含まれていても許可
+    pub allow_nested_repos: bool,
+}
+
+/// undo バッファの1エントリ
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UndoEntry {
+    /// 削除したパス（絶対パス）
+    pub path: PathBuf,
+    /// 削除前の内容（ディレクトリ・上限超過・読み取り失敗時は None）
+    pub contents: Option<Vec<u8>>,
+}
+
+impl UndoEntry {
+    /// 保持した内容を元のパスに書き戻す
+    pub fn restore(&self) -> std::io::Result<bool> {
+        match &self.contents {
+            Some(contents) => fs::write(&self.path, contents).map(|_| true),
+            None => Ok(false),
+        }
+    }
+}
+
+/// 処理したパス
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ReportEntry {
+    /// 引数として渡されたパス
+    pub path: PathBuf,
+    /// `cwd` 基準で解決し正規化した絶対パス（シンボリックリンクは解決しない）
+    pub resolved: PathBuf,
+}
+
+/// 削除せずにスキップした理由
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SkipReason {
+    /// ドライラン（安全チェックは通過し、削除対象と判定）
+    DryRun,
+    /// `force` 指定時の存在しないパス
+    Missing,
+}
+
+/// `safe_delete` の結果
+///
+/// 各パスは `deleted` / `skipped` / `blocked` / `not_found` / `failed` のいずれか1つに入る。
+#[derive(Debug, Default)]
+pub struct DeleteReport {
+    /// 削除したパス
+    pub deleted: Vec<ReportEntry>,
+    /// 削除せずにスキップしたパスと理由
+    pub skipped: Vec<(ReportEntry, SkipReason)>,
+    /// 安全チェックでブロックしたパスと理由（終了コード 2 のエラー）
+    pub blocked: Vec<(ReportEntry, SafeRmError)>,
+    /// 存在しないパス（`force` 指定時は `skipped` に入る）
+    pub not_found: Vec<ReportEntry>,
+    /// I/O エラーなどで削除に失敗したパスとエラー
+    pub failed: Vec<(ReportEntry, SafeRmError)>,
+    /// undo バッファ（`undo_capture_limit` 指定時のみ、削除順）
+    pub undo: Vec<UndoEntry>,
+    /// `directory_read_policy = "skip_unreadable"` でチェックから除外したディレクトリ
+    pub skipped_unreadable: Vec<PathBuf>,
+}
+
+impl DeleteReport {
+    /// CLI と同じ規則の終了コード
+    ///
+    /// 失敗がなければ 0、それ以外は CLI の最終結果と同じく `PartialFailure` の
+    /// 終了コード（ブロックを含めば 2、それ以外は 1）。
+    pub fn exit_code(&self) -> u8 {
+        if self.blocked.is_empty() && self.not_found.is_empty() && self.failed.is_empty() {
+            return 0;
+        }
+        SafeRmError::PartialFailure {
+            removed: self.deleted.len(),
+            blocked: self.blocked.len(),
+            not_found: self.not_found.len(),
+            io_errors: self.failed.len(),
+        }
+        .exit_code()
+    }
+
+    /// 処理結果を分類して追加
+    fn push(&mut self, entry: ReportEntry, result: Result<Option<SkipReason>, SafeRmError>) {
+        match result {
+            Ok(None) => self.deleted.push(entry),
+            Ok(Some(reason)) => self.skipped.push((entry, reason)),
+            Err(SafeRmError::NotFound(_)) => self.not_found.push(entry),
+            Err(e) if e.exit_code() == 2 => self.blocked.push((entry, e)),
+            Err(e) => self.failed.push((entry, e)),
+        }
+    }
+}
+
+/// 安全チェックを適用してパスを削除
+///
+/// `cwd` は相対パスの解決基準兼リポジトリ検出の起点。
+pub fn safe_delete(
+    cwd: &Path,
+    paths: &[PathBuf],
+    config: &Config,
+    options: &DeleteOptions,
+) -> DeleteReport {
+    let mut report = DeleteReport::default();
+
+    let git_checker = match GitChecker::try_open(cwd) {
+        Ok(checker) => checker.map(|c| {
+            c.with_policy(config.deletable)
+                .with_generated_as_deletable(config.treat_generated_as_deletable)
+                .with_directory_read_policy(config.directory_read_policy)
+                .with_status_timeout(config.git_timeout())
+        }),
+        Err(e) if config.requires_git_repository() => {
+            for path in paths {
+                let error = SafeRmError::GitUnavailable {
+                    path: cwd.to_path_buf(),
+                    reason: e.to_string(),
+                };
+                report.push(report_entry(cwd, path), Err(error));
+            }
+            return report;
+        }
+        Err(_) => None,
+    };
+    let project_root = config.project_boundary(
+        git_checker.as_ref().and_then(|checker| checker.workdir()),
+        cwd,
+    );
+    // use_lock: ステータス取得から削除まで同じリポジトリでの同時実行を直列化
+    let _lock = match git_checker.as_ref().filter(|_| config.use_lock) {
+        Some(checker) => match RepoLock::acquire(checker.common_dir(), config.lock_timeout()) {
+            Ok(lock) => Some(lock),
+            Err(e) => {
+                for path in paths {
+                    report.push(report_entry(cwd, path), Err(copy_lock_error(&e)));
+                }
+                return report;
+            }
+        },
+        None => None,
+    };
+    let status_cache = match &git_checker {
+        Some(checker) if !config.allow_project_deletion => match checker.try_get_statuses(true) {
+            Ok(cache) => cache,
+            // ステータスが分からないまま削除しないよう、全パスを失敗として返す
+            Err(_) => {
+                for path in paths {
+                    let error = SafeRmError::GitTimeout {
+                        path: project_root.clone(),
+                        timeout_ms: config.git_timeout_ms.unwrap_or_default(),
+                    };
+                    report.push(report_entry(cwd, path), Err(error));
+                }
+                return report;
+            }
+        },
+        _ => HashMap::new(),
+    };
+
+    let ctx = Context {
+        cwd,
+        project_root: &project_root,
+        git_checker: git_checker.as_ref(),
+        status_cache: &status_cache,
+        config,
+        options,
+    };
+    for path in paths {
+        let result = delete_one(path, &ctx, &mut report.undo, &mut report.skipped_unreadable);
+        debug_log!(
+            "{}: {}",
+            path.display(),
+            match &result {
+                Ok(None) => "deleted".to_string(),
+                Ok(Some(reason)) => format!("skipped ({:?})", reason),
+                Err(e) if e.exit_code() == 2 => format!("blocked ({})", e.code()),
+                Err(e) => format!("failed ({})", e.code()),
+            }
+        );
+        report.push(report_entry(cwd, path), result);
+    }
+    if let Some(checker) = &git_checker {
+        report
+            .skipped_unreadable
+            .extend(checker.take_skipped_unreadable());
+    }
+    report.skipped_unreadable.sort();
+    report.skipped_unreadable.dedup();
+    report
+}
+
+/// ロック取得の失敗を全パスの結果として複製（SafeRmError は Clone でないため）
+fn copy_lock_error(err: &SafeRmError) -> SafeRmError {
+    match err {
+        SafeRmError::LockTimeout { path, timeout_ms } => SafeRmError::LockTimeout {
+            path: path.clone(),
+            timeout_ms: *timeout_ms,
+        },
+        other => SafeRmError::IoError(std::io::Error::other(other.to_string())),
+    }
+}
+
+fn report_entry(cwd: &Path, path: &Path) -> ReportEntry {
+    ReportEntry {
+        path: path.to_path_buf(),
+        resolved: cwd.join(path).clean(),
+    }
+}
+
+struct Context<'a> {
+    cwd: &'a Path,
+    project_root: &'a Path,
+    git_checker: Option<&'a GitChecker>,
+    status_cache: &'a HashMap<Vec<u8>, FileStatus>,
+    config: &'a Config,
+    options: &'a DeleteOptions,
+}
+
+/// 1パスを処理（削除した場合は `Ok(None)`、スキップした場合はその理由）
+fn delete_one(
+    path: &Path,
+    ctx: &Context,
+    undo: &mut Vec<UndoEntry>,
+    skipped_unreadable: &mut Vec<PathBuf>,
+) -> Result<Option<SkipReason>, SafeRmError> {
+    let Context {
+        cwd,
+        project_root,
+        git_checker,
+        status_cache,
+        config,
+        options,
+    } = *ctx;
+
+    if path.as_os_str().is_empty() {
+        return Err(SafeRmError::InvalidPath {
+            path: path.to_path_buf(),
+            reason: "empty path argument".to_string(),
+        });
+    }
+
+    let abs_path = cwd.join(path);
+    let normalized_path = abs_path.clean();
+    let allowed = config
+        .allowed_path_bypass(&abs_path, project_root)
+        .is_some();
+
+    // allowed_paths 外は包含検証を最初に行う（プロジェクト外の存在情報を漏らさない）
+    let canonical_path = if allowed {
+        None
+    } else {
+        Some(PathChecker::verify_containment_with_case(
+            project_root,
+            cwd,
+            path,
+            config.case_insensitive,
+        )?)
+    };
+    if let Some(canonical_path) = &canonical_path {
+        if !config.is_within_deletion_scope(canonical_path, project_root) {
+            return Err(SafeRmError::OutsideDeletionScope {
+                path: path.to_path_buf(),
+                scopes: config.deletion_scopes(project_root),
+            });
+        }
+    }
+
+    let metadata = fs::symlink_metadata(&abs_path);
+    PathChecker::check_directory_assertion(path, &abs_path, &metadata)?;
+    let metadata = match metadata {
+        Ok(m) => m,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
+            return if options.force {
+                Ok(Some(SkipReason::Missing))
+            } else {
+                Err(SafeRmError::NotFound(abs_path))
+            };
+        }
+        Err(e) => return Err(SafeRmError::IoError(e)),
+    };
+
+    if metadata.is_dir() && !options.recursive {
+        return Err(SafeRmError::IsDirectory(abs_path));
+    }
+    if !options.force && !config.allow_special_files {
+        if let Some(kind) = PathChecker::special_file_kind(metadata.file_type()) {
+            return Err(SafeRmError::SpecialFile {
+                path: abs_path,
+                kind,
+            });
+        }
+    }
+
+    if !options.force
+        && metadata.is_dir()
+        && PathChecker::is_cwd_or_ancestor(&abs_path, cwd, config.case_insensitive)
+    {
+        return Err(SafeRmError::RefuseCwdDeletion {
+            path: normalized_path,
+            cwd: cwd.to_path_buf(),
+        });
+    }
+
+    if let Some(block) = config.is_blocked_by_policy(&abs_path, project_root)? {
+        return Err(block.into());
+    }
+    if metadata.is_dir() && !options.allow_nested_repos {
+        if let Some(path) = GitChecker::find_nested_repository(
+            &normalized_path,
+            config.directory_read_policy,
+            skipped_unreadable,
+        )? {
+            return Err(SafeRmError::NestedRepository { path });
+        }
+    }
+    if let Some(min_age) = config.min_age_seconds.filter(|_| !options.force) {
+        if let Ok(modified) = metadata.modified() {
+            let age = std::time::SystemTime::now()
+                .duration_since(modified)
+                .map_or(0, |elapsed| elapsed.as_secs());
+            if age < min_age {
+                return Err(SafeRmError::TooRecent {
+                    path: abs_path,
+                    age,
+                });
+            }
+        }
+    }
+
+    let always_deletable =
+        !metadata.is_dir() && config.is_always_deletable_extension(&normalized_path);
+    if let Some(canonical_path) = &canonical_path {
+        let git_check_path = PathChecker::git_check_path(
+            &normalized_path,
+            canonical_path,
+            metadata.file_type().is_symlink(),
+        );
+        // コンフリクト中のファイルはモードに関係なくブロック
+        if let Some(checker) = git_checker {
+            checker.check_conflicts(&git_check_path)?;
+        }
+        if config.requires_git_check(canonical_path, project_root) && !always_deletable {
+            match git_checker {
+                Some(checker) => {
+                    checker.check_path_with_cache(&git_check_path, status_cache)?;
+                }
+                None if !config.deletable.allows(FileStatus::NotInRepo) => {
+                    return Err(SafeRmError::DirtyFiles {
+                        path: normalized_path,
+                        status: FileStatus::NotInRepo,
+                    });
+                }
+                None => {}
+            }
+        }
+    }
+
+    if let Some(hook) = config.pre_delete_hook_path() {
+        hook::run_pre_delete_hook(&hook, &normalized_path, options.dry_run)?;
+    }
+
+    if options.dry_run {
+        return Ok(Some(SkipReason::DryRun));
+    }
+
+    // 削除前に内容を退避（上限以下の通常ファイルのみ）
+    let entry = options.undo_capture_limit.map(|limit| UndoEntry {
+        path: normalized_path.clone(),
+        contents: (metadata.is_file() && metadata.len() <= limit)
+            .then(|| fs::read(&normalized_path).ok())
+            .flatten(),
+    });
+
+    let kind = if !metadata.is_dir() {
+        RemoveKind::File
+    } else if options.recursive {
+        RemoveKind::Tree
+    } else {
+        RemoveKind::EmptyDir
+    };
+    retry::remove_with_retry(
+        &FsRemover,
+        &abs_path,
+        kind,
+        &RetryPolicy::from_config(config),
+    )
+    .map_err(|e| SafeRmError::from_removal(&abs_path, e))?;
+
+    undo.extend(entry);
+    Ok(None)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_undo_captures_small_file_but_not_large() {
+        let temp_dir = TempDir::new().unwrap();
+        let cwd = temp_dir.path().canonicalize().unwrap();
+        fs::write(cwd.join("small.txt"), "small").unwrap();
+        fs::write(cwd.join("large.bin"), vec![0u8; 2048]).unwrap();
+
+        let options = DeleteOptions {
+            undo_capture_limit: Some(1024),
+            ..Default::default()
+        };
+        let report = safe_delete(
+            &cwd,
+            &[PathBuf::from("small.txt"), PathBuf::from("large.bin")],
+            &Config::default(),
+            &options,
+        );
+
+        assert_eq!(report.exit_code(), 0);
+        assert_eq!(report.deleted.len(), 2);
+        assert_eq!(report.undo.len(), 2);
+        assert_eq!(report.undo[0].path, cwd.join("small.txt"));
+        assert_eq!(report.undo[0].contents.as_deref(), Some(&b"small"[..]));
+        assert_eq!(report.undo[1].path, cwd.join("large.bin"));
+        assert!(report.undo[1].contents.is_none());
+
+        assert!(report.undo[0].restore().unwrap());
+        assert_eq!(fs::read_to_string(cwd.join("small.txt")).unwrap(), "small");
+        assert!(!report.undo[1].restore().unwrap());
+    }
+
+    #[test]
+    fn test_undo_not_captured_by_default() {
+        let temp_dir = TempDir::new().unwrap();
+        let cwd = temp_dir.path().canonicalize().unwrap();
+        fs::write(cwd.join("a.txt"), "a").unwrap();
+
+        let report = safe_delete(
+            &cwd,
+            &[PathBuf::from("a.txt")],
+            &Config::default(),
+            &DeleteOptions::default(),
+        );
+        assert_eq!(
+            report.deleted,
+            vec![ReportEntry {
+                path: PathBuf::from("a.txt"),
+                resolved: cwd.join("a.txt"),
+            }]
+        );
+        assert!(report.undo.is_empty());
+    }
+
+    #[test]
+    fn test_outside_project_is_blocked() {
+        let temp_dir = TempDir::new().unwrap();
+        let cwd = temp_dir.path().canonicalize().unwrap();
+
+        let report = safe_delete(
+            &cwd,
+            &[PathBuf::from("../outside.txt")],
+            &Config::default(),
+            &DeleteOptions::default(),
+        );
+        assert!(report.deleted.is_empty());
+        let (entry, error) = &report.blocked[0];
+        assert_eq!(entry.path, PathBuf::from("../outside.txt"));
+        assert_eq!(entry.resolved, cwd.parent().unwrap().join("outside.txt"));
+        assert!(matches!(error, SafeRmError::OutsideProject { .. }));
+        assert_eq!(report.exit_code(), 2);
+    }
+
+    #[test]
+    fn test_recent_file_is_blocked_unless_force() {
+        let temp_dir = TempDir::new().unwrap();
+        let cwd = temp_dir.path().canonicalize().unwrap();
+        fs::write(cwd.join("a.txt"), "a").unwrap();
+        let mut config = Config::default();
+        config.min_age_seconds = Some(600);
+
+        let report = safe_delete(
+            &cwd,
+            &[PathBuf::from("a.txt")],
+            &config,
+            &DeleteOptions::default(),
+        );
+        assert!(matches!(report.blocked[0].1, SafeRmError::TooRecent { .. }));
+        assert!(cwd.join("a.txt").exists());
+
+        let options = DeleteOptions {
+            force: true,
+            ..Default::default()
+        };
+        let report = safe_delete(&cwd, &[PathBuf::from("a.txt")], &config, &options);
+        assert_eq!(report.deleted.len(), 1);
+    }
+
+    #[test]
+    fn test_dry_run_keeps_file() {
+        let temp_dir = TempDir::new().unwrap();
+        let cwd = temp_dir.path().canonicalize().unwrap();
+        fs::write(cwd.join("a.txt"), "a").unwrap();
+
+        let options = DeleteOptions {
+            dry_run: true,
+            undo_capture_limit: Some(1024),
+            ..Default::default()
+        };
+        let report = safe_delete(
+            &cwd,
+            &[PathBuf::from("a.txt")],
+            &Config::default(),
+            &options,
+        );
+        assert!(report.deleted.is_empty());
+        assert_eq!(report.skipped[0].1, SkipReason::DryRun);
+        assert!(report.undo.is_empty());
+        assert!(cwd.join("a.txt").exists());
+    }
+
+    #[test]
+    fn test_missing_paths_and_mixed_exit_code() {
+        let temp_dir = TempDir::new().unwrap();
+        let cwd = temp_dir.path().canonicalize().unwrap();
+        let paths = [
+            PathBuf::from("missing.txt"),
+            PathBuf::from("../outside.txt"),
+        ];
+
+        let report = safe_delete(
+            &cwd,
+            &paths[..1],
+            &Config::default(),
+            &DeleteOptions::default(),
+        );
+        assert_eq!(report.not_found[0].resolved, cwd.join("missing.txt"));
+        assert_eq!(report.exit_code(), 1);
+
+        let report = safe_delete(&cwd, &paths, &Config::default(), &DeleteOptions::default());
+        assert_eq!(report.not_found.len(), 1);
+        assert_eq!(report.blocked.len(), 1);
+        assert_eq!(report.exit_code(), 2);
+
+        let options = DeleteOptions {
+            force: true,
+            ..Default::default()
+        };
+        let report = safe_delete(&cwd, &paths[..1], &Config::default(), &options);
+        assert_eq!(report.skipped[0].1, SkipReason::Missing);
+        assert_eq!(report.exit_code(), 0);
+    }
+}
@@ -2,11 +2,13 @@
 //!
 //! SafeRmError および関連型を定義し、全エラー状態を処理する。
 
+use crate::config::{ExitCodes, PolicyBlock};
+use serde::Serialize;
 use std::fmt;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 
 /// Git 追跡ファイルのステータス（エラーメッセージ用の前方宣言）
-#[derive(Debug, Clone, Copy, PartialEq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize)]
 pub enum FileStatus {
     /// HEAD と一致（削除許可）
     Clean,
@@ -18,6 +20,10 @@ pub enum FileStatus {
     Staged,
     /// 未追跡（削除禁止）
     Untracked,
+    /// マージコンフリクト中（常に削除禁止）
+    Conflicted,
+    /// `--since` の参照から内容が変わった、または参照に存在しない（常に削除禁止）
+    ChangedSinceRef,
     /// Git 管理外
     NotInRepo,
 }
@@ -30,6 +36,8 @@ impl fmt::Display for FileStatus {
             Self::Modified => write!(f, "Modified"),
             Self::Staged => write!(f, "Staged"),
             Self::Untracked => write!(f, "Untracked"),
+            Self::Conflicted => write!(f, "Conflicted"),
+            Self::ChangedSinceRef => write!(f, "ChangedSinceRef"),
             Self::NotInRepo => write!(f, "NotInRepo"),
         }
     }
@@ -43,25 +51,77 @@ pub enum SafeRmError {
     NotFound(PathBuf),
     /// ディレクトリに -r フラグなし
     IsDirectory(PathBuf),
-    /// 部分的な失敗
-    PartialFailure { success: usize, failed: usize },
+    /// 末尾が `/` のパス引数がディレクトリ以外に解決された
+    NotADirectory { path: PathBuf },
+    /// -d 指定時に空でないディレクトリ
+    DirectoryNotEmpty(PathBuf),
+    /// 削除時に権限不足（親ディレクトリが書き込み不可など。再試行では解決しない）
+    PermissionDenied(PathBuf),
+    /// 一括削除の確認で中止（count は削除予定のファイル数）
+    ConfirmationDeclined { count: usize },
+    /// 部分的な失敗（カテゴリ別件数。ブロックを含む場合は終了コード 2）
+    PartialFailure {
+        removed: usize,
+        blocked: usize,
+        not_found: usize,
+        io_errors: usize,
+    },
 
     // ブロックエラー（Exit 2）
+    /// 空文字列などの無効なパス引数
+    InvalidPath { path: PathBuf, reason: String },
     /// シェル展開を含むパス（セキュリティリスク）
     ShellExpansionDetected { path: String, pattern: String },
     /// 危険なオプションの使用
     DangerousOption { option: String },
     /// ディレクトリ読み取り失敗（fail-closed）
     DirectoryReadError { path: PathBuf },
+    /// protected_patterns に一致するパス
+    ProtectedPattern { path: PathBuf, pattern: String },
+    /// denied_paths の配下、またはそれを含むディレクトリ
+    DeniedPath { path: PathBuf, denied: String },
+    /// Git リポジトリを開けない（strict モードでは fail-closed）
+    GitUnavailable { path: PathBuf, reason: String },
+    /// `--since` の参照をツリーに解決できない（変更の有無を判定できないため fail-closed）
+    InvalidGitRef { reference: String, reason: String },
     /// プロジェクト外へのアクセス
     OutsideProject {
         path: PathBuf,
         project_root: PathBuf,
     },
+    /// 他プロセスが開いているファイル（`--check-open=block`）
+    FileInUse { path: PathBuf, pid: u32 },
+    /// deletion_scope 外へのアクセス
+    OutsideDeletionScope { path: PathBuf, scopes: Vec<PathBuf> },
+    /// 展開後のパス数が max_paths を超過
+    TooManyPaths { count: usize, limit: usize },
+    /// `-r` の対象にネストした Git リポジトリ（サブモジュール等）が含まれる
+    NestedRepository { path: PathBuf },
+    /// 最終更新から min_age_seconds が経過していない（age は経過秒数）
+    TooRecent { path: PathBuf, age: u64 },
+    /// デバイスファイル・ソケット・FIFO などの特殊ファイル（kind は種別名）
+    SpecialFile { path: PathBuf, kind: &'static str },
+    /// カレントディレクトリ自体またはその祖先
+    RefuseCwdDeletion { path: PathBuf, cwd: PathBuf },
+    /// safe-rm 自身の設定ファイルまたはその親ディレクトリ
+    RefuseSelfConfig { path: PathBuf, config: PathBuf },
+    /// 一括削除の確認が必要だが非対話環境で `--yes` もない
+    ConfirmationRequired { count: usize },
+    /// pre_delete_hook が削除を拒否（0 以外で終了、または起動失敗）
+    HookRejected { path: PathBuf, reason: String },
     /// 未コミット変更のあるファイル
     DirtyFiles { path: PathBuf, status: FileStatus },
+    /// 削除できないファイルを含むディレクトリ（`--list-dirty` で全件を収集）
+    DirtyDirectory {
+        dir: PathBuf,
+        offenders: Vec<(PathBuf, FileStatus)>,
+    },
 
     // システムエラー（Exit 1）
+    /// git_timeout_ms 内に Git ステータスを取得できなかった（fail-closed）
+    GitTimeout { path: PathBuf, timeout_ms: u64 },
+    /// lock_timeout_ms 内に use_lock のロックを取得できなかった（fail-closed）
+    LockTimeout { path: PathBuf, timeout_ms: u64 },
     /// I/O エラー
     IoError(std::io::Error),
     /// Git 操作エラー
@@ -69,22 +129,106 @@ pub enum SafeRmError {
 }
 
 impl SafeRmError {
+    /// 削除処理の I/O エラーを変換（権限不足は `PermissionDenied`、それ以外は `IoError`）
+    pub fn from_removal(path: &Path, err: std::io::Error) -> Self {
+        if err.kind() == std::io::ErrorKind::PermissionDenied {
+            Self::PermissionDenied(path.to_path_buf())
+        } else {
+            Self::IoError(err)
+        }
+    }
+
+    /// 設定の `[exit_codes]` を反映した終了コードを取得
+    ///
+    /// `exit_code()` が 2 なら security_block。1 のうち、一部のパスは削除できた実行は
+    /// partial_failure、それ以外（何も削除できなかった実行を含む）は file_error。
+    pub fn exit_code_with(&self, codes: &ExitCodes) -> u8 {
+        match self {
+            _ if self.exit_code() == 2 => codes.security_block.get(),
+            Self::PartialFailure { removed, .. } if *removed > 0 => codes.partial_failure.get(),
+            _ => codes.file_error.get(),
+        }
+    }
+
     /// 終了コードを取得
     pub fn exit_code(&self) -> u8 {
         match self {
             // ブロック（安全のため削除を拒否）
-            Self::ShellExpansionDetected { .. }
+            Self::InvalidPath { .. }
+            | Self::ShellExpansionDetected { .. }
             | Self::DangerousOption { .. }
             | Self::DirectoryReadError { .. }
+            | Self::GitUnavailable { .. }
+            | Self::InvalidGitRef { .. }
+            | Self::ProtectedPattern { .. }
+            | Self::DeniedPath { .. }
             | Self::OutsideProject { .. }
-            | Self::DirtyFiles { .. } => 2,
+            | Self::OutsideDeletionScope { .. }
+            | Self::FileInUse { .. }
+            | Self::TooManyPaths { .. }
+            | Self::NestedRepository { .. }
+            | Self::TooRecent { .. }
+            | Self::SpecialFile { .. }
+            | Self::RefuseCwdDeletion { .. }
+            | Self::RefuseSelfConfig { .. }
+            | Self::ConfirmationRequired { .. }
+            | Self::HookRejected { .. }
+            | Self::DirtyFiles { .. }
+            | Self::DirtyDirectory { .. } => 2,
+            Self::PartialFailure { blocked, .. } if *blocked > 0 => 2,
             // ファイル操作エラー
-            Self::NotFound(_) | Self::IsDirectory(_) | Self::PartialFailure { .. } => 1,
+            Self::NotFound(_)
+            | Self::IsDirectory(_)
+            | Self::NotADirectory { .. }
+            | Self::DirectoryNotEmpty(_)
+            | Self::PermissionDenied(_)
+            | Self::ConfirmationDeclined { .. }
+            | Self::PartialFailure { .. } => 1,
             // その他のエラー
             _ => 1,
         }
     }
 
+    /// 機械可読なエラーコード（メッセージの文言や言語に依存しない安定した識別子）
+    ///
+    /// JSON 出力の `code` として使われるため、既存の値は変更しないこと。
+    pub fn code(&self) -> &'static str {
+        match self {
+            Self::NotFound(_) => "not_found",
+            Self::IsDirectory(_) => "is_directory",
+            Self::NotADirectory { .. } => "not_a_directory",
+            Self::DirectoryNotEmpty(_) => "directory_not_empty",
+            Self::PermissionDenied(_) => "permission_denied",
+            Self::PartialFailure { .. } => "partial_failure",
+            Self::InvalidPath { .. } => "invalid_path",
+            Self::ShellExpansionDetected { .. } => "shell_expansion",
+            Self::DangerousOption { .. } => "dangerous_option",
+            Self::DirectoryReadError { .. } => "directory_read_error",
+            Self::ProtectedPattern { .. } => "protected_pattern",
+            Self::DeniedPath { .. } => "denied_path",
+            Self::GitUnavailable { .. } => "git_unavailable",
+            Self::InvalidGitRef { .. } => "invalid_git_ref",
+            Self::GitTimeout { .. } => "git_timeout",
+            Self::LockTimeout { .. } => "lock_timeout",
+            Self::OutsideProject { .. } => "outside_project",
+            Self::FileInUse { .. } => "file_in_use",
+            Self::OutsideDeletionScope { .. } => "outside_deletion_scope",
+            Self::TooManyPaths { .. } => "too_many_paths",
+            Self::NestedRepository { .. } => "nested_repository",
+            Self::TooRecent { .. } => "too_recent",
+            Self::SpecialFile { .. } => "special_file",
+            Self::RefuseCwdDeletion { .. } => "refuse_cwd_deletion",
+            Self::RefuseSelfConfig { .. } => "refuse_self_config",
+            Self::ConfirmationRequired { .. } => "confirmation_required",
+            Self::ConfirmationDeclined { .. } => "confirmation_declined",
+            Self::HookRejected { .. } => "hook_rejected",
+            Self::DirtyFiles { .. } => "dirty_files",
+            Self::DirtyDirectory { .. } => "dirty_directory",
+            Self::IoError(_) => "io_error",
+            Self::GitError(_) => "git_error",
+        }
+    }
+
     /// AI と人間向けのエラーメッセージ
     pub fn user_message(&self) -> String {
         match self {
@@ -100,8 +244,39 @@ impl SafeRmError {
                     path.display()
                 )
             }
-            Self::PartialFailure { success, failed } => {
-                format!("{} file(s) removed, {} failed", success, failed)
+            Self::NotADirectory { path } => {
+                format!("cannot remove '{}': Not a directory", path.display())
+            }
+            Self::DirectoryNotEmpty(path) => {
+                format!(
+                    "cannot remove '{}': Directory not empty (use -r for recursive)",
+                    path.display()
+                )
+            }
+            Self::PermissionDenied(path) => {
+                format!("cannot remove '{}': Permission denied", path.display())
+            }
+            Self::PartialFailure {
+                removed,
+                blocked,
+                not_found,
+                io_errors,
+            } => {
+                format!(
+                    "{} file(s) removed, {} failed ({} blocked, {} not found, {} other error(s))",
+                    removed,
+                    blocked + not_found + io_errors,
+                    blocked,
+                    not_found,
+                    io_errors
+                )
+            }
+            Self::InvalidPath { path, reason } => {
+                format!(
+                    "無効なパスは許可されていません。\nPath: '{}'\nReason: {}",
+                    path.display(),
+                    reason
+                )
             }
             Self::ShellExpansionDetected { path, pattern } => {
                 format!(
@@ -121,6 +296,121 @@ impl SafeRmError {
                     path.display()
                 )
             }
+            Self::FileInUse { path, pid } => {
+                format!(
+                    "他のプロセスが開いているファイルは削除できません。\nPath: {}\nPID: {}",
+                    path.display(),
+                    pid
+                )
+            }
+            Self::OutsideDeletionScope { path, scopes } => {
+                let scopes: Vec<String> = scopes.iter().map(|s| s.display().to_string()).collect();
+                format!(
+                    "削除スコープ外へのアクセスは禁止されています。\nPath: {}\nScope: {}",
+                    path.display(),
+                    scopes.join(", ")
+                )
+            }
+            Self::TooManyPaths { count, limit } => {
+                format!(
+                    "指定されたパスが多すぎます（安全のため何も削除していません）。\nCount: {}\nLimit: {}",
+                    count, limit
+                )
+            }
+            Self::NestedRepository { path } => {
+                format!(
+                    "ネストした Git リポジトリを含むディレクトリは削除できません（履歴が失われます）。\nRepository: {}\n意図した削除であれば --allow-nested-repos を指定してください。",
+                    path.display()
+                )
+            }
+            Self::TooRecent { path, age } => {
+                format!(
+                    "最近変更されたファイルは削除できません（人間が確認できるよう時間を置いてください）。\nPath: {}\nAge: {}s\n意図した削除であれば -f を指定してください。",
+                    path.display(),
+                    age
+                )
+            }
+            Self::SpecialFile { path, kind } => {
+                format!(
+                    "特殊ファイル（{}）は削除できません。\nPath: {}\n意図した削除であれば -f を指定するか、設定で allow_special_files = true にしてください。",
+                    kind,
+                    path.display()
+                )
+            }
+            Self::RefuseCwdDeletion { path, cwd } => {
+                format!(
+                    "カレントディレクトリまたはその親ディレクトリは削除できません（実行中の作業ディレクトリが失われます）。\nPath: {}\nCwd: {}\n意図した削除であれば -f を指定してください。",
+                    path.display(),
+                    cwd.display()
+                )
+            }
+            Self::RefuseSelfConfig { path, config } => {
+                format!(
+                    "safe-rm の設定ファイル（またはそれを含むディレクトリ）は削除できません（安全設定が失われます）。\nPath: {}\nConfig: {}\n意図した削除であれば -f と --allow-self-config を指定してください。",
+                    path.display(),
+                    config.display()
+                )
+            }
+            Self::ConfirmationRequired { count } => {
+                format!(
+                    "{} 件のファイルの削除には確認が必要です（何も削除していません）。\n非対話環境では確認できないため、意図した削除であれば --yes を指定してください。",
+                    count
+                )
+            }
+            Self::ConfirmationDeclined { count } => {
+                format!(
+                    "{} 件のファイルの削除を中止しました（何も削除していません）。",
+                    count
+                )
+            }
+            Self::HookRejected { path, reason } => {
+                format!(
+                    "pre_delete_hook により削除が拒否されました。\nPath: {}\nReason: {}",
+                    path.display(),
+                    reason
+                )
+            }
+            Self::ProtectedPattern { path, pattern } => {
+                format!(
+                    "保護パターンに一致するファイルは削除できません。\nPath: {}\nPattern: {}",
+                    path.display(),
+                    pattern
+                )
+            }
+            Self::DeniedPath { path, denied } => {
+                format!(
+                    "denied_paths に含まれるパスは削除できません。\nPath: {}\nDenied: {}",
+                    path.display(),
+                    denied
+                )
+            }
+            Self::GitUnavailable { path, reason } => {
+                format!(
+                    "Git リポジトリを開けません（Git チェックができないため削除をブロック）。\nPath: {}\nReason: {}",
+                    path.display(),
+                    reason
+                )
+            }
+            Self::InvalidGitRef { reference, reason } => {
+                format!(
+                    "--since の参照を解決できません（変更の有無を判定できないため削除をブロック）。\nRef: {}\nReason: {}",
+                    reference, reason
+                )
+            }
+            Self::GitTimeout { path, timeout_ms } => {
+                format!(
+                    "Git ステータスの取得がタイムアウトしました（Git チェックができないため削除を中止）。\nRepository: {}\nTimeout: {}ms",
+                    path.display(),
+                    timeout_ms
+                )
+            }
+            Self::LockTimeout { path, timeout_ms } => {
+                format!(
+                    "別の safe-rm の実行がロックを保持しています（同時実行による競合を避けるため削除を中止）。\nLock: {}\nTimeout: {}ms",
+                    path.display(),
+                    timeout_ms
+                )
+            }
             Self::OutsideProject { path, project_root } => {
                 format!(
                     "プロジェクト外へのアクセスは禁止されています。\nPath: {}\nProject: {}",
@@ -128,6 +418,24 @@ impl SafeRmError {
                     project_root.display()
                 )
             }
+            Self::DirtyFiles {
+                path,
+                status: FileStatus::Conflicted,
+            } => {
+                format!(
+                    "マージコンフリクト中のファイルは削除できません。\nPath: {}\nStatus: Conflicted\n先にコンフリクトを解消してください。",
+                    path.display()
+                )
+            }
+            Self::DirtyFiles {
+                path,
+                status: FileStatus::ChangedSinceRef,
+            } => {
+                format!(
+                    "--since の参照以降に変更されたファイルは削除できません。\nPath: {}\nStatus: ChangedSinceRef",
+                    path.display()
+                )
+            }
             Self::DirtyFiles { path, status } => {
                 format!(
                     "未コミットの変更があるファイルは削除できません。\nPath: {}\nStatus: {}\n先にgit commitしてください。",
@@ -135,6 +443,18 @@ impl SafeRmError {
                     status
                 )
             }
+            Self::DirtyDirectory { dir, offenders } => {
+                let lines: Vec<String> = offenders
+                    .iter()
+                    .map(|(path, status)| format!("  {} ({})", path.display(), status))
+                    .collect();
+                format!(
+                    "削除できないファイルを含むディレクトリは削除できません（{} 件）。\nPath: {}\n{}\n先にgit commitしてください。",
+                    offenders.len(),
+                    dir.display(),
+                    lines.join("\n")
+                )
+            }
             Self::IoError(e) => format!("I/O error: {}", e),
             Self::GitError(e) => format!("Git error: {}", e),
         }
@@ -163,6 +483,15 @@ impl From<std::io::Error> for SafeRmError {
     }
 }
 
+impl From<PolicyBlock> for SafeRmError {
+    fn from(block: PolicyBlock) -> Self {
+        match block {
+            PolicyBlock::Protected { path, pattern } => Self::ProtectedPattern { path, pattern },
+            PolicyBlock::Denied { path, denied } => Self::DeniedPath { path, denied },
+        }
+    }
+}
+
 impl From<git2::Error> for SafeRmError {
     fn from(err: git2::Error) -> Self {
         Self::GitError(err)
@@ -206,12 +535,59 @@ mod tests {
         );
         assert_eq!(
             SafeRmError::PartialFailure {
-                success: 2,
-                failed: 1
+                removed: 2,
+                blocked: 0,
+                not_found: 1,
+                io_errors: 0
             }
             .exit_code(),
             1
         );
+        assert_eq!(
+            SafeRmError::PartialFailure {
+                removed: 0,
+                blocked: 1,
+                not_found: 1,
+                io_errors: 0
+            }
+            .exit_code(),
+            2
+        );
+    }
+
+    #[test]
+    fn test_exit_code_with_custom_codes() {
+        let codes: ExitCodes =
+            toml::from_str("security_block = 20\nfile_error = 10\npartial_failure = 30").unwrap();
+        let dirty = SafeRmError::DirtyFiles {
+            path: PathBuf::from("./file.txt"),
+            status: FileStatus::Modified,
+        };
+        assert_eq!(dirty.exit_code_with(&codes), 20);
+        assert_eq!(
+            SafeRmError::NotFound(PathBuf::from("missing.txt")).exit_code_with(&codes),
+            10
+        );
+        let partial = |blocked| SafeRmError::PartialFailure {
+            removed: 1,
+            blocked,
+            not_found: 1,
+            io_errors: 0,
+        };
+        assert_eq!(partial(0).exit_code_with(&codes), 30);
+        assert_eq!(partial(1).exit_code_with(&codes), 20);
+        // 何も削除できなかった場合は部分的な失敗ではない
+        let nothing_removed = SafeRmError::PartialFailure {
+            removed: 0,
+            blocked: 0,
+            not_found: 2,
+            io_errors: 0,
+        };
+        assert_eq!(nothing_removed.exit_code_with(&codes), 10);
+
+        // デフォルトは exit_code() と同じ
+        assert_eq!(dirty.exit_code_with(&ExitCodes::default()), 2);
+        assert_eq!(partial(0).exit_code_with(&ExitCodes::default()), 1);
     }
 
     #[test]
@@ -224,22 +600,36 @@ mod tests {
 
     #[test]
     fn test_user_message_is_directory() {
+        let err = SafeRmError::DirectoryNotEmpty(PathBuf::from("./full"));
+        assert_eq!(err.exit_code(), 1);
+        assert!(err.user_message().contains("Directory not empty"));
+
         let err = SafeRmError::IsDirectory(PathBuf::from("./mydir"));
         let msg = err.user_message();
         assert!(msg.contains("mydir"));
         assert!(msg.contains("Is a directory"));
         assert!(msg.contains("-r"));
+
+        let err = SafeRmError::NotADirectory {
+            path: PathBuf::from("./foo/"),
+        };
+        assert_eq!(err.exit_code(), 1);
+        assert_eq!(err.code(), "not_a_directory");
+        assert!(err.user_message().contains("Not a directory"));
     }
 
     #[test]
     fn test_user_message_partial_failure() {
         let err = SafeRmError::PartialFailure {
-            success: 3,
-            failed: 2,
+            removed: 3,
+            blocked: 1,
+            not_found: 1,
+            io_errors: 0,
         };
         let msg = err.user_message();
         assert!(msg.contains("3 file(s) removed"));
         assert!(msg.contains("2 failed"));
+        assert!(msg.contains("1 blocked, 1 not found, 0 other error(s)"));
     }
 
     #[test]
@@ -331,6 +721,30 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_exit_code_invalid_path_returns_2() {
+        assert_eq!(
+            SafeRmError::InvalidPath {
+                path: PathBuf::new(),
+                reason: "empty path".into()
+            }
+            .exit_code(),
+            2
+        );
+    }
+
+    #[test]
+    fn test_user_message_invalid_path() {
+        let err = SafeRmError::InvalidPath {
+            path: PathBuf::new(),
+            reason: "empty path".into(),
+        };
+        let msg = err.user_message();
+        assert!(msg.contains("Path: ''"));
+        assert!(msg.contains("empty path"));
+        assert!(msg.contains("無効なパス"));
+    }
+
     #[test]
     fn test_user_message_shell_expansion() {
         let err = SafeRmError::ShellExpansionDetected {
@@ -363,6 +777,196 @@ mod tests {
         assert!(msg.contains("ディレクトリの読み取り"));
     }
 
+    #[test]
+    fn test_file_in_use_returns_2() {
+        let err = SafeRmError::FileInUse {
+            path: PathBuf::from("/repo/app.log"),
+            pid: 4242,
+        };
+        assert_eq!(err.exit_code(), 2);
+        assert!(err.user_message().contains("PID: 4242"));
+    }
+
+    #[test]
+    fn test_outside_deletion_scope_returns_2() {
+        let err = SafeRmError::OutsideDeletionScope {
+            path: PathBuf::from("src/main.rs"),
+            scopes: vec![PathBuf::from("/repo/tmp"), PathBuf::from("/repo/out")],
+        };
+        assert_eq!(err.exit_code(), 2);
+        let msg = err.user_message();
+        assert!(msg.contains("src/main.rs"));
+        assert!(msg.contains("/repo/tmp, /repo/out"));
+    }
+
+    #[test]
+    fn test_too_many_paths_returns_2() {
+        let err = SafeRmError::TooManyPaths {
+            count: 1200,
+            limit: 1000,
+        };
+        assert_eq!(err.exit_code(), 2);
+        let msg = err.user_message();
+        assert!(msg.contains("Count: 1200"));
+        assert!(msg.contains("Limit: 1000"));
+    }
+
+    #[test]
+    fn test_nested_repository_returns_2() {
+        let err = SafeRmError::NestedRepository {
+            path: PathBuf::from("/repo/vendor/lib"),
+        };
+        assert_eq!(err.exit_code(), 2);
+        assert_eq!(err.code(), "nested_repository");
+        let msg = err.user_message();
+        assert!(msg.contains("/repo/vendor/lib"));
+        assert!(msg.contains("--allow-nested-repos"));
+    }
+
+    #[test]
+    fn test_confirmation_errors_exit_codes() {
+        let required = SafeRmError::ConfirmationRequired { count: 120 };
+        assert_eq!(required.exit_code(), 2);
+        assert!(required.user_message().contains("--yes"));
+        let declined = SafeRmError::ConfirmationDeclined { count: 120 };
+        assert_eq!(declined.exit_code(), 1);
+        assert_eq!(declined.code(), "confirmation_declined");
+    }
+
+    #[test]
+    fn test_too_recent_returns_2() {
+        let err = SafeRmError::TooRecent {
+            path: PathBuf::from("draft.txt"),
+            age: 3,
+        };
+        assert_eq!(err.exit_code(), 2);
+        assert_eq!(err.code(), "too_recent");
+        assert!(err.user_message().contains("Age: 3s"));
+    }
+
+    #[test]
+    fn test_special_file_returns_2() {
+        let err = SafeRmError::SpecialFile {
+            path: PathBuf::from("/repo/pipe"),
+            kind: "fifo",
+        };
+        assert_eq!(err.exit_code(), 2);
+        assert_eq!(err.code(), "special_file");
+        assert!(err.user_message().contains("（fifo）"));
+    }
+
+    #[test]
+    fn test_dirty_directory_lists_all_offenders() {
+        let err = SafeRmError::DirtyDirectory {
+            dir: PathBuf::from("/repo/src"),
+            offenders: vec![
+                (PathBuf::from("/repo/src/a.rs"), FileStatus::Modified),
+                (PathBuf::from("/repo/src/b.rs"), FileStatus::Untracked),
+            ],
+        };
+        assert_eq!(err.exit_code(), 2);
+        assert_eq!(err.code(), "dirty_directory");
+        let msg = err.user_message();
+        assert!(msg.contains("（2 件）"));
+        assert!(msg.contains("  /repo/src/a.rs (Modified)"));
+        assert!(msg.contains("  /repo/src/b.rs (Untracked)"));
+    }
+
+    #[test]
+    fn test_refuse_cwd_deletion_returns_2() {
+        let err = SafeRmError::RefuseCwdDeletion {
+            path: PathBuf::from("/repo"),
+            cwd: PathBuf::from("/repo/src"),
+        };
+        assert_eq!(err.exit_code(), 2);
+        assert_eq!(err.code(), "refuse_cwd_deletion");
+        assert!(err.user_message().contains("Cwd: /repo/src"));
+    }
+
+    #[test]
+    fn test_refuse_self_config_returns_2() {
+        let err = SafeRmError::RefuseSelfConfig {
+            path: PathBuf::from("/home/u/.config/safe-rm"),
+            config: PathBuf::from("/home/u/.config/safe-rm/config.toml"),
+        };
+        assert_eq!(err.exit_code(), 2);
+        assert_eq!(err.code(), "refuse_self_config");
+        assert!(
+            err.user_message()
+                .contains("Config: /home/u/.config/safe-rm/config.toml")
+        );
+        assert!(err.user_message().contains("--allow-self-config"));
+    }
+
+    #[test]
+    fn test_denied_path_returns_2() {
+        let err = SafeRmError::from(PolicyBlock::Denied {
+            path: PathBuf::from("/repo/vendor/a.rs"),
+            denied: "vendor".to_string(),
+        });
+        assert_eq!(err.exit_code(), 2);
+        assert_eq!(err.code(), "denied_path");
+        assert!(err.user_message().contains("Denied: vendor"));
+    }
+
+    #[test]
+    fn test_protected_pattern_returns_2() {
+        let err = SafeRmError::ProtectedPattern {
+            path: PathBuf::from("/repo/.env"),
+            pattern: ".env*".to_string(),
+        };
+        assert_eq!(err.exit_code(), 2);
+        let msg = err.user_message();
+        assert!(msg.contains("/repo/.env"));
+        assert!(msg.contains(".env*"));
+    }
+
+    #[test]
+    fn test_git_unavailable_returns_2_with_reason() {
+        let err = SafeRmError::GitUnavailable {
+            path: PathBuf::from("/tmp/repo"),
+            reason: "malformed .git".to_string(),
+        };
+        assert_eq!(err.exit_code(), 2);
+        let msg = err.user_message();
+        assert!(msg.contains("/tmp/repo"));
+        assert!(msg.contains("malformed .git"));
+    }
+
+    #[test]
+    fn test_invalid_git_ref_returns_2() {
+        let err = SafeRmError::InvalidGitRef {
+            reference: "v9".to_string(),
+            reason: "revspec 'v9' not found".to_string(),
+        };
+        assert_eq!(err.exit_code(), 2);
+        assert_eq!(err.code(), "invalid_git_ref");
+        assert!(err.user_message().contains("Ref: v9"));
+    }
+
+    #[test]
+    fn test_changed_since_ref_message() {
+        let err = SafeRmError::DirtyFiles {
+            path: PathBuf::from("/repo/a.txt"),
+            status: FileStatus::ChangedSinceRef,
+        };
+        assert_eq!(err.exit_code(), 2);
+        assert!(err.user_message().contains("Status: ChangedSinceRef"));
+    }
+
+    #[test]
+    fn test_git_timeout_returns_1() {
+        let err = SafeRmError::GitTimeout {
+            path: PathBuf::from("/tmp/repo"),
+            timeout_ms: 500,
+        };
+        assert_eq!(err.exit_code(), 1);
+        assert_eq!(err.code(), "git_timeout");
+        let msg = err.user_message();
+        assert!(msg.contains("/tmp/repo"));
+        assert!(msg.contains("500ms"));
+    }
+
     // --- IoError / GitError のテスト ---
 
     #[test]
@@ -395,6 +999,62 @@ mod tests {
         assert_eq!(SafeRmError::GitError(git_err).exit_code(), 1);
     }
 
+    #[test]
+    fn test_code_is_stable_identifier() {
+        assert_eq!(
+            SafeRmError::NotFound(PathBuf::from("a.txt")).code(),
+            "not_found"
+        );
+        assert_eq!(
+            SafeRmError::IsDirectory(PathBuf::from("dir")).code(),
+            "is_directory"
+        );
+        assert_eq!(
+            SafeRmError::OutsideProject {
+                path: PathBuf::from("/etc/passwd"),
+                project_root: PathBuf::from("/repo"),
+            }
+            .code(),
+            "outside_project"
+        );
+        assert_eq!(
+            SafeRmError::DirtyFiles {
+                path: PathBuf::from("a.txt"),
+                status: FileStatus::Modified,
+            }
+            .code(),
+            "dirty_files"
+        );
+        assert_eq!(
+            SafeRmError::IoError(std::io::Error::other("x")).code(),
+            "io_error"
+        );
+        assert_eq!(
+            SafeRmError::PermissionDenied(PathBuf::from("a.txt")).code(),
+            "permission_denied"
+        );
+    }
+
+    #[test]
+    fn test_from_removal_distinguishes_permission_denied() {
+        let denied = std::io::Error::from(std::io::ErrorKind::PermissionDenied);
+        let err = SafeRmError::from_removal(Path::new("locked/a.txt"), denied);
+        assert!(
+            matches!(&err, SafeRmError::PermissionDenied(path) if path == Path::new("locked/a.txt"))
+        );
+        assert_eq!(err.exit_code(), 1);
+        assert_eq!(
+            err.user_message(),
+            "cannot remove 'locked/a.txt': Permission denied"
+        );
+
+        let other = std::io::Error::other("disk on fire");
+        assert!(matches!(
+            SafeRmError::from_removal(Path::new("a.txt"), other),
+            SafeRmError::IoError(_)
+        ));
+    }
+
     // --- std::error::Error の source() テスト ---
 
     #[test]
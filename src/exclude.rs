@@ -0,0 +1,474 @@
+//! safe-rm の再帰削除時の除外（`--exclude`）
+//!
+//! `-r` でディレクトリを削除する際、除外パターンに一致するエントリをその場に残す。
+//! `remove_dir_all` は使えないため手動で走査し、除外エントリを含むディレクトリは残す。
+//! 同じ走査は削除件数を数えられるため `--progress` でも使用する。
+//! パターンの解釈は protected_patterns と同じく、`/` を含まなければファイル名と、
+//! 含めば削除対象ディレクトリからの相対パスと照合する。
+//! `--older-than` 指定時は基準より新しいファイルも同様に残す（こちらは表示しない）。
+//! `--one-file-system` 指定時は削除対象と異なるファイルシステム上のエントリ（マウントポイント）を
+//! 辿らずに残す（Unix のみ。`st_dev` で判定）。
+//! `--depth` 指定時は削除対象ディレクトリから N 階層より深いエントリを辿らずに残す。
+//! `--partial-dir` 指定時は Git のチェックで削除できないファイルを残す（こちらは呼び出し側で表示）。
+
+use crate::error::SafeRmError;
+use crate::retry::{Remover, RetryPolicy};
+use globset::{GlobBuilder, GlobMatcher};
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+/// コンパイル済み除外パターン
+#[derive(Debug, Default, Clone)]
+pub struct ExcludeSet {
+    matchers: Vec<(GlobMatcher, bool)>,
+    /// これより後に更新されたファイルは残す（`--older-than`）
+    newer_than: Option<SystemTime>,
+    /// 削除対象と異なるファイルシステムへ入らない（`--one-file-system`）
+    one_file_system: bool,
+    /// 削除対象ディレクトリから辿る階層の上限（`--depth`）
+    max_depth: Option<usize>,
+    /// 削除できないため残すファイル（削除対象ディレクトリからの相対パス、`--partial-dir`）
+    kept: Vec<PathBuf>,
+}
+
+/// 除外しながらツリーを削除した結果（いずれも削除対象ディレクトリからの相対パス、ソート済み）
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct TreeRemoval {
+    /// 除外パターンに一致して残したエントリ
+    pub excluded: Vec<PathBuf>,
+    /// 別のファイルシステムのため残したエントリ（`--one-file-system`）
+    pub mount_points: Vec<PathBuf>,
+    /// `--depth` の上限より深いため残したエントリ
+    pub too_deep: Vec<PathBuf>,
+}
+
+impl ExcludeSet {
+    /// パターンをコンパイル（無効なパターンは削除前にブロック）
+    pub fn new(patterns: &[String]) -> Result<Self, SafeRmError> {
+        let matchers = patterns
+            .iter()
+            .map(|pattern| {
+                GlobBuilder::new(pattern.trim_start_matches('/'))
+                    .literal_separator(true)
+                    .build()
+                    .map(|glob| (glob.compile_matcher(), !pattern.contains('/')))
+                    .map_err(|e| SafeRmError::InvalidPath {
+                        path: PathBuf::from(pattern),
+                        reason: format!("invalid exclude pattern: {}", e),
+                    })
+            })
+            .collect::<Result<_, _>>()?;
+        Ok(Self {
+            matchers,
+            newer_than: None,
+            one_file_system: false,
+            max_depth: None,
+            kept: Vec::new(),
+        })
+    }
+
+    /// 削除対象と異なるファイルシステム上のエントリを残すよう設定（`--one-file-system`）
+    pub fn one_file_system(mut self) -> Self {
+        self.one_file_system = true;
+        self
+    }
+
+    /// `--one-file-system` 指定時、判定の基準となる `root` のデバイス ID
+    ///
+    /// 未指定の場合や、デバイス ID を取得できない環境（非 Unix）では None。
+    pub fn root_device(&self, root: &Path) -> io::Result<Option<u64>> {
+        if !self.one_file_system {
+            return Ok(None);
+        }
+        Ok(device_id(&fs::symlink_metadata(root)?))
+    }
+
+    /// `cutoff` より後に更新されたファイルを残すよう設定（`--older-than`）
+    pub fn keep_newer_than(mut self, cutoff: SystemTime) -> Self {
+        self.newer_than = Some(cutoff);
+        self
+    }
+
+    /// 削除対象ディレクトリから `depth` 階層までのエントリのみ削除するよう設定（`--depth`）
+    pub fn max_depth(mut self, depth: usize) -> Self {
+        self.max_depth = Some(depth);
+        self
+    }
+
+    /// 削除対象ディレクトリからの相対パスで指定したファイルを残すよう設定（`--partial-dir`）
+    pub fn keep_paths(mut self, paths: Vec<PathBuf>) -> Self {
+        self.kept = paths;
+        self
+    }
+
+    /// 除外パターンも更新時刻の基準も `--one-file-system` も `--depth` も残すファイルも
+    /// 指定されていないか
+    pub fn is_empty(&self) -> bool {
+        self.matchers.is_empty()
+            && self.newer_than.is_none()
+            && !self.one_file_system
+            && self.max_depth.is_none()
+            && self.kept.is_empty()
+    }
+
+    /// 削除対象ディレクトリからの相対パスが `keep_paths` で残すファイルか
+    pub fn is_kept(&self, relative: &Path) -> bool {
+        self.kept.iter().any(|kept| kept == relative)
+    }
+
+    /// 削除対象ディレクトリからの相対パスが `--depth` の上限より深いか（上限未設定なら常に false）
+    ///
+    /// 直下のエントリを深さ1とする。
+    pub fn is_beyond_depth(&self, relative: &Path) -> bool {
+        self.max_depth
+            .is_some_and(|depth| relative.components().count() > depth)
+    }
+
+    /// `--older-than` の基準より後に更新されたか（基準未設定なら常に false）
+    ///
+    /// シンボリックリンクはリンク自体の更新時刻で判定する。
+    /// 更新時刻を取得できない場合は削除しない側（true）に倒す。
+    pub fn is_newer(&self, path: &Path) -> io::Result<bool> {
+        let Some(cutoff) = self.newer_than else {
+            return Ok(false);
+        };
+        let metadata = fs::symlink_metadata(path)?;
+        Ok(metadata
+            .modified()
+            .map_or(true, |modified| modified > cutoff))
+    }
+
+    /// 削除対象ディレクトリからの相対パスが除外対象か
+    pub fn is_excluded(&self, relative: &Path) -> bool {
+        let file_name = relative.file_name().map(Path::new);
+        self.matchers.iter().any(|(matcher, basename_only)| {
+            if *basename_only {
+                file_name.is_some_and(|name| matcher.is_match(name))
+            } else {
+                matcher.is_match(relative)
+            }
+        })
+    }
+}
+
+/// `path` が `root_device`（`ExcludeSet::root_device`）と異なるファイルシステム上にあるか
+///
+/// 基準がない場合は常に false。
+pub fn is_other_device(root_device: Option<u64>, path: &Path) -> io::Result<bool> {
+    match root_device {
+        Some(root_device) => Ok(device_id(&fs::symlink_metadata(path)?) != Some(root_device)),
+        None => Ok(false),
+    }
+}
+
+#[cfg(unix)]
+fn device_id(metadata: &fs::Metadata) -> Option<u64> {
+    use std::os::unix::fs::MetadataExt;
+    Some(metadata.dev())
+}
+
+#[cfg(not(unix))]
+fn device_id(_metadata: &fs::Metadata) -> Option<u64> {
+    None
+}
+
+/// 除外エントリを残してディレクトリツリーを削除
+///
+/// シンボリックリンクは辿らずリンク自体を削除する。除外エントリ、`--older-than` の基準より
+/// 新しいファイル、別のファイルシステム上のエントリ、`--depth` より深いエントリを含む
+/// ディレクトリ（`root` 自身を含む）は残す。
+/// ファイル（ディレクトリ以外）を1件削除するごとに `on_removed` を呼ぶ（`--progress` 用）。
+pub fn remove_tree_excluding(
+    remover: &impl Remover,
+    root: &Path,
+    excludes: &ExcludeSet,
+    policy: &RetryPolicy,
+    on_removed: &mut dyn FnMut(),
+) -> io::Result<TreeRemoval> {
+    let mut walk = Walk {
+        remover,
+        excludes,
+        policy,
+        on_removed,
+        root_device: excludes.root_device(root)?,
+        kept: TreeRemoval::default(),
+        retained: 0,
+    };
+    walk.remove_children(root, Path::new(""))?;
+    if walk.retained == 0 {
+        policy.run(|| remover.remove_dir(root))?;
+    }
+    let mut kept = walk.kept;
+    kept.excluded.sort();
+    kept.mount_points.sort();
+    kept.too_deep.sort();
+    Ok(kept)
+}
+
+/// 再帰削除の走査状態
+struct Walk<'a, R: Remover> {
+    remover: &'a R,
+    excludes: &'a ExcludeSet,
+    policy: &'a RetryPolicy,
+    on_removed: &'a mut dyn FnMut(),
+    root_device: Option<u64>,
+    kept: TreeRemoval,
+    /// 残したエントリの総数（新しいため残したファイルを含む）
+    retained: usize,
+}
+
+impl<R: Remover> Walk<'_, R> {
+    /// `dir` の中身を削除し、残したエントリを `kept` に追加
+    fn remove_children(&mut self, dir: &Path, relative: &Path) -> io::Result<()> {
+        for entry in fs::read_dir(dir)? {
+            let entry = entry?;
+            let path = entry.path();
+            let entry_relative = relative.join(entry.file_name());
+            if self.excludes.is_kept(&entry_relative) {
+                self.retained += 1;
+                continue;
+            }
+            if self.excludes.is_excluded(&entry_relative) {
+                self.kept.excluded.push(entry_relative);
+                self.retained += 1;
+                continue;
+            }
+            if self.excludes.is_beyond_depth(&entry_relative) {
+                self.kept.too_deep.push(entry_relative);
+                self.retained += 1;
+                continue;
+            }
+            if is_other_device(self.root_device, &path)? {
+                self.kept.mount_points.push(entry_relative);
+                self.retained += 1;
+                continue;
+            }
+
+            if entry.file_type()?.is_dir() {
+                let retained_before = self.retained;
+                self.remove_children(&path, &entry_relative)?;
+                if self.retained == retained_before {
+                    self.policy.run(|| self.remover.remove_dir(&path))?;
+                }
+            } else if self.excludes.is_newer(&path)? {
+                self.retained += 1;
+            } else {
+                self.policy.run(|| self.remover.remove_file(&path))?;
+                (self.on_removed)();
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::retry::FsRemover;
+    use std::time::Duration;
+
+    const NO_RETRY: RetryPolicy = RetryPolicy {
+        retries: 0,
+        delay: Duration::ZERO,
+    };
+
+    fn excludes(patterns: &[&str]) -> ExcludeSet {
+        let patterns: Vec<String> = patterns.iter().map(|p| p.to_string()).collect();
+        ExcludeSet::new(&patterns).unwrap()
+    }
+
+    #[test]
+    fn test_basename_and_relative_patterns() {
+        let set = excludes(&["*.keep", "sub/config"]);
+        assert!(set.is_excluded(Path::new("a.keep")));
+        assert!(set.is_excluded(Path::new("deep/nested/b.keep")));
+        assert!(set.is_excluded(Path::new("sub/config")));
+        assert!(!set.is_excluded(Path::new("other/sub/config")));
+        assert!(!set.is_excluded(Path::new("a.txt")));
+    }
+
+    #[test]
+    fn test_invalid_pattern_is_error() {
+        let err = ExcludeSet::new(&["[".to_string()]).unwrap_err();
+        assert!(err.user_message().contains("invalid exclude pattern"));
+    }
+
+    #[test]
+    fn test_remove_tree_excluding_keeps_entries_beyond_depth() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let root = temp_dir.path().join("build");
+        fs::create_dir_all(root.join("a").join("b")).unwrap();
+        fs::create_dir_all(root.join("shallow")).unwrap();
+        fs::write(root.join("top.o"), "t").unwrap();
+        fs::write(root.join("a").join("mid.o"), "m").unwrap();
+        fs::write(root.join("a").join("b").join("deep.o"), "d").unwrap();
+        fs::write(root.join("shallow").join("x.o"), "x").unwrap();
+
+        let kept = remove_tree_excluding(
+            &FsRemover,
+            &root,
+            &excludes(&[]).max_depth(2),
+            &NO_RETRY,
+            &mut || {},
+        )
+        .unwrap();
+
+        assert_eq!(kept.too_deep, vec![PathBuf::from("a/b/deep.o")]);
+        assert!(root.join("a").join("b").join("deep.o").exists());
+        assert!(!root.join("a").join("mid.o").exists());
+        assert!(!root.join("top.o").exists());
+        assert!(!root.join("shallow").exists());
+    }
+
+    #[test]
+    fn test_remove_tree_excluding_keeps_kept_paths_and_parents() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let root = temp_dir.path().join("src");
+        fs::create_dir_all(root.join("sub")).unwrap();
+        fs::create_dir_all(root.join("clean")).unwrap();
+        fs::write(root.join("sub").join("dirty.rs"), "d").unwrap();
+        fs::write(root.join("sub").join("ok.rs"), "o").unwrap();
+        fs::write(root.join("clean").join("a.rs"), "a").unwrap();
+
+        let kept = remove_tree_excluding(
+            &FsRemover,
+            &root,
+            &excludes(&[]).keep_paths(vec![PathBuf::from("sub/dirty.rs")]),
+            &NO_RETRY,
+            &mut || {},
+        )
+        .unwrap();
+
+        // 残したファイルは呼び出し側で表示するため除外の一覧には含めない
+        assert_eq!(kept, TreeRemoval::default());
+        assert!(root.join("sub").join("dirty.rs").exists());
+        assert!(!root.join("sub").join("ok.rs").exists());
+        assert!(!root.join("clean").exists());
+    }
+
+    #[test]
+    fn test_remove_tree_excluding_keeps_parents_of_excluded() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let root = temp_dir.path().join("build");
+        fs::create_dir_all(root.join("cache").join("deep")).unwrap();
+        fs::create_dir_all(root.join("out")).unwrap();
+        fs::write(root.join("cache").join("deep").join("keep.txt"), "k").unwrap();
+        fs::write(root.join("cache").join("drop.txt"), "d").unwrap();
+        fs::write(root.join("out").join("a.o"), "o").unwrap();
+
+        let kept = remove_tree_excluding(
+            &FsRemover,
+            &root,
+            &excludes(&["keep.txt"]),
+            &NO_RETRY,
+            &mut || {},
+        )
+        .unwrap();
+
+        assert_eq!(kept.excluded, vec![PathBuf::from("cache/deep/keep.txt")]);
+        assert!(root.join("cache").join("deep").join("keep.txt").exists());
+        assert!(!root.join("cache").join("drop.txt").exists());
+        assert!(!root.join("out").exists());
+    }
+
+    #[test]
+    fn test_remove_tree_excluding_counts_removed_files() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let root = temp_dir.path().join("build");
+        fs::create_dir_all(root.join("out")).unwrap();
+        fs::write(root.join("a.o"), "a").unwrap();
+        fs::write(root.join("out").join("b.o"), "b").unwrap();
+        fs::write(root.join("out").join("c.keep"), "c").unwrap();
+
+        let mut removed = 0;
+        remove_tree_excluding(
+            &FsRemover,
+            &root,
+            &excludes(&["*.keep"]),
+            &NO_RETRY,
+            &mut || removed += 1,
+        )
+        .unwrap();
+        assert_eq!(removed, 2);
+    }
+
+    #[test]
+    fn test_remove_tree_keeps_newer_files_silently() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let root = temp_dir.path().join("cache");
+        fs::create_dir_all(root.join("old")).unwrap();
+        fs::create_dir_all(root.join("mixed")).unwrap();
+        for name in ["old/a.bin", "mixed/b.bin", "mixed/new.bin"] {
+            fs::write(root.join(name), "x").unwrap();
+        }
+        let day_ago = SystemTime::now() - Duration::from_secs(86_400);
+        for name in ["old/a.bin", "mixed/b.bin"] {
+            fs::File::options()
+                .write(true)
+                .open(root.join(name))
+                .unwrap()
+                .set_modified(day_ago)
+                .unwrap();
+        }
+
+        let set = excludes(&[]).keep_newer_than(SystemTime::now() - Duration::from_secs(3600));
+        let kept = remove_tree_excluding(&FsRemover, &root, &set, &NO_RETRY, &mut || {}).unwrap();
+
+        // 新しいファイルは表示用の一覧には含めない
+        assert_eq!(kept, TreeRemoval::default());
+        assert!(!root.join("old").exists());
+        assert!(!root.join("mixed").join("b.bin").exists());
+        assert!(root.join("mixed").join("new.bin").exists());
+    }
+
+    #[test]
+    fn test_remove_tree_excluding_without_matches_removes_root() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let root = temp_dir.path().join("build");
+        fs::create_dir_all(root.join("out")).unwrap();
+        fs::write(root.join("out").join("a.o"), "o").unwrap();
+
+        let kept = remove_tree_excluding(
+            &FsRemover,
+            &root,
+            &excludes(&["*.keep"]),
+            &NO_RETRY,
+            &mut || {},
+        )
+        .unwrap();
+
+        assert_eq!(kept, TreeRemoval::default());
+        assert!(!root.exists());
+    }
+
+    #[test]
+    fn test_same_file_system_is_not_skipped() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let root = temp_dir.path().join("build");
+        fs::create_dir_all(root.join("out")).unwrap();
+        fs::write(root.join("out").join("a.o"), "o").unwrap();
+
+        let set = excludes(&[]).one_file_system();
+        assert!(!set.is_empty());
+        let kept = remove_tree_excluding(&FsRemover, &root, &set, &NO_RETRY, &mut || {}).unwrap();
+        assert_eq!(kept, TreeRemoval::default());
+        assert!(!root.exists());
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn test_other_device_detected_by_st_dev() {
+        // マウントを作れない環境でも、/proc は別ファイルシステムとして判定できる
+        let temp_dir = tempfile::tempdir().unwrap();
+        let root_device = excludes(&[])
+            .one_file_system()
+            .root_device(temp_dir.path())
+            .unwrap();
+        assert!(root_device.is_some());
+        assert!(!is_other_device(root_device, temp_dir.path()).unwrap());
+        assert!(is_other_device(root_device, Path::new("/proc")).unwrap());
+        assert!(!is_other_device(None, Path::new("/proc")).unwrap());
+    }
+}
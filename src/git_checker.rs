@@ -2,14 +2,44 @@
 //!
 //! Git リポジトリを検出し、安全な削除のためにファイルステータスを確認する。
 
+use crate::config::{DeletablePolicy, DirectoryReadPolicy};
 use crate::error::{FileStatus, SafeRmError};
-use git2::{Repository, Status, StatusOptions};
+use git2::{
+    AttrCheckFlags, AttrValue, IndexEntryExtendedFlag, IndexEntryFlag, ObjectType, Oid, Repository,
+    Status, StatusOptions, Tree,
+};
+use std::cell::{OnceCell, RefCell};
 use std::collections::HashMap;
 use std::path::{Path, PathBuf};
+use std::sync::mpsc;
+use std::time::Duration;
+
+/// ディレクトリ走査の最大深さ
+///
+/// シンボリックリンクは辿らないためループはしないが、異常に深いツリーで
+/// スタックを使い切らないよう、超えた場合は fail-closed（DirectoryReadError）とする。
+pub const MAX_DIRECTORY_DEPTH: usize = 256;
 
 /// Git ステータスチェッカー
 pub struct GitChecker {
     repo: Repository,
+    policy: DeletablePolicy,
+    /// `linguist-generated` 属性のファイルをステータスに関係なく削除許可
+    generated_deletable: bool,
+    /// ステータス一括取得の打ち切り時間（`git_timeout_ms`）
+    status_timeout: Option<Duration>,
+    /// `--since` で指定した参照のツリー（内容が異なるファイルをブロック）
+    since_tree: Option<Oid>,
+    /// ディレクトリチェックで読み取れないディレクトリの扱い（`directory_read_policy`）
+    directory_read_policy: DirectoryReadPolicy,
+    /// `skip_unreadable` で除外したディレクトリ（`take_skipped_unreadable` で取り出す）
+    skipped_unreadable: RefCell<Vec<PathBuf>>,
+    /// skip-worktree / assume-unchanged のインデックスエントリ（初回参照時に構築）
+    hidden_entries: OnceCell<HashMap<Vec<u8>, (Oid, u32)>>,
+    /// マージコンフリクト中のファイル（ワークディレクトリ相対、初回参照時に構築）
+    conflicts: OnceCell<Vec<PathBuf>>,
+    /// インデックスの全エントリのパス（ソート済み、初回参照時に構築。読めなければ None）
+    tracked_paths: OnceCell<Option<Vec<Vec<u8>>>>,
 }
 
 impl GitChecker {
@@ -22,7 +52,104 @@ impl GitChecker {
     /// * `Some(GitChecker)` - Git リポジトリが存在
     /// * `None` - Git リポジトリなし（Git チェックスキップ）
     pub fn open(path: &Path) -> Option<Self> {
-        Repository::discover(path).ok().map(|repo| Self { repo })
+        Self::try_open(path).ok().flatten()
+    }
+
+    /// 指定パスから Git リポジトリを検出して開く（初期化失敗を区別）
+    ///
+    /// 環境変数 `GIT_DIR` が設定されている場合は git と同じく `GIT_DIR` / `GIT_WORK_TREE` 等から
+    /// リポジトリを開く（`path` からの探索は行わない）。この場合は明示的な指定のため、
+    /// 開けなければリポジトリなしとせず `GitUnavailable` とする。
+    /// libgit2 のリンク不良などで panic した場合もエラーとして扱う。
+    ///
+    /// # Returns
+    /// * `Ok(Some(GitChecker))` - Git リポジトリが存在
+    /// * `Ok(None)` - Git リポジトリなし
+    /// * `Err(SafeRmError::GitUnavailable)` - 破損した `.git` など、リポジトリを開けない
+    ///   （libgit2 がリポジトリを見つけられなくても、`path` か祖先に `.git` があれば該当）
+    pub fn try_open(path: &Path) -> Result<Option<Self>, SafeRmError> {
+        let unavailable = |reason: String| SafeRmError::GitUnavailable {
+            path: path.to_path_buf(),
+            reason,
+        };
+
+        let from_env = std::env::var_os("GIT_DIR").is_some();
+        let open = || {
+            if from_env {
+                Repository::open_from_env()
+            } else {
+                Repository::discover(path)
+            }
+        };
+
+        match std::panic::catch_unwind(open) {
+            // HEAD が壊れていても開けてしまう場合がある。そのままではステータスが取れず
+            // すべて変更なしに見えるため、開けないものとして扱う（未コミットの新規リポジトリは可）
+            Ok(Ok(repo))
+                if repo
+                    .head()
+                    .is_err_and(|e| e.code() != git2::ErrorCode::UnbornBranch) =>
+            {
+                let reason = repo.head().err().map(|e| e.message().to_string());
+                debug_log!("invalid HEAD in {}: {:?}", repo.path().display(), reason);
+                Err(unavailable(format!(
+                    "cannot resolve HEAD: {}",
+                    reason.unwrap_or_default()
+                )))
+            }
+            Ok(Ok(repo)) => {
+                debug_log!(
+                    "repository discovered from {}: {}",
+                    path.display(),
+                    repo.workdir().unwrap_or(repo.path()).display()
+                );
+                Ok(Some(Self {
+                    repo,
+                    policy: DeletablePolicy::default(),
+                    generated_deletable: false,
+                    status_timeout: None,
+                    since_tree: None,
+                    directory_read_policy: DirectoryReadPolicy::default(),
+                    skipped_unreadable: RefCell::new(Vec::new()),
+                    hidden_entries: OnceCell::new(),
+                    conflicts: OnceCell::new(),
+                    tracked_paths: OnceCell::new(),
+                }))
+            }
+            // 上位まで走査してもリポジトリが見つからない（通常の「リポジトリ外」）。
+            // ただし `.git` があるのに見つからない場合は、HEAD の破損などで libgit2 が
+            // リポジトリと認識できないだけなので開けないものとして扱う
+            Ok(Err(e))
+                if !from_env
+                    && e.code() == git2::ErrorCode::NotFound
+                    && e.class() == git2::ErrorClass::Repository =>
+            {
+                match path
+                    .ancestors()
+                    .find(|dir| std::fs::symlink_metadata(dir.join(".git")).is_ok())
+                {
+                    Some(dir) => {
+                        debug_log!("unrecognized .git in {}: {}", dir.display(), e);
+                        Err(unavailable(format!(
+                            "{} is not a valid repository: {}",
+                            dir.join(".git").display(),
+                            e.message()
+                        )))
+                    }
+                    None => {
+                        debug_log!("no repository found from {}", path.display());
+                        Ok(None)
+                    }
+                }
+            }
+            Ok(Err(e)) => {
+                debug_log!("cannot open repository from {}: {}", path.display(), e);
+                Err(unavailable(e.message().to_string()))
+            }
+            Err(_) => Err(unavailable(
+                "libgit2 panicked during initialization".to_string(),
+            )),
+        }
     }
 
     /// Git リポジトリのワークディレクトリ（ルート）を取得
@@ -36,41 +163,138 @@ impl GitChecker {
             .map(|p| p.canonicalize().unwrap_or_else(|_| p.to_path_buf()))
     }
 
+    /// リポジトリの共通 Git ディレクトリ（worktree でも本体の `.git`）
+    ///
+    /// use_lock のロックファイルの置き場所。worktree 間でも同じロックを共有する。
+    pub fn common_dir(&self) -> &Path {
+        self.repo.commondir()
+    }
+
     /// 全ファイルのステータスを一括取得（バッチ処理用）
     ///
     /// 一度の Git API 呼び出しで全ステータスを取得し、HashMap として返す。
     /// これにより、多数のファイルを処理する際の API 呼び出し回数を削減。
     ///
     /// # Returns
-    /// * `HashMap<String, FileStatus>` - 相対パス → ステータスのマップ
-    pub fn get_all_statuses(&self) -> HashMap<String, FileStatus> {
-        let mut status_map = HashMap::new();
+    /// * `HashMap<Vec<u8>, FileStatus>` - 相対パス → ステータスのマップ
+    pub fn get_all_statuses(&self) -> HashMap<Vec<u8>, FileStatus> {
+        self.get_statuses(true)
+    }
+
+    /// 全ファイルのステータスを一括取得（無視ファイルと未追跡ディレクトリ配下を列挙するか指定）
+    ///
+    /// 未追跡ディレクトリの再帰はファイル数に比例して遅い（未追跡 5 万ファイルで
+    /// 約 220ms → 15ms）。`include_ignored = false` では無視ファイルの列挙とこの再帰を省き、
+    /// キャッシュにないパスは `get_file_status_from_cache` がその場で問い合わせるため
+    /// 判定結果は変わらない。個別の問い合わせが多くなる厳格モードでは true を使う。
+    pub fn get_statuses(&self, include_ignored: bool) -> HashMap<Vec<u8>, FileStatus> {
+        Self::collect_statuses(&self.repo, include_ignored)
+    }
+
+    /// 追跡中のファイルと無視されていない未追跡ファイル（ワークディレクトリ相対、ソート済み）
+    ///
+    /// `--pathspec` の照合対象（`git ls-files --cached --others --exclude-standard` 相当）。
+    /// 作業ツリーから既に削除された追跡ファイルは含めない。
+    pub fn worktree_files(&self) -> Result<Vec<PathBuf>, SafeRmError> {
+        let workdir = self
+            .repo
+            .workdir()
+            .ok_or_else(|| SafeRmError::GitUnavailable {
+                path: self.repo.path().to_path_buf(),
+                reason: "bare repository has no working tree".to_string(),
+            })?;
+        let mut files: Vec<Vec<u8>> = self.repo.index()?.iter().map(|entry| entry.path).collect();
 
         let mut opts = StatusOptions::new();
         opts.include_untracked(true);
-        opts.include_ignored(true);
         opts.recurse_untracked_dirs(true);
+        opts.include_ignored(false);
+        for entry in self.repo.statuses(Some(&mut opts))?.iter() {
+            if entry.status().contains(Status::WT_NEW) {
+                files.push(entry.path_bytes().to_vec());
+            }
+        }
+        files.sort();
+        files.dedup();
+
+        Ok(files
+            .into_iter()
+            .map(Self::path_from_git_bytes)
+            .filter(|path| workdir.join(path).symlink_metadata().is_ok())
+            .collect())
+    }
 
-        if let Ok(statuses) = self.repo.statuses(Some(&mut opts)) {
+    /// `get_statuses` に `with_status_timeout` の打ち切り時間を適用した版
+    ///
+    /// 打ち切り時間が設定されている場合、別スレッドで同じリポジトリを開き直して走査し、
+    /// 時間内に終わらなければ走査を待たずに `GitTimeout` を返す（スレッドは放置され、
+    /// プロセス終了時に破棄される）。未設定の場合は `get_statuses` と同じ。
+    pub fn try_get_statuses(
+        &self,
+        include_ignored: bool,
+    ) -> Result<HashMap<Vec<u8>, FileStatus>, SafeRmError> {
+        let Some(timeout) = self.status_timeout else {
+            return Ok(self.get_statuses(include_ignored));
+        };
+
+        let git_dir = self.repo.path().to_path_buf();
+        let workdir = self.repo.workdir().map(Path::to_path_buf);
+        let scan = move || {
+            // GIT_WORK_TREE 等で開いた場合もワークディレクトリを引き継ぐ
+            Repository::open(&git_dir)
+                .and_then(|repo| {
+                    if let Some(workdir) = &workdir {
+                        repo.set_workdir(workdir, false)?;
+                    }
+                    Ok(repo)
+                })
+                .map(|repo| Self::collect_statuses(&repo, include_ignored))
+                .unwrap_or_default()
+        };
+        run_with_timeout(timeout, scan).ok_or_else(|| SafeRmError::GitTimeout {
+            path: self
+                .workdir()
+                .unwrap_or_else(|| self.repo.path().to_path_buf()),
+            timeout_ms: timeout.as_millis().try_into().unwrap_or(u64::MAX),
+        })
+    }
+
+    /// リポジトリ全体のステータスを走査してマップにする（取得失敗時は空）
+    fn collect_statuses(repo: &Repository, include_ignored: bool) -> HashMap<Vec<u8>, FileStatus> {
+        let started = std::time::Instant::now();
+        let mut status_map = HashMap::new();
+
+        let mut opts = StatusOptions::new();
+        opts.include_untracked(true);
+        opts.include_ignored(include_ignored);
+        opts.recurse_untracked_dirs(include_ignored);
+
+        if let Ok(statuses) = repo.statuses(Some(&mut opts)) {
             for entry in statuses.iter() {
-                if let Some(path) = entry.path() {
-                    let status = Self::convert_status(entry.status());
-                    status_map.insert(path.to_string(), status);
-                }
+                // 非 UTF-8 のファイル名も落とさないようバイト列をキーにする
+                let status = Self::convert_status(entry.status());
+                status_map.insert(entry.path_bytes().to_vec(), status);
             }
         }
 
+        debug_log!(
+            "status cache built: {} entries (include_ignored = {}) in {:?}",
+            status_map.len(),
+            include_ignored,
+            started.elapsed()
+        );
         status_map
     }
 
     /// キャッシュからファイルステータスを取得
     ///
     /// `get_all_statuses()` で事前取得したキャッシュを使用。
-    /// キャッシュにない場合は Clean として扱う（Git 追跡済みで変更なし）。
+    /// キャッシュにない場合は Clean と決め打ちせず、その場で再問い合わせする。
+    /// スナップショット後に作成・変更されたファイルを Clean と誤判定しないため。
     pub fn get_file_status_from_cache(
         &self,
         path: &Path,
-        cache: &HashMap<String, FileStatus>,
+        cache: &HashMap<Vec<u8>, FileStatus>,
     ) -> FileStatus {
         let workdir = match self.repo.workdir() {
             Some(dir) => dir,
@@ -88,19 +312,18 @@ impl GitChecker {
         if let Some(&status) = cache.get(&path_key) {
             return status;
         }
+        trace_log!("status cache miss: {}", path.display());
 
         // キャッシュにない場合: .gitignore チェック
-        if self.is_ignored_path(path) {
+        // 追跡済みのファイルは .gitignore に一致しても無視されないため、
+        // スナップショット後に変更されていないか status_file で確認する
+        if self.is_ignored_path(path) && !self.is_tracked(&path_key) {
             return FileStatus::Ignored;
         }
 
-        // Git 追跡済みで変更がない（Clean）か、リポジトリ外
-        // status_file で確認
-        match self.repo.status_file(relative_path) {
-            Ok(status) if status.is_empty() => FileStatus::Clean,
-            Ok(status) => Self::convert_status(status),
-            Err(_) => FileStatus::NotInRepo,
-        }
+        // 追跡済みで変更なし（Clean）か、スナップショット後に変化したファイル。
+        // status_file が失敗した場合も NotInRepo に倒さず、statuses() で再確認する
+        self.get_file_status(path)
     }
 
     /// ファイルの Git ステータスを取得
@@ -118,7 +341,14 @@ impl GitChecker {
 
         // status_file を使用して直接ステータスを取得
         match self.repo.status_file(relative_path) {
-            Ok(status) => Self::convert_status(status),
+            Ok(status) => match Self::convert_status(status) {
+                // skip-worktree / assume-unchanged のファイルは変更があっても Clean と
+                // 報告されるため、インデックスの内容と比較して判定し直す
+                FileStatus::Clean => self
+                    .hidden_entry_status(path, &Self::to_git_relative_key(relative_path))
+                    .unwrap_or(FileStatus::Clean),
+                status => status,
+            },
             Err(e) => {
                 // ファイルが追跡されていない場合のエラーハンドリング
                 if e.code() == git2::ErrorCode::NotFound {
@@ -131,10 +361,8 @@ impl GitChecker {
                     let relative_path_key = Self::to_git_relative_key(relative_path);
                     if let Ok(statuses) = self.repo.statuses(Some(&mut opts)) {
                         for entry in statuses.iter() {
-                            if let Some(entry_path) = entry.path() {
-                                if entry_path == relative_path_key {
-                                    return Self::convert_status(entry.status());
-                                }
+                            if entry.path_bytes() == relative_path_key.as_slice() {
+                                return Self::convert_status(entry.status());
                             }
                         }
                     }
@@ -147,11 +375,112 @@ impl GitChecker {
         }
     }
 
+    /// skip-worktree / assume-unchanged（sparse-checkout 等）のファイルのステータス
+    ///
+    /// Git はこれらのワークツリー変更を報告しないため、ワークツリーの内容から blob ID を
+    /// 計算してインデックスと比較する。一致すれば Clean、異なるか読めなければ Modified。
+    /// フィルタ（改行変換など）は適用しないため、差異は安全側（Modified）に倒れる。
+    /// 対象外のファイルは None。
+    fn hidden_entry_status(&self, path: &Path, key: &[u8]) -> Option<FileStatus> {
+        let (id, mode) = self.hidden_entries().get(key)?;
+        Some(match Self::worktree_blob_id(path, *mode == 0o120000) {
+            Ok(actual) if actual == *id => FileStatus::Clean,
+            _ => FileStatus::Modified,
+        })
+    }
+
+    /// ワークツリーのファイル内容から blob ID を計算（シンボリックリンクはリンク先文字列）
+    fn worktree_blob_id(path: &Path, symlink: bool) -> std::io::Result<Oid> {
+        if symlink {
+            std::fs::read_link(path).and_then(|target| {
+                Oid::hash_object(ObjectType::Blob, target.as_os_str().as_encoded_bytes())
+                    .map_err(std::io::Error::other)
+            })
+        } else {
+            Oid::hash_file(ObjectType::Blob, path).map_err(std::io::Error::other)
+        }
+    }
+
+    /// skip-worktree / assume-unchanged ビットを持つインデックスエントリ
+    fn hidden_entries(&self) -> &HashMap<Vec<u8>, (Oid, u32)> {
+        self.hidden_entries.get_or_init(|| {
+            let Ok(index) = self.repo.index() else {
+                return HashMap::new();
+            };
+            index
+                .iter()
+                .filter(|entry| {
+                    IndexEntryExtendedFlag::from_bits_truncate(entry.flags_extended)
+                        .is_skip_worktree()
+                        || IndexEntryFlag::from_bits_truncate(entry.flags)
+                            .contains(IndexEntryFlag::VALID)
+                })
+                .map(|entry| (entry.path, (entry.id, entry.mode)))
+                .collect()
+        })
+    }
+
+    /// パス自体、またはその配下でマージコンフリクト中のファイルがあればブロック
+    ///
+    /// Git ステータスを確認しないモード（allow_project_deletion 等）でも使用し、
+    /// コンフリクト解消中の作業が黙って削除されるのを防ぐ。
+    pub fn check_conflicts(&self, path: &Path) -> Result<(), SafeRmError> {
+        let Some(workdir) = self.repo.workdir() else {
+            return Ok(());
+        };
+        let Ok(relative) = path.strip_prefix(workdir) else {
+            return Ok(());
+        };
+        match self
+            .conflicts()
+            .iter()
+            .filter(|conflict| conflict.starts_with(relative))
+            .min()
+        {
+            Some(conflict) => Err(SafeRmError::DirtyFiles {
+                path: workdir.join(conflict),
+                status: FileStatus::Conflicted,
+            }),
+            None => Ok(()),
+        }
+    }
+
+    /// インデックス上でコンフリクトしているパス
+    fn conflicts(&self) -> &[PathBuf] {
+        self.conflicts.get_or_init(|| {
+            let Ok(index) = self.repo.index() else {
+                return Vec::new();
+            };
+            let Ok(conflicts) = index.conflicts() else {
+                return Vec::new();
+            };
+            conflicts
+                .flatten()
+                .filter_map(|conflict| conflict.our.or(conflict.their).or(conflict.ancestor))
+                .map(|entry| Self::path_from_git_bytes(entry.path))
+                .collect()
+        })
+    }
+
+    /// インデックスのパス（バイト列）を PathBuf に変換
+    ///
+    /// Unix ではファイル名が任意のバイト列になり得るため、そのまま OsString にする。
+    #[cfg(unix)]
+    fn path_from_git_bytes(bytes: Vec<u8>) -> PathBuf {
+        use std::os::unix::ffi::OsStringExt;
+        PathBuf::from(std::ffi::OsString::from_vec(bytes))
+    }
+
+    #[cfg(not(unix))]
+    fn path_from_git_bytes(bytes: Vec<u8>) -> PathBuf {
+        PathBuf::from(String::from_utf8_lossy(&bytes).into_owned())
+    }
+
     /// git2 のステータスフラグから FileStatus への変換
     fn convert_status(status: Status) -> FileStatus {
-        // Ignored チェック（最優先）
-        if status.contains(Status::IGNORED) {
-            return FileStatus::Ignored;
+        // コンフリクトは他のフラグより優先して削除禁止
+        if status.contains(Status::CONFLICTED) {
+            return FileStatus::Conflicted;
         }
 
         // Index 変更（Staged）
@@ -172,6 +501,11 @@ impl GitChecker {
             return FileStatus::Modified;
         }
 
+        // Ignored チェック（追跡済みファイルが .gitignore に一致しても変更を優先）
+        if status.contains(Status::IGNORED) {
+            return FileStatus::Ignored;
+        }
+
         // 未追跡
         if status.contains(Status::WT_NEW) {
             return FileStatus::Untracked;
@@ -181,12 +515,167 @@ impl GitChecker {
         FileStatus::Clean
     }
 
-    /// ステータスが削除許可かどうかを判定
-    pub fn is_deletable(status: FileStatus) -> bool {
-        matches!(
-            status,
-            FileStatus::Clean | FileStatus::Ignored | FileStatus::NotInRepo
-        )
+    /// 削除可否の判定に使うポリシーを設定
+    pub fn with_policy(mut self, policy: DeletablePolicy) -> Self {
+        self.policy = policy;
+        self
+    }
+
+    /// `linguist-generated` 属性のファイルを削除許可にするか設定（`treat_generated_as_deletable`）
+    pub fn with_generated_as_deletable(mut self, enabled: bool) -> Self {
+        self.generated_deletable = enabled;
+        self
+    }
+
+    /// 読み取れないディレクトリの扱いを設定（`directory_read_policy`）
+    pub fn with_directory_read_policy(mut self, policy: DirectoryReadPolicy) -> Self {
+        self.directory_read_policy = policy;
+        self
+    }
+
+    /// `skip_unreadable` でチェックから除外したディレクトリを取り出す（呼び出し側で警告する）
+    pub fn take_skipped_unreadable(&self) -> Vec<PathBuf> {
+        let mut dirs = self.skipped_unreadable.take();
+        dirs.sort();
+        dirs.dedup();
+        dirs
+    }
+
+    /// 他のチェック（ネストしたリポジトリの検出など）で除外したディレクトリを記録
+    pub fn record_skipped_unreadable(&self, dirs: impl IntoIterator<Item = PathBuf>) {
+        self.skipped_unreadable.borrow_mut().extend(dirs);
+    }
+
+    /// ディレクトリの中身を列挙（読み取り失敗時は directory_read_policy に従う）
+    ///
+    /// `fail_closed` では `DirectoryReadError`。`skip_unreadable` では除外したディレクトリとして
+    /// 記録し、空の一覧を返す（途中で失敗した場合もそこまでの一覧は使わない）。
+    fn read_dir_entries(&self, dir: &Path) -> Result<Vec<std::fs::DirEntry>, SafeRmError> {
+        match std::fs::read_dir(dir).and_then(|entries| entries.collect()) {
+            Ok(entries) => Ok(entries),
+            Err(_) if self.directory_read_policy == DirectoryReadPolicy::SkipUnreadable => {
+                self.skipped_unreadable.borrow_mut().push(dir.to_path_buf());
+                Ok(Vec::new())
+            }
+            // fail-closed: ディレクトリ読み取り失敗は削除をブロック
+            Err(_) => Err(SafeRmError::DirectoryReadError {
+                path: dir.to_path_buf(),
+            }),
+        }
+    }
+
+    /// `try_get_statuses` の打ち切り時間を設定（`git_timeout_ms`。None なら打ち切らない）
+    pub fn with_status_timeout(mut self, timeout: Option<Duration>) -> Self {
+        self.status_timeout = timeout;
+        self
+    }
+
+    /// `--since` の参照を設定（コミット・タグ・ブランチなど、ツリーに解決できるもの）
+    pub fn with_since_ref(mut self, reference: &str) -> Result<Self, SafeRmError> {
+        let tree_id = self
+            .repo
+            .revparse_single(reference)
+            .and_then(|object| object.peel_to_tree())
+            .map(|tree| tree.id())
+            .map_err(|e| SafeRmError::InvalidGitRef {
+                reference: reference.to_string(),
+                reason: e.message().to_string(),
+            })?;
+        self.since_tree = Some(tree_id);
+        Ok(self)
+    }
+
+    /// `with_since_ref` の参照から内容が変わったファイルがあればブロック
+    ///
+    /// 参照に存在しないファイルも変更扱い（`ChangedSinceRef`）。.gitignore 対象は
+    /// 参照に含まれないため対象外。ディレクトリはシンボリックリンクを辿らずに配下を
+    /// 再帰的にチェックする。参照が未設定、またはワークツリー外のパスは常に許可。
+    pub fn check_unchanged_since(&self, path: &Path) -> Result<(), SafeRmError> {
+        let (Some(tree_id), Some(workdir)) = (self.since_tree, self.repo.workdir()) else {
+            return Ok(());
+        };
+        let tree = self.repo.find_tree(tree_id)?;
+        self.check_unchanged_since_at(&tree, workdir, path, 0)
+    }
+
+    fn check_unchanged_since_at(
+        &self,
+        tree: &Tree,
+        workdir: &Path,
+        path: &Path,
+        depth: usize,
+    ) -> Result<(), SafeRmError> {
+        if Self::is_real_directory(path) {
+            Self::check_depth(path, depth)?;
+            if self.get_directory_status(path) == FileStatus::Ignored {
+                return Ok(());
+            }
+            for entry in self.read_dir_entries(path)? {
+                self.check_unchanged_since_at(tree, workdir, &entry.path(), depth + 1)?;
+            }
+            return Ok(());
+        }
+
+        let Ok(relative) = path.strip_prefix(workdir) else {
+            return Ok(());
+        };
+        if self.is_ignored_path(path) {
+            return Ok(());
+        }
+        let unchanged = tree.get_path(relative).is_ok_and(|entry| {
+            let symlink = std::fs::symlink_metadata(path)
+                .is_ok_and(|metadata| metadata.file_type().is_symlink());
+            Self::worktree_blob_id(path, symlink).is_ok_and(|actual| actual == entry.id())
+        });
+        if unchanged {
+            Ok(())
+        } else {
+            Err(SafeRmError::DirtyFiles {
+                path: path.to_path_buf(),
+                status: FileStatus::ChangedSinceRef,
+            })
+        }
+    }
+
+    /// ステータスが削除許可かどうかを判定（設定の `deletable` に従う）
+    pub fn is_deletable(&self, status: FileStatus) -> bool {
+        self.policy.allows(status)
+    }
+
+    /// パスが削除許可かどうかを判定
+    ///
+    /// `is_deletable` に加え、`with_generated_as_deletable` 有効時は生成ファイルを許可する。
+    /// コンフリクト中のファイルは生成ファイルでも許可しない。
+    pub fn is_path_deletable(&self, path: &Path, status: FileStatus) -> bool {
+        self.is_deletable(status)
+            || (self.generated_deletable
+                && status != FileStatus::Conflicted
+                && self.is_generated(path))
+    }
+
+    /// `.gitattributes` で `linguist-generated`（または `linguist-generated=true`）が設定されているか
+    ///
+    /// ワークツリーの `.gitattributes` を優先し、なければインデックスのものを参照する。
+    pub fn is_generated(&self, path: &Path) -> bool {
+        let Some(relative_path) = self
+            .repo
+            .workdir()
+            .and_then(|workdir| path.strip_prefix(workdir).ok())
+        else {
+            return false;
+        };
+        match self.repo.get_attr(
+            relative_path,
+            "linguist-generated",
+            AttrCheckFlags::FILE_THEN_INDEX,
+        ) {
+            Ok(value) => match AttrValue::from_string(value) {
+                AttrValue::True => true,
+                AttrValue::String(value) => value.eq_ignore_ascii_case("true"),
+                _ => false,
+            },
+            Err(_) => false,
+        }
     }
 
     /// ファイルまたはディレクトリをチェック
@@ -205,7 +694,7 @@ impl GitChecker {
     /// 単一ファイルのチェック
     fn check_file(&self, path: &Path) -> Result<(), SafeRmError> {
         let status = self.get_file_status(path);
-        if Self::is_deletable(status) {
+        if self.is_path_deletable(path, status) {
             Ok(())
         } else {
             Err(SafeRmError::DirtyFiles {
@@ -221,6 +710,12 @@ impl GitChecker {
     /// * `Ok(())` - 全ファイルが Clean または Ignored
     /// * `Err(SafeRmError::DirtyFiles)` - Dirty ファイルが存在
     pub fn check_directory(&self, dir: &Path) -> Result<(), SafeRmError> {
+        self.check_directory_at(dir, 0)
+    }
+
+    fn check_directory_at(&self, dir: &Path, depth: usize) -> Result<(), SafeRmError> {
+        Self::check_depth(dir, depth)?;
+
         // まずディレクトリ自体が Ignored かチェック（早期許可）
         let dir_status = self.get_directory_status(dir);
         if dir_status == FileStatus::Ignored {
@@ -228,7 +723,78 @@ impl GitChecker {
         }
 
         // ディレクトリ内のファイルを再帰的にチェック
-        self.check_directory_recursive(dir)
+        self.check_directory_recursive(dir, depth)
+    }
+
+    /// 走査深さの上限チェック（超過は fail-closed）
+    fn check_depth(dir: &Path, depth: usize) -> Result<(), SafeRmError> {
+        if depth > MAX_DIRECTORY_DEPTH {
+            return Err(SafeRmError::DirectoryReadError {
+                path: dir.to_path_buf(),
+            });
+        }
+        Ok(())
+    }
+
+    /// ディレクトリ（自身を含む）配下で最初に見つかったネストした Git リポジトリ
+    ///
+    /// `.git` ディレクトリ、またはサブモジュール・worktree の `.git` ファイルを持つ
+    /// ディレクトリを返す。シンボリックリンクは辿らない。読み取りに失敗した場合は
+    /// 見落としを避けるため `DirectoryReadError`（fail-closed）。`skip_unreadable` では
+    /// 読み取れないディレクトリを `skipped` に記録して走査を続ける。
+    pub fn find_nested_repository(
+        dir: &Path,
+        policy: DirectoryReadPolicy,
+        skipped: &mut Vec<PathBuf>,
+    ) -> Result<Option<PathBuf>, SafeRmError> {
+        Self::find_nested_repository_at(dir, 0, policy, skipped)
+    }
+
+    fn find_nested_repository_at(
+        dir: &Path,
+        depth: usize,
+        policy: DirectoryReadPolicy,
+        skipped: &mut Vec<PathBuf>,
+    ) -> Result<Option<PathBuf>, SafeRmError> {
+        Self::check_depth(dir, depth)?;
+        if std::fs::symlink_metadata(dir.join(".git")).is_ok() {
+            return Ok(Some(dir.to_path_buf()));
+        }
+
+        let entries: Vec<std::fs::DirEntry> =
+            match std::fs::read_dir(dir).and_then(|entries| entries.collect()) {
+                Ok(entries) => entries,
+                Err(_) if policy == DirectoryReadPolicy::SkipUnreadable => {
+                    skipped.push(dir.to_path_buf());
+                    return Ok(None);
+                }
+                Err(_) => {
+                    return Err(SafeRmError::DirectoryReadError {
+                        path: dir.to_path_buf(),
+                    });
+                }
+            };
+        let mut subdirs: Vec<PathBuf> = entries
+            .into_iter()
+            .map(|entry| entry.path())
+            .filter(|path| Self::is_real_directory(path))
+            .collect();
+        subdirs.sort();
+        for subdir in subdirs {
+            if let Some(found) =
+                Self::find_nested_repository_at(&subdir, depth + 1, policy, skipped)?
+            {
+                return Ok(Some(found));
+            }
+        }
+        Ok(None)
+    }
+
+    /// ディレクトリ自体が .gitignore で無視されているか
+    ///
+    /// 配下を走査せずに判定できるため、一括ステータス取得を省略する判断に使う。
+    pub fn is_ignored_directory(&self, dir: &Path) -> bool {
+        Self::is_real_directory(dir) && self.get_directory_status(dir) == FileStatus::Ignored
     }
 
     /// ディレクトリ自体のステータスを取得
@@ -244,26 +810,64 @@ impl GitChecker {
         };
 
         // ディレクトリパスの末尾にスラッシュを追加して gitignore マッチング
-        let dir_pattern = format!("{}/", Self::to_git_relative_key(relative_path));
+        let mut dir_pattern = Self::to_git_relative_key(relative_path);
+        dir_pattern.push(b'/');
 
         let mut opts = StatusOptions::new();
-        opts.pathspec(&dir_pattern);
+        opts.pathspec(dir_pattern.clone());
         opts.include_ignored(true);
 
-        if let Ok(statuses) = self.repo.statuses(Some(&mut opts)) {
-            for entry in statuses.iter() {
-                if entry.status().contains(Status::IGNORED) {
-                    return FileStatus::Ignored;
-                }
-            }
+        let ignored = self.repo.statuses(Some(&mut opts)).is_ok_and(|statuses| {
+            statuses
+                .iter()
+                .any(|entry| entry.status().contains(Status::IGNORED))
+        })
+            // ディレクトリが .gitignore にマッチするかを直接チェック
+            || self.is_ignored_path(dir);
+
+        // 無視ディレクトリ内でも追跡済みのファイル（`git add -f` や否定パターンで追加）は
+        // 無視されないため、ディレクトリ単位で許可せず配下を個別に判定させる
+        if ignored && !self.has_tracked_entries_under(&dir_pattern) {
+            FileStatus::Ignored
+        } else {
+            FileStatus::Clean
         }
+    }
 
-        // ディレクトリが .gitignore にマッチするかを直接チェック
-        if self.is_ignored_path(dir) {
-            return FileStatus::Ignored;
-        }
+    /// インデックスに `key`（ワークディレクトリ相対パス）のエントリがあるか
+    ///
+    /// インデックスを読めない場合は fail-closed で true を返す。
+    fn is_tracked(&self, key: &[u8]) -> bool {
+        self.tracked_paths().is_none_or(|paths| {
+            paths
+                .binary_search_by(|path| path.as_slice().cmp(key))
+                .is_ok()
+        })
+    }
 
-        FileStatus::Clean
+    /// インデックスに `prefix`（末尾 `/` 付きのワークディレクトリ相対パス）配下のエントリがあるか
+    ///
+    /// インデックスを読めない場合は fail-closed で true を返す。
+    fn has_tracked_entries_under(&self, prefix: &[u8]) -> bool {
+        let Some(paths) = self.tracked_paths() else {
+            return true;
+        };
+        let start = paths.partition_point(|path| path.as_slice() < prefix);
+        paths
+            .get(start)
+            .is_some_and(|path| path.starts_with(prefix))
+    }
+
+    /// インデックスの全エントリのパス（ソート済み。読めなければ None）
+    fn tracked_paths(&self) -> Option<&[Vec<u8>]> {
+        self.tracked_paths
+            .get_or_init(|| {
+                let index = self.repo.index().ok()?;
+                let mut paths: Vec<Vec<u8>> = index.iter().map(|entry| entry.path).collect();
+                paths.sort();
+                Some(paths)
+            })
+            .as_deref()
     }
 
     /// パスが .gitignore に含まれるかチェック
@@ -284,30 +888,19 @@ impl GitChecker {
     }
 
     /// ディレクトリ内のファイルを再帰的にチェック
-    fn check_directory_recursive(&self, dir: &Path) -> Result<(), SafeRmError> {
-        let entries = match std::fs::read_dir(dir) {
-            Ok(e) => e,
-            Err(_) => {
-                // fail-closed: ディレクトリ読み取り失敗は削除をブロック
-                return Err(SafeRmError::DirectoryReadError {
-                    path: dir.to_path_buf(),
-                });
-            }
-        };
-
-        for entry_result in entries {
-            let entry = entry_result.map_err(|_| SafeRmError::DirectoryReadError {
-                path: dir.to_path_buf(),
-            })?;
+    ///
+    /// シンボリックリンクはディレクトリを指していても辿らず、1エントリとして判定する。
+    fn check_directory_recursive(&self, dir: &Path, depth: usize) -> Result<(), SafeRmError> {
+        for entry in self.read_dir_entries(dir)? {
             let path = entry.path();
 
             if Self::is_real_directory(&path) {
                 // サブディレクトリは再帰的にチェック
-                self.check_directory(&path)?;
+                self.check_directory_at(&path, depth + 1)?;
             } else {
                 // ファイルのステータスをチェック
                 let status = self.get_file_status(&path);
-                if !Self::is_deletable(status) {
+                if !self.is_path_deletable(&path, status) {
                     return Err(SafeRmError::DirtyFiles { path, status });
                 }
             }
@@ -323,45 +916,45 @@ impl GitChecker {
     pub fn check_directory_with_cache(
         &self,
         dir: &Path,
-        cache: &HashMap<String, FileStatus>,
+        cache: &HashMap<Vec<u8>, FileStatus>,
     ) -> Result<(), SafeRmError> {
+        self.check_directory_with_cache_at(dir, cache, 0)
+    }
+
+    fn check_directory_with_cache_at(
+        &self,
+        dir: &Path,
+        cache: &HashMap<Vec<u8>, FileStatus>,
+        depth: usize,
+    ) -> Result<(), SafeRmError> {
+        Self::check_depth(dir, depth)?;
+
         // まずディレクトリ自体が Ignored かチェック（早期許可）
         let dir_status = self.get_directory_status(dir);
         if dir_status == FileStatus::Ignored {
             return Ok(());
         }
 
-        self.check_directory_recursive_with_cache(dir, cache)
+        self.check_directory_recursive_with_cache(dir, cache, depth)
     }
 
     /// キャッシュを使用した再帰的ディレクトリチェック
     fn check_directory_recursive_with_cache(
         &self,
         dir: &Path,
-        cache: &HashMap<String, FileStatus>,
+        cache: &HashMap<Vec<u8>, FileStatus>,
+        depth: usize,
     ) -> Result<(), SafeRmError> {
-        let entries = match std::fs::read_dir(dir) {
-            Ok(e) => e,
-            Err(_) => {
-                return Err(SafeRmError::DirectoryReadError {
-                    path: dir.to_path_buf(),
-                });
-            }
-        };
-
-        for entry_result in entries {
-            let entry = entry_result.map_err(|_| SafeRmError::DirectoryReadError {
-                path: dir.to_path_buf(),
-            })?;
+        for entry in self.read_dir_entries(dir)? {
             let path = entry.path();
 
             if Self::is_real_directory(&path) {
                 // サブディレクトリも再帰的にチェック
-                self.check_directory_with_cache(&path, cache)?;
+                self.check_directory_with_cache_at(&path, cache, depth + 1)?;
             } else {
                 // キャッシュからステータスを取得
                 let status = self.get_file_status_from_cache(&path, cache);
-                if !Self::is_deletable(status) {
+                if !self.is_path_deletable(&path, status) {
                     return Err(SafeRmError::DirtyFiles { path, status });
                 }
             }
@@ -374,10 +967,10 @@ impl GitChecker {
     pub fn check_file_with_cache(
         &self,
         path: &Path,
-        cache: &HashMap<String, FileStatus>,
+        cache: &HashMap<Vec<u8>, FileStatus>,
     ) -> Result<(), SafeRmError> {
         let status = self.get_file_status_from_cache(path, cache);
-        if Self::is_deletable(status) {
+        if self.is_path_deletable(path, status) {
             Ok(())
         } else {
             Err(SafeRmError::DirtyFiles {
@@ -391,7 +984,7 @@ impl GitChecker {
     pub fn check_path_with_cache(
         &self,
         path: &Path,
-        cache: &HashMap<String, FileStatus>,
+        cache: &HashMap<Vec<u8>, FileStatus>,
     ) -> Result<(), SafeRmError> {
         if Self::is_real_directory(path) {
             self.check_directory_with_cache(path, cache)
@@ -400,12 +993,87 @@ impl GitChecker {
         }
     }
 
-    /// Git status のキー形式 (forward slash) に揃える
-    fn to_git_relative_key(path: &Path) -> String {
+    /// ディレクトリのブロック要因をすべて集めてチェック（`--list-dirty`）
+    ///
+    /// `check_directory_with_cache` と同じ判定だが、最初のブロッカーで止まらずに
+    /// 削除できないファイルをすべて `DirtyDirectory` として返す。
+    pub fn check_directory_listing_all_with_cache(
+        &self,
+        dir: &Path,
+        cache: &HashMap<Vec<u8>, FileStatus>,
+    ) -> Result<(), SafeRmError> {
+        if self.get_directory_status(dir) == FileStatus::Ignored {
+            return Ok(());
+        }
+        // Ignored のサブディレクトリは1エントリとして返るが、走査時と同じく常に許可
+        let mut offenders: Vec<_> = self
+            .collect_directory_statuses_with_cache(dir, cache)?
+            .into_iter()
+            .filter(|(path, status)| {
+                !Self::is_real_directory(path) && !self.is_path_deletable(path, *status)
+            })
+            .collect();
+        offenders.sort();
+        if offenders.is_empty() {
+            Ok(())
+        } else {
+            Err(SafeRmError::DirtyDirectory {
+                dir: dir.to_path_buf(),
+                offenders,
+            })
+        }
+    }
+
+    /// ディレクトリ配下の全ファイルのステータスを収集（プレビュー用）
+    ///
+    /// `check_directory_with_cache` と同じ走査規則（symlink 非追従、Ignored ディレクトリは
+    /// 配下を走査せずディレクトリ自体を1エントリとして扱う）で、最初のブロッカーで
+    /// 止まらずに全エントリを返す。
+    pub fn collect_directory_statuses_with_cache(
+        &self,
+        dir: &Path,
+        cache: &HashMap<Vec<u8>, FileStatus>,
+    ) -> Result<Vec<(PathBuf, FileStatus)>, SafeRmError> {
+        let mut results = Vec::new();
+        self.collect_directory_statuses_recursive(dir, cache, &mut results, 0)?;
+        Ok(results)
+    }
+
+    fn collect_directory_statuses_recursive(
+        &self,
+        dir: &Path,
+        cache: &HashMap<Vec<u8>, FileStatus>,
+        results: &mut Vec<(PathBuf, FileStatus)>,
+        depth: usize,
+    ) -> Result<(), SafeRmError> {
+        Self::check_depth(dir, depth)?;
+        for entry in self.read_dir_entries(dir)? {
+            let path = entry.path();
+
+            if Self::is_real_directory(&path) {
+                if self.get_directory_status(&path) == FileStatus::Ignored {
+                    results.push((path, FileStatus::Ignored));
+                } else {
+                    self.collect_directory_statuses_recursive(&path, cache, results, depth + 1)?;
+                }
+            } else {
+                let status = self.get_file_status_from_cache(&path, cache);
+                results.push((path, status));
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Git status のキー形式 (forward slash 区切りのバイト列) に揃える
+    ///
+    /// 非 UTF-8 のファイル名を置換文字に潰すとキャッシュのキーと一致しなくなるため、
+    /// OS のバイト列のまま扱う。
+    fn to_git_relative_key(path: &Path) -> Vec<u8> {
         path.components()
-            .map(|component| component.as_os_str().to_string_lossy().into_owned())
+            .map(|component| component.as_os_str().as_encoded_bytes())
             .collect::<Vec<_>>()
-            .join("/")
+            .join(&b'/')
     }
 
     /// シンボリックリンクを辿らずに「実体がディレクトリか」を判定
@@ -416,6 +1084,22 @@ impl GitChecker {
     }
 }
 
+/// `f` を別スレッドで実行し、`timeout` 内に終われば結果を返す
+///
+/// 時間切れの場合は None を返し、スレッドは待たずに放置する。
+fn run_with_timeout<T, F>(timeout: Duration, f: F) -> Option<T>
+where
+    T: Send + 'static,
+    F: FnOnce() -> T + Send + 'static,
+{
+    let (sender, receiver) = mpsc::channel();
+    std::thread::spawn(move || {
+        // 受信側が時間切れで破棄されていれば送信は失敗するが、結果は不要
+        let _ = sender.send(f());
+    });
+    receiver.recv_timeout(timeout).ok()
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -468,157 +1152,576 @@ mod tests {
             .unwrap();
     }
 
-    // Task 6.1: Gitリポジトリ検出のテスト
-
+    // Task 6.1: Gitリポジトリ検出のテスト
+
+    #[test]
+    fn test_open_git_repo() {
+        let temp_dir = create_test_repo();
+        let repo_path = temp_dir.path().canonicalize().unwrap();
+        let checker = GitChecker::open(&repo_path);
+        assert!(checker.is_some());
+    }
+
+    #[test]
+    fn test_open_non_git_directory() {
+        let temp_dir = TempDir::new().unwrap();
+        let checker = GitChecker::open(temp_dir.path());
+        assert!(checker.is_none());
+    }
+
+    #[test]
+    fn test_try_open_non_git_directory_is_none() {
+        let temp_dir = TempDir::new().unwrap();
+        assert!(matches!(GitChecker::try_open(temp_dir.path()), Ok(None)));
+    }
+
+    #[test]
+    fn test_try_open_corrupt_git_file_is_unavailable() {
+        let temp_dir = TempDir::new().unwrap();
+        fs::write(temp_dir.path().join(".git"), "garbage").unwrap();
+
+        let result = GitChecker::try_open(temp_dir.path());
+        assert!(matches!(result, Err(SafeRmError::GitUnavailable { .. })));
+        // open() は従来どおり「リポジトリなし」に縮退する
+        assert!(GitChecker::open(temp_dir.path()).is_none());
+    }
+
+    #[test]
+    fn test_try_open_corrupt_head_is_unavailable() {
+        let temp_dir = create_test_repo();
+        fs::write(temp_dir.path().join(".git").join("HEAD"), "garbage").unwrap();
+        fs::create_dir(temp_dir.path().join("sub")).unwrap();
+
+        let result = GitChecker::try_open(&temp_dir.path().join("sub"));
+        assert!(matches!(result, Err(SafeRmError::GitUnavailable { .. })));
+    }
+
+    #[test]
+    fn test_try_open_unrecognized_git_directory_is_unavailable() {
+        let temp_dir = TempDir::new().unwrap();
+        fs::create_dir(temp_dir.path().join(".git")).unwrap();
+        fs::create_dir(temp_dir.path().join("sub")).unwrap();
+
+        // libgit2 はリポジトリなし（NotFound）とするが、`.git` があるため開けないものとして扱う
+        let result = GitChecker::try_open(&temp_dir.path().join("sub"));
+        assert!(matches!(result, Err(SafeRmError::GitUnavailable { .. })));
+    }
+
+    #[test]
+    fn test_try_open_dangling_gitdir_is_unavailable() {
+        let temp_dir = TempDir::new().unwrap();
+        let missing = temp_dir.path().join("missing-gitdir");
+        fs::write(
+            temp_dir.path().join(".git"),
+            format!("gitdir: {}", missing.display()),
+        )
+        .unwrap();
+
+        let result = GitChecker::try_open(temp_dir.path());
+        assert!(matches!(result, Err(SafeRmError::GitUnavailable { .. })));
+    }
+
+    // Task 6.2: ファイルステータス判定のテスト
+
+    #[test]
+    fn test_get_file_status_clean() {
+        let temp_dir = create_test_repo();
+        let repo_path = temp_dir.path().canonicalize().unwrap();
+
+        // ファイルを作成してコミット
+        commit_file(&repo_path, "clean.txt", "clean content");
+
+        let checker = GitChecker::open(&repo_path).unwrap();
+        let file_path = repo_path.join("clean.txt");
+        let status = checker.get_file_status(&file_path);
+
+        assert_eq!(status, FileStatus::Clean);
+    }
+
+    #[test]
+    fn test_get_file_status_modified() {
+        let temp_dir = create_test_repo();
+        let repo_path = temp_dir.path().canonicalize().unwrap();
+
+        // ファイルを作成してコミット
+        commit_file(&repo_path, "modified.txt", "original content");
+
+        // ファイルを変更
+        let file_path = repo_path.join("modified.txt");
+        fs::write(&file_path, "modified content").unwrap();
+
+        let checker = GitChecker::open(&repo_path).unwrap();
+        let status = checker.get_file_status(&file_path);
+
+        assert_eq!(status, FileStatus::Modified);
+    }
+
+    #[test]
+    fn test_get_file_status_staged() {
+        let temp_dir = create_test_repo();
+        let repo_path = temp_dir.path().canonicalize().unwrap();
+
+        // ファイルを作成
+        let file_path = repo_path.join("staged.txt");
+        fs::write(&file_path, "staged content").unwrap();
+
+        // git add（コミットせず）
+        Command::new("git")
+            .args(["add", "staged.txt"])
+            .current_dir(&repo_path)
+            .output()
+            .unwrap();
+
+        let checker = GitChecker::open(&repo_path).unwrap();
+        let status = checker.get_file_status(&file_path);
+
+        assert_eq!(status, FileStatus::Staged);
+    }
+
+    #[test]
+    fn test_get_file_status_untracked() {
+        let temp_dir = create_test_repo();
+        let repo_path = temp_dir.path().canonicalize().unwrap();
+
+        // 初期コミットを作成（空のリポジトリでないことを確認）
+        commit_file(&repo_path, "initial.txt", "initial");
+
+        // 未追跡ファイルを作成
+        let file_path = repo_path.join("untracked.txt");
+        fs::write(&file_path, "untracked content").unwrap();
+
+        let checker = GitChecker::open(&repo_path).unwrap();
+        let status = checker.get_file_status(&file_path);
+
+        assert_eq!(status, FileStatus::Untracked);
+    }
+
+    #[test]
+    fn test_get_file_status_ignored() {
+        let temp_dir = create_test_repo();
+        let repo_path = temp_dir.path().canonicalize().unwrap();
+
+        // .gitignore を作成
+        let gitignore_path = repo_path.join(".gitignore");
+        fs::write(&gitignore_path, "*.log\n").unwrap();
+
+        Command::new("git")
+            .args(["add", ".gitignore"])
+            .current_dir(&repo_path)
+            .output()
+            .unwrap();
+
+        Command::new("git")
+            .args(["commit", "-m", "Add .gitignore"])
+            .current_dir(&repo_path)
+            .output()
+            .unwrap();
+
+        // 無視されるファイルを作成
+        let file_path = repo_path.join("debug.log");
+        fs::write(&file_path, "log content").unwrap();
+
+        let checker = GitChecker::open(&repo_path).unwrap();
+        let status = checker.get_file_status(&file_path);
+
+        assert_eq!(status, FileStatus::Ignored);
+    }
+
+    fn update_index(repo_path: &Path, args: &[&str]) {
+        let output = Command::new("git")
+            .arg("update-index")
+            .args(args)
+            .current_dir(repo_path)
+            .output()
+            .unwrap();
+        assert!(output.status.success());
+    }
+
+    #[test]
+    fn test_find_nested_repository() {
+        let temp_dir = TempDir::new().unwrap();
+        let root = temp_dir.path();
+        let find = |dir: &Path| {
+            GitChecker::find_nested_repository(
+                dir,
+                DirectoryReadPolicy::FailClosed,
+                &mut Vec::new(),
+            )
+        };
+        fs::create_dir_all(root.join("vendor").join("lib").join(".git")).unwrap();
+        fs::create_dir_all(root.join("vendor").join("sub")).unwrap();
+        // サブモジュールは .git ファイルを持つ
+        fs::write(
+            root.join("vendor").join("sub").join(".git"),
+            "gitdir: ../../.git/modules/sub",
+        )
+        .unwrap();
+        fs::create_dir_all(root.join("plain").join("nested")).unwrap();
+
+        assert_eq!(
+            find(&root.join("vendor")).unwrap(),
+            Some(root.join("vendor").join("lib"))
+        );
+        assert_eq!(
+            find(&root.join("vendor").join("sub")).unwrap(),
+            Some(root.join("vendor").join("sub"))
+        );
+        assert_eq!(find(&root.join("plain")).unwrap(), None);
+    }
+
+    #[test]
+    fn test_statuses_without_ignored_fall_back_per_file() {
+        let temp_dir = create_test_repo();
+        let repo_path = temp_dir.path().canonicalize().unwrap();
+        commit_file(&repo_path, ".gitignore", "node_modules/\n");
+        fs::create_dir_all(repo_path.join("node_modules").join("pkg")).unwrap();
+        fs::write(
+            repo_path.join("node_modules").join("pkg").join("index.js"),
+            "x",
+        )
+        .unwrap();
+        fs::create_dir_all(repo_path.join("new_dir")).unwrap();
+        fs::write(repo_path.join("new_dir").join("file.txt"), "x").unwrap();
+
+        let checker = GitChecker::open(&repo_path).unwrap();
+        let cache = checker.get_statuses(false);
+        assert!(cache.values().all(|status| *status != FileStatus::Ignored));
+
+        // キャッシュにない無視ファイル・未追跡ディレクトリ配下もその場の問い合わせで正しく判定
+        let ignored = repo_path.join("node_modules").join("pkg").join("index.js");
+        assert_eq!(
+            checker.get_file_status_from_cache(&ignored, &cache),
+            FileStatus::Ignored
+        );
+        assert!(
+            checker
+                .check_path_with_cache(&repo_path.join("node_modules"), &cache)
+                .is_ok()
+        );
+        assert_eq!(
+            checker.get_file_status_from_cache(&repo_path.join("new_dir").join("file.txt"), &cache),
+            FileStatus::Untracked
+        );
+    }
+
     #[test]
-    fn test_open_git_repo() {
+    fn test_try_get_statuses_with_timeout_matches_get_statuses() {
         let temp_dir = create_test_repo();
         let repo_path = temp_dir.path().canonicalize().unwrap();
-        let checker = GitChecker::open(&repo_path);
-        assert!(checker.is_some());
+        commit_file(&repo_path, "tracked.txt", "x");
+        fs::write(repo_path.join("tracked.txt"), "changed").unwrap();
+        fs::write(repo_path.join("untracked.txt"), "x").unwrap();
+
+        let checker = GitChecker::open(&repo_path)
+            .unwrap()
+            .with_status_timeout(Some(Duration::from_secs(30)));
+        let cache = checker.try_get_statuses(true).unwrap();
+        assert_eq!(cache, checker.get_statuses(true));
+        assert_eq!(
+            cache.get(b"tracked.txt".as_slice()),
+            Some(&FileStatus::Modified)
+        );
+        assert_eq!(
+            cache.get(b"untracked.txt".as_slice()),
+            Some(&FileStatus::Untracked)
+        );
     }
 
     #[test]
-    fn test_open_non_git_directory() {
-        let temp_dir = TempDir::new().unwrap();
-        let checker = GitChecker::open(temp_dir.path());
-        assert!(checker.is_none());
-    }
+    fn test_run_with_timeout_abandons_slow_work() {
+        let slow = run_with_timeout(Duration::from_millis(10), || {
+            std::thread::sleep(Duration::from_secs(5));
+            1
+        });
+        assert_eq!(slow, None);
 
-    // Task 6.2: ファイルステータス判定のテスト
+        let fast = run_with_timeout(Duration::from_secs(5), || 2);
+        assert_eq!(fast, Some(2));
+    }
 
     #[test]
-    fn test_get_file_status_clean() {
+    fn test_ignored_directory_with_tracked_modified_file_is_blocked() {
         let temp_dir = create_test_repo();
         let repo_path = temp_dir.path().canonicalize().unwrap();
-
-        // ファイルを作成してコミット
-        commit_file(&repo_path, "clean.txt", "clean content");
+        commit_file(&repo_path, ".gitignore", "build/\n!build/keep.me\n");
+        let build = repo_path.join("build");
+        fs::create_dir(&build).unwrap();
+        fs::write(build.join("keep.me"), "v1").unwrap();
+        // 親ディレクトリが無視されていると否定パターンでは再包含されないため強制追加
+        Command::new("git")
+            .args(["add", "-f", "build/keep.me"])
+            .current_dir(&repo_path)
+            .output()
+            .unwrap();
+        Command::new("git")
+            .args(["commit", "-m", "Track keep.me"])
+            .current_dir(&repo_path)
+            .output()
+            .unwrap();
+        fs::write(build.join("keep.me"), "v2").unwrap();
+        fs::write(build.join("out.o"), "o").unwrap();
 
         let checker = GitChecker::open(&repo_path).unwrap();
-        let file_path = repo_path.join("clean.txt");
-        let status = checker.get_file_status(&file_path);
+        assert!(!checker.is_ignored_directory(&build));
+        let cache = checker.get_all_statuses();
+        let err = checker.check_path_with_cache(&build, &cache).unwrap_err();
+        assert!(matches!(
+            err,
+            SafeRmError::DirtyFiles {
+                status: FileStatus::Modified,
+                ..
+            }
+        ));
+        assert!(checker.check_path(&build).is_err());
 
-        assert_eq!(status, FileStatus::Clean);
+        // 追跡ファイルが Clean なら無視ファイルと合わせて削除可能
+        Command::new("git")
+            .args(["checkout", "--", "build/keep.me"])
+            .current_dir(&repo_path)
+            .output()
+            .unwrap();
+        assert!(checker.check_path(&build).is_ok());
     }
 
     #[test]
-    fn test_get_file_status_modified() {
+    fn test_generated_file_deletable_only_when_enabled() {
         let temp_dir = create_test_repo();
         let repo_path = temp_dir.path().canonicalize().unwrap();
+        commit_file(
+            &repo_path,
+            ".gitattributes",
+            "gen/** linguist-generated=true\n",
+        );
+        fs::create_dir(repo_path.join("gen")).unwrap();
+        commit_file(&repo_path, "gen/schema.rs", "v1");
+        commit_file(&repo_path, "hand.rs", "v1");
+        fs::write(repo_path.join("gen").join("schema.rs"), "v2").unwrap();
+        fs::write(repo_path.join("hand.rs"), "v2").unwrap();
 
-        // ファイルを作成してコミット
-        commit_file(&repo_path, "modified.txt", "original content");
-
-        // ファイルを変更
-        let file_path = repo_path.join("modified.txt");
-        fs::write(&file_path, "modified content").unwrap();
-
+        let generated = repo_path.join("gen").join("schema.rs");
         let checker = GitChecker::open(&repo_path).unwrap();
-        let status = checker.get_file_status(&file_path);
+        assert!(checker.is_generated(&generated));
+        assert!(!checker.is_generated(&repo_path.join("hand.rs")));
+        assert!(checker.check_path(&generated).is_err());
 
-        assert_eq!(status, FileStatus::Modified);
+        let checker = checker.with_generated_as_deletable(true);
+        assert!(checker.check_path(&generated).is_ok());
+        assert!(checker.check_path(&repo_path.join("gen")).is_ok());
+        assert!(checker.check_path(&repo_path.join("hand.rs")).is_err());
+        assert!(!checker.is_path_deletable(&generated, FileStatus::Conflicted));
     }
 
     #[test]
-    fn test_get_file_status_staged() {
+    fn test_skip_worktree_unmodified_is_clean() {
         let temp_dir = create_test_repo();
         let repo_path = temp_dir.path().canonicalize().unwrap();
+        commit_file(&repo_path, "sparse.txt", "content");
+        update_index(&repo_path, &["--skip-worktree", "sparse.txt"]);
 
-        // ファイルを作成
-        let file_path = repo_path.join("staged.txt");
-        fs::write(&file_path, "staged content").unwrap();
+        let checker = GitChecker::open(&repo_path).unwrap();
+        let cache = checker.get_all_statuses();
+        let file = repo_path.join("sparse.txt");
+        assert_eq!(checker.get_file_status(&file), FileStatus::Clean);
+        assert!(checker.check_file_with_cache(&file, &cache).is_ok());
+    }
 
-        // git add（コミットせず）
-        Command::new("git")
-            .args(["add", "staged.txt"])
-            .current_dir(&repo_path)
-            .output()
-            .unwrap();
+    #[test]
+    fn test_skip_worktree_with_hidden_changes_is_modified() {
+        let temp_dir = create_test_repo();
+        let repo_path = temp_dir.path().canonicalize().unwrap();
+        commit_file(&repo_path, "sparse.txt", "content");
+        update_index(&repo_path, &["--skip-worktree", "sparse.txt"]);
+        fs::write(repo_path.join("sparse.txt"), "local edits").unwrap();
 
         let checker = GitChecker::open(&repo_path).unwrap();
-        let status = checker.get_file_status(&file_path);
-
-        assert_eq!(status, FileStatus::Staged);
+        let cache = checker.get_all_statuses();
+        assert_eq!(
+            checker.get_file_status_from_cache(&repo_path.join("sparse.txt"), &cache),
+            FileStatus::Modified
+        );
     }
 
     #[test]
-    fn test_get_file_status_untracked() {
+    fn test_assume_unchanged_with_hidden_changes_is_modified() {
         let temp_dir = create_test_repo();
         let repo_path = temp_dir.path().canonicalize().unwrap();
+        commit_file(&repo_path, "local.cfg", "default");
+        update_index(&repo_path, &["--assume-unchanged", "local.cfg"]);
 
-        // 初期コミットを作成（空のリポジトリでないことを確認）
-        commit_file(&repo_path, "initial.txt", "initial");
-
-        // 未追跡ファイルを作成
-        let file_path = repo_path.join("untracked.txt");
-        fs::write(&file_path, "untracked content").unwrap();
+        let checker = GitChecker::open(&repo_path).unwrap();
+        assert_eq!(
+            checker.get_file_status(&repo_path.join("local.cfg")),
+            FileStatus::Clean
+        );
 
+        fs::write(repo_path.join("local.cfg"), "tuned").unwrap();
         let checker = GitChecker::open(&repo_path).unwrap();
-        let status = checker.get_file_status(&file_path);
+        assert_eq!(
+            checker.get_file_status(&repo_path.join("local.cfg")),
+            FileStatus::Modified
+        );
+    }
 
-        assert_eq!(status, FileStatus::Untracked);
+    /// デフォルトポリシーの GitChecker（TempDir はチェッカーより長く保持する）
+    fn default_checker() -> (TempDir, GitChecker) {
+        let temp_dir = create_test_repo();
+        let checker = GitChecker::open(temp_dir.path()).unwrap();
+        (temp_dir, checker)
     }
 
     #[test]
-    fn test_get_file_status_ignored() {
+    fn test_policy_can_block_not_in_repo() {
         let temp_dir = create_test_repo();
         let repo_path = temp_dir.path().canonicalize().unwrap();
+        commit_file(&repo_path, "initial.txt", "init");
 
-        // .gitignore を作成
-        let gitignore_path = repo_path.join(".gitignore");
-        fs::write(&gitignore_path, "*.log\n").unwrap();
-
-        Command::new("git")
-            .args(["add", ".gitignore"])
-            .current_dir(&repo_path)
-            .output()
-            .unwrap();
-
-        Command::new("git")
-            .args(["commit", "-m", "Add .gitignore"])
-            .current_dir(&repo_path)
-            .output()
-            .unwrap();
-
-        // 無視されるファイルを作成
-        let file_path = repo_path.join("debug.log");
-        fs::write(&file_path, "log content").unwrap();
+        // ネストしたリポジトリ内のファイルは外側から見て Git 管理外
+        let nested = repo_path.join("vendor");
+        fs::create_dir(&nested).unwrap();
+        Repository::init(&nested).unwrap();
+        let file = nested.join("lib.txt");
+        fs::write(&file, "x").unwrap();
 
         let checker = GitChecker::open(&repo_path).unwrap();
-        let status = checker.get_file_status(&file_path);
+        assert_eq!(checker.get_file_status(&file), FileStatus::NotInRepo);
+        assert!(checker.check_path(&file).is_ok());
 
-        assert_eq!(status, FileStatus::Ignored);
+        let policy = DeletablePolicy {
+            not_in_repo: false,
+            ..DeletablePolicy::default()
+        };
+        let checker = checker.with_policy(policy);
+        assert!(matches!(
+            checker.check_path(&file),
+            Err(SafeRmError::DirtyFiles {
+                status: FileStatus::NotInRepo,
+                ..
+            })
+        ));
     }
 
     #[test]
     fn test_is_deletable_clean() {
-        assert!(GitChecker::is_deletable(FileStatus::Clean));
+        assert!(default_checker().1.is_deletable(FileStatus::Clean));
     }
 
     #[test]
     fn test_is_deletable_ignored() {
-        assert!(GitChecker::is_deletable(FileStatus::Ignored));
+        assert!(default_checker().1.is_deletable(FileStatus::Ignored));
     }
 
     #[test]
     fn test_is_deletable_not_in_repo() {
-        assert!(GitChecker::is_deletable(FileStatus::NotInRepo));
+        assert!(default_checker().1.is_deletable(FileStatus::NotInRepo));
+    }
+
+    #[test]
+    fn test_is_not_deletable_conflicted() {
+        assert!(!default_checker().1.is_deletable(FileStatus::Conflicted));
+        let permissive = DeletablePolicy {
+            modified: true,
+            staged: true,
+            untracked: true,
+            ..DeletablePolicy::default()
+        };
+        assert!(!permissive.allows(FileStatus::Conflicted));
+    }
+
+    #[test]
+    fn test_is_not_deletable_changed_since_ref() {
+        let permissive = DeletablePolicy {
+            modified: true,
+            staged: true,
+            untracked: true,
+            ..DeletablePolicy::default()
+        };
+        assert!(!permissive.allows(FileStatus::ChangedSinceRef));
+    }
+
+    #[test]
+    fn test_check_unchanged_since_tagged_commit() {
+        let temp_dir = create_test_repo();
+        let repo_path = temp_dir.path().canonicalize().unwrap();
+        fs::write(repo_path.join(".gitignore"), "*.log\n").unwrap();
+        commit_file(&repo_path, ".gitignore", "*.log\n");
+        fs::create_dir_all(repo_path.join("dir")).unwrap();
+        commit_file(&repo_path, "dir/same.txt", "same");
+        commit_file(&repo_path, "edited.txt", "v1");
+        Command::new("git")
+            .args(["tag", "v1"])
+            .current_dir(&repo_path)
+            .output()
+            .unwrap();
+        // タグ以降のコミット（HEAD とは一致するため通常の Git チェックは通る）
+        commit_file(&repo_path, "edited.txt", "v2");
+        commit_file(&repo_path, "dir/added.txt", "added");
+        fs::write(repo_path.join("dir").join("debug.log"), "log").unwrap();
+
+        let checker = GitChecker::open(&repo_path)
+            .unwrap()
+            .with_since_ref("v1")
+            .unwrap();
+        assert!(
+            checker
+                .check_unchanged_since(&repo_path.join("dir").join("same.txt"))
+                .is_ok()
+        );
+        assert!(
+            checker
+                .check_unchanged_since(&repo_path.join("dir").join("debug.log"))
+                .is_ok(),
+            "Ignored files are never part of the ref"
+        );
+        assert!(matches!(
+            checker.check_unchanged_since(&repo_path.join("edited.txt")),
+            Err(SafeRmError::DirtyFiles {
+                status: FileStatus::ChangedSinceRef,
+                ..
+            })
+        ));
+        match checker.check_unchanged_since(&repo_path.join("dir")) {
+            Err(SafeRmError::DirtyFiles { path, status }) => {
+                assert_eq!(path, repo_path.join("dir").join("added.txt"));
+                assert_eq!(status, FileStatus::ChangedSinceRef);
+            }
+            other => panic!("expected ChangedSinceRef, got {:?}", other),
+        }
+
+        // 参照未設定なら常に許可
+        let plain = GitChecker::open(&repo_path).unwrap();
+        assert!(
+            plain
+                .check_unchanged_since(&repo_path.join("edited.txt"))
+                .is_ok()
+        );
+    }
+
+    #[test]
+    fn test_with_since_ref_rejects_unknown_ref() {
+        let temp_dir = create_test_repo();
+        commit_file(temp_dir.path(), "a.txt", "a");
+        let result = GitChecker::open(temp_dir.path())
+            .unwrap()
+            .with_since_ref("no-such-ref");
+        assert!(matches!(
+            result,
+            Err(SafeRmError::InvalidGitRef { reference, .. }) if reference == "no-such-ref"
+        ));
     }
 
     #[test]
     fn test_is_not_deletable_modified() {
-        assert!(!GitChecker::is_deletable(FileStatus::Modified));
+        assert!(!default_checker().1.is_deletable(FileStatus::Modified));
     }
 
     #[test]
     fn test_is_not_deletable_staged() {
-        assert!(!GitChecker::is_deletable(FileStatus::Staged));
+        assert!(!default_checker().1.is_deletable(FileStatus::Staged));
     }
 
     #[test]
     fn test_is_not_deletable_untracked() {
-        assert!(!GitChecker::is_deletable(FileStatus::Untracked));
+        assert!(!default_checker().1.is_deletable(FileStatus::Untracked));
     }
 
     // Task 6.3: ディレクトリ再帰チェックのテスト
@@ -840,12 +1943,12 @@ mod tests {
         let statuses = checker.get_all_statuses();
 
         // Modified, Untracked, Ignored は status に含まれる
-        assert!(statuses.contains_key("modified.txt"));
-        assert!(statuses.contains_key("new.txt"));
-        assert!(statuses.contains_key("debug.log"));
+        assert!(statuses.contains_key(b"modified.txt".as_slice()));
+        assert!(statuses.contains_key(b"new.txt".as_slice()));
+        assert!(statuses.contains_key(b"debug.log".as_slice()));
 
         // Clean ファイルはステータスリストに含まれない（変更なし）
-        assert!(!statuses.contains_key("clean.txt"));
+        assert!(!statuses.contains_key(b"clean.txt".as_slice()));
     }
 
     #[test]
@@ -921,6 +2024,103 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_recheck_detects_change_after_snapshot() {
+        let temp_dir = create_test_repo();
+        let repo_path = temp_dir.path().canonicalize().unwrap();
+
+        commit_file(&repo_path, ".gitignore", "*.log\n");
+        let log_file = repo_path.join("debug.log");
+        fs::write(&log_file, "log").unwrap();
+
+        let checker = GitChecker::open(&repo_path).unwrap();
+        let cache = checker.get_all_statuses();
+        assert_eq!(
+            cache.get(b"debug.log".as_slice()),
+            Some(&FileStatus::Ignored)
+        );
+
+        // スナップショット後に ignore ルールが外れ、debug.log は Untracked になる
+        fs::write(repo_path.join(".gitignore"), "").unwrap();
+
+        // キャッシュは古い Ignored のまま許可してしまう
+        assert!(checker.check_path_with_cache(&log_file, &cache).is_ok());
+        // --recheck 相当の都度判定では最新ステータスでブロックされる
+        let result = checker.check_path(&log_file);
+        assert!(
+            matches!(
+                result,
+                Err(SafeRmError::DirtyFiles {
+                    status: FileStatus::Untracked,
+                    ..
+                })
+            ),
+            "Fresh status should block the now-untracked file"
+        );
+    }
+
+    #[test]
+    fn test_cache_miss_new_untracked_file_after_snapshot_is_blocked() {
+        let temp_dir = create_test_repo();
+        let repo_path = temp_dir.path().canonicalize().unwrap();
+        commit_file(&repo_path, "init.txt", "init");
+
+        let checker = GitChecker::open(&repo_path).unwrap();
+        let cache = checker.get_all_statuses();
+
+        // スナップショット後に作成された未追跡ファイル（キャッシュに存在しない）
+        let late_dir = repo_path.join("late");
+        fs::create_dir(&late_dir).unwrap();
+        let late_file = late_dir.join("new.txt");
+        fs::write(&late_file, "new").unwrap();
+        let late_root_file = repo_path.join("new_root.txt");
+        fs::write(&late_root_file, "new").unwrap();
+
+        assert_eq!(
+            checker.get_file_status_from_cache(&late_file, &cache),
+            FileStatus::Untracked
+        );
+        assert_eq!(
+            checker.get_file_status_from_cache(&late_root_file, &cache),
+            FileStatus::Untracked
+        );
+        assert!(checker.check_path_with_cache(&late_dir, &cache).is_err());
+        assert!(
+            checker
+                .check_file_with_cache(&late_root_file, &cache)
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn test_collect_directory_statuses_reports_all_entries() {
+        let temp_dir = create_test_repo();
+        let repo_path = temp_dir.path().canonicalize().unwrap();
+
+        commit_file(&repo_path, ".gitignore", "out/\n");
+        fs::create_dir_all(repo_path.join("mixed").join("out")).unwrap();
+        commit_file(&repo_path, "mixed/clean.txt", "clean");
+        fs::write(repo_path.join("mixed").join("new.txt"), "new").unwrap();
+        fs::write(repo_path.join("mixed").join("out").join("a.o"), "").unwrap();
+
+        let checker = GitChecker::open(&repo_path).unwrap();
+        let cache = checker.get_all_statuses();
+        let mut statuses = checker
+            .collect_directory_statuses_with_cache(&repo_path.join("mixed"), &cache)
+            .unwrap();
+        statuses.sort();
+
+        let mixed = repo_path.join("mixed");
+        assert_eq!(
+            statuses,
+            vec![
+                (mixed.join("clean.txt"), FileStatus::Clean),
+                (mixed.join("new.txt"), FileStatus::Untracked),
+                (mixed.join("out"), FileStatus::Ignored),
+            ]
+        );
+    }
+
     #[test]
     fn test_workdir_returns_path() {
         let temp_dir = create_test_repo();
@@ -934,6 +2134,17 @@ mod tests {
         assert!(wd.is_absolute(), "Workdir should be absolute");
     }
 
+    #[test]
+    fn test_open_from_deep_subdirectory_finds_repo_root() {
+        let temp_dir = create_test_repo();
+        let repo_path = temp_dir.path().canonicalize().unwrap();
+        let deep_dir = repo_path.join("a").join("b").join("c");
+        fs::create_dir_all(&deep_dir).unwrap();
+
+        let checker = GitChecker::open(&deep_dir).unwrap();
+        assert_eq!(checker.workdir(), Some(repo_path));
+    }
+
     #[test]
     fn test_get_file_status_from_cache_not_in_repo() {
         let temp_dir = create_test_repo();
@@ -987,6 +2198,12 @@ mod tests {
         assert_eq!(status, FileStatus::Untracked);
     }
 
+    #[test]
+    fn test_convert_status_conflicted_takes_precedence() {
+        let status = GitChecker::convert_status(Status::CONFLICTED | Status::WT_MODIFIED);
+        assert_eq!(status, FileStatus::Conflicted);
+    }
+
     #[test]
     fn test_convert_status_ignored_takes_precedence() {
         // IGNORED + WT_NEW の場合、IGNORED が優先される
@@ -1024,6 +2241,47 @@ mod tests {
         assert_eq!(status, FileStatus::Modified);
     }
 
+    #[test]
+    fn test_convert_status_changes_take_precedence_over_ignored() {
+        assert_eq!(
+            GitChecker::convert_status(Status::WT_MODIFIED | Status::IGNORED),
+            FileStatus::Modified
+        );
+        assert_eq!(
+            GitChecker::convert_status(Status::INDEX_MODIFIED | Status::IGNORED),
+            FileStatus::Staged
+        );
+        assert_eq!(
+            GitChecker::convert_status(Status::IGNORED),
+            FileStatus::Ignored
+        );
+    }
+
+    #[test]
+    fn test_tracked_file_matching_gitignore_modified_after_snapshot() {
+        let temp_dir = create_test_repo();
+        let repo_path = temp_dir.path().canonicalize().unwrap();
+        commit_file(&repo_path, "config.local", "original");
+        commit_file(&repo_path, ".gitignore", "config.local\n");
+        let file = repo_path.join("config.local");
+
+        let checker = GitChecker::open(&repo_path).unwrap();
+        let cache = checker.get_all_statuses();
+        assert_eq!(
+            checker.get_file_status_from_cache(&file, &cache),
+            FileStatus::Clean,
+            "Tracked files are not ignored even if they match .gitignore"
+        );
+
+        // スナップショット後の変更でも Ignored と誤判定しない
+        fs::write(&file, "changed").unwrap();
+        assert_eq!(
+            checker.get_file_status_from_cache(&file, &cache),
+            FileStatus::Modified
+        );
+        assert!(checker.check_path_with_cache(&file, &cache).is_err());
+    }
+
     #[test]
     fn test_convert_status_wt_typechange() {
         let status = GitChecker::convert_status(Status::WT_TYPECHANGE);
@@ -1034,7 +2292,7 @@ mod tests {
     fn test_to_git_relative_key_uses_forward_slash() {
         let nested = Path::new("subdir").join("file.txt");
         let key = GitChecker::to_git_relative_key(&nested);
-        assert_eq!(key, "subdir/file.txt");
+        assert_eq!(key, b"subdir/file.txt");
     }
 
     #[test]
@@ -1058,7 +2316,7 @@ mod tests {
 
         let checker = GitChecker::open(&repo_path).unwrap();
         let mut cache = HashMap::new();
-        cache.insert("nested/file.txt".to_string(), FileStatus::Untracked);
+        cache.insert(b"nested/file.txt".to_vec(), FileStatus::Untracked);
 
         let status = checker.get_file_status_from_cache(&nested_file, &cache);
         assert_eq!(status, FileStatus::Untracked);
@@ -1126,6 +2384,110 @@ mod tests {
         );
     }
 
+    #[test]
+    #[cfg(unix)]
+    fn test_symlink_cycle_is_not_traversed() {
+        let temp_dir = create_test_repo();
+        let repo_path = temp_dir.path().canonicalize().unwrap();
+
+        // a/loop -> ..（親へのループ）と a/self -> .（自己ループ）をコミット
+        let dir = repo_path.join("a");
+        fs::create_dir(&dir).unwrap();
+        fs::write(dir.join("f.txt"), "f").unwrap();
+        std::os::unix::fs::symlink("..", dir.join("loop")).unwrap();
+        std::os::unix::fs::symlink(".", dir.join("self")).unwrap();
+        Command::new("git")
+            .args(["add", "-A"])
+            .current_dir(&repo_path)
+            .output()
+            .unwrap();
+        Command::new("git")
+            .args(["commit", "-m", "Add cycle"])
+            .current_dir(&repo_path)
+            .output()
+            .unwrap();
+
+        let checker = GitChecker::open(&repo_path).unwrap();
+        let cache = checker.get_all_statuses();
+        assert!(checker.check_directory(&dir).is_ok());
+        assert!(checker.check_directory_with_cache(&dir, &cache).is_ok());
+
+        // リンクは1エントリとして数えられ、リンク先は走査されない
+        let entries = checker
+            .collect_directory_statuses_with_cache(&dir, &cache)
+            .unwrap();
+        assert_eq!(entries.len(), 3);
+    }
+
+    #[test]
+    fn test_directory_deeper_than_limit_is_fail_closed() {
+        let temp_dir = create_test_repo();
+        let repo_path = temp_dir.path().canonicalize().unwrap();
+        commit_file(&repo_path, "initial.txt", "init");
+
+        let top = repo_path.join("deep");
+        let mut leaf = top.clone();
+        for _ in 0..=MAX_DIRECTORY_DEPTH {
+            leaf.push("d");
+        }
+        fs::create_dir_all(&leaf).unwrap();
+
+        let checker = GitChecker::open(&repo_path).unwrap();
+        let cache = HashMap::new();
+        assert!(matches!(
+            checker.check_directory(&top),
+            Err(SafeRmError::DirectoryReadError { .. })
+        ));
+        assert!(matches!(
+            checker.check_directory_with_cache(&top, &cache),
+            Err(SafeRmError::DirectoryReadError { .. })
+        ));
+        assert!(
+            checker
+                .collect_directory_statuses_with_cache(&top, &cache)
+                .is_err()
+        );
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn test_non_utf8_untracked_file_is_not_clean() {
+        use std::os::unix::ffi::OsStrExt;
+
+        let temp_dir = create_test_repo();
+        let repo_path = temp_dir.path().canonicalize().unwrap();
+        commit_file(&repo_path, "clean.txt", "clean");
+        let dir = repo_path.join("dir");
+        fs::create_dir(&dir).unwrap();
+        let file = dir.join(std::ffi::OsStr::from_bytes(b"bad\xff.txt"));
+        // 非 UTF-8 のファイル名を拒否するファイルシステム（APFS など）では検証できない
+        if fs::write(&file, "new").is_err() {
+            return;
+        }
+
+        let checker = GitChecker::open(&repo_path).unwrap();
+        let cache = checker.get_all_statuses();
+        assert_eq!(
+            cache.get(b"dir/bad\xff.txt".as_slice()),
+            Some(&FileStatus::Untracked)
+        );
+        assert_eq!(
+            checker.get_file_status_from_cache(&file, &cache),
+            FileStatus::Untracked
+        );
+        assert_eq!(
+            checker.get_file_status_from_cache(&file, &HashMap::new()),
+            FileStatus::Untracked
+        );
+        assert!(matches!(
+            checker.check_directory_with_cache(&dir, &cache),
+            Err(SafeRmError::DirtyFiles {
+                status: FileStatus::Untracked,
+                ..
+            })
+        ));
+    }
+
     #[test]
     #[cfg(unix)]
     fn test_is_real_directory_does_not_follow_symlink() {
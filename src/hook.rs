@@ -0,0 +1,96 @@
+//! safe-rm の削除前フック（`pre_delete_hook`）
+//!
+//! 安全チェックを通過した対象ごとに外部コマンドを実行し、0 以外で終了したら削除をブロックする。
+//! パスはシェルを介さず argv として渡すため、パス名によるコマンドインジェクションは起きない。
+
+use crate::error::SafeRmError;
+use std::path::Path;
+use std::process::{Command, Stdio};
+
+/// 削除前フックを実行し、拒否された場合はエラーを返す
+///
+/// `hook` に対象の絶対パスを1つ引数として渡す。ドライランでは環境変数
+/// `SAFE_RM_DRY_RUN=1` を設定する（フック側で副作用を避けられるように）。
+/// フックを起動できない場合も fail-closed で拒否として扱う。
+pub fn run_pre_delete_hook(hook: &Path, path: &Path, dry_run: bool) -> Result<(), SafeRmError> {
+    let rejected = |reason: String| SafeRmError::HookRejected {
+        path: path.to_path_buf(),
+        reason,
+    };
+
+    // フックの stdout は safe-rm の結果出力と混ざらないよう捨て、stderr は理由として使う
+    let output = Command::new(hook)
+        .arg(path)
+        .env("SAFE_RM_DRY_RUN", if dry_run { "1" } else { "0" })
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::piped())
+        .output()
+        .map_err(|e| rejected(format!("failed to run {}: {}", hook.display(), e)))?;
+
+    if output.status.success() {
+        return Ok(());
+    }
+    let status = match output.status.code() {
+        Some(code) => format!("exit status {}", code),
+        None => "terminated by signal".to_string(),
+    };
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    Err(rejected(
+        match stderr.lines().find(|line| !line.trim().is_empty()) {
+            Some(message) => format!("{}: {}", status, message.trim()),
+            None => status,
+        },
+    ))
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+    use std::fs;
+    use std::os::unix::fs::PermissionsExt;
+
+    fn write_hook(dir: &Path, body: &str) -> std::path::PathBuf {
+        let hook = dir.join("hook.sh");
+        fs::write(&hook, format!("#!/bin/sh\n{}\n", body)).unwrap();
+        fs::set_permissions(&hook, fs::Permissions::from_mode(0o755)).unwrap();
+        hook
+    }
+
+    #[test]
+    fn test_hook_receives_path_as_single_argument() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let hook = write_hook(
+            temp_dir.path(),
+            r#"[ "$#" -eq 1 ] && [ "$1" = '/tmp/a b;$(rm -rf x).txt' ]"#,
+        );
+        assert!(run_pre_delete_hook(&hook, Path::new("/tmp/a b;$(rm -rf x).txt"), false).is_ok());
+    }
+
+    #[test]
+    fn test_hook_rejection_reports_status_and_stderr() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let hook = write_hook(temp_dir.path(), "echo 'ticket is closed' >&2; exit 3");
+        let err = run_pre_delete_hook(&hook, Path::new("/tmp/a.txt"), false).unwrap_err();
+        assert_eq!(err.exit_code(), 2);
+        assert!(
+            err.user_message()
+                .contains("exit status 3: ticket is closed")
+        );
+    }
+
+    #[test]
+    fn test_missing_hook_is_rejection() {
+        let err = run_pre_delete_hook(Path::new("/nonexistent/hook"), Path::new("/tmp/a"), true)
+            .unwrap_err();
+        assert!(matches!(err, SafeRmError::HookRejected { .. }));
+    }
+
+    #[test]
+    fn test_dry_run_is_exposed_to_hook() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let hook = write_hook(temp_dir.path(), r#"[ "$SAFE_RM_DRY_RUN" = 1 ]"#);
+        assert!(run_pre_delete_hook(&hook, Path::new("/tmp/a"), true).is_ok());
+        assert!(run_pre_delete_hook(&hook, Path::new("/tmp/a"), false).is_err());
+    }
+}
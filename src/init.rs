@@ -1,9 +1,11 @@
 //! safe-rm の設定初期化
 //!
-//! ~/.config/safe-rm/config.toml にデフォルト設定ファイルを生成する。
+//! ~/.config/safe-rm/config.toml にデフォルト設定ファイルを生成する（`reset` で削除）。
 
 use crate::config::Config;
 use std::fs;
+use std::io::{BufRead, IsTerminal, Write};
+use std::path::{Path, PathBuf};
 
 /// ~/.claude/skills を有効にしたデフォルト設定テンプレート
 const CONFIG_TEMPLATE: &str = r#"# safe-rm configuration
@@ -25,9 +27,10 @@ recursive = true
 "#;
 
 /// init サブコマンドを実行
-pub fn run_init() -> Result<(), String> {
-    let config_path =
-        Config::config_path().ok_or_else(|| "Cannot determine config directory".to_string())?;
+///
+/// `config_path` は `--config` を考慮した設定ファイルのパス。
+pub fn run_init(config_path: Option<PathBuf>) -> Result<(), String> {
+    let config_path = config_path.ok_or_else(|| "Cannot determine config directory".to_string())?;
 
     let config_dir = config_path
         .parent()
@@ -42,7 +45,7 @@ pub fn run_init() -> Result<(), String> {
     // 設定ファイルが既に存在するか確認
     if config_path.exists() {
         eprintln!("Config file already exists: {}", config_path.display());
-        eprintln!("To regenerate, delete the file first and run `safe-rm init` again.");
+        eprintln!("To regenerate, run `safe-rm reset` first and then `safe-rm init` again.");
         return Ok(());
     }
 
@@ -58,6 +61,94 @@ pub fn run_init() -> Result<(), String> {
     Ok(())
 }
 
+/// reset サブコマンドを実行
+///
+/// `config_path` は `Config::config_path_with_override` で解決した設定ファイルのパス。
+/// `yes` がなければ stdin で確認する（stdin が端末でなければ確認できないため中止）。
+pub fn run_reset(
+    config_path: Option<PathBuf>,
+    force: bool,
+    yes: bool,
+    allow_self_config: bool,
+) -> Result<(), String> {
+    let stdin = std::io::stdin();
+    if !yes && !stdin.is_terminal() {
+        return Err("Confirmation required; re-run with --yes to reset non-interactively".into());
+    }
+    reset(
+        config_path,
+        force,
+        yes,
+        allow_self_config,
+        stdin.lock(),
+        std::io::stderr(),
+    )
+}
+
+/// `init` が生成した設定ファイルを削除
+///
+/// テンプレートから編集されたファイルは独自ルールを失わないよう `force` がなければ拒否する。
+/// さらに、非対話のエージェントが自身のポリシーを消せないよう、確認を省略（`yes`）して
+/// 削除するには `allow_self_config` も必要（削除コマンドの `-f --allow-self-config` と同じ扱い）。
+/// `yes` がなければ `output` へ確認を表示し、`input` の回答が `y` / `yes` の場合のみ削除する。
+fn reset(
+    config_path: Option<PathBuf>,
+    force: bool,
+    yes: bool,
+    allow_self_config: bool,
+    input: impl BufRead,
+    mut output: impl Write,
+) -> Result<(), String> {
+    let config_path = config_path.ok_or_else(|| "Cannot determine config directory".to_string())?;
+
+    let content = match fs::read_to_string(&config_path) {
+        Ok(content) => content,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
+            eprintln!("Config file does not exist: {}", config_path.display());
+            return Ok(());
+        }
+        Err(e) => return Err(format!("Cannot read config file: {}", e)),
+    };
+    if content != CONFIG_TEMPLATE {
+        if !force {
+            return Err(format!(
+                "Config file has been modified from the template: {}\nRe-run with --force to remove it anyway.",
+                config_path.display()
+            ));
+        }
+        if yes && !allow_self_config {
+            return Err(format!(
+                "Config file has been modified from the template: {}\nRemoving it without confirmation also requires --allow-self-config.",
+                config_path.display()
+            ));
+        }
+    }
+
+    if !yes && !confirm_reset(input, &mut output, &config_path)? {
+        return Err("Reset cancelled".to_string());
+    }
+
+    fs::remove_file(&config_path).map_err(|e| format!("Cannot remove config file: {}", e))?;
+    println!("Removed config file: {}", config_path.display());
+    Ok(())
+}
+
+/// 削除の確認（空行や入力終了は中止）
+fn confirm_reset(
+    mut input: impl BufRead,
+    mut output: impl Write,
+    config_path: &Path,
+) -> Result<bool, String> {
+    let io_error = |e: std::io::Error| format!("Cannot read confirmation: {}", e);
+    write!(output, "Remove {}? [y/N] ", config_path.display()).map_err(io_error)?;
+    output.flush().map_err(io_error)?;
+
+    let mut answer = String::new();
+    input.read_line(&mut answer).map_err(io_error)?;
+    let answer = answer.trim();
+    Ok(answer.eq_ignore_ascii_case("y") || answer.eq_ignore_ascii_case("yes"))
+}
+
 /// 表示用の設定パスを取得
 pub fn config_path_display() -> String {
     Config::config_path()
@@ -110,6 +201,116 @@ recursive = false
         assert!(content.contains("recursive"));
     }
 
+    fn write_config(content: &str) -> (tempfile::TempDir, PathBuf) {
+        let tmp_dir = tempfile::tempdir().unwrap();
+        let config_path = tmp_dir.path().join("config.toml");
+        fs::write(&config_path, content).unwrap();
+        (tmp_dir, config_path)
+    }
+
+    #[test]
+    fn test_reset_removes_unmodified_template_after_confirmation() {
+        let (_tmp_dir, config_path) = write_config(CONFIG_TEMPLATE);
+        let mut output = Vec::new();
+
+        reset(
+            Some(config_path.clone()),
+            false,
+            false,
+            false,
+            &b"y\n"[..],
+            &mut output,
+        )
+        .unwrap();
+
+        assert!(!config_path.exists());
+        assert!(String::from_utf8(output).unwrap().contains("[y/N]"));
+    }
+
+    #[test]
+    fn test_reset_declined_keeps_file() {
+        let (_tmp_dir, config_path) = write_config(CONFIG_TEMPLATE);
+
+        let result = reset(
+            Some(config_path.clone()),
+            false,
+            false,
+            false,
+            &b"\n"[..],
+            Vec::new(),
+        );
+
+        assert!(result.is_err());
+        assert!(config_path.exists());
+    }
+
+    #[test]
+    fn test_reset_refuses_modified_config_without_force() {
+        let custom = format!("{}\nmax_paths = 10\n", CONFIG_TEMPLATE);
+        let (_tmp_dir, config_path) = write_config(&custom);
+
+        let err = reset(
+            Some(config_path.clone()),
+            false,
+            true,
+            false,
+            &b""[..],
+            Vec::new(),
+        )
+        .unwrap_err();
+        assert!(err.contains("modified from the template"), "{}", err);
+        assert_eq!(fs::read_to_string(&config_path).unwrap(), custom);
+
+        // 確認を省略する場合は --allow-self-config も必要
+        let err = reset(
+            Some(config_path.clone()),
+            true,
+            true,
+            false,
+            &b""[..],
+            Vec::new(),
+        )
+        .unwrap_err();
+        assert!(err.contains("--allow-self-config"), "{}", err);
+        assert_eq!(fs::read_to_string(&config_path).unwrap(), custom);
+
+        reset(
+            Some(config_path.clone()),
+            true,
+            true,
+            true,
+            &b""[..],
+            Vec::new(),
+        )
+        .unwrap();
+        assert!(!config_path.exists());
+    }
+
+    #[test]
+    fn test_reset_modified_config_with_force_after_confirmation() {
+        let custom = format!("{}\nmax_paths = 10\n", CONFIG_TEMPLATE);
+        let (_tmp_dir, config_path) = write_config(&custom);
+
+        reset(
+            Some(config_path.clone()),
+            true,
+            false,
+            false,
+            &b"y\n"[..],
+            Vec::new(),
+        )
+        .unwrap();
+        assert!(!config_path.exists());
+    }
+
+    #[test]
+    fn test_reset_missing_file_is_noop() {
+        let tmp_dir = tempfile::tempdir().unwrap();
+        let config_path = tmp_dir.path().join("config.toml");
+
+        assert!(reset(Some(config_path), false, true, false, &b""[..], Vec::new()).is_ok());
+    }
+
     #[test]
     fn test_config_path_display_returns_string() {
         let display = config_path_display();
@@ -3,9 +3,62 @@
 //! Git状態に基づくアクセス制御を備えたファイル削除ライブラリ。
 //! Clean または Ignored 状態のファイルのみ削除を許可する。
 
+// 内部の判断（リポジトリ検出、ステータスキャッシュ構築、パス毎の判定）の記録。
+// `logging` フィーチャ有効時のみ log クレートへ出力し、無効時は引数の型検査だけでコードを生成しない。
+#[cfg(feature = "logging")]
+macro_rules! debug_log {
+    ($($arg:tt)+) => {
+        log::debug!($($arg)+)
+    };
+}
+
+#[cfg(not(feature = "logging"))]
+macro_rules! debug_log {
+    ($($arg:tt)+) => {
+        if false {
+            let _ = format_args!($($arg)+);
+        }
+    };
+}
+
+#[cfg(feature = "logging")]
+macro_rules! trace_log {
+    ($($arg:tt)+) => {
+        log::trace!($($arg)+)
+    };
+}
+
+#[cfg(not(feature = "logging"))]
+macro_rules! trace_log {
+    ($($arg:tt)+) => {
+        if false {
+            let _ = format_args!($($arg)+);
+        }
+    };
+}
+
+pub mod audit;
+pub mod backup;
 pub mod cli;
 pub mod config;
+pub mod confirm;
+pub mod delete;
 pub mod error;
+pub mod exclude;
 pub mod git_checker;
+pub mod hook;
 pub mod init;
+pub mod lock;
+pub mod open_files;
 pub mod path_checker;
+pub mod pathspec;
+pub mod progress;
+pub mod report;
+pub mod retry;
+pub mod status;
+pub mod summary;
+pub mod trace;
+pub mod verify;
+
+pub use delete::{DeleteOptions, DeleteReport, ReportEntry, SkipReason, UndoEntry, safe_delete};
+pub use status::{Classification, Containment, classify};
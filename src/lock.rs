@@ -0,0 +1,106 @@
+//! safe-rm の同時実行の直列化（use_lock）
+//!
+//! 同じリポジトリで複数のエージェントが同時に実行すると、一方が取得した Git ステータスの
+//! スナップショットと、もう一方が削除した後の作業ツリーが食い違う。
+//! use_lock 有効時はリポジトリの Git ディレクトリの `safe-rm.lock` に advisory lock をかけ、
+//! ステータス取得から削除までを直列化する。ロックはファイルを閉じると解放されるため、
+//! 異常終了してもロックが残り続けることはない（ロックファイル自体は残すが無害）。
+
+use crate::error::SafeRmError;
+use fs4::{FileExt, TryLockError};
+use std::fs::{File, OpenOptions};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+
+/// Git ディレクトリ内のロックファイル名
+pub const LOCK_FILE_NAME: &str = "safe-rm.lock";
+
+/// ロック取得を再試行する間隔
+const POLL_INTERVAL: Duration = Duration::from_millis(50);
+
+/// 保持中のリポジトリロック（drop で解放）
+#[derive(Debug)]
+pub struct RepoLock {
+    _file: File,
+    path: PathBuf,
+}
+
+impl RepoLock {
+    /// `git_dir` のロックを取得（他の実行が保持していれば `timeout` まで待つ）
+    ///
+    /// 時間内に取得できなければ `LockTimeout`（fail-closed で何も削除しない）。
+    pub fn acquire(git_dir: &Path, timeout: Duration) -> Result<Self, SafeRmError> {
+        let path = git_dir.join(LOCK_FILE_NAME);
+        let file = OpenOptions::new()
+            .create(true)
+            .truncate(false)
+            .write(true)
+            .open(&path)?;
+        let deadline = Instant::now() + timeout;
+        loop {
+            // Rust 1.89 以降の `File::try_lock` ではなく fs4 の実装を明示的に呼ぶ（MSRV 1.85）
+            match FileExt::try_lock(&file) {
+                Ok(()) => return Ok(Self { _file: file, path }),
+                Err(TryLockError::WouldBlock) if Instant::now() < deadline => {
+                    std::thread::sleep(POLL_INTERVAL);
+                }
+                Err(TryLockError::WouldBlock) => {
+                    return Err(SafeRmError::LockTimeout {
+                        path,
+                        timeout_ms: timeout.as_millis().try_into().unwrap_or(u64::MAX),
+                    });
+                }
+                Err(TryLockError::Error(e)) => return Err(SafeRmError::IoError(e)),
+            }
+        }
+    }
+
+    /// ロックファイルのパス
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_acquire_creates_lock_file() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let lock = RepoLock::acquire(temp_dir.path(), Duration::ZERO).unwrap();
+        assert_eq!(lock.path(), temp_dir.path().join(LOCK_FILE_NAME));
+        assert!(lock.path().exists());
+    }
+
+    #[test]
+    fn test_held_lock_times_out() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let _held = RepoLock::acquire(temp_dir.path(), Duration::ZERO).unwrap();
+
+        let err = RepoLock::acquire(temp_dir.path(), Duration::from_millis(120)).unwrap_err();
+        assert_eq!(err.code(), "lock_timeout");
+        assert_eq!(err.exit_code(), 1);
+        assert!(err.user_message().contains("120ms"));
+    }
+
+    #[test]
+    fn test_lock_is_released_on_drop() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        drop(RepoLock::acquire(temp_dir.path(), Duration::ZERO).unwrap());
+        assert!(RepoLock::acquire(temp_dir.path(), Duration::ZERO).is_ok());
+    }
+
+    #[test]
+    fn test_waits_for_lock_released_by_other_holder() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let held = RepoLock::acquire(temp_dir.path(), Duration::ZERO).unwrap();
+        let releaser = std::thread::spawn(move || {
+            std::thread::sleep(Duration::from_millis(100));
+            drop(held);
+        });
+
+        assert!(RepoLock::acquire(temp_dir.path(), Duration::from_secs(10)).is_ok());
+        releaser.join().unwrap();
+    }
+}
@@ -3,131 +3,1254 @@
 //! Git状態に基づくアクセス制御を備えたファイル削除プロキシ。
 //! Clean または Ignored 状態のファイルのみ削除を許可する。
 
-use std::collections::HashMap;
+use std::cell::RefCell;
+use std::collections::hash_map::Entry;
+use std::collections::{BTreeMap, HashMap};
 use std::fs;
-use std::path::Path;
+use std::io::{IsTerminal, Write};
+use std::path::{Path, PathBuf};
 use std::process::ExitCode;
+use std::time::Instant;
 
 use path_clean::PathClean;
-use safe_rm::cli::{CliArgs, Commands};
-use safe_rm::config::Config;
+use safe_rm::audit::{AuditAction, AuditEntry, AuditLog};
+use safe_rm::backup::{self, BackupSession, RESTORE_SCRIPT};
+use safe_rm::cli::{self, CliArgs, ColorChoice, Commands, OpenCheckMode, OutputFormat};
+use safe_rm::config::{Config, ConfigWarning, DeleteOrder};
+use safe_rm::confirm;
 use safe_rm::error::{FileStatus, SafeRmError};
+use safe_rm::exclude::{self, ExcludeSet};
 use safe_rm::git_checker::GitChecker;
+use safe_rm::hook;
 use safe_rm::init;
+use safe_rm::lock::RepoLock;
+use safe_rm::open_files::OpenFileIndex;
 use safe_rm::path_checker::PathChecker;
+use safe_rm::pathspec;
+use safe_rm::progress::Progress;
+use safe_rm::report::{self, RunReport};
+use safe_rm::retry::{self, FsRemover, RemoveKind, RetryPolicy};
+use safe_rm::status::{self, StatusContext};
+use safe_rm::summary::{RemovalTotal, TypeSummary};
+use safe_rm::trace::Trace;
+use safe_rm::verify;
 
 fn main() -> ExitCode {
+    // logging フィーチャ有効時は RUST_LOG（例: RUST_LOG=safe_rm=debug）で内部の判断を stderr へ出力
+    #[cfg(feature = "logging")]
+    env_logger::init();
+
     let args = CliArgs::parse_args();
+    let colors = Colors::new(args.color);
+    let format = args.format;
+    // --config > SAFE_RM_CONFIG（複数指定時は順に重ねる）> デフォルトパス
+    let config_paths = Config::config_paths_with_override(args.config.as_deref());
+    let config_path = config_paths.last().cloned();
 
-    // サブコマンドの処理
-    if let Some(Commands::Init) = args.command {
-        match init::run_init() {
+    // サブコマンドの処理（終了コードは設定の [exit_codes] で上書きできる）
+    let (result, exit_codes) = match &args.command {
+        Some(Commands::Init) => match init::run_init(config_path) {
             Ok(()) => return ExitCode::SUCCESS,
             Err(e) => {
                 eprintln!("safe-rm: {}", e);
                 return ExitCode::FAILURE;
             }
+        },
+        Some(Commands::Reset {
+            force,
+            yes,
+            allow_self_config,
+        }) => {
+            let reset_path = Config::config_path_with_override(args.config.as_deref());
+            return match init::run_reset(reset_path, *force, *yes, *allow_self_config) {
+                Ok(()) => ExitCode::SUCCESS,
+                Err(e) => {
+                    eprintln!("safe-rm: {}", e);
+                    ExitCode::FAILURE
+                }
+            };
         }
-    }
+        Some(Commands::Status { paths }) => {
+            let config = load_config(&config_paths, format, args.verbose);
+            (
+                run_status(paths, format, &config, &config_paths),
+                config.exit_codes,
+            )
+        }
+        Some(Commands::Verify) => return run_verify(config_path.as_deref()),
+        Some(Commands::Config { list_allowed }) => {
+            return run_config(config_path.as_deref(), *list_allowed);
+        }
+        Some(Commands::Version { json }) => {
+            run_version(*json || format == OutputFormat::Json);
+            return ExitCode::SUCCESS;
+        }
+        None => {
+            let config = load_config(&config_paths, format, args.verbose);
+            let exit_codes = config.exit_codes;
+            (run(args, &colors, config, &config_paths), exit_codes)
+        }
+    };
 
-    match run(args) {
+    match result {
         Ok(()) => ExitCode::SUCCESS,
         Err(e) => {
-            eprintln!("safe-rm: {}", e);
-            e.exit_code().into()
+            print_error(format, &colors, None, &e);
+            e.exit_code_with(&exit_codes).into()
+        }
+    }
+}
+
+/// エラーを stderr へ出力
+///
+/// JSON 形式では1エラー1行の `{"type":"error","code":...}` とし、
+/// 文言や言語に依存せず `code` で判別できるようにする。
+/// JSON の `path` は `--relative-to` に関係なく引数のまま、`resolved` は絶対パス。
+fn print_error(
+    format: OutputFormat,
+    colors: &Colors,
+    path: Option<(&Path, &PathDisplay)>,
+    error: &SafeRmError,
+) {
+    match format {
+        OutputFormat::Human => {
+            let message = match path {
+                Some((path, display)) => {
+                    format!("safe-rm: {}: {}", display.show(path).display(), error)
+                }
+                None => format!("safe-rm: {}", error),
+            };
+            eprintln!("{}", colors.error(&message));
+        }
+        OutputFormat::Json => {
+            let resolved = path.map(|(path, display)| display.resolve(path));
+            eprintln!(
+                "{}",
+                report::error_entry(path.map(|(path, _)| path), resolved.as_deref(), error)
+            );
         }
     }
 }
 
+/// 出力に表示するパスの整形（`--relative-to`）
+struct PathDisplay {
+    cwd: PathBuf,
+    /// 指定時、パスをこのディレクトリからの相対パスで表示
+    base: Option<PathBuf>,
+}
+
+impl PathDisplay {
+    /// `--relative-to` の基準ディレクトリを cwd 基準で解決（シンボリックリンクも解決）
+    fn new(cwd: &Path, relative_to: Option<&Path>) -> Self {
+        let base = relative_to.map(|dir| {
+            let dir = cwd.join(dir);
+            dir.canonicalize().unwrap_or_else(|_| dir.clean())
+        });
+        Self {
+            cwd: cwd.to_path_buf(),
+            base,
+        }
+    }
+
+    /// 引数のパスを cwd 基準の絶対パスに解決
+    fn resolve(&self, path: &Path) -> PathBuf {
+        self.cwd.join(path).clean()
+    }
+
+    /// 表示用のパス
+    ///
+    /// `--relative-to` 未指定なら引数のまま。指定時は基準からの相対パスとし、
+    /// 基準の外にあるパスは絶対パスで表示する。
+    fn show(&self, path: &Path) -> PathBuf {
+        let Some(base) = &self.base else {
+            return path.to_path_buf();
+        };
+        let resolved = self.resolve(path);
+        match resolved.strip_prefix(base) {
+            Ok(relative) if relative.as_os_str().is_empty() => PathBuf::from("."),
+            Ok(relative) => relative.to_path_buf(),
+            Err(_) => resolved,
+        }
+    }
+}
+
+/// ANSI エスケープによる出力の色付け設定
+#[derive(Debug, Clone, Copy)]
+struct Colors {
+    stdout: bool,
+    stderr: bool,
+}
+
+impl Colors {
+    const GREEN: &'static str = "\x1b[32m";
+    const YELLOW: &'static str = "\x1b[33m";
+    const RED: &'static str = "\x1b[31m";
+    const RESET: &'static str = "\x1b[0m";
+
+    /// `--color` と NO_COLOR 環境変数から色付け有無を決定
+    ///
+    /// NO_COLOR（空でない値）は `--color=always` より優先して色付けを無効化する。
+    fn new(choice: ColorChoice) -> Self {
+        let no_color = std::env::var_os("NO_COLOR").is_some_and(|v| !v.is_empty());
+        match choice {
+            _ if no_color => Self::disabled(),
+            ColorChoice::Never => Self::disabled(),
+            ColorChoice::Always => Self {
+                stdout: true,
+                stderr: true,
+            },
+            ColorChoice::Auto => Self {
+                stdout: std::io::stdout().is_terminal(),
+                stderr: std::io::stderr().is_terminal(),
+            },
+        }
+    }
+
+    fn disabled() -> Self {
+        Self {
+            stdout: false,
+            stderr: false,
+        }
+    }
+
+    fn paint(enabled: bool, color: &str, text: &str) -> String {
+        if enabled {
+            format!("{}{}{}", color, text, Self::RESET)
+        } else {
+            text.to_string()
+        }
+    }
+
+    /// 削除成功（stdout, 緑）
+    fn removed(&self, text: &str) -> String {
+        Self::paint(self.stdout, Self::GREEN, text)
+    }
+
+    /// ドライラン（stdout, 黄）
+    fn would_remove(&self, text: &str) -> String {
+        Self::paint(self.stdout, Self::YELLOW, text)
+    }
+
+    /// エラー（stderr, 赤）
+    fn error(&self, text: &str) -> String {
+        Self::paint(self.stderr, Self::RED, text)
+    }
+}
+
+/// パス毎の処理で共有する実行コンテキスト
+struct RunContext<'a> {
+    project_root: &'a Path,
+    cwd: &'a Path,
+    git_checker: &'a Option<GitChecker>,
+    status_cache: &'a HashMap<Vec<u8>, FileStatus>,
+    args: &'a CliArgs,
+    config: &'a Config,
+    colors: &'a Colors,
+    /// `--backup` 指定時の退避先（削除の代わりに移動）
+    backup: Option<&'a RefCell<BackupSession>>,
+    /// `--check-open` 指定時の使用中ファイル索引
+    open_files: Option<&'a OpenFileIndex>,
+    /// `--exclude` の除外パターン
+    excludes: &'a ExcludeSet,
+    /// `--relative-to` による表示用パスの整形
+    display: &'a PathDisplay,
+    /// `--trace` 指定時の所要時間の出力
+    trace: Option<&'a Trace>,
+    /// 読み込んだ設定ファイル（自身の設定の削除を防ぐ）
+    config_files: &'a [PathBuf],
+}
+
+/// 緊急時に安全チェックを無効化する環境変数（`--i-understand-the-risk` と併用時のみ有効）
+const BREAK_GLASS_ENV: &str = "SAFE_RM_DISABLE";
+
+/// `SAFE_RM_DISABLE=1` と `--i-understand-the-risk` の両方が指定されているか
+///
+/// 片方だけでは誤って有効にならないよう、通常どおりの安全チェックを行う。
+fn break_glass_requested(args: &CliArgs) -> bool {
+    args.i_understand_the_risk
+        && std::env::var_os(BREAK_GLASS_ENV).is_some_and(|value| value == "1")
+}
+
 /// メイン実行ロジック
-fn run(args: CliArgs) -> Result<(), SafeRmError> {
-    // ユーザー設定の読み込み
-    let config = Config::load();
+fn run(
+    mut args: CliArgs,
+    colors: &Colors,
+    mut config: Config,
+    config_paths: &[PathBuf],
+) -> Result<(), SafeRmError> {
+    let started = Instant::now();
+    let trace = args.trace.then(|| Trace::new(args.format));
 
     // カレントディレクトリの取得
     let cwd = std::env::current_dir().map_err(SafeRmError::IoError)?;
 
-    // Git リポジトリを開く（存在する場合）
-    let git_checker = GitChecker::open(&cwd);
+    // --count はドライランの判定で件数だけを出力する
+    if args.count {
+        args.dry_run = true;
+    }
+
+    // 緊急用: 包含検証と Git チェックを無効化（protected_patterns などの明示的な設定は残す）
+    let break_glass = break_glass_requested(&args);
+    if break_glass {
+        print_break_glass_warning(args.format);
+        args.no_git = true;
+        args.per_repo = false;
+        config.deletion_scope.clear();
+    }
+
+    // Git リポジトリを開く（存在する場合）。--no-git ではリポジトリを開かずステータス取得も省く
+    let open_started = Instant::now();
+    let git_checker = if args.no_git {
+        None
+    } else {
+        open_git_checker(&cwd, &config, args.format)?
+    };
+    let git_checker = match (git_checker, &args.since) {
+        (Some(checker), Some(reference)) => Some(checker.with_since_ref(reference)?),
+        (None, Some(reference)) => {
+            return Err(SafeRmError::InvalidGitRef {
+                reference: reference.clone(),
+                reason: "not inside a Git repository".to_string(),
+            });
+        }
+        (checker, None) => checker,
+    };
+    if let Some(trace) = &trace {
+        trace.phase("open_repository", open_started.elapsed());
+    }
 
     // Git リポジトリルートをプロジェクト境界として使用（cwd ではなく）
     // 例: frontend/ から実行して backend/file.txt を削除する場合にも正しく動作
-    let project_root = git_checker
-        .as_ref()
-        .and_then(|checker| checker.workdir())
-        .unwrap_or_else(|| cwd.clone());
+    // boundary = "cwd" ならカレントディレクトリ配下に限定する
+    // --root 指定時はそちらを境界とする（--no-git で未指定なら cwd）
+    let project_root = match &args.root {
+        _ if break_glass => cwd.ancestors().last().unwrap_or(&cwd).to_path_buf(),
+        Some(root) => resolve_root_override(root, &cwd)?,
+        None => config.project_boundary(
+            git_checker.as_ref().and_then(|checker| checker.workdir()),
+            &cwd,
+        ),
+    };
+
+    for message in config.project_wide_allowed_path_warnings(&project_root) {
+        print_warning(args.format, None, &message);
+    }
+
+    let mut targets = collect_targets(&args, &config, &project_root, &cwd, git_checker.as_ref())?;
+    if break_glass {
+        check_preserve_root(&targets, &cwd, &project_root)?;
+    }
+    check_max_paths(&targets, &args, &config)?;
+    check_confirmation(&targets, &args, &config, &cwd)?;
+    let mut excludes = ExcludeSet::new(&args.exclude)?;
+    if let Some(older_than) = args.older_than {
+        let now = std::time::SystemTime::now();
+        excludes =
+            excludes.keep_newer_than(now.checked_sub(older_than).unwrap_or(std::time::UNIX_EPOCH));
+    }
+    if args.one_file_system {
+        excludes = excludes.one_file_system();
+    }
+    if let Some(depth) = args.depth {
+        excludes = excludes.max_depth(depth);
+    }
+    sort_targets(&mut targets, config.delete_order, &cwd, &excludes);
+    let display = PathDisplay::new(&cwd, args.relative_to.as_deref());
+
+    // use_lock: 確認の後、ステータス取得の前から実行終了までリポジトリのロックを保持
+    let mut repo_cache = RepoCache::default();
+    repo_cache.lock(git_checker.as_ref(), &config)?;
 
     // Git ステータスを必要時のみ一括事前取得（パフォーマンス最適化）
-    // allow_project_deletion 有効時はスキップ
-    let status_cache: HashMap<String, FileStatus> = if !config.allow_project_deletion {
-        git_checker
-            .as_ref()
-            .map(|checker| checker.get_all_statuses())
-            .unwrap_or_default()
-    } else {
-        HashMap::new()
+    let status_started = Instant::now();
+    let status_cache = build_status_cache(git_checker.as_ref(), &args, &config, &targets, &cwd)?;
+    if let Some(trace) = &trace {
+        trace.phase("git_status", status_started.elapsed());
+    }
+
+    // 監査ログを開けない場合は記録漏れを避けるため削除前に中断
+    let mut audit_log = args
+        .audit_log
+        .clone()
+        .or_else(|| config.audit_log_path())
+        .map(|path| AuditLog::open(&path))
+        .transpose()
+        .map_err(SafeRmError::IoError)?;
+
+    // ドライランでは何も移動しないためバックアップディレクトリも作らない
+    let backup = match &args.backup {
+        Some(dir) if !args.dry_run => Some(RefCell::new(
+            BackupSession::create(dir).map_err(SafeRmError::IoError)?,
+        )),
+        _ => None,
+    };
+
+    // 使用中ファイルの走査はコストが高いため、指定時に1回だけ行う
+    let open_files = args.check_open.map(|_| OpenFileIndex::scan());
+
+    // 読み込みに使った設定ファイルと同じものを保護する（SAFE_RM_CONFIG や --config を再解釈しない）
+    let config_files: Vec<PathBuf> = config_paths
+        .iter()
+        .filter(|path| path.exists())
+        .cloned()
+        .collect();
+
+    let ctx = RunContext {
+        project_root: &project_root,
+        cwd: &cwd,
+        git_checker: &git_checker,
+        status_cache: &status_cache,
+        args: &args,
+        config: &config,
+        colors,
+        backup: backup.as_ref(),
+        open_files: open_files.as_ref(),
+        excludes: &excludes,
+        display: &display,
+        trace: trace.as_ref(),
+        config_files: &config_files,
     };
 
     let mut success_count = 0;
-    let mut error_count = 0;
-    let mut max_exit_code: u8 = 0;
-    let mut last_error: Option<SafeRmError> = None;
+    let mut blocked_count = 0;
+    let mut not_found_count = 0;
+    let mut io_error_count = 0;
+    let mut last_block: Option<SafeRmError> = None;
+    let mut blocked_reasons: BTreeMap<&'static str, usize> = BTreeMap::new();
+    let mut type_summary = TypeSummary::new();
+    let mut removal_total = RemovalTotal::new();
+    let mut run_report = args.report.as_ref().map(|_| RunReport::new());
 
-    for path in &args.paths {
-        match process_path(
-            path,
-            &project_root,
-            &cwd,
-            &git_checker,
-            &status_cache,
-            &args,
-            &config,
-        ) {
+    if args.emit_script {
+        println!("#!/bin/sh\n# Generated by safe-rm --dry-run --emit-script\nset -e");
+    }
+
+    for (index, path) in targets.iter().enumerate() {
+        let summary = args.by_type.then_some(&mut type_summary);
+        let total = args.dry_run.then_some(&mut removal_total);
+        let path_started = Instant::now();
+
+        // --per-repo: パス毎に最寄りのリポジトリを境界・Git チェック対象とする
+        let repo_root = if args.per_repo {
+            repo_cache.resolve(&cwd.join(path).clean(), &args, &config)
+        } else {
+            Ok(None)
+        };
+        // boundary = "cwd" ではリポジトリ毎でも cwd を境界とする（Git チェックのみ切り替える）
+        let path_root = match &repo_root {
+            Ok(Some(root)) => Some(config.project_boundary(Some(root.clone()), &cwd)),
+            _ => None,
+        };
+        let path_ctx = match (&repo_root, &path_root) {
+            (Ok(Some(root)), Some(path_root)) => {
+                repo_cache
+                    .repos
+                    .get(root)
+                    .map(|(checker, cache)| RunContext {
+                        project_root: path_root,
+                        git_checker: checker,
+                        status_cache: cache,
+                        ..ctx
+                    })
+            }
+            _ => None,
+        }
+        .unwrap_or(RunContext { ..ctx });
+
+        // 削除後はステータスが変わるため、監査用のステータスは処理前に取得
+        let pre_status = audit_log
+            .as_ref()
+            .and_then(|_| audit_status(path, &path_ctx));
+        let result = match repo_root {
+            // リポジトリを開けなかったパスは処理せずエラーとして扱う
+            Err(e) => Err(e),
+            Ok(_) => {
+                let result = process_path(path, &path_ctx, summary, total);
+                if let Some(checker) = path_ctx.git_checker {
+                    print_skipped_unreadable(args.format, &checker.take_skipped_unreadable());
+                }
+                result
+            }
+        };
+        if let Some(trace) = &trace {
+            trace.path(path, path_started.elapsed());
+        }
+        #[cfg(feature = "logging")]
+        log::debug!(
+            "{}: {}",
+            path.display(),
+            match &result {
+                Ok(true) if args.dry_run => "would remove".to_string(),
+                Ok(true) => "removed".to_string(),
+                Ok(false) => "skipped".to_string(),
+                Err(e) if e.exit_code() == 2 => format!("blocked ({})", e.code()),
+                Err(e) => format!("failed ({})", e.code()),
+            }
+        );
+
+        if let Some(log) = audit_log.as_mut() {
+            record_audit(log, path, &cwd, args.dry_run, pre_status, &result);
+        }
+
+        if let Some(run_report) = run_report.as_mut() {
+            let resolved = display.resolve(path);
+            run_report.push(match &result {
+                Ok(true) => report::removed_entry(path, &resolved, args.dry_run),
+                Ok(false) => report::skipped_entry(path, &resolved),
+                Err(e) => report::error_entry(Some(path), Some(&resolved), e),
+            });
+        }
+
+        match result {
             Ok(deleted) => {
                 if deleted {
                     success_count += 1;
                 }
             }
             Err(e) => {
-                eprintln!("safe-rm: {}: {}", path.display(), e);
-                let exit_code = e.exit_code();
-                if exit_code > max_exit_code {
-                    max_exit_code = exit_code;
-                    last_error = Some(e);
-                } else if last_error.is_none() {
-                    last_error = Some(e);
+                if args.emit_script {
+                    // 出力したスクリプトと混ぜても実行されないようコメントにする
+                    for line in format!("{}: {}", display.show(path).display(), e).lines() {
+                        eprintln!("# {}", line);
+                    }
+                } else {
+                    print_error(args.format, colors, Some((path, &display)), &e);
+                }
+                match e {
+                    _ if e.exit_code() == 2 => {
+                        blocked_count += 1;
+                        *blocked_reasons.entry(e.code()).or_default() += 1;
+                        last_block = Some(e);
+                    }
+                    SafeRmError::NotFound(_) => not_found_count += 1,
+                    _ => io_error_count += 1,
+                }
+                // --fail-fast: 後続の削除が先の削除の成功を前提にしている場合に備え、残りは処理しない
+                if args.fail_fast {
+                    let remaining = targets.len() - index - 1;
+                    if remaining > 0 && args.format == OutputFormat::Human {
+                        eprintln!(
+                            "safe-rm: stopped at first error (--fail-fast); {} path(s) not processed",
+                            remaining
+                        );
+                    }
+                    break;
                 }
-                error_count += 1;
             }
         }
     }
 
-    if error_count > 0 {
-        // 最も高い終了コードのエラーを返す（セキュリティブロックが優先）
-        if max_exit_code == 2 {
-            // セキュリティエラーを直接返す
-            Err(last_error.unwrap())
-        } else {
+    if let Some(trace) = &trace {
+        trace.phase("total", started.elapsed());
+    }
+
+    // --emit-script ではスクリプトとして実行できるよう集計をコメントにする
+    let comment = if args.emit_script { "# " } else { "" };
+    if args.by_type && !type_summary.is_empty() {
+        println!("{}summary: {}", comment, type_summary);
+    }
+    // --print0 の stdout はパスと NUL のみ、--count は件数のみとする
+    if args.count {
+        println!("{}", removal_total.files);
+        // 件数の見積もりが目的のため、ブロック等は stderr に報告済みとして成功扱い
+        return Ok(());
+    }
+    if args.dry_run && !args.print0 {
+        println!("{}total: would remove {}", comment, removal_total);
+    }
+
+    if let Some(session) = &backup {
+        let session = session.borrow();
+        if !session.entries().is_empty() {
+            let line = format!(
+                "backup: {} (restore with: sh {})",
+                session.run_dir().display(),
+                session.run_dir().join(RESTORE_SCRIPT).display()
+            );
+            if args.print0 {
+                eprintln!("{}", line);
+            } else {
+                println!("{}", line);
+            }
+        }
+    }
+
+    // strict モードではバッチが完了しなかった理由を把握できるよう常に表示
+    if (args.summary || !config.allow_project_deletion) && !blocked_reasons.is_empty() {
+        print_blocked_summary(args.format, comment, &blocked_reasons);
+    }
+
+    let result = match last_block {
+        // ブロックのみの場合はそのエラーを直接返す
+        Some(e) if not_found_count == 0 && io_error_count == 0 => Err(e),
+        // 失敗が混在する場合はカテゴリ別の内訳を返す（ブロックを含めば終了コード 2）
+        _ if blocked_count + not_found_count + io_error_count > 0 => {
             Err(SafeRmError::PartialFailure {
-                success: success_count,
-                failed: error_count,
+                removed: success_count,
+                blocked: blocked_count,
+                not_found: not_found_count,
+                io_errors: io_error_count,
             })
         }
+        _ => Ok(()),
+    };
+
+    // 部分的な失敗でも CI が結果を確認できるよう、レポートは常に書き出す
+    if let (Some(report_path), Some(run_report)) = (&args.report, &run_report) {
+        let summary = serde_json::json!({
+            "type": "summary",
+            "dry_run": args.dry_run,
+            "removed": success_count,
+            "blocked": blocked_count,
+            "not_found": not_found_count,
+            "io_errors": io_error_count,
+            "exit_code": result
+                .as_ref()
+                .err()
+                .map_or(0, |e| e.exit_code_with(&config.exit_codes)),
+        });
+        if let Err(e) = run_report.write_atomic(report_path, summary) {
+            let error = SafeRmError::IoError(std::io::Error::new(
+                e.kind(),
+                format!("cannot write report ({}): {}", report_path.display(), e),
+            ));
+            // 削除結果のエラーを優先し、レポートの書き込み失敗は報告のみ
+            if result.is_ok() {
+                return Err(error);
+            }
+            print_error(args.format, colors, None, &error);
+        }
+    }
+
+    result
+}
+
+/// 監査ログ用に削除前の Git ステータスを取得（通常ファイルのみ）
+fn audit_status(path: &Path, ctx: &RunContext) -> Option<FileStatus> {
+    let checker = ctx.git_checker.as_ref()?;
+    let abs_path = ctx.cwd.join(path);
+    if !fs::symlink_metadata(&abs_path).ok()?.is_file() {
+        return None;
+    }
+    let canonical = abs_path.canonicalize().ok()?;
+    Some(checker.get_file_status_from_cache(&canonical, ctx.status_cache))
+}
+
+/// 処理結果を監査ログに1行追記
+///
+/// 書き込み失敗は警告のみとし、削除結果（終了コード）には影響させない。
+fn record_audit(
+    log: &mut AuditLog,
+    path: &Path,
+    cwd: &Path,
+    dry_run: bool,
+    pre_status: Option<FileStatus>,
+    result: &Result<bool, SafeRmError>,
+) {
+    let (action, status, reason) = match result {
+        // -f で存在しないパスを無視した場合は何も起きていないため記録しない
+        Ok(false) => return,
+        Ok(true) if dry_run => (AuditAction::WouldRemove, pre_status, None),
+        Ok(true) => (AuditAction::Removed, pre_status, None),
+        Err(e) => {
+            let action = if e.exit_code() == 2 {
+                AuditAction::Blocked
+            } else {
+                AuditAction::Failed
+            };
+            let status = match e {
+                SafeRmError::DirtyFiles { status, .. } => Some(*status),
+                _ => pre_status,
+            };
+            (action, status, Some(e.to_string()))
+        }
+    };
+
+    let abs_path = cwd.join(path).clean();
+    let entry = AuditEntry {
+        path: &abs_path,
+        action,
+        status,
+        reason,
+        cwd,
+    };
+    if let Err(e) = log.record(&entry) {
+        eprintln!("safe-rm: warning: cannot write audit log: {}", e);
+    }
+}
+
+/// config サブコマンド: 実効設定を表示（設定ファイルのパース失敗時は終了コード 1）
+///
+/// `--list-allowed` では allowed_paths の各エントリのみを1行ずつ表示する。
+fn run_config(config_path: Option<&Path>, list_allowed: bool) -> ExitCode {
+    if list_allowed {
+        return match verify::list_allowed_paths(config_path) {
+            Ok(lines) => {
+                for line in &lines {
+                    println!("{}", line);
+                }
+                ExitCode::SUCCESS
+            }
+            Err(e) => {
+                eprintln!("safe-rm: {}", e);
+                ExitCode::FAILURE
+            }
+        };
+    }
+
+    let (lines, failed) = verify::describe_config(config_path);
+    for line in &lines {
+        println!("{}", line);
+    }
+
+    if failed {
+        ExitCode::FAILURE
     } else {
-        Ok(())
+        ExitCode::SUCCESS
     }
 }
 
-/// 単一パスの削除処理
-fn process_path(
+/// version サブコマンド: バージョンとビルド情報を表示
+///
+/// エージェントが新しいフラグを使う前に対応状況を確認できるよう、`--json`（または
+/// `--format json`）では `{"version":...,"git2":...,"rustc":...,"features":[...]}` を出力する。
+/// `git2` はリンクしている libgit2 のバージョン、`features` は有効な Cargo フィーチャ。
+fn run_version(json: bool) {
+    let (major, minor, rev) = git2::Version::get().libgit2_version();
+    let git2 = format!("{}.{}.{}", major, minor, rev);
+    let rustc = option_env!("SAFE_RM_RUSTC_VERSION").unwrap_or("unknown");
+    let features: Vec<&str> = [("logging", cfg!(feature = "logging"))]
+        .into_iter()
+        .filter_map(|(name, enabled)| enabled.then_some(name))
+        .collect();
+
+    if json {
+        println!(
+            "{}",
+            serde_json::json!({
+                "version": env!("CARGO_PKG_VERSION"),
+                "git2": git2,
+                "rustc": rustc,
+                "features": features,
+            })
+        );
+    } else {
+        println!("safe-rm {}", env!("CARGO_PKG_VERSION"));
+        println!("libgit2: {}", git2);
+        println!("rustc: {}", rustc);
+        if features.is_empty() {
+            println!("features: (none)");
+        } else {
+            println!("features: {}", features.join(", "));
+        }
+    }
+}
+
+/// verify サブコマンド: 環境を診断し、失敗項目があれば終了コード 1
+fn run_verify(config_path: Option<&Path>) -> ExitCode {
+    let cwd = match std::env::current_dir() {
+        Ok(cwd) => cwd,
+        Err(e) => {
+            eprintln!("safe-rm: {}", e);
+            return ExitCode::FAILURE;
+        }
+    };
+
+    let checks = verify::run_checks(config_path, &cwd);
+    for check in &checks {
+        println!("{}", check);
+    }
+
+    if verify::has_failures(&checks) {
+        ExitCode::FAILURE
+    } else {
+        ExitCode::SUCCESS
+    }
+}
+
+/// 安全チェック無効化時も、rm の `--preserve-root` と同じくファイルシステムのルートは削除しない
+fn check_preserve_root(targets: &[PathBuf], cwd: &Path, fs_root: &Path) -> Result<(), SafeRmError> {
+    match targets
+        .iter()
+        .find(|target| PathChecker::try_canonicalize(&cwd.join(target)) == fs_root)
+    {
+        Some(target) => Err(SafeRmError::InvalidPath {
+            path: target.clone(),
+            reason: "refusing to remove the file system root".to_string(),
+        }),
+        None => Ok(()),
+    }
+}
+
+/// 安全チェックを無効化して実行することを stderr へ警告
+fn print_break_glass_warning(format: OutputFormat) {
+    let message = format!(
+        "{}=1 and --i-understand-the-risk are set: project containment and Git checks are DISABLED for this run. Files are deleted like plain rm.",
+        BREAK_GLASS_ENV
+    );
+    match format {
+        OutputFormat::Human => {
+            eprintln!("safe-rm: WARNING: {}", message);
+        }
+        OutputFormat::Json => eprintln!(
+            "{}",
+            serde_json::json!({
+                "type": "warning",
+                "path": null,
+                "message": message,
+            })
+        ),
+    }
+}
+
+/// 警告を `--format` に応じた形式で stderr に出力（JSON では `{"type":"warning",...}` の1行）
+fn print_warning(format: OutputFormat, path: Option<&Path>, message: &str) {
+    match format {
+        OutputFormat::Human => eprintln!("safe-rm: warning: {}", message),
+        OutputFormat::Json => eprintln!(
+            "{}",
+            serde_json::json!({
+                "type": "warning",
+                "path": path,
+                "message": message,
+            })
+        ),
+    }
+}
+
+/// ブロックされたパスの件数をエラーコード別に stderr へ1行で出力
+///
+/// JSON 形式では `{"type":"blocked_summary","blocked":{"dirty_files":2,...}}` とする。
+fn print_blocked_summary(
+    format: OutputFormat,
+    comment: &str,
+    blocked_reasons: &BTreeMap<&'static str, usize>,
+) {
+    match format {
+        OutputFormat::Human => {
+            let counts: Vec<String> = blocked_reasons
+                .iter()
+                .map(|(code, count)| format!("{}={}", code, count))
+                .collect();
+            eprintln!(
+                "{}safe-rm: blocked by reason: {}",
+                comment,
+                counts.join(", ")
+            );
+        }
+        OutputFormat::Json => eprintln!(
+            "{}",
+            serde_json::json!({
+                "type": "blocked_summary",
+                "blocked": blocked_reasons,
+            })
+        ),
+    }
+}
+
+/// `directory_read_policy = "skip_unreadable"` で除外したディレクトリを stderr に警告
+fn print_skipped_unreadable(format: OutputFormat, dirs: &[PathBuf]) {
+    for dir in dirs {
+        let message = format!(
+            "skipped unreadable directory '{}' (directory_read_policy = skip_unreadable)",
+            dir.display()
+        );
+        print_warning(format, Some(dir), &message);
+    }
+}
+
+/// ユーザー設定を読み込み、警告を `--format` に応じた形式で stderr に出力
+///
+/// JSON 形式では1警告1行の `{"type":"warning","path":...,"message":...}` とし、
+/// JSON を読むパイプラインの stderr にプレーンテキストを混ぜない。
+/// `--verbose` 時は存在しない allowed_paths エントリも警告する（フォールバックは従来どおり）。
+fn load_config(config_paths: &[PathBuf], format: OutputFormat, verbose: bool) -> Config {
+    let (config, mut warnings) = Config::load_layered_with_warnings(config_paths);
+    if verbose {
+        if let Some(path) = config_paths.last() {
+            warnings.extend(
+                config
+                    .missing_allowed_path_warnings()
+                    .into_iter()
+                    .map(|message| ConfigWarning {
+                        path: path.clone(),
+                        message,
+                    }),
+            );
+        }
+    }
+    for warning in &warnings {
+        print_warning(format, Some(&warning.path), &warning.message);
+    }
+    config
+}
+
+/// status サブコマンド: 削除せずに各パスのステータスと削除可否を表示
+fn run_status(
+    paths: &[PathBuf],
+    format: OutputFormat,
+    config: &Config,
+    config_paths: &[PathBuf],
+) -> Result<(), SafeRmError> {
+    let cwd = std::env::current_dir().map_err(SafeRmError::IoError)?;
+    let git_checker = open_git_checker(&cwd, config, format)?;
+    let project_root = config.project_boundary(
+        git_checker.as_ref().and_then(|checker| checker.workdir()),
+        &cwd,
+    );
+    // デフォルトモードでは無視ファイルの走査を省く（キャッシュにないパスは個別に問い合わせる）
+    let status_cache = git_checker
+        .as_ref()
+        .map(|checker| checker.try_get_statuses(!config.allow_project_deletion))
+        .transpose()?
+        .unwrap_or_default();
+    let config_files: Vec<PathBuf> = config_paths
+        .iter()
+        .filter(|path| path.exists())
+        .cloned()
+        .collect();
+
+    let ctx = StatusContext {
+        cwd: &cwd,
+        project_root: &project_root,
+        git_checker: git_checker.as_ref(),
+        status_cache: &status_cache,
+        config,
+        config_files: &config_files,
+    };
+    let reports: Vec<_> = paths.iter().map(|p| status::evaluate(p, &ctx)).collect();
+
+    match format {
+        OutputFormat::Human => {
+            for report in &reports {
+                println!("{}", report);
+            }
+        }
+        OutputFormat::Json => {
+            let json = serde_json::to_string_pretty(&reports)
+                .map_err(|e| SafeRmError::IoError(std::io::Error::other(e)))?;
+            println!("{}", json);
+        }
+    }
+    Ok(())
+}
+
+/// Git ステータスを一括事前取得
+///
+/// allow_project_deletion 有効時、および --recheck 時（削除直前に再取得）は空のまま。
+/// git_timeout_ms 内に取得できなければ `GitTimeout`（fail-closed で何も削除しない）。
+fn build_status_cache(
+    git_checker: Option<&GitChecker>,
+    args: &CliArgs,
+    config: &Config,
+    targets: &[PathBuf],
+    cwd: &Path,
+) -> Result<HashMap<Vec<u8>, FileStatus>, SafeRmError> {
+    if config.allow_project_deletion || args.recheck {
+        return Ok(HashMap::new());
+    }
+    // `-r node_modules` や `-r target crates/foo/target` のように対象がすべて無視ディレクトリなら、
+    // チェックはディレクトリ自体の判定で完了するためリポジトリ全体の走査を省略
+    // （深い階層の無視ディレクトリでもリポジトリ全体の無視ファイルを列挙せずに済む）
+    if let Some(checker) = git_checker {
+        if args.recursive
+            && !targets.is_empty()
+            && targets
+                .iter()
+                .all(|target| is_ignored_directory_target(checker, &cwd.join(target)))
+        {
+            return Ok(HashMap::new());
+        }
+    }
+    git_checker
+        .map(|checker| checker.try_get_statuses(true))
+        .transpose()
+        .map(Option::unwrap_or_default)
+}
+
+/// 対象が実ディレクトリ（シンボリックリンクでない）かつ Git で無視されているか
+fn is_ignored_directory_target(checker: &GitChecker, path: &Path) -> bool {
+    path.canonicalize()
+        .is_ok_and(|canonical| checker.is_ignored_directory(&canonical))
+}
+
+/// `--root` で指定された境界を解決
+///
+/// 境界の外から実行されると包含検証が意味をなさないため、cwd の祖先（または cwd 自体）に限る。
+fn resolve_root_override(root: &Path, cwd: &Path) -> Result<PathBuf, SafeRmError> {
+    let invalid = |reason: &str| SafeRmError::InvalidPath {
+        path: root.to_path_buf(),
+        reason: reason.to_string(),
+    };
+    let canonical_root = cwd
+        .join(root)
+        .canonicalize()
+        .map_err(|_| invalid("--root directory does not exist"))?;
+    if !canonical_root.is_dir() {
+        return Err(invalid("--root is not a directory"));
+    }
+    let canonical_cwd = cwd.canonicalize().map_err(SafeRmError::IoError)?;
+    if !canonical_cwd.starts_with(&canonical_root) {
+        return Err(invalid(
+            "--root must be the current directory or one of its ancestors",
+        ));
+    }
+    Ok(canonical_root)
+}
+
+/// Git リポジトリを開く
+///
+/// 破損した `.git` などで開けない場合、strict モードや strict_paths 指定時は Git チェックが
+/// できないため fail-closed でエラーを返し、それ以外は警告してリポジトリなしとして続行する。
+fn open_git_checker(
     path: &Path,
+    config: &Config,
+    format: OutputFormat,
+) -> Result<Option<GitChecker>, SafeRmError> {
+    match GitChecker::try_open(path) {
+        Ok(checker) => Ok(checker.map(|c| {
+            c.with_policy(config.deletable)
+                .with_generated_as_deletable(config.treat_generated_as_deletable)
+                .with_directory_read_policy(config.directory_read_policy)
+                .with_status_timeout(config.git_timeout())
+        })),
+        Err(e) if config.requires_git_repository() => Err(e),
+        Err(SafeRmError::GitUnavailable { path, reason }) => {
+            print_warning(
+                format,
+                Some(&path),
+                &format!(
+                    "cannot open Git repository ({}); continuing without Git checks",
+                    reason
+                ),
+            );
+            Ok(None)
+        }
+        Err(e) => Err(e),
+    }
+}
+
+/// リポジトリ毎の GitChecker とステータスキャッシュ
+type RepoEntry = (Option<GitChecker>, HashMap<Vec<u8>, FileStatus>);
+
+/// `--per-repo` 用: パス毎に検出したリポジトリのキャッシュ
+#[derive(Default)]
+struct RepoCache {
+    /// 検出開始ディレクトリ → リポジトリルート（リポジトリ外は None）
+    discovered: HashMap<PathBuf, Option<PathBuf>>,
+    /// リポジトリルート → (GitChecker, ステータスキャッシュ)
+    repos: HashMap<PathBuf, RepoEntry>,
+    /// use_lock: 共通 Git ディレクトリ → 保持中のロック（同じリポジトリを二重にロックしない）
+    locks: HashMap<PathBuf, RepoLock>,
+}
+
+impl RepoCache {
+    /// use_lock 有効時、リポジトリのロックを取得して実行終了まで保持
+    ///
+    /// リポジトリ外（checker なし）ではロックしない。
+    fn lock(&mut self, checker: Option<&GitChecker>, config: &Config) -> Result<(), SafeRmError> {
+        let Some(checker) = checker.filter(|_| config.use_lock) else {
+            return Ok(());
+        };
+        let common_dir = checker
+            .common_dir()
+            .canonicalize()
+            .unwrap_or_else(|_| checker.common_dir().to_path_buf());
+        if let Entry::Vacant(entry) = self.locks.entry(common_dir) {
+            let lock = RepoLock::acquire(entry.key(), config.lock_timeout())?;
+            entry.insert(lock);
+        }
+        Ok(())
+    }
+
+    /// 対象パスを含むリポジトリを検出してルートを返す
+    ///
+    /// 対象自体がリポジトリルートの場合にリポジトリ全体を「プロジェクト内」と
+    /// 誤判定しないよう、検出は対象の親（存在する最寄りの祖先）から開始する。
+    fn resolve(
+        &mut self,
+        abs_path: &Path,
+        args: &CliArgs,
+        config: &Config,
+    ) -> Result<Option<PathBuf>, SafeRmError> {
+        let Some(start) = abs_path.ancestors().skip(1).find(|dir| dir.is_dir()) else {
+            return Ok(None);
+        };
+        if let Some(root) = self.discovered.get(start) {
+            return Ok(root.clone());
+        }
+
+        let checker = open_git_checker(start, config, args.format)?;
+        let root = checker.as_ref().and_then(|c| c.workdir());
+        if let Some(root) = &root {
+            if !self.repos.contains_key(root) {
+                self.lock(checker.as_ref(), config)?;
+                let cache = build_status_cache(checker.as_ref(), args, config, &[], root)?;
+                self.repos.insert(root.clone(), (checker, cache));
+            }
+        }
+        self.discovered.insert(start.to_path_buf(), root.clone());
+        Ok(root)
+    }
+}
+
+/// 設定の delete_order に従って削除対象を並べ替える（安定ソート）
+///
+/// `smallest_first` のサイズは `--dry-run` の合計と同じく、ディレクトリ配下の
+/// 除外されないファイルの合計。存在しないパスは 0 として先頭に来る。
+fn sort_targets(targets: &mut [PathBuf], order: DeleteOrder, cwd: &Path, excludes: &ExcludeSet) {
+    match order {
+        DeleteOrder::AsGiven => {}
+        DeleteOrder::SmallestFirst => targets.sort_by_cached_key(|target| {
+            let mut total = RemovalTotal::new();
+            total.add_path(&cwd.join(target), excludes);
+            total.bytes
+        }),
+        DeleteOrder::PathSorted => targets.sort_by_cached_key(|target| cwd.join(target).clean()),
+    }
+}
+
+/// 位置引数と `--files-from` のリストを結合して削除対象を決定
+///
+/// 相対パスで指定されたリストファイル自体もプロジェクト内にあることを検証する。
+fn collect_targets(
+    args: &CliArgs,
+    config: &Config,
     project_root: &Path,
     cwd: &Path,
-    git_checker: &Option<GitChecker>,
-    status_cache: &HashMap<String, FileStatus>,
-    args: &CliArgs,
+    git_checker: Option<&GitChecker>,
+) -> Result<Vec<PathBuf>, SafeRmError> {
+    let mut targets = args.paths.clone();
+
+    if let Some(list_path) = &args.files_from {
+        let list_file = if list_path.is_absolute() {
+            list_path.clone()
+        } else {
+            PathChecker::verify_containment_with_case(
+                project_root,
+                cwd,
+                list_path,
+                config.case_insensitive,
+            )?
+        };
+        let content = fs::read_to_string(&list_file).map_err(SafeRmError::IoError)?;
+        targets.extend(cli::parse_file_list(&content));
+    }
+
+    if args.pathspec {
+        targets = expand_pathspecs(&targets, git_checker, cwd)?;
+    }
+
+    if args.contents_only {
+        targets = expand_contents(targets, config, project_root, cwd)?;
+    }
+
+    Ok(targets)
+}
+
+/// `--pathspec` の各 pathspec を一致するファイルに展開
+///
+/// pathspec はリポジトリに対して解決するため、リポジトリ外ではエラーとする。
+fn expand_pathspecs(
+    specs: &[PathBuf],
+    git_checker: Option<&GitChecker>,
+    cwd: &Path,
+) -> Result<Vec<PathBuf>, SafeRmError> {
+    let Some((checker, workdir)) =
+        git_checker.and_then(|checker| checker.workdir().map(|workdir| (checker, workdir)))
+    else {
+        return Err(SafeRmError::InvalidPath {
+            path: specs.first().cloned().unwrap_or_default(),
+            reason: "--pathspec requires a Git repository with a working tree".to_string(),
+        });
+    };
+    let files = checker.worktree_files()?;
+    pathspec::expand(specs, &files, &workdir, cwd)
+}
+
+/// `--contents-only` の対象ディレクトリを直下のエントリに展開
+///
+/// 各エントリは通常の対象と同じ安全チェックを受け、ディレクトリ自体は残る。
+/// プロジェクト外（allowed_paths 以外）のディレクトリは中身を列挙せずそのまま残し、
+/// 通常どおりブロックさせる。存在しないパスも通常の処理（`-f` でスキップ）に任せる。
+/// ディレクトリ以外は意図と異なる削除を避けるため、何も削除する前にエラーとする。
+fn expand_contents(
+    targets: Vec<PathBuf>,
     config: &Config,
+    project_root: &Path,
+    cwd: &Path,
+) -> Result<Vec<PathBuf>, SafeRmError> {
+    let mut expanded = Vec::new();
+    for target in targets {
+        let abs_path = cwd.join(&target);
+        let contained = config
+            .allowed_path_bypass(&abs_path, project_root)
+            .is_some()
+            || PathChecker::verify_containment_with_case(
+                project_root,
+                cwd,
+                &target,
+                config.case_insensitive,
+            )
+            .is_ok();
+        let metadata = match fs::symlink_metadata(&abs_path) {
+            Ok(metadata) if contained => metadata,
+            _ => {
+                expanded.push(target);
+                continue;
+            }
+        };
+        if !metadata.is_dir() {
+            return Err(SafeRmError::InvalidPath {
+                path: target,
+                reason: "not a directory (--contents-only)".to_string(),
+            });
+        }
+
+        let mut children = fs::read_dir(&abs_path)
+            .and_then(|entries| {
+                entries
+                    .map(|entry| entry.map(|entry| target.join(entry.file_name())))
+                    .collect::<std::io::Result<Vec<_>>>()
+            })
+            .map_err(|_| SafeRmError::DirectoryReadError {
+                path: abs_path.clone(),
+            })?;
+        children.sort();
+        expanded.extend(children);
+    }
+    Ok(expanded)
+}
+
+/// 単一パスの削除処理
+///
+/// `summary` が指定された場合、削除（またはドライラン）対象を種別ごとに集計する。
+/// `total` が指定された場合、削除対象のファイル数と合計サイズを集計する（ドライラン用）。
+fn process_path(
+    path: &Path,
+    ctx: &RunContext,
+    summary: Option<&mut TypeSummary>,
+    total: Option<&mut RemovalTotal>,
 ) -> Result<bool, SafeRmError> {
+    let RunContext {
+        project_root,
+        cwd,
+        git_checker,
+        status_cache,
+        args,
+        config,
+        colors,
+        ..
+    } = *ctx;
+
+    // 空文字列は cwd.join("") で cwd 自体に解決されてしまうため、解決前に拒否
+    if path.as_os_str().is_empty() {
+        return Err(SafeRmError::InvalidPath {
+            path: path.to_path_buf(),
+            reason: "empty path argument".to_string(),
+        });
+    }
+
     // 絶対パスに変換（相対パスは cwd から解決、git root からではない）
     let abs_path = if path.is_absolute() {
         path.to_path_buf()
@@ -135,10 +1258,17 @@ fn process_path(
         cwd.join(path)
     };
 
+    // protected_patterns と denied_paths は allowed_paths や Git ステータスに関係なくブロック
+    if let Some(block) = config.is_blocked_by_policy(&abs_path, project_root)? {
+        return Err(block.into());
+    }
+
     // allowed_paths 内のパスか確認（包含検証と Git チェックをバイパス）
-    if config.is_path_allowed(&abs_path) {
+    if let Some(matched) = config.allowed_path_bypass(&abs_path, project_root) {
         // メタデータを1回の syscall で取得（exists() + is_dir() の代替）
-        let metadata = match std::fs::symlink_metadata(&abs_path) {
+        let metadata = std::fs::symlink_metadata(&abs_path);
+        PathChecker::check_directory_assertion(path, &abs_path, &metadata)?;
+        let metadata = match metadata {
             Ok(m) => m,
             Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
                 if args.force {
@@ -150,18 +1280,55 @@ fn process_path(
             Err(e) => return Err(SafeRmError::IoError(e)),
         };
 
-        // ディレクトリに -r フラグがない場合はエラー
-        if metadata.is_dir() && !args.recursive {
-            return Err(SafeRmError::IsDirectory(abs_path));
+        // ディレクトリに -r フラグがない場合はエラー（-d なら空ディレクトリのみ可）
+        check_directory_flags(&abs_path, &metadata, args)?;
+        check_special_file(&abs_path, &metadata, args, config)?;
+        check_cwd_deletion(&abs_path, &metadata, ctx)?;
+        check_self_config(&abs_path, ctx)?;
+        if is_newer_than_cutoff(&abs_path, &metadata, ctx)? {
+            return Ok(false);
+        }
+
+        check_nested_repository(&abs_path, &metadata, ctx)?;
+        check_min_age(&abs_path, &metadata, args, config)?;
+        check_open_file(&abs_path, &metadata, ctx)?;
+        check_pre_delete_hook(&abs_path, ctx)?;
+
+        if let Some(summary) = summary {
+            summary.add_path(&abs_path, ctx.excludes);
+        }
+        if let Some(total) = total {
+            total.add_path(&abs_path, ctx.excludes);
         }
 
         // 削除実行（またはドライラン）— 包含検証と Git チェックをスキップ
-        if args.dry_run {
-            println!("would remove: {} (allowed by config)", path.display());
+        if args.emit_script {
+            println!("{}", script_command(&abs_path, &metadata, args.recursive));
+            Ok(true)
+        } else if args.count {
+            Ok(true)
+        } else if args.print0 {
+            if !args.dry_run {
+                remove_target(path, &abs_path, &metadata, ctx)?;
+            }
+            print_null_terminated(&ctx.display.show(path));
+            Ok(true)
+        } else if args.dry_run {
+            println!(
+                "{} {} (allowed by config: {})",
+                colors.would_remove("would remove:"),
+                ctx.display.show(path).display(),
+                matched
+            );
             Ok(true)
         } else {
-            delete_path_with_metadata(&abs_path, args.recursive, &metadata)?;
-            println!("removed: {} (allowed by config)", path.display());
+            remove_target(path, &abs_path, &metadata, ctx)?;
+            println!(
+                "{} {} (allowed by config: {})",
+                colors.removed("removed:"),
+                ctx.display.show(path).display(),
+                matched
+            );
             Ok(true)
         }
     } else {
@@ -169,11 +1336,19 @@ fn process_path(
 
         // パスがプロジェクト内にあることを最初に検証（セキュリティチェック優先）
         // プロジェクト外のファイル存在情報の漏洩を防止
-        let canonical_path = PathChecker::verify_containment_with_base(project_root, cwd, path)?;
+        let canonical_path = PathChecker::verify_containment_with_case(
+            project_root,
+            cwd,
+            path,
+            config.case_insensitive,
+        )?;
+        check_deletion_scope(path, &canonical_path, project_root, config)?;
         let normalized_path = abs_path.clean();
 
         // メタデータを1回の syscall で取得（exists() + is_dir() の代替）
-        let metadata = match std::fs::symlink_metadata(&abs_path) {
+        let metadata = std::fs::symlink_metadata(&abs_path);
+        PathChecker::check_directory_assertion(path, &abs_path, &metadata)?;
+        let metadata = match metadata {
             Ok(m) => m,
             Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
                 if args.force {
@@ -185,76 +1360,750 @@ fn process_path(
             Err(e) => return Err(SafeRmError::IoError(e)),
         };
 
-        // ディレクトリに -r フラグがない場合はエラー
-        if metadata.is_dir() && !args.recursive {
-            return Err(SafeRmError::IsDirectory(abs_path));
+        // ディレクトリに -r フラグがない場合はエラー（-d なら空ディレクトリのみ可）
+        check_directory_flags(&abs_path, &metadata, args)?;
+        check_special_file(&abs_path, &metadata, args, config)?;
+        check_cwd_deletion(&abs_path, &metadata, ctx)?;
+        check_self_config(&abs_path, ctx)?;
+        if is_newer_than_cutoff(&abs_path, &metadata, ctx)? {
+            return Ok(false);
         }
 
+        check_nested_repository(&normalized_path, &metadata, ctx)?;
+        check_min_age(&abs_path, &metadata, args, config)?;
+
         // 事前取得キャッシュを使用して Git ステータスをチェック（バッチ最適化）
-        // allow_project_deletion 有効時はスキップ（包含検証は上記で完了）
-        if !config.allow_project_deletion {
+        // allow_project_deletion 有効時（strict_paths に掛かるパスを除く）、および
+        // always_deletable_extensions に一致するファイルはスキップ（包含検証は上記で完了）
+        let always_deletable =
+            !metadata.is_dir() && config.is_always_deletable_extension(&normalized_path);
+        // シンボリックリンクはリンク自体をチェック（親のみ canonicalize）
+        let git_check_path = &PathChecker::git_check_path(
+            &normalized_path,
+            &canonical_path,
+            metadata.file_type().is_symlink(),
+        );
+        // コンフリクト中のファイルはモードに関係なくブロック
+        if let Some(checker) = git_checker {
+            checker.check_conflicts(git_check_path)?;
+        }
+        // --partial-dir で残すファイル（削除対象ディレクトリからの相対パスとステータス）
+        let mut kept_dirty = Vec::new();
+        if config.requires_git_check(&canonical_path, project_root)
+            && !always_deletable
+            && !args.no_git
+        {
             if let Some(checker) = git_checker {
-                // シンボリックリンクの場合、親ディレクトリのみ canonicalize し
-                // リンク名自体は保持。「リンク自体をチェック」するセマンティクスを
-                // 維持しつつ、リポジトリエイリアスパスを解決する。
-                let symlink_git_check_path: Option<std::path::PathBuf> =
-                    if metadata.file_type().is_symlink() {
-                        Some(
-                            normalized_path
-                                .file_name()
-                                .and_then(|name| {
-                                    normalized_path
-                                        .parent()
-                                        .and_then(|parent| parent.canonicalize().ok())
-                                        .map(|canonical_parent| canonical_parent.join(name))
-                                })
-                                .unwrap_or_else(|| normalized_path.clone()),
-                        )
+                let result = if (args.list_dirty || args.partial_dir) && metadata.is_dir() {
+                    // 最初のブロッカーで止めず、ディレクトリ内の削除できないファイルを全件報告
+                    let fresh;
+                    let cache = if args.recheck {
+                        fresh = checker.try_get_statuses(true)?;
+                        &fresh
                     } else {
-                        None
+                        status_cache
                     };
-                let git_check_path = symlink_git_check_path.as_deref().unwrap_or(&canonical_path);
-                checker.check_path_with_cache(git_check_path, status_cache)?;
+                    match checker.check_directory_listing_all_with_cache(git_check_path, cache) {
+                        // --partial-dir: 削除できないファイルと親ディレクトリを残して残りを削除
+                        Err(SafeRmError::DirtyDirectory { dir, offenders }) if args.partial_dir => {
+                            kept_dirty = offenders
+                                .into_iter()
+                                .filter_map(|(offender, status)| {
+                                    let relative = offender.strip_prefix(&dir).ok()?;
+                                    Some((relative.to_path_buf(), status))
+                                })
+                                .collect();
+                            Ok(())
+                        }
+                        result => result,
+                    }
+                } else if args.recheck {
+                    // 事前スキャン後の変更を取りこぼさないよう status_file で都度判定
+                    checker.check_path(git_check_path)
+                } else {
+                    checker.check_path_with_cache(git_check_path, status_cache)
+                };
+                if let Err(e) = result {
+                    if args.dry_run
+                        && !args.emit_script
+                        && !args.print0
+                        && !args.count
+                        && metadata.is_dir()
+                    {
+                        print_blocked_directory_preview(
+                            &ctx.display.show(path),
+                            git_check_path,
+                            checker,
+                            status_cache,
+                        );
+                    }
+                    return Err(e);
+                }
+            } else if !config.deletable.allows(FileStatus::NotInRepo) {
+                // リポジトリ自体がない場合も Git 管理外として扱う
+                return Err(SafeRmError::DirtyFiles {
+                    path: abs_path,
+                    status: FileStatus::NotInRepo,
+                });
             }
         }
+        if let Some(checker) = git_checker {
+            checker.check_unchanged_since(git_check_path)?;
+        }
+
+        check_open_file(&abs_path, &metadata, ctx)?;
+        check_pre_delete_hook(&abs_path, ctx)?;
+
+        // 残すファイルを除外に加えたコンテキストで削除（ドライランの集計も同様）
+        let partial_excludes;
+        let partial_ctx;
+        let ctx = if kept_dirty.is_empty() {
+            ctx
+        } else {
+            partial_excludes = ctx.excludes.clone().keep_paths(
+                kept_dirty
+                    .iter()
+                    .map(|(relative, _)| relative.clone())
+                    .collect(),
+            );
+            partial_ctx = RunContext {
+                excludes: &partial_excludes,
+                ..*ctx
+            };
+            &partial_ctx
+        };
+
+        if let Some(summary) = summary {
+            summary.add_path(&abs_path, ctx.excludes);
+        }
+        if let Some(total) = total {
+            total.add_path(&abs_path, ctx.excludes);
+        }
 
         // 削除実行（またはドライラン）
-        if args.dry_run {
-            println!("would remove: {}", path.display());
+        let result = if args.emit_script {
+            println!("{}", script_command(&abs_path, &metadata, args.recursive));
+            Ok(true)
+        } else if args.count {
+            Ok(true)
+        } else if args.print0 {
+            if !args.dry_run {
+                remove_target(path, &abs_path, &metadata, ctx)?;
+            }
+            print_null_terminated(&ctx.display.show(path));
+            Ok(true)
+        } else if args.dry_run {
+            println!(
+                "{} {}",
+                colors.would_remove("would remove:"),
+                ctx.display.show(path).display()
+            );
             Ok(true)
         } else {
-            delete_path_with_metadata(&abs_path, args.recursive, &metadata)?;
-            println!("removed: {}", path.display());
+            remove_target(path, &abs_path, &metadata, ctx)?;
+            println!(
+                "{} {}",
+                colors.removed("removed:"),
+                ctx.display.show(path).display()
+            );
             Ok(true)
+        };
+        if !args.print0 && !args.count {
+            for (relative, status) in &kept_dirty {
+                println!(
+                    "skipped: {} ({})",
+                    ctx.display.show(path).join(relative).display(),
+                    status
+                );
+            }
+        }
+        result
+    }
+}
+
+/// `--print0`: パスと NUL 終端のみを stdout へ書き出す（非 UTF-8 のパスもそのまま）
+fn print_null_terminated(path: &Path) {
+    let mut stdout = std::io::stdout().lock();
+    // パイプ先が閉じられた場合などの書き込み失敗は削除結果に影響させない
+    let _ = stdout
+        .write_all(path.as_os_str().as_encoded_bytes())
+        .and_then(|()| stdout.write_all(b"\0"))
+        .and_then(|()| stdout.flush());
+}
+
+/// ドライランでブロックされたディレクトリの内訳を表示
+///
+/// 自身がブロック要因のファイル（blocker）と、自身は削除可能だが
+/// 含まれるディレクトリにブロック要因があるため削除できないファイルを区別する。
+/// 後者は個別指定すれば削除できる。
+fn print_blocked_directory_preview(
+    display_dir: &Path,
+    checked_dir: &Path,
+    checker: &GitChecker,
+    status_cache: &HashMap<Vec<u8>, FileStatus>,
+) {
+    let Ok(mut entries) = checker.collect_directory_statuses_with_cache(checked_dir, status_cache)
+    else {
+        return;
+    };
+    entries.sort();
+
+    for (entry, status) in entries {
+        let deletable = checker.is_path_deletable(&entry, status);
+        let display = entry
+            .strip_prefix(checked_dir)
+            .map(|rel| display_dir.join(rel))
+            .unwrap_or(entry);
+        if deletable {
+            println!(
+                "blocked by directory: {} ({}, but {} has blockers)",
+                display.display(),
+                status,
+                display_dir.display()
+            );
+        } else {
+            println!("blocker: {} ({})", display.display(), status);
+        }
+    }
+}
+
+/// ディレクトリに対するフラグを検証
+///
+/// -r なしはエラー。ただし -d 指定時は空のディレクトリに限り許可する。
+fn check_directory_flags(
+    abs_path: &Path,
+    metadata: &std::fs::Metadata,
+    args: &CliArgs,
+) -> Result<(), SafeRmError> {
+    if !metadata.is_dir() || args.recursive {
+        return Ok(());
+    }
+    if !args.dir {
+        return Err(SafeRmError::IsDirectory(abs_path.to_path_buf()));
+    }
+    let mut entries = fs::read_dir(abs_path).map_err(SafeRmError::IoError)?;
+    if entries.next().is_some() {
+        return Err(SafeRmError::DirectoryNotEmpty(abs_path.to_path_buf()));
+    }
+    Ok(())
+}
+
+/// `--older-than` の基準より新しいファイル（エラーにせず黙ってスキップする）か
+///
+/// ディレクトリは配下のエントリごとに削除時に判定するため対象外。
+fn is_newer_than_cutoff(
+    abs_path: &Path,
+    metadata: &std::fs::Metadata,
+    ctx: &RunContext,
+) -> Result<bool, SafeRmError> {
+    if metadata.is_dir() {
+        return Ok(false);
+    }
+    ctx.excludes
+        .is_newer(abs_path)
+        .map_err(SafeRmError::IoError)
+}
+
+/// デバイスファイル・ソケット・FIFO などの特殊ファイルをブロック
+///
+/// `-f` または allow_special_files で許可する。
+fn check_special_file(
+    abs_path: &Path,
+    metadata: &std::fs::Metadata,
+    args: &CliArgs,
+    config: &Config,
+) -> Result<(), SafeRmError> {
+    if args.force || config.allow_special_files {
+        return Ok(());
+    }
+    match PathChecker::special_file_kind(metadata.file_type()) {
+        Some(kind) => Err(SafeRmError::SpecialFile {
+            path: abs_path.to_path_buf(),
+            kind,
+        }),
+        None => Ok(()),
+    }
+}
+
+/// カレントディレクトリ自体またはその祖先の削除をブロック（`-f` で無視）
+///
+/// 実行中のプロセスやシェルの作業ディレクトリが消えるのを防ぐ。シンボリックリンクは
+/// リンク自体を削除するため対象外。
+fn check_cwd_deletion(
+    abs_path: &Path,
+    metadata: &std::fs::Metadata,
+    ctx: &RunContext,
+) -> Result<(), SafeRmError> {
+    if ctx.args.force || !metadata.is_dir() {
+        return Ok(());
+    }
+    if PathChecker::is_cwd_or_ancestor(abs_path, ctx.cwd, ctx.config.case_insensitive) {
+        return Err(SafeRmError::RefuseCwdDeletion {
+            path: abs_path.clean(),
+            cwd: ctx.cwd.to_path_buf(),
+        });
+    }
+    Ok(())
+}
+
+/// safe-rm 自身の設定ファイル（またはそれを含むディレクトリ）の削除をブロック
+///
+/// allowed_paths 経由でも、エージェントが安全設定を消して制限を外せないようにする。
+/// `-f` と `--allow-self-config` の併用時のみ許可。
+fn check_self_config(abs_path: &Path, ctx: &RunContext) -> Result<(), SafeRmError> {
+    if ctx.args.force && ctx.args.allow_self_config {
+        return Ok(());
+    }
+    match ctx.config_files.iter().find(|config| {
+        PathChecker::is_same_or_ancestor(abs_path, config, ctx.config.case_insensitive)
+    }) {
+        Some(config) => Err(SafeRmError::RefuseSelfConfig {
+            path: abs_path.clean(),
+            config: config.clone(),
+        }),
+        None => Ok(()),
+    }
+}
+
+/// `--check-open` 指定時、他プロセスが開いているファイルを警告またはブロック
+///
+/// シンボリックリンクはリンク自体を削除するため対象外。
+fn check_open_file(
+    abs_path: &Path,
+    metadata: &std::fs::Metadata,
+    ctx: &RunContext,
+) -> Result<(), SafeRmError> {
+    let (Some(index), Some(mode)) = (ctx.open_files, ctx.args.check_open) else {
+        return Ok(());
+    };
+    if metadata.file_type().is_symlink() {
+        return Ok(());
+    }
+    let Ok(canonical) = abs_path.canonicalize() else {
+        return Ok(());
+    };
+    let Some((open_path, pid)) = index.holder(&canonical) else {
+        return Ok(());
+    };
+    match mode {
+        OpenCheckMode::Warn => {
+            print_warning(
+                ctx.args.format,
+                Some(open_path),
+                &format!("{} is open by process {}", open_path.display(), pid),
+            );
+            Ok(())
         }
+        OpenCheckMode::Block => Err(SafeRmError::FileInUse {
+            path: open_path.to_path_buf(),
+            pid,
+        }),
+    }
+}
+
+/// `-r` の対象に含まれるネストした Git リポジトリをブロック（`--allow-nested-repos` で許可）
+///
+/// `skip_unreadable` で除外したディレクトリは Git チェックの分とまとめて警告するため
+/// GitChecker に記録する（リポジトリ外では即座に警告）。
+fn check_nested_repository(
+    path: &Path,
+    metadata: &std::fs::Metadata,
+    ctx: &RunContext,
+) -> Result<(), SafeRmError> {
+    if !metadata.is_dir() || ctx.args.allow_nested_repos {
+        return Ok(());
+    }
+    let mut skipped = Vec::new();
+    let found =
+        GitChecker::find_nested_repository(path, ctx.config.directory_read_policy, &mut skipped)?;
+    match ctx.git_checker {
+        Some(checker) => checker.record_skipped_unreadable(skipped),
+        None => print_skipped_unreadable(ctx.args.format, &skipped),
+    }
+    match found {
+        Some(repo) => Err(SafeRmError::NestedRepository { path: repo }),
+        None => Ok(()),
     }
 }
 
+/// pre_delete_hook を実行し、拒否されたらブロック（対象の絶対パスを渡す）
+fn check_pre_delete_hook(abs_path: &Path, ctx: &RunContext) -> Result<(), SafeRmError> {
+    match ctx.config.pre_delete_hook_path() {
+        Some(hook) => hook::run_pre_delete_hook(&hook, &abs_path.clean(), ctx.args.dry_run),
+        None => Ok(()),
+    }
+}
+
+/// 最終更新から `--min-age` / min_age_seconds が経過していない対象をブロック（`-f` で無視）
+///
+/// 更新時刻が未来（時計のずれ）の場合も経過 0 秒として扱う。
+fn check_min_age(
+    path: &Path,
+    metadata: &std::fs::Metadata,
+    args: &CliArgs,
+    config: &Config,
+) -> Result<(), SafeRmError> {
+    let Some(min_age) = args.min_age.or(config.min_age_seconds) else {
+        return Ok(());
+    };
+    if args.force {
+        return Ok(());
+    }
+    let Ok(modified) = metadata.modified() else {
+        return Ok(());
+    };
+    let age = std::time::SystemTime::now()
+        .duration_since(modified)
+        .map(|elapsed| elapsed.as_secs())
+        .unwrap_or(0);
+    if age < min_age {
+        return Err(SafeRmError::TooRecent {
+            path: path.to_path_buf(),
+            age,
+        });
+    }
+    Ok(())
+}
+
+/// 一括削除の確認（`--confirm`、`-I`、または削除予定のファイル数が confirm_threshold 超過時）
+///
+/// 削除前に一度だけ stderr へ件数と対象の一部を表示し、stdin の回答を待つ。
+/// stdin が端末でない場合は確認できないため、`--yes` がなければブロックする
+/// （`-I` は GNU rm と同じく端末でなくても stdin から回答を読む）。
+/// ドライランは何も削除しないため確認しない。
+fn check_confirmation(
+    targets: &[PathBuf],
+    args: &CliArgs,
+    config: &Config,
+    cwd: &Path,
+) -> Result<(), SafeRmError> {
+    let once =
+        args.interactive_once && confirm::interactive_once_applies(targets.len(), args.recursive);
+    if args.yes || args.dry_run || (!once && !args.confirm && config.confirm_threshold.is_none()) {
+        return Ok(());
+    }
+    let paths: Vec<PathBuf> = targets.iter().map(|path| cwd.join(path)).collect();
+    let count = confirm::count_files(&paths);
+    if !once
+        && !args.confirm
+        && config
+            .confirm_threshold
+            .is_none_or(|threshold| count <= threshold)
+    {
+        return Ok(());
+    }
+
+    let stdin = std::io::stdin();
+    if !once && !stdin.is_terminal() {
+        return Err(SafeRmError::ConfirmationRequired { count });
+    }
+    if confirm::prompt(stdin.lock(), std::io::stderr(), count, targets)
+        .map_err(SafeRmError::IoError)?
+    {
+        Ok(())
+    } else {
+        Err(SafeRmError::ConfirmationDeclined { count })
+    }
+}
+
+/// 展開後のパス数が上限（`--max-paths` > 設定の max_paths）を超えたら削除前に中断
+fn check_max_paths(
+    targets: &[PathBuf],
+    args: &CliArgs,
+    config: &Config,
+) -> Result<(), SafeRmError> {
+    match args.max_paths.or(config.max_paths) {
+        Some(limit) if targets.len() > limit => Err(SafeRmError::TooManyPaths {
+            count: targets.len(),
+            limit,
+        }),
+        _ => Ok(()),
+    }
+}
+
+/// deletion_scope 外のパスをブロック
+fn check_deletion_scope(
+    path: &Path,
+    canonical_path: &Path,
+    project_root: &Path,
+    config: &Config,
+) -> Result<(), SafeRmError> {
+    if config.is_within_deletion_scope(canonical_path, project_root) {
+        Ok(())
+    } else {
+        Err(SafeRmError::OutsideDeletionScope {
+            path: path.to_path_buf(),
+            scopes: config.deletion_scopes(project_root),
+        })
+    }
+}
+
+/// 削除を実行（`--backup` 指定時は退避先へ移動）
+///
+/// `--exclude` / `--one-file-system` / `--depth` 指定時のディレクトリは除外エントリや
+/// マウントポイント、上限より深いエントリを残して削除し、残したエントリを表示する。
+/// `--progress` 指定時も同じ走査で削除し、削除件数を stderr へ出力する。
+fn remove_target(
+    path: &Path,
+    abs_path: &Path,
+    metadata: &std::fs::Metadata,
+    ctx: &RunContext,
+) -> Result<(), SafeRmError> {
+    let started = Instant::now();
+    let result = remove_target_untimed(path, abs_path, metadata, ctx);
+    if let Some(trace) = ctx.trace {
+        trace.add_removal(started.elapsed());
+    }
+    result
+}
+
+fn remove_target_untimed(
+    path: &Path,
+    abs_path: &Path,
+    metadata: &std::fs::Metadata,
+    ctx: &RunContext,
+) -> Result<(), SafeRmError> {
+    let walk = !ctx.excludes.is_empty() || (ctx.args.progress && ctx.backup.is_none());
+    if metadata.is_dir() && ctx.args.recursive && walk {
+        let shown = ctx.display.show(path);
+        let mut progress = ctx
+            .args
+            .progress
+            .then(|| Progress::new(std::io::stderr(), ctx.args.format, &shown));
+        let result = exclude::remove_tree_excluding(
+            &FsRemover,
+            abs_path,
+            ctx.excludes,
+            &RetryPolicy::from_config(ctx.config),
+            &mut || {
+                if let Some(progress) = progress.as_mut() {
+                    progress.file_removed();
+                }
+            },
+        );
+        if let Some(progress) = progress {
+            progress.finish();
+        }
+        let kept = result.map_err(|e| SafeRmError::from_removal(abs_path, e))?;
+        if ctx.args.print0 {
+            return Ok(());
+        }
+        for entry in kept.excluded {
+            println!("skipped: {} (excluded)", shown.join(entry).display());
+        }
+        for entry in kept.mount_points {
+            println!(
+                "skipped: {} (on another file system)",
+                shown.join(entry).display()
+            );
+        }
+        for entry in kept.too_deep {
+            println!("skipped: {} (beyond --depth)", shown.join(entry).display());
+        }
+        return Ok(());
+    }
+
+    match ctx.backup {
+        Some(session) => {
+            let mut session = session.borrow_mut();
+            let original = abs_path.clean();
+            if ctx.args.verify_backup {
+                session.stash_verified(&original)
+            } else {
+                session.stash(&original)
+            }
+            .map(|_| ())
+            .map_err(SafeRmError::IoError)
+        }
+        None => delete_path_with_metadata(
+            abs_path,
+            ctx.args.recursive,
+            metadata,
+            &RetryPolicy::from_config(ctx.config),
+        ),
+    }
+}
+
+/// `--emit-script` 用に、削除と同等の `rm` / `rmdir` コマンドを組み立てる
+///
+/// スクリプトを別の場所から実行しても同じ対象を指すよう、パスは絶対パスで出力する。
+fn script_command(abs_path: &Path, metadata: &std::fs::Metadata, recursive: bool) -> String {
+    let command = if !metadata.is_dir() {
+        "rm"
+    } else if recursive {
+        "rm -r"
+    } else {
+        "rmdir"
+    };
+    format!("{} -- {}", command, backup::shell_quote(&abs_path.clean()))
+}
+
 /// メタデータを使用してファイルまたはディレクトリを削除（追加 syscall を回避）
 fn delete_path_with_metadata(
     path: &Path,
     recursive: bool,
     metadata: &std::fs::Metadata,
+    policy: &RetryPolicy,
 ) -> Result<(), SafeRmError> {
-    if metadata.is_dir() {
-        if recursive {
-            fs::remove_dir_all(path).map_err(SafeRmError::IoError)?;
-        } else {
-            fs::remove_dir(path).map_err(SafeRmError::IoError)?;
-        }
+    let kind = if !metadata.is_dir() {
+        RemoveKind::File
+    } else if recursive {
+        RemoveKind::Tree
     } else {
-        fs::remove_file(path).map_err(SafeRmError::IoError)?;
-    }
-    Ok(())
+        RemoveKind::EmptyDir
+    };
+    retry::remove_with_retry(&FsRemover, path, kind, policy)
+        .map_err(|e| SafeRmError::from_removal(path, e))
 }
 
 #[cfg(test)]
 mod tests {
+    use super::*;
     #[test]
     fn test_project_compiles() {
         // プロジェクトが正しくコンパイルされることを確認するスモークテスト
     }
 
+    #[test]
+    fn test_colors_paint_when_enabled() {
+        let colors = Colors {
+            stdout: true,
+            stderr: false,
+        };
+        assert_eq!(colors.removed("removed:"), "\x1b[32mremoved:\x1b[0m");
+        assert_eq!(
+            colors.would_remove("would remove:"),
+            "\x1b[33mwould remove:\x1b[0m"
+        );
+        // stderr 側は無効
+        assert_eq!(colors.error("safe-rm: error"), "safe-rm: error");
+    }
+
+    #[test]
+    fn test_colors_never_is_plain() {
+        let colors = Colors::new(ColorChoice::Never);
+        assert_eq!(colors.removed("removed:"), "removed:");
+        assert_eq!(colors.error("safe-rm: error"), "safe-rm: error");
+    }
+
+    fn strict_config() -> Config {
+        let mut config = Config::default();
+        config.allow_project_deletion = false;
+        config
+    }
+
+    fn ignored_dir_repo() -> (tempfile::TempDir, PathBuf, GitChecker) {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let repo_path = temp_dir.path().canonicalize().unwrap();
+        git2::Repository::init(&repo_path).unwrap();
+        fs::write(repo_path.join(".gitignore"), "node_modules/\n").unwrap();
+        fs::create_dir_all(repo_path.join("node_modules").join("pkg")).unwrap();
+        fs::write(
+            repo_path.join("node_modules").join("pkg").join("index.js"),
+            "x",
+        )
+        .unwrap();
+        fs::write(repo_path.join("untracked.txt"), "x").unwrap();
+        let checker = GitChecker::open(&repo_path).unwrap();
+        (temp_dir, repo_path, checker)
+    }
+
+    #[test]
+    fn test_status_scan_skipped_for_single_ignored_directory() {
+        let (_temp_dir, repo_path, checker) = ignored_dir_repo();
+        let args = <CliArgs as clap::Parser>::parse_from(["safe-rm", "-r", "node_modules"]);
+
+        let cache = build_status_cache(
+            Some(&checker),
+            &args,
+            &strict_config(),
+            &[PathBuf::from("node_modules")],
+            &repo_path,
+        )
+        .unwrap();
+        assert!(cache.is_empty(), "whole-repo scan should be skipped");
+    }
+
+    #[test]
+    fn test_status_scan_skipped_when_all_targets_are_ignored_directories() {
+        let (_temp_dir, repo_path, checker) = ignored_dir_repo();
+        let nested = repo_path.join("crates").join("core").join("node_modules");
+        fs::create_dir_all(&nested).unwrap();
+        fs::write(nested.join("index.js"), "x").unwrap();
+        let args = <CliArgs as clap::Parser>::parse_from(["safe-rm", "-r", "node_modules"]);
+
+        let cache = build_status_cache(
+            Some(&checker),
+            &args,
+            &strict_config(),
+            &[
+                PathBuf::from("node_modules"),
+                PathBuf::from("crates/core/node_modules"),
+            ],
+            &repo_path,
+        )
+        .unwrap();
+        assert!(cache.is_empty(), "whole-repo scan should be skipped");
+    }
+
+    #[test]
+    fn test_status_scan_runs_for_other_targets() {
+        let (_temp_dir, repo_path, checker) = ignored_dir_repo();
+        let args = <CliArgs as clap::Parser>::parse_from([
+            "safe-rm",
+            "-r",
+            "node_modules",
+            "untracked.txt",
+        ]);
+
+        let cache = build_status_cache(
+            Some(&checker),
+            &args,
+            &strict_config(),
+            &[
+                PathBuf::from("node_modules"),
+                PathBuf::from("untracked.txt"),
+            ],
+            &repo_path,
+        )
+        .unwrap();
+        assert!(cache.contains_key(b"untracked.txt".as_slice()));
+    }
+
+    #[test]
+    fn test_sort_targets_by_delete_order() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let cwd = temp_dir.path();
+        fs::write(cwd.join("b_large.txt"), vec![0u8; 300]).unwrap();
+        fs::create_dir(cwd.join("c_dir")).unwrap();
+        fs::write(cwd.join("c_dir").join("inner.txt"), vec![0u8; 200]).unwrap();
+        fs::write(cwd.join("a_small.txt"), vec![0u8; 10]).unwrap();
+        let given: Vec<PathBuf> = ["b_large.txt", "c_dir", "a_small.txt", "missing.txt"]
+            .iter()
+            .map(PathBuf::from)
+            .collect();
+        let excludes = ExcludeSet::new(&[]).unwrap();
+        let sorted = |order| {
+            let mut targets = given.clone();
+            sort_targets(&mut targets, order, cwd, &excludes);
+            targets
+        };
+
+        assert_eq!(sorted(DeleteOrder::AsGiven), given);
+        assert_eq!(
+            sorted(DeleteOrder::SmallestFirst),
+            ["missing.txt", "a_small.txt", "c_dir", "b_large.txt"]
+                .map(PathBuf::from)
+                .to_vec()
+        );
+        assert_eq!(
+            sorted(DeleteOrder::PathSorted),
+            ["a_small.txt", "b_large.txt", "c_dir", "missing.txt"]
+                .map(PathBuf::from)
+                .to_vec()
+        );
+    }
+
     #[test]
     fn test_version_available() {
         let version = env!("CARGO_PKG_VERSION");
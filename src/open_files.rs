@@ -0,0 +1,128 @@
+//! safe-rm の使用中ファイル検出
+//!
+//! `--check-open` 指定時、Unix では `/proc/*/fd` を走査して他プロセスが開いているファイルを検出する。
+//! 走査はコストが高いため実行ごとに1回だけ行う。`/proc` がない環境（macOS 等）や
+//! Unix 以外では常に空になる（何も検出しない）。
+
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+/// 他プロセスが開いているファイルの索引（パス → PID）
+#[derive(Debug, Default)]
+pub struct OpenFileIndex {
+    open: HashMap<PathBuf, u32>,
+}
+
+impl OpenFileIndex {
+    /// 実行中のプロセスが開いているファイルを走査（自プロセスは除外）
+    #[cfg(unix)]
+    pub fn scan() -> Self {
+        Self::scan_proc(Path::new("/proc"))
+    }
+
+    /// Unix 以外では何も検出しない
+    #[cfg(not(unix))]
+    pub fn scan() -> Self {
+        Self::default()
+    }
+
+    /// `proc_root` 配下の `<pid>/fd/*` のリンク先を収集
+    ///
+    /// 権限がなく読めないプロセスは黙って無視する。
+    #[cfg(unix)]
+    fn scan_proc(proc_root: &Path) -> Self {
+        let mut open = HashMap::new();
+        let own_pid = std::process::id();
+        let Ok(entries) = std::fs::read_dir(proc_root) else {
+            return Self { open };
+        };
+
+        for entry in entries.flatten() {
+            let Some(pid) = entry
+                .file_name()
+                .to_str()
+                .and_then(|name| name.parse::<u32>().ok())
+            else {
+                continue;
+            };
+            if pid == own_pid {
+                continue;
+            }
+            let Ok(fds) = std::fs::read_dir(entry.path().join("fd")) else {
+                continue;
+            };
+            for fd in fds.flatten() {
+                if let Ok(target) = std::fs::read_link(fd.path()) {
+                    // ソケットやパイプ（"socket:[..]" 等）は絶対パスでないため除外
+                    if target.is_absolute() {
+                        open.entry(target).or_insert(pid);
+                    }
+                }
+            }
+        }
+        Self { open }
+    }
+
+    /// `path`（canonicalize 済み）自体、またはその配下で開かれているファイルと PID
+    pub fn holder(&self, path: &Path) -> Option<(&Path, u32)> {
+        if let Some((open_path, pid)) = self.open.get_key_value(path) {
+            return Some((open_path, *pid));
+        }
+        self.open
+            .iter()
+            .filter(|(open_path, _)| open_path.starts_with(path))
+            .min_by(|a, b| a.0.cmp(b.0))
+            .map(|(open_path, pid)| (open_path.as_path(), *pid))
+    }
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+    use std::fs;
+    use std::os::unix::fs::symlink;
+
+    #[test]
+    fn test_scan_proc_collects_fd_targets() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let proc_root = temp_dir.path().join("proc");
+        let data = temp_dir.path().join("data");
+        fs::create_dir_all(data.join("nested")).unwrap();
+        let file = data.join("nested").join("held.txt");
+        fs::write(&file, "x").unwrap();
+
+        let fd_dir = proc_root.join("4242").join("fd");
+        fs::create_dir_all(&fd_dir).unwrap();
+        symlink(&file, fd_dir.join("3")).unwrap();
+        symlink("socket:[1234]", fd_dir.join("4")).unwrap();
+        fs::create_dir_all(proc_root.join("self")).unwrap();
+
+        let index = OpenFileIndex::scan_proc(&proc_root);
+        assert_eq!(index.holder(&file), Some((file.as_path(), 4242)));
+        // ディレクトリ配下のファイルも検出
+        assert_eq!(index.holder(&data), Some((file.as_path(), 4242)));
+        assert_eq!(index.holder(&data.join("other.txt")), None);
+    }
+
+    #[test]
+    fn test_scan_proc_skips_own_process() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let file = temp_dir.path().join("mine.txt");
+        fs::write(&file, "x").unwrap();
+        let fd_dir = temp_dir
+            .path()
+            .join(std::process::id().to_string())
+            .join("fd");
+        fs::create_dir_all(&fd_dir).unwrap();
+        symlink(&file, fd_dir.join("3")).unwrap();
+
+        let index = OpenFileIndex::scan_proc(temp_dir.path());
+        assert_eq!(index.holder(&file), None);
+    }
+
+    #[test]
+    fn test_missing_proc_root_is_empty() {
+        let index = OpenFileIndex::scan_proc(Path::new("/nonexistent/proc"));
+        assert_eq!(index.holder(Path::new("/tmp")), None);
+    }
+}
@@ -40,22 +40,33 @@ impl PathChecker {
         project_root: &Path,
         resolve_base: &Path,
         target_path: &Path,
+    ) -> Result<PathBuf, SafeRmError> {
+        Self::verify_containment_with_case(project_root, resolve_base, target_path, false)
+    }
+
+    /// パスがプロジェクトルート内にあることを検証（大文字小文字の扱いを指定）
+    ///
+    /// `case_insensitive` が true の場合、境界チェックを大文字小文字を区別せずに行う。
+    /// macOS (APFS/HFS+) や Windows など大文字小文字を区別しないファイルシステム向け。
+    pub fn verify_containment_with_case(
+        project_root: &Path,
+        resolve_base: &Path,
+        target_path: &Path,
+        case_insensitive: bool,
     ) -> Result<PathBuf, SafeRmError> {
         // 1. パスを絶対パスに変換（相対パスは resolve_base から解決）
         let absolute_path = Self::to_absolute(resolve_base, target_path);
 
-        // 2. 字句的に正規化（.. を解決）
-        let cleaned_path = absolute_path.clean();
-
-        // 3. 可能であればシンボリックリンクを解決
-        //    末尾が未作成でも、既存の親ディレクトリまで解決してエイリアス差異を吸収する
-        let canonical_path = Self::try_canonicalize(&cleaned_path);
+        // 2. 既存部分は OS に解決させ（.. もシンボリックリンクを辿った先で解決）、
+        //    未作成部分だけを字句的に正規化する。先に字句的に .. を潰すと
+        //    `link/../x` がリンク先の親ではなくプロジェクト内として扱われてしまう
+        let canonical_path = Self::try_canonicalize(&absolute_path);
 
-        // 4. プロジェクトルートも正規化
-        let canonical_root = Self::try_canonicalize(&project_root.clean());
+        // 3. プロジェクトルートも同じ方法で正規化
+        let canonical_root = Self::try_canonicalize(project_root);
 
-        // 5. 境界チェック
-        if !Self::is_contained(&canonical_root, &canonical_path) {
+        // 4. 境界チェック
+        if !Self::path_starts_with(&canonical_path, &canonical_root, case_insensitive) {
             return Err(SafeRmError::OutsideProject {
                 path: target_path.to_path_buf(),
                 project_root: project_root.to_path_buf(),
@@ -65,6 +76,26 @@ impl PathChecker {
         Ok(canonical_path)
     }
 
+    /// Git チェックに使うパスを決定
+    ///
+    /// シンボリックリンクの場合、親ディレクトリのみ canonicalize しリンク名自体は保持する。
+    /// 「リンク自体をチェック」するセマンティクスを維持しつつ、リポジトリのエイリアスパスを解決する。
+    /// それ以外は canonicalize 済みパスをそのまま使う。
+    pub fn git_check_path(normalized: &Path, canonical: &Path, is_symlink: bool) -> PathBuf {
+        if !is_symlink {
+            return canonical.to_path_buf();
+        }
+        normalized
+            .file_name()
+            .and_then(|name| {
+                normalized
+                    .parent()
+                    .and_then(|parent| parent.canonicalize().ok())
+                    .map(|canonical_parent| canonical_parent.join(name))
+            })
+            .unwrap_or_else(|| normalized.to_path_buf())
+    }
+
     /// 相対パスを絶対パスに変換
     fn to_absolute(base: &Path, path: &Path) -> PathBuf {
         if path.is_absolute() {
@@ -75,41 +106,163 @@ impl PathChecker {
     }
 
     /// 可能であれば canonicalize する。
-    /// 末尾が未作成で失敗した場合は、既存の親ディレクトリまで canonicalize してから
-    /// 未作成部分を再結合する。
-    fn try_canonicalize(path: &Path) -> PathBuf {
+    /// 末尾が未作成で失敗した場合は、解決できる最長の先頭部分まで canonicalize してから
+    /// 残り（未作成部分）を再結合して字句的に正規化する。
+    ///
+    /// `..` を含むパスもそのまま OS に解決させるため、シンボリックリンク経由の `..` は
+    /// リンク先の親を指す（実際の削除と同じ解釈）。未作成部分の `..` は OS でも
+    /// 辿れないため、字句的に解決しても実際の削除より広い範囲を指すことはない。
+    pub fn try_canonicalize(path: &Path) -> PathBuf {
         if let Ok(canonical) = path.canonicalize() {
             return canonical;
         }
 
-        let mut current = path;
-        let mut missing_segments: Vec<std::ffi::OsString> = Vec::new();
-
-        while let Some(parent) = current.parent() {
-            if let Some(name) = current.file_name() {
-                missing_segments.push(name.to_os_string());
+        let components: Vec<_> = path.components().collect();
+        for split in (1..components.len()).rev() {
+            let prefix: PathBuf = components[..split].iter().collect();
+            if let Ok(canonical_prefix) = prefix.canonicalize() {
+                let rest: PathBuf = components[split..].iter().collect();
+                return canonical_prefix.join(rest).clean();
             }
+        }
 
-            if let Ok(canonical_parent) = parent.canonicalize() {
-                let mut rebuilt = canonical_parent;
-                for segment in missing_segments.iter().rev() {
-                    rebuilt.push(segment);
-                }
-                return rebuilt;
-            }
+        path.clean()
+    }
 
-            current = parent;
+    /// 親ディレクトリのみ `try_canonicalize` し、末尾の要素を再結合する
+    ///
+    /// 末尾がシンボリックリンクでもリンク自体を指したまま、親のエイリアス（シンボリックリンク経由の
+    /// パス）を実パスに解決する。末尾が `..` など名前を持たない場合は全体を `try_canonicalize` する。
+    /// 末尾が区切り文字（`link/`）の場合は OS がリンク先を辿るため、同じく全体を解決する。
+    pub fn canonicalize_parent(path: &Path) -> PathBuf {
+        if Self::has_trailing_separator(path) {
+            return Self::try_canonicalize(path);
+        }
+        match (path.parent(), path.file_name()) {
+            (Some(parent), Some(name)) => Self::try_canonicalize(parent).join(name),
+            _ => Self::try_canonicalize(path),
         }
+    }
+
+    /// `target` が `cwd` 自体またはその祖先か（シンボリックリンクを解決して比較）
+    pub fn is_cwd_or_ancestor(target: &Path, cwd: &Path, case_insensitive: bool) -> bool {
+        Self::is_same_or_ancestor(target, cwd, case_insensitive)
+    }
 
-        path.to_path_buf()
+    /// `target` が `path` 自体またはその祖先か（シンボリックリンクを解決して比較）
+    pub fn is_same_or_ancestor(target: &Path, path: &Path, case_insensitive: bool) -> bool {
+        let target = Self::try_canonicalize(target);
+        let path = Self::try_canonicalize(path);
+        Self::path_starts_with(&path, &target, case_insensitive)
+    }
+
+    /// 通常のファイル・ディレクトリ・シンボリックリンク以外（特殊ファイル）の種別名を返す
+    ///
+    /// Unix のブロック/キャラクタデバイス、FIFO、ソケットが該当する。それ以外の環境では常に None。
+    pub fn special_file_kind(file_type: std::fs::FileType) -> Option<&'static str> {
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::FileTypeExt;
+            if file_type.is_block_device() {
+                return Some("block device");
+            }
+            if file_type.is_char_device() {
+                return Some("character device");
+            }
+            if file_type.is_fifo() {
+                return Some("fifo");
+            }
+            if file_type.is_socket() {
+                return Some("socket");
+            }
+        }
+        #[cfg(not(unix))]
+        let _ = file_type;
+        None
+    }
+
+    /// パス引数が区切り文字で終わるか（`foo/`）
+    pub fn has_trailing_separator(path: &Path) -> bool {
+        path.as_os_str()
+            .as_encoded_bytes()
+            .last()
+            .is_some_and(|&b| std::path::is_separator(b as char))
+    }
+
+    /// 末尾が区切り文字のパス引数（`foo/`）がディレクトリに解決されるか検証
+    ///
+    /// rm と同様に末尾の `/` をディレクトリであることの表明とみなし、ファイル等に解決される場合は
+    /// 同名のファイルを誤って削除しないよう `NotADirectory` を返す。`metadata` は対象の
+    /// `symlink_metadata` の結果（非ディレクトリでは OS が ENOTDIR を返す）。存在しない場合は
+    /// 呼び出し側の NotFound 処理に任せる。
+    ///
+    /// ディレクトリへのシンボリックリンクに `/` を付けると OS はリンク先を辿るため、リンク自体ではなく
+    /// リンク先のツリーを削除してしまう。意図が曖昧なため `InvalidPath` で拒否する。
+    pub fn check_directory_assertion(
+        target_path: &Path,
+        abs_path: &Path,
+        metadata: &std::io::Result<std::fs::Metadata>,
+    ) -> Result<(), SafeRmError> {
+        if !Self::has_trailing_separator(target_path) {
+            return Ok(());
+        }
+        let is_directory = match metadata {
+            Ok(metadata) => metadata.is_dir(),
+            Err(e) => e.kind() != std::io::ErrorKind::NotADirectory,
+        };
+        if !is_directory {
+            return Err(SafeRmError::NotADirectory {
+                path: abs_path.to_path_buf(),
+            });
+        }
+        let link_path: PathBuf = abs_path.components().collect();
+        if std::fs::symlink_metadata(&link_path).is_ok_and(|m| m.file_type().is_symlink()) {
+            return Err(SafeRmError::InvalidPath {
+                path: target_path.to_path_buf(),
+                reason: "trailing '/' on a symbolic link would remove the link target; \
+                         name the link without '/' or the target directory itself"
+                    .to_string(),
+            });
+        }
+        Ok(())
     }
 
     /// パスがルート内に含まれているかチェック
+    #[cfg(test)]
     fn is_contained(root: &Path, path: &Path) -> bool {
         // パスがルートと同一か、ルートの子孫である
         path.starts_with(root)
     }
 
+    /// `path` が `base` と同一か、その子孫であるかをコンポーネント単位で判定
+    ///
+    /// `case_insensitive` が true の場合、各コンポーネントを小文字化して比較する。
+    pub fn path_starts_with(path: &Path, base: &Path, case_insensitive: bool) -> bool {
+        if !case_insensitive {
+            return path.starts_with(base);
+        }
+
+        let mut path_components = path.components();
+        base.components().all(|base_component| {
+            path_components.next().is_some_and(|path_component| {
+                Self::fold_case(path_component.as_os_str())
+                    == Self::fold_case(base_component.as_os_str())
+            })
+        })
+    }
+
+    /// 2つのパスが同一かを判定（`case_insensitive` なら大文字小文字を区別しない）
+    pub fn path_eq(a: &Path, b: &Path, case_insensitive: bool) -> bool {
+        if !case_insensitive {
+            return a == b;
+        }
+        a.components().count() == b.components().count() && Self::path_starts_with(a, b, true)
+    }
+
+    fn fold_case(component: &std::ffi::OsStr) -> String {
+        component.to_string_lossy().to_lowercase()
+    }
+
     /// ホームディレクトリへの参照をチェック
     #[allow(dead_code)]
     fn is_home_reference(path: &Path) -> bool {
@@ -290,6 +443,45 @@ mod tests {
         assert!(result.is_err());
     }
 
+    #[test]
+    #[cfg(unix)]
+    fn test_verify_containment_dot_dot_through_symlink_is_resolved_physically() {
+        let temp_dir = TempDir::new().unwrap();
+        let base = temp_dir.path().canonicalize().unwrap();
+        let project_root = base.join("repo");
+        fs::create_dir_all(&project_root).unwrap();
+        fs::create_dir_all(base.join("outside").join("sub")).unwrap();
+        fs::write(base.join("outside").join("secret"), "secret").unwrap();
+        std::os::unix::fs::symlink(base.join("outside").join("sub"), project_root.join("link"))
+            .unwrap();
+
+        // 字句的には repo/secret だが、OS は link の先の親（outside）で .. を解決する
+        for target in ["link/../secret", "link/../missing", "link/missing/../../x"] {
+            let result = PathChecker::verify_containment(&project_root, Path::new(target));
+            assert!(
+                matches!(result, Err(SafeRmError::OutsideProject { .. })),
+                "{} must be outside the project: {:?}",
+                target,
+                result
+            );
+        }
+    }
+
+    #[test]
+    fn test_verify_containment_nonexistent_parent_resolving_outside() {
+        let temp_dir = TempDir::new().unwrap();
+        let project_root = temp_dir.path().canonicalize().unwrap();
+
+        let result =
+            PathChecker::verify_containment(&project_root, Path::new("missing/../../outside.txt"));
+        assert!(matches!(result, Err(SafeRmError::OutsideProject { .. })));
+
+        // 未作成の親を経由しても中に戻るパスはプロジェクト内
+        let result =
+            PathChecker::verify_containment(&project_root, Path::new("missing/a/../../inside.txt"));
+        assert_eq!(result.unwrap(), project_root.join("inside.txt"));
+    }
+
     #[test]
     fn test_to_absolute_relative() {
         let base = Path::new("/project");
@@ -334,6 +526,82 @@ mod tests {
         assert!(!PathChecker::is_contained(root, path));
     }
 
+    #[test]
+    fn test_git_check_path_non_symlink_uses_canonical() {
+        let result = PathChecker::git_check_path(
+            Path::new("/alias/repo/file"),
+            Path::new("/real/repo/file"),
+            false,
+        );
+        assert_eq!(result, PathBuf::from("/real/repo/file"));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_git_check_path_symlink_keeps_link_name() {
+        let temp_dir = TempDir::new().unwrap();
+        let root = temp_dir.path().canonicalize().unwrap();
+        fs::write(root.join("target.txt"), "x").unwrap();
+        std::os::unix::fs::symlink(root.join("target.txt"), root.join("link")).unwrap();
+
+        let result =
+            PathChecker::git_check_path(&root.join("link"), &root.join("target.txt"), true);
+        assert_eq!(result, root.join("link"));
+    }
+
+    #[test]
+    fn test_path_starts_with_case_insensitive() {
+        let root = Path::new("/Project/Src");
+        let path = Path::new("/project/SRC/main.rs");
+        assert!(PathChecker::path_starts_with(path, root, true));
+        assert!(!PathChecker::path_starts_with(path, root, false));
+    }
+
+    #[test]
+    fn test_path_starts_with_case_insensitive_sibling_rejected() {
+        let root = Path::new("/project");
+        let path = Path::new("/PROJECT2/file.txt");
+        assert!(!PathChecker::path_starts_with(path, root, true));
+    }
+
+    #[test]
+    fn test_path_eq_case_insensitive() {
+        assert!(PathChecker::path_eq(
+            Path::new("/Tmp/Logs"),
+            Path::new("/tmp/logs"),
+            true
+        ));
+        assert!(!PathChecker::path_eq(
+            Path::new("/tmp/logs/a"),
+            Path::new("/tmp/logs"),
+            true
+        ));
+    }
+
+    #[test]
+    fn test_verify_containment_with_case_mixed_case_root() {
+        let temp_dir = TempDir::new().unwrap();
+        let project_root = temp_dir.path().canonicalize().unwrap().join("proj");
+        fs::create_dir(&project_root).unwrap();
+        fs::write(project_root.join("file.txt"), "x").unwrap();
+
+        // ユーザーが異なる大文字小文字でルートを指定したケース
+        let typed_root = project_root.with_file_name("PROJ");
+        let target = project_root.join("file.txt");
+
+        assert!(
+            PathChecker::verify_containment_with_case(&typed_root, &typed_root, &target, true)
+                .is_ok()
+        );
+        if !typed_root.exists() {
+            // 大文字小文字を区別するファイルシステムでは既定の比較で拒否される
+            assert!(
+                PathChecker::verify_containment_with_case(&typed_root, &typed_root, &target, false)
+                    .is_err()
+            );
+        }
+    }
+
     #[test]
     fn test_is_home_reference() {
         assert!(PathChecker::is_home_reference(Path::new("~")));
@@ -447,6 +715,26 @@ mod tests {
         assert!(result.ends_with("a/b/c/file.txt") || result.ends_with("a\\b\\c\\file.txt"));
     }
 
+    #[test]
+    #[cfg(unix)]
+    fn test_canonicalize_parent_follows_link_with_trailing_separator() {
+        let temp_dir = TempDir::new().unwrap();
+        let root = temp_dir.path().canonicalize().unwrap();
+        fs::create_dir(root.join("target")).unwrap();
+        std::os::unix::fs::symlink("target", root.join("alias")).unwrap();
+
+        // リンク自体を指したまま
+        assert_eq!(
+            PathChecker::canonicalize_parent(&root.join("alias")),
+            root.join("alias")
+        );
+        // 末尾の `/` はリンク先を指す
+        assert_eq!(
+            PathChecker::canonicalize_parent(&root.join("alias/")),
+            root.join("target")
+        );
+    }
+
     #[test]
     fn test_verify_containment_empty_path_component() {
         // 空のパスコンポーネントを含むケース
@@ -482,4 +770,57 @@ mod tests {
         );
         assert_eq!(result.unwrap(), project_root.join("missing.txt"));
     }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_check_directory_assertion() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let file = temp_dir.path().join("foo");
+        fs::write(&file, "x").unwrap();
+        let dir = temp_dir.path().join("bar");
+        fs::create_dir(&dir).unwrap();
+        let check = |arg: &str| {
+            let abs_path = temp_dir.path().join(arg);
+            PathChecker::check_directory_assertion(
+                Path::new(arg),
+                &abs_path,
+                &fs::symlink_metadata(&abs_path),
+            )
+        };
+
+        assert!(check("foo").is_ok());
+        assert!(matches!(
+            check("foo/"),
+            Err(SafeRmError::NotADirectory { .. })
+        ));
+        assert!(check("bar/").is_ok());
+        assert!(check("bar").is_ok());
+        // 存在しないパスは NotFound として呼び出し側で扱う
+        assert!(check("missing/").is_ok());
+
+        // ディレクトリへのシンボリックリンクに `/` を付けるとリンク先を辿るため拒否
+        std::os::unix::fs::symlink("bar", temp_dir.path().join("alias")).unwrap();
+        assert!(check("alias").is_ok());
+        assert!(matches!(
+            check("alias/"),
+            Err(SafeRmError::InvalidPath { .. })
+        ));
+    }
+
+    #[test]
+    fn test_special_file_kind() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let file = temp_dir.path().join("file.txt");
+        fs::write(&file, "x").unwrap();
+        let kind = |path: &Path| {
+            PathChecker::special_file_kind(fs::symlink_metadata(path).unwrap().file_type())
+        };
+        assert_eq!(kind(&file), None);
+        assert_eq!(kind(temp_dir.path()), None);
+        assert_eq!(kind(Path::new("/dev/null")), Some("character device"));
+
+        let socket = temp_dir.path().join("sock");
+        let _listener = std::os::unix::net::UnixListener::bind(&socket).unwrap();
+        assert_eq!(kind(&socket), Some("socket"));
+    }
 }
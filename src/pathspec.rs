@@ -0,0 +1,264 @@
+//! safe-rm の `--pathspec`（引数を Git の pathspec として解釈）
+//!
+//! libgit2 は pathspec の magic（`:(glob)` など）を解釈しないため、よく使う magic をここで
+//! 解釈し、追跡中・未追跡のファイル一覧（`GitChecker::worktree_files`）と照合する。
+//! 一致したファイルは通常のパスと同じく1件ずつ安全チェックを受ける。
+//! magic なしの pathspec は git と同じくカレントディレクトリ基準で、`*` は `/` にも一致する。
+//! `:(glob)` では `*` は `/` に一致せず、`**` が階層をまたぐ。`:(top)` / `:/` はリポジトリ
+//! ルート基準、`:(literal)` はワイルドカードを解釈しない。`:(exclude)` などその他の magic は
+//! 誤った対象を選ばないよう拒否する。いずれもディレクトリを指定した場合は配下のファイルに一致する。
+
+use crate::error::SafeRmError;
+use crate::path_checker::PathChecker;
+use globset::{GlobBuilder, GlobMatcher};
+use path_clean::PathClean;
+use std::collections::HashSet;
+use std::path::{Component, Path, PathBuf};
+
+/// 解析済みの pathspec
+#[derive(Debug)]
+pub struct Pathspec {
+    /// ワークディレクトリ相対のパターン（字句的に正規化済み）
+    pattern: PathBuf,
+    /// ワイルドカードを含む場合の照合器（`:(literal)` では None）
+    matcher: Option<GlobMatcher>,
+}
+
+/// magic の指定
+#[derive(Debug, Default)]
+struct Magic {
+    top: bool,
+    glob: bool,
+    literal: bool,
+}
+
+impl Pathspec {
+    /// pathspec を解析
+    ///
+    /// `prefix` はカレントディレクトリのワークディレクトリ相対パス（`:(top)` では使わない）。
+    /// リポジトリ外を指すもの、リポジトリ全体に一致するもの、未対応の magic はエラー。
+    pub fn parse(spec: &str, prefix: &Path) -> Result<Self, SafeRmError> {
+        let invalid = |reason: &str| SafeRmError::InvalidPath {
+            path: PathBuf::from(spec),
+            reason: reason.to_string(),
+        };
+        let (magic, rest) = split_magic(spec).map_err(|reason| invalid(&reason))?;
+
+        let base = if magic.top { Path::new("") } else { prefix };
+        let pattern = base.join(rest).clean();
+        if pattern.components().next() == Some(Component::ParentDir) || pattern.is_absolute() {
+            return Err(invalid("pathspec is outside the repository"));
+        }
+        if pattern == Path::new(".") {
+            return Err(invalid("pathspec matches the entire repository"));
+        }
+
+        let has_wildcard = rest.contains(['*', '?', '[']);
+        let matcher = if magic.literal || !has_wildcard {
+            None
+        } else {
+            let text = pattern
+                .to_str()
+                .ok_or_else(|| invalid("pathspec is not valid UTF-8"))?;
+            let glob = GlobBuilder::new(text)
+                .literal_separator(magic.glob)
+                .build()
+                .map_err(|e| invalid(&format!("invalid pathspec: {}", e)))?;
+            Some(glob.compile_matcher())
+        };
+        Ok(Self { pattern, matcher })
+    }
+
+    /// ワークディレクトリ相対のファイルパスが一致するか
+    ///
+    /// パターン自体、またはパターンをディレクトリとするその配下のパスに一致する。
+    pub fn matches(&self, path: &Path) -> bool {
+        path.starts_with(&self.pattern)
+            || self.matcher.as_ref().is_some_and(|matcher| {
+                path.ancestors()
+                    .filter(|ancestor| !ancestor.as_os_str().is_empty())
+                    .any(|ancestor| matcher.is_match(ancestor))
+            })
+    }
+}
+
+/// 先頭の magic（`:(top,glob)` / `:/`）を取り除き、指定と残りのパターンを返す
+fn split_magic(spec: &str) -> Result<(Magic, &str), String> {
+    let mut magic = Magic::default();
+    let Some(body) = spec.strip_prefix(':') else {
+        return Ok((magic, spec));
+    };
+
+    let rest = if let Some(long) = body.strip_prefix('(') {
+        let (words, rest) = long
+            .split_once(')')
+            .ok_or_else(|| "unterminated pathspec magic".to_string())?;
+        for word in words.split(',') {
+            match word {
+                "top" => magic.top = true,
+                "glob" => magic.glob = true,
+                "literal" => magic.literal = true,
+                other => return Err(format!("unsupported pathspec magic '{}'", other)),
+            }
+        }
+        rest
+    } else {
+        // 短縮形: `:/`（top）。`:!` / `:^`（exclude）は未対応
+        let end = body
+            .find(|c: char| !matches!(c, '/' | '!' | '^'))
+            .unwrap_or(body.len());
+        for c in body[..end].chars() {
+            match c {
+                '/' => magic.top = true,
+                other => return Err(format!("unsupported pathspec magic '{}'", other)),
+            }
+        }
+        body[end..].strip_prefix(':').unwrap_or(&body[end..])
+    };
+
+    if magic.glob && magic.literal {
+        return Err("'glob' and 'literal' pathspec magic are incompatible".to_string());
+    }
+    Ok((magic, rest))
+}
+
+/// pathspec に一致するファイルを削除対象として列挙（指定順、重複なし）
+///
+/// `files` は `GitChecker::worktree_files` の一覧、`workdir` はそのワークディレクトリ。
+/// 結果はカレントディレクトリ配下なら相対パス、それ以外は絶対パス。一致するファイルがない
+/// pathspec はそのまま通常のパスとして返す（存在しなければ通常どおり not found になり、
+/// `-f` ではスキップされる）。
+pub fn expand(
+    specs: &[PathBuf],
+    files: &[PathBuf],
+    workdir: &Path,
+    cwd: &Path,
+) -> Result<Vec<PathBuf>, SafeRmError> {
+    let cwd = PathChecker::try_canonicalize(cwd);
+    let prefix = cwd.strip_prefix(workdir).unwrap_or(Path::new(""));
+
+    let mut seen = HashSet::new();
+    let mut targets = Vec::new();
+    for spec in specs {
+        let text = spec.to_str().ok_or_else(|| SafeRmError::InvalidPath {
+            path: spec.clone(),
+            reason: "pathspec is not valid UTF-8".to_string(),
+        })?;
+        let pathspec = Pathspec::parse(text, prefix)?;
+        let mut matched = false;
+        for file in files.iter().filter(|file| pathspec.matches(file)) {
+            matched = true;
+            let abs_path = workdir.join(file);
+            let target = match abs_path.strip_prefix(&cwd) {
+                Ok(relative) => relative.to_path_buf(),
+                Err(_) => abs_path,
+            };
+            if seen.insert(target.clone()) {
+                targets.push(target);
+            }
+        }
+        if !matched {
+            targets.push(spec.clone());
+        }
+    }
+    Ok(targets)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn spec(text: &str, prefix: &str) -> Pathspec {
+        Pathspec::parse(text, Path::new(prefix)).unwrap()
+    }
+
+    #[test]
+    fn test_plain_pathspec_is_relative_to_prefix_and_matches_directories() {
+        let set = spec("cache", "sub");
+        assert!(set.matches(Path::new("sub/cache/a.bin")));
+        assert!(set.matches(Path::new("sub/cache")));
+        assert!(!set.matches(Path::new("cache/a.bin")));
+        assert!(!set.matches(Path::new("sub/cachex")));
+    }
+
+    #[test]
+    fn test_plain_wildcard_crosses_directories() {
+        let set = spec("*.tmp", "");
+        assert!(set.matches(Path::new("a.tmp")));
+        assert!(set.matches(Path::new("deep/nested/b.tmp")));
+        assert!(!set.matches(Path::new("a.txt")));
+    }
+
+    #[test]
+    fn test_glob_magic_respects_separators() {
+        let set = spec(":(glob)*.tmp", "");
+        assert!(set.matches(Path::new("a.tmp")));
+        assert!(!set.matches(Path::new("deep/b.tmp")));
+
+        let set = spec(":(glob)**/*.tmp", "");
+        assert!(set.matches(Path::new("a.tmp")));
+        assert!(set.matches(Path::new("deep/nested/b.tmp")));
+    }
+
+    #[test]
+    fn test_top_magic_ignores_prefix() {
+        assert!(spec(":/logs", "sub").matches(Path::new("logs/a.log")));
+        assert!(spec(":(top)logs", "sub").matches(Path::new("logs/a.log")));
+        assert!(spec(":(top,glob)logs/*.log", "sub").matches(Path::new("logs/a.log")));
+    }
+
+    #[test]
+    fn test_literal_magic_disables_wildcards() {
+        let set = spec(":(literal)*.tmp", "");
+        assert!(set.matches(Path::new("*.tmp")));
+        assert!(!set.matches(Path::new("a.tmp")));
+    }
+
+    #[test]
+    fn test_rejects_unsupported_or_dangerous_pathspecs() {
+        for (text, reason) in [
+            (":(exclude)a", "unsupported pathspec magic 'exclude'"),
+            (":!a", "unsupported pathspec magic '!'"),
+            (":(glob,literal)a", "incompatible"),
+            (":(glob", "unterminated"),
+            (":/", "entire repository"),
+            ("..", "entire repository"),
+            ("../../x", "outside the repository"),
+        ] {
+            let err = Pathspec::parse(text, Path::new("sub")).unwrap_err();
+            assert_eq!(err.exit_code(), 2);
+            assert!(
+                err.user_message().contains(reason),
+                "{}: {}",
+                text,
+                err.user_message()
+            );
+        }
+    }
+
+    #[test]
+    fn test_expand_keeps_order_dedups_and_passes_through_unmatched() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let workdir = temp_dir.path().canonicalize().unwrap();
+        let files: Vec<PathBuf> = ["a.tmp", "b.txt", "sub/c.tmp"]
+            .iter()
+            .map(PathBuf::from)
+            .collect();
+        std::fs::create_dir(workdir.join("sub")).unwrap();
+
+        let specs = vec![
+            PathBuf::from(":/*.tmp"),
+            PathBuf::from(":/a.tmp"),
+            PathBuf::from("none.log"),
+        ];
+        let targets = expand(&specs, &files, &workdir, &workdir.join("sub")).unwrap();
+        assert_eq!(
+            targets,
+            vec![
+                workdir.join("a.tmp"),
+                PathBuf::from("c.tmp"),
+                PathBuf::from("none.log"),
+            ]
+        );
+    }
+}
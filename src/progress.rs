@@ -0,0 +1,125 @@
+//! safe-rm の再帰削除の進捗表示（`--progress`）
+//!
+//! 大きなツリーの削除中、削除したファイル数を一定件数または一定時間ごとに stderr へ出力する。
+//! `--format json` では1行1オブジェクトの `{"type":"progress",...}` とし、stdout の結果を汚さない。
+
+use crate::cli::OutputFormat;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+
+/// この件数ごとに進捗を出力
+const REPORT_EVERY_FILES: usize = 500;
+/// 件数に達しなくてもこの間隔で進捗を出力
+const REPORT_INTERVAL: Duration = Duration::from_secs(1);
+
+/// 削除ファイル数の進捗出力
+pub struct Progress<W: Write> {
+    out: W,
+    format: OutputFormat,
+    path: PathBuf,
+    removed: usize,
+    reported: usize,
+    last_report: Instant,
+}
+
+impl<W: Write> Progress<W> {
+    /// `path`（ユーザー指定の表示用パス）の削除進捗を `out` へ出力
+    pub fn new(out: W, format: OutputFormat, path: &Path) -> Self {
+        Self {
+            out,
+            format,
+            path: path.to_path_buf(),
+            removed: 0,
+            reported: 0,
+            last_report: Instant::now(),
+        }
+    }
+
+    /// ファイルを1件削除したことを記録し、必要なら進捗を出力
+    pub fn file_removed(&mut self) {
+        self.removed += 1;
+        if self.removed - self.reported >= REPORT_EVERY_FILES
+            || self.last_report.elapsed() >= REPORT_INTERVAL
+        {
+            self.report();
+        }
+    }
+
+    /// 未出力の件数があれば最終的な件数を出力
+    pub fn finish(mut self) {
+        if self.removed != self.reported {
+            self.report();
+        }
+    }
+
+    fn report(&mut self) {
+        // 進捗は補助情報のため書き込み失敗は無視
+        let _ = match self.format {
+            OutputFormat::Human => writeln!(
+                self.out,
+                "safe-rm: progress: {}: {} file(s) removed",
+                self.path.display(),
+                self.removed
+            ),
+            OutputFormat::Json => writeln!(
+                self.out,
+                "{}",
+                serde_json::json!({
+                    "type": "progress",
+                    "path": self.path,
+                    "removed": self.removed,
+                })
+            ),
+        };
+        self.reported = self.removed;
+        self.last_report = Instant::now();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_reports_every_n_files_and_on_finish() {
+        let mut out = Vec::new();
+        let mut progress = Progress::new(&mut out, OutputFormat::Human, Path::new("node_modules"));
+        for _ in 0..(REPORT_EVERY_FILES + 3) {
+            progress.file_removed();
+        }
+        progress.finish();
+
+        let text = String::from_utf8(out).unwrap();
+        let lines: Vec<&str> = text.lines().collect();
+        assert_eq!(
+            lines.first(),
+            Some(&"safe-rm: progress: node_modules: 500 file(s) removed")
+        );
+        assert_eq!(
+            lines.last(),
+            Some(&"safe-rm: progress: node_modules: 503 file(s) removed")
+        );
+    }
+
+    #[test]
+    fn test_json_lines() {
+        let mut out = Vec::new();
+        let mut progress = Progress::new(&mut out, OutputFormat::Json, Path::new("build"));
+        progress.file_removed();
+        progress.finish();
+
+        let text = String::from_utf8(out).unwrap();
+        let last: serde_json::Value = serde_json::from_str(text.lines().last().unwrap()).unwrap();
+        assert_eq!(last["type"], "progress");
+        assert_eq!(last["path"], "build");
+        assert_eq!(last["removed"], 1);
+    }
+
+    #[test]
+    fn test_nothing_removed_prints_nothing() {
+        let mut out = Vec::new();
+        Progress::new(&mut out, OutputFormat::Human, Path::new("empty")).finish();
+        assert!(out.is_empty());
+    }
+}
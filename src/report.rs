@@ -0,0 +1,144 @@
+//! safe-rm の実行レポート（`--report`）
+//!
+//! パスごとの結果を `--format json` と同じ形式の JSON 行として蓄積し、
+//! 実行の最後に集計行を加えてファイルへ書き出す。端末には通常の人間向け出力を残したまま、
+//! CI などが機械可読な結果を読めるようにする。
+//! 読み手が書きかけのレポートを見ないよう、同じディレクトリの一時ファイルに書いてから rename する。
+
+use crate::error::SafeRmError;
+use serde_json::{Value, json};
+use std::fs;
+use std::io::{self, Write};
+use std::path::Path;
+
+/// エラー1件の JSON（`--format json` のエラー行と共通）
+///
+/// `path` は引数のまま、`resolved` はその絶対パス。実行全体のエラーではどちらも null。
+pub fn error_entry(path: Option<&Path>, resolved: Option<&Path>, error: &SafeRmError) -> Value {
+    json!({
+        "type": "error",
+        "code": error.code(),
+        "exit_code": error.exit_code(),
+        "path": path,
+        "resolved": resolved,
+        "message": error.user_message(),
+    })
+}
+
+/// 削除（ドライランでは削除対象と判定）したパスの JSON
+pub fn removed_entry(path: &Path, resolved: &Path, dry_run: bool) -> Value {
+    json!({
+        "type": if dry_run { "would_remove" } else { "removed" },
+        "path": path,
+        "resolved": resolved,
+    })
+}
+
+/// 削除せずスキップしたパス（`-f` で無視した存在しないパス、`--older-than` より新しいファイル）の JSON
+pub fn skipped_entry(path: &Path, resolved: &Path) -> Value {
+    json!({
+        "type": "skipped",
+        "path": path,
+        "resolved": resolved,
+    })
+}
+
+/// パスごとの結果を蓄積するレポート
+#[derive(Debug, Default)]
+pub struct RunReport {
+    entries: Vec<Value>,
+}
+
+impl RunReport {
+    /// 空のレポートを作成
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 1件追加
+    pub fn push(&mut self, entry: Value) {
+        self.entries.push(entry);
+    }
+
+    /// 集計行（`{"type":"summary",...}`）を末尾に加えて `path` へ書き出す
+    ///
+    /// 一時ファイルへ書き込んで fsync してから rename するため、読み手には
+    /// 以前の内容か完成したレポートのどちらかしか見えない。
+    pub fn write_atomic(&self, path: &Path, summary: Value) -> io::Result<()> {
+        let dir = match path.parent() {
+            Some(dir) if !dir.as_os_str().is_empty() => dir,
+            _ => Path::new("."),
+        };
+        let file_name = path.file_name().ok_or_else(|| {
+            io::Error::new(io::ErrorKind::InvalidInput, "report path has no file name")
+        })?;
+        let mut tmp_name = file_name.to_os_string();
+        tmp_name.push(format!(".tmp-{}", std::process::id()));
+        let tmp_path = dir.join(tmp_name);
+
+        let result = (|| {
+            let mut file = fs::File::create(&tmp_path)?;
+            for entry in self.entries.iter().chain(std::iter::once(&summary)) {
+                writeln!(file, "{}", entry)?;
+            }
+            file.sync_all()?;
+            fs::rename(&tmp_path, path)
+        })();
+        if result.is_err() {
+            let _ = fs::remove_file(&tmp_path);
+        }
+        result
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::PathBuf;
+
+    #[test]
+    fn test_write_atomic_writes_entries_and_summary() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let report_path = temp_dir.path().join("report.jsonl");
+        fs::write(&report_path, "old\n").unwrap();
+
+        let mut report = RunReport::new();
+        report.push(removed_entry(
+            Path::new("a.txt"),
+            Path::new("/repo/a.txt"),
+            false,
+        ));
+        report.push(error_entry(
+            Some(Path::new("b.txt")),
+            Some(Path::new("/repo/b.txt")),
+            &SafeRmError::NotFound(PathBuf::from("/repo/b.txt")),
+        ));
+        report
+            .write_atomic(&report_path, json!({"type": "summary", "exit_code": 1}))
+            .unwrap();
+
+        let lines: Vec<Value> = fs::read_to_string(&report_path)
+            .unwrap()
+            .lines()
+            .map(|line| serde_json::from_str(line).unwrap())
+            .collect();
+        assert_eq!(lines.len(), 3);
+        assert_eq!(lines[0]["type"], "removed");
+        assert_eq!(lines[0]["resolved"], "/repo/a.txt");
+        assert_eq!(lines[1]["code"], "not_found");
+        assert_eq!(lines[2]["type"], "summary");
+        // 一時ファイルは残らない
+        assert_eq!(fs::read_dir(temp_dir.path()).unwrap().count(), 1);
+    }
+
+    #[test]
+    fn test_write_atomic_failure_keeps_previous_report() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let report_path = temp_dir.path().join("missing-dir").join("report.jsonl");
+        assert!(
+            RunReport::new()
+                .write_atomic(&report_path, json!({"type": "summary"}))
+                .is_err()
+        );
+    }
+}
@@ -0,0 +1,320 @@
+//! safe-rm の I/O リトライ
+//!
+//! NFS/SMB などで発生する一時的な I/O エラーに対し、削除操作を短い待機を挟んで再試行する。
+//! 実際のファイル操作は `Remover` トレイトの背後に置き、テストで失敗を注入できるようにする。
+
+use crate::config::Config;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::Duration;
+
+/// 削除操作の抽象
+pub trait Remover {
+    fn remove_file(&self, path: &Path) -> io::Result<()>;
+    fn remove_dir(&self, path: &Path) -> io::Result<()>;
+    fn remove_dir_all(&self, path: &Path) -> io::Result<()>;
+    fn rename(&self, from: &Path, to: &Path) -> io::Result<()>;
+}
+
+/// 実ファイルシステムに対する削除
+pub struct FsRemover;
+
+impl Remover for FsRemover {
+    fn remove_file(&self, path: &Path) -> io::Result<()> {
+        fs::remove_file(path)
+    }
+
+    fn remove_dir(&self, path: &Path) -> io::Result<()> {
+        fs::remove_dir(path)
+    }
+
+    fn remove_dir_all(&self, path: &Path) -> io::Result<()> {
+        fs::remove_dir_all(path)
+    }
+
+    fn rename(&self, from: &Path, to: &Path) -> io::Result<()> {
+        fs::rename(from, to)
+    }
+}
+
+/// 削除の種類
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RemoveKind {
+    File,
+    EmptyDir,
+    Tree,
+}
+
+/// リトライ方針
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RetryPolicy {
+    /// 初回失敗後の最大再試行回数（0 でリトライなし）
+    pub retries: u32,
+    /// 再試行前の待機時間
+    pub delay: Duration,
+}
+
+impl RetryPolicy {
+    /// 設定の io_retries / io_retry_delay_ms から構築
+    pub fn from_config(config: &Config) -> Self {
+        Self {
+            retries: config.io_retries,
+            delay: Duration::from_millis(config.io_retry_delay_ms),
+        }
+    }
+
+    /// 一時的なエラーの場合のみ `op` を再試行し、最後のエラーを返す
+    pub fn run<T>(&self, mut op: impl FnMut() -> io::Result<T>) -> io::Result<T> {
+        let mut attempt = 0;
+        loop {
+            match op() {
+                Err(e) if attempt < self.retries && is_transient(&e) => {
+                    attempt += 1;
+                    std::thread::sleep(self.delay);
+                }
+                result => return result,
+            }
+        }
+    }
+}
+
+/// 再試行で回復しうるエラーか（NotFound や PermissionDenied は対象外）
+pub fn is_transient(error: &io::Error) -> bool {
+    matches!(
+        error.kind(),
+        io::ErrorKind::ResourceBusy
+            | io::ErrorKind::StaleNetworkFileHandle
+            | io::ErrorKind::Interrupted
+            | io::ErrorKind::WouldBlock
+            | io::ErrorKind::TimedOut
+    )
+}
+
+/// `policy` に従ってリトライしながら削除
+pub fn remove_with_retry(
+    remover: &impl Remover,
+    path: &Path,
+    kind: RemoveKind,
+    policy: &RetryPolicy,
+) -> io::Result<()> {
+    match kind {
+        RemoveKind::File => policy.run(|| remover.remove_file(path)),
+        RemoveKind::EmptyDir => policy.run(|| remover.remove_dir(path)),
+        RemoveKind::Tree => remove_tree(remover, path, policy),
+    }
+}
+
+/// ディレクトリツリーを「見かけ上アトミック」に削除
+///
+/// まず同じ親ディレクトリ内の一時名へ rename してから削除するため、途中で失敗しても
+/// 元の名前は解決されなくなる（半端に残ったツリーは一時名の隠しディレクトリに残る）。
+/// 別デバイス（`CrossesDevices`）で rename できない場合のみその場で削除する。それ以外の
+/// rename の失敗（ENOTDIR など）は、想定外の対象を辿って削除しないようそのまま返す。
+fn remove_tree(remover: &impl Remover, path: &Path, policy: &RetryPolicy) -> io::Result<()> {
+    let Some(staging) = staging_path(path) else {
+        return policy.run(|| remover.remove_dir_all(path));
+    };
+    match policy.run(|| remover.rename(path, &staging)) {
+        Ok(()) => policy.run(|| remover.remove_dir_all(&staging)),
+        Err(e) if e.kind() == io::ErrorKind::CrossesDevices => {
+            policy.run(|| remover.remove_dir_all(path))
+        }
+        Err(e) => Err(e),
+    }
+}
+
+/// 削除前の退避先となる兄弟パス（例: `.node_modules.safe-rm-1234-0`）
+fn staging_path(path: &Path) -> Option<PathBuf> {
+    static COUNTER: AtomicUsize = AtomicUsize::new(0);
+    let name = path.file_name()?;
+    let parent = path.parent()?;
+    let seq = COUNTER.fetch_add(1, Ordering::Relaxed);
+    Some(parent.join(format!(
+        ".{}.safe-rm-{}-{}",
+        name.to_string_lossy(),
+        std::process::id(),
+        seq
+    )))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::cell::Cell;
+
+    /// 指定回数だけ `kind` のエラーを返し、その後成功する Remover
+    struct FlakyRemover {
+        failures: Cell<u32>,
+        kind: io::ErrorKind,
+        calls: Cell<u32>,
+    }
+
+    impl FlakyRemover {
+        fn new(failures: u32, kind: io::ErrorKind) -> Self {
+            Self {
+                failures: Cell::new(failures),
+                kind,
+                calls: Cell::new(0),
+            }
+        }
+
+        fn attempt(&self) -> io::Result<()> {
+            self.calls.set(self.calls.get() + 1);
+            if self.failures.get() > 0 {
+                self.failures.set(self.failures.get() - 1);
+                Err(io::Error::from(self.kind))
+            } else {
+                Ok(())
+            }
+        }
+    }
+
+    impl Remover for FlakyRemover {
+        fn remove_file(&self, _: &Path) -> io::Result<()> {
+            self.attempt()
+        }
+        fn remove_dir(&self, _: &Path) -> io::Result<()> {
+            self.attempt()
+        }
+        fn remove_dir_all(&self, _: &Path) -> io::Result<()> {
+            self.attempt()
+        }
+        fn rename(&self, _: &Path, _: &Path) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    /// rename は実ファイルシステムで行い、remove_dir_all は途中失敗を模擬する Remover
+    struct FailingTreeRemover;
+
+    impl Remover for FailingTreeRemover {
+        fn remove_file(&self, path: &Path) -> io::Result<()> {
+            fs::remove_file(path)
+        }
+        fn remove_dir(&self, path: &Path) -> io::Result<()> {
+            fs::remove_dir(path)
+        }
+        fn remove_dir_all(&self, _: &Path) -> io::Result<()> {
+            Err(io::Error::other("simulated mid-delete failure"))
+        }
+        fn rename(&self, from: &Path, to: &Path) -> io::Result<()> {
+            fs::rename(from, to)
+        }
+    }
+
+    /// rename が常に `kind` で失敗し、remove_dir_all の呼び出し回数を数える Remover
+    struct RenameFailingRemover {
+        kind: io::ErrorKind,
+        removed: Cell<u32>,
+    }
+
+    impl Remover for RenameFailingRemover {
+        fn remove_file(&self, _: &Path) -> io::Result<()> {
+            Ok(())
+        }
+        fn remove_dir(&self, _: &Path) -> io::Result<()> {
+            Ok(())
+        }
+        fn remove_dir_all(&self, _: &Path) -> io::Result<()> {
+            self.removed.set(self.removed.get() + 1);
+            Ok(())
+        }
+        fn rename(&self, _: &Path, _: &Path) -> io::Result<()> {
+            Err(io::Error::from(self.kind))
+        }
+    }
+
+    fn policy(retries: u32) -> RetryPolicy {
+        RetryPolicy {
+            retries,
+            delay: Duration::ZERO,
+        }
+    }
+
+    #[test]
+    fn test_transient_failure_then_success() {
+        let remover = FlakyRemover::new(2, io::ErrorKind::ResourceBusy);
+        let result = remove_with_retry(&remover, Path::new("f"), RemoveKind::File, &policy(3));
+        assert!(result.is_ok());
+        assert_eq!(remover.calls.get(), 3);
+    }
+
+    #[test]
+    fn test_gives_up_after_retries() {
+        let remover = FlakyRemover::new(5, io::ErrorKind::ResourceBusy);
+        let result = remove_with_retry(&remover, Path::new("f"), RemoveKind::Tree, &policy(2));
+        assert_eq!(result.unwrap_err().kind(), io::ErrorKind::ResourceBusy);
+        assert_eq!(remover.calls.get(), 3);
+    }
+
+    #[test]
+    fn test_not_found_and_permission_denied_not_retried() {
+        for kind in [io::ErrorKind::NotFound, io::ErrorKind::PermissionDenied] {
+            let remover = FlakyRemover::new(1, kind);
+            let result = remove_with_retry(&remover, Path::new("f"), RemoveKind::File, &policy(3));
+            assert_eq!(result.unwrap_err().kind(), kind);
+            assert_eq!(remover.calls.get(), 1);
+        }
+    }
+
+    #[test]
+    fn test_tree_mid_delete_failure_hides_original_name() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let dir = temp_dir.path().join("build");
+        fs::create_dir_all(dir.join("nested")).unwrap();
+        fs::write(dir.join("nested").join("f.txt"), "x").unwrap();
+
+        let result = remove_with_retry(&FailingTreeRemover, &dir, RemoveKind::Tree, &policy(0));
+        assert!(result.is_err());
+        assert!(!dir.exists(), "original name must no longer resolve");
+
+        // 半端なツリーは隠しの一時名に残る
+        let leftovers: Vec<_> = fs::read_dir(temp_dir.path())
+            .unwrap()
+            .map(|e| e.unwrap().file_name().to_string_lossy().into_owned())
+            .collect();
+        assert_eq!(leftovers.len(), 1);
+        assert!(leftovers[0].starts_with(".build.safe-rm-"));
+    }
+
+    #[test]
+    fn test_tree_removed_via_staging() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let dir = temp_dir.path().join("build");
+        fs::create_dir_all(dir.join("nested")).unwrap();
+
+        remove_with_retry(&FsRemover, &dir, RemoveKind::Tree, &policy(0)).unwrap();
+        assert!(!dir.exists());
+        assert_eq!(fs::read_dir(temp_dir.path()).unwrap().count(), 0);
+    }
+
+    #[test]
+    fn test_tree_falls_back_in_place_only_across_devices() {
+        let remover = RenameFailingRemover {
+            kind: io::ErrorKind::CrossesDevices,
+            removed: Cell::new(0),
+        };
+        remove_with_retry(&remover, Path::new("d/build"), RemoveKind::Tree, &policy(0)).unwrap();
+        assert_eq!(remover.removed.get(), 1);
+
+        // ENOTDIR（`link/` の rename など）はその場で削除せずそのまま返す
+        let remover = RenameFailingRemover {
+            kind: io::ErrorKind::NotADirectory,
+            removed: Cell::new(0),
+        };
+        let result =
+            remove_with_retry(&remover, Path::new("d/link/"), RemoveKind::Tree, &policy(0));
+        assert_eq!(result.unwrap_err().kind(), io::ErrorKind::NotADirectory);
+        assert_eq!(remover.removed.get(), 0);
+    }
+
+    #[test]
+    fn test_default_policy_has_no_retries() {
+        let remover = FlakyRemover::new(1, io::ErrorKind::ResourceBusy);
+        let policy = RetryPolicy::from_config(&Config::default());
+        assert!(remove_with_retry(&remover, Path::new("f"), RemoveKind::File, &policy).is_err());
+        assert_eq!(remover.calls.get(), 1);
+    }
+}
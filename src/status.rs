@@ -0,0 +1,657 @@
+//! safe-rm の status サブコマンド
+//!
+//! 削除を行わずに、各パスの Git ステータスと削除可否を判定する。
+//! 判定は通常の削除フローと同じ順序（allowed_paths → 包含検証 → 追加の安全チェック →
+//! Git チェック → pre_delete_hook）で行う。
+
+use crate::config::{Config, PolicyBlock};
+use crate::error::{FileStatus, SafeRmError};
+use crate::git_checker::GitChecker;
+use crate::hook;
+use crate::path_checker::PathChecker;
+use path_clean::PathClean;
+use serde::Serialize;
+use std::collections::HashMap;
+use std::fmt;
+use std::path::{Path, PathBuf};
+
+/// 判定に使用する実行環境
+pub struct StatusContext<'a> {
+    /// 相対パスの解決基準
+    pub cwd: &'a Path,
+    /// プロジェクト境界
+    pub project_root: &'a Path,
+    /// Git リポジトリ（リポジトリ外では None）
+    pub git_checker: Option<&'a GitChecker>,
+    /// 事前取得した Git ステータス
+    pub status_cache: &'a HashMap<Vec<u8>, FileStatus>,
+    /// ユーザー設定
+    pub config: &'a Config,
+    /// 削除から保護する safe-rm 自身の設定ファイル
+    pub config_files: &'a [PathBuf],
+}
+
+/// 1パス分の判定結果
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct PathReport {
+    /// 指定されたパス
+    pub path: PathBuf,
+    /// Git ステータス（存在しない・プロジェクト外などで判定できない場合は None）
+    pub status: Option<FileStatus>,
+    /// 削除が許可されるか
+    pub deletable: bool,
+    /// 補足（ブロック理由や allowed_paths による許可など）
+    pub reason: Option<String>,
+}
+
+impl fmt::Display for PathReport {
+    /// `<status> <deletable?> <path>` 形式
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let status = self
+            .status
+            .map(|s| s.to_string())
+            .unwrap_or_else(|| "-".to_string());
+        let deletable = if self.deletable { "yes" } else { "no" };
+        write!(f, "{} {} {}", status, deletable, self.path.display())?;
+        if let Some(reason) = &self.reason {
+            write!(f, " ({})", reason)?;
+        }
+        Ok(())
+    }
+}
+
+/// 包含検証の結果
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Containment {
+    /// プロジェクト内かつ deletion_scope 内
+    Inside,
+    /// プロジェクト外
+    OutsideProject,
+    /// プロジェクト内だが deletion_scope 外
+    OutsideDeletionScope,
+    /// protected_patterns・denied_paths・allowed_paths で判定が確定したため検証していない
+    Skipped,
+}
+
+/// 1パス分の削除可否の判定（読み取り専用）
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Classification {
+    /// 判定したパス（絶対パス）
+    pub path: PathBuf,
+    /// 包含検証の結果
+    pub containment: Containment,
+    /// Git ステータス（判定していない場合は None）
+    pub status: Option<FileStatus>,
+    /// 一致した allowed_paths エントリ（包含検証と Git チェックをバイパス）
+    pub allowed_by: Option<String>,
+    /// 一致した protected_patterns（常にブロック）
+    pub protected_by: Option<String>,
+    /// 一致した denied_paths エントリ（常にブロック）
+    pub denied_by: Option<String>,
+    /// ブロックした追加の安全チェック（`nested_repository` などのエラーコード）
+    pub blocked_by: Option<String>,
+    /// 削除が許可されるか
+    ///
+    /// `-f` などのオプションなしで CLI が許可するかの判定。呼び出し時のオプションに依存する
+    /// チェック（`-r` / `-d`、`--since`、`--check-open`、確認プロンプト）は含まないため、
+    /// 削除の可否としては部分的な判定。
+    pub deletable: bool,
+}
+
+/// パスを削除せずに判定（埋め込み用の公開 API）
+///
+/// 相対パスは `project_root` 基準で解決する。Git リポジトリは `project_root` から検出し、
+/// ステータスは事前取得せずパス毎に問い合わせる。存在しないパスは `NotFound`、
+/// strict モード（または strict_paths 指定時）にリポジトリを開けない場合は `GitUnavailable` を返す。
+///
+/// ネストしたリポジトリ・特殊ファイル・`project_root` とその祖先・safe-rm の設定ファイル・
+/// min_age_seconds のチェックと pre_delete_hook（`SAFE_RM_DRY_RUN=1` で実行）も CLI と同様に
+/// 適用するが、`deletable` は部分的な判定（[`Classification::deletable`] を参照）。
+pub fn classify(
+    path: &Path,
+    project_root: &Path,
+    config: &Config,
+) -> Result<Classification, SafeRmError> {
+    let git_checker = match GitChecker::try_open(project_root) {
+        Ok(checker) => checker.map(|c| {
+            c.with_policy(config.deletable)
+                .with_generated_as_deletable(config.treat_generated_as_deletable)
+                .with_directory_read_policy(config.directory_read_policy)
+        }),
+        Err(e) if config.requires_git_repository() => return Err(e),
+        Err(_) => None,
+    };
+    let status_cache = HashMap::new();
+    let config_files: Vec<PathBuf> = Config::config_paths()
+        .into_iter()
+        .filter(|path| path.exists())
+        .collect();
+    let ctx = StatusContext {
+        cwd: project_root,
+        project_root,
+        git_checker: git_checker.as_ref(),
+        status_cache: &status_cache,
+        config,
+        config_files: &config_files,
+    };
+    classify_in(path, &ctx)
+}
+
+/// `classify` の本体（status サブコマンドは事前取得したキャッシュで呼ぶ）
+pub fn classify_in(path: &Path, ctx: &StatusContext) -> Result<Classification, SafeRmError> {
+    if path.as_os_str().is_empty() {
+        return Err(SafeRmError::InvalidPath {
+            path: path.to_path_buf(),
+            reason: "empty path".to_string(),
+        });
+    }
+
+    let abs_path = ctx.cwd.join(path);
+    let classification = |containment, status, deletable| Classification {
+        path: abs_path.clean(),
+        containment,
+        status,
+        allowed_by: None,
+        protected_by: None,
+        denied_by: None,
+        blocked_by: None,
+        deletable,
+    };
+    let blocked = |error: SafeRmError, containment, status| Classification {
+        blocked_by: Some(error.code().to_string()),
+        ..classification(containment, status, false)
+    };
+
+    match ctx
+        .config
+        .is_blocked_by_policy(&abs_path, ctx.project_root)?
+    {
+        Some(PolicyBlock::Protected { pattern, .. }) => {
+            return Ok(Classification {
+                protected_by: Some(pattern),
+                ..classification(Containment::Skipped, None, false)
+            });
+        }
+        Some(PolicyBlock::Denied { denied, .. }) => {
+            return Ok(Classification {
+                denied_by: Some(denied),
+                ..classification(Containment::Skipped, None, false)
+            });
+        }
+        None => {}
+    }
+    if let Some(matched) = ctx.config.allowed_path_bypass(&abs_path, ctx.project_root) {
+        let metadata = std::fs::symlink_metadata(&abs_path)
+            .map_err(|_| SafeRmError::NotFound(abs_path.clone()))?;
+        if let Some(error) = check_additional(&abs_path, &metadata, ctx)? {
+            return Ok(blocked(error, Containment::Skipped, None));
+        }
+        if let Some(error) = check_hook(&abs_path, ctx)? {
+            return Ok(blocked(error, Containment::Skipped, None));
+        }
+        return Ok(Classification {
+            allowed_by: Some(matched.to_string()),
+            ..classification(Containment::Skipped, None, true)
+        });
+    }
+
+    let canonical_path = match PathChecker::verify_containment_with_case(
+        ctx.project_root,
+        ctx.cwd,
+        path,
+        ctx.config.case_insensitive,
+    ) {
+        Ok(p) => p,
+        Err(_) => return Ok(classification(Containment::OutsideProject, None, false)),
+    };
+    if !ctx
+        .config
+        .is_within_deletion_scope(&canonical_path, ctx.project_root)
+    {
+        return Ok(classification(
+            Containment::OutsideDeletionScope,
+            None,
+            false,
+        ));
+    }
+
+    let metadata = std::fs::symlink_metadata(&abs_path)
+        .map_err(|_| SafeRmError::NotFound(abs_path.clone()))?;
+    if let Some(error) = check_additional(&abs_path, &metadata, ctx)? {
+        return Ok(blocked(error, Containment::Inside, None));
+    }
+
+    let Some(checker) = ctx.git_checker else {
+        let deletable = !ctx
+            .config
+            .requires_git_check(&canonical_path, ctx.project_root)
+            || ctx.config.deletable.allows(FileStatus::NotInRepo);
+        if deletable {
+            if let Some(error) = check_hook(&abs_path, ctx)? {
+                return Ok(blocked(
+                    error,
+                    Containment::Inside,
+                    Some(FileStatus::NotInRepo),
+                ));
+            }
+        }
+        return Ok(classification(
+            Containment::Inside,
+            Some(FileStatus::NotInRepo),
+            deletable,
+        ));
+    };
+
+    // シンボリックリンクはリンク自体を判定（親のみ canonicalize）
+    let git_check_path = PathChecker::git_check_path(
+        &abs_path.clean(),
+        &canonical_path,
+        metadata.file_type().is_symlink(),
+    );
+
+    // コンフリクト中のファイルはモードに関係なく削除不可
+    if checker.check_conflicts(&git_check_path).is_err() {
+        return Ok(classification(
+            Containment::Inside,
+            Some(FileStatus::Conflicted),
+            false,
+        ));
+    }
+
+    let status = if metadata.is_dir() {
+        directory_status(checker, &git_check_path, ctx.status_cache)?
+    } else {
+        checker.get_file_status_from_cache(&git_check_path, ctx.status_cache)
+    };
+    let deletable = !ctx
+        .config
+        .requires_git_check(&canonical_path, ctx.project_root)
+        || checker.is_path_deletable(&git_check_path, status)
+        || (!metadata.is_dir() && ctx.config.is_always_deletable_extension(path));
+    if deletable {
+        if let Some(error) = check_hook(&abs_path, ctx)? {
+            return Ok(blocked(error, Containment::Inside, Some(status)));
+        }
+    }
+    Ok(classification(Containment::Inside, Some(status), deletable))
+}
+
+/// CLI が Git チェックの前に行う追加の安全チェック（`-f` なしの既定動作）
+///
+/// ブロックする場合はそのエラーを返す。ディレクトリを読めない場合などはそのままエラー。
+fn check_additional(
+    abs_path: &Path,
+    metadata: &std::fs::Metadata,
+    ctx: &StatusContext,
+) -> Result<Option<SafeRmError>, SafeRmError> {
+    let config = ctx.config;
+    if !config.allow_special_files {
+        if let Some(kind) = PathChecker::special_file_kind(metadata.file_type()) {
+            return Ok(Some(SafeRmError::SpecialFile {
+                path: abs_path.to_path_buf(),
+                kind,
+            }));
+        }
+    }
+    if metadata.is_dir()
+        && PathChecker::is_cwd_or_ancestor(abs_path, ctx.cwd, config.case_insensitive)
+    {
+        return Ok(Some(SafeRmError::RefuseCwdDeletion {
+            path: abs_path.clean(),
+            cwd: ctx.cwd.to_path_buf(),
+        }));
+    }
+    if let Some(config_file) = ctx.config_files.iter().find(|config_file| {
+        PathChecker::is_same_or_ancestor(abs_path, config_file, config.case_insensitive)
+    }) {
+        return Ok(Some(SafeRmError::RefuseSelfConfig {
+            path: abs_path.clean(),
+            config: config_file.clone(),
+        }));
+    }
+    if metadata.is_dir() {
+        // status は警告を出さないため、読み飛ばしたディレクトリは報告しない
+        let mut skipped = Vec::new();
+        if let Some(repo) = GitChecker::find_nested_repository(
+            &abs_path.clean(),
+            config.directory_read_policy,
+            &mut skipped,
+        )? {
+            return Ok(Some(SafeRmError::NestedRepository { path: repo }));
+        }
+    }
+    if let (Some(min_age), Ok(modified)) = (config.min_age_seconds, metadata.modified()) {
+        let age = std::time::SystemTime::now()
+            .duration_since(modified)
+            .map(|elapsed| elapsed.as_secs())
+            .unwrap_or(0);
+        if age < min_age {
+            return Ok(Some(SafeRmError::TooRecent {
+                path: abs_path.to_path_buf(),
+                age,
+            }));
+        }
+    }
+    Ok(None)
+}
+
+/// pre_delete_hook をドライランとして実行し、拒否された場合はそのエラーを返す
+fn check_hook(abs_path: &Path, ctx: &StatusContext) -> Result<Option<SafeRmError>, SafeRmError> {
+    let Some(hook) = ctx.config.pre_delete_hook_path() else {
+        return Ok(None);
+    };
+    match hook::run_pre_delete_hook(&hook, &abs_path.clean(), true) {
+        Ok(()) => Ok(None),
+        Err(error @ SafeRmError::HookRejected { .. }) => Ok(Some(error)),
+        Err(error) => Err(error),
+    }
+}
+
+/// パスの Git ステータスと削除可否を判定（status サブコマンドの1行分）
+pub fn evaluate(path: &Path, ctx: &StatusContext) -> PathReport {
+    let report = |status, deletable, reason: Option<String>| PathReport {
+        path: path.to_path_buf(),
+        status,
+        deletable,
+        reason,
+    };
+
+    let classification = match classify_in(path, ctx) {
+        Ok(classification) => classification,
+        Err(SafeRmError::InvalidPath { reason, .. }) => return report(None, false, Some(reason)),
+        Err(SafeRmError::NotFound(_)) => {
+            return report(None, false, Some("not found".to_string()));
+        }
+        Err(_) => return report(None, false, Some("directory read error".to_string())),
+    };
+
+    let reason = if let Some(pattern) = &classification.protected_by {
+        Some(format!("protected pattern: {}", pattern))
+    } else if let Some(denied) = &classification.denied_by {
+        Some(format!("denied path: {}", denied))
+    } else if let Some(code) = &classification.blocked_by {
+        Some(code.replace('_', " "))
+    } else if let Some(matched) = &classification.allowed_by {
+        Some(format!("allowed by config: {}", matched))
+    } else {
+        match classification.containment {
+            Containment::OutsideProject => Some("outside project".to_string()),
+            Containment::OutsideDeletionScope => Some("outside deletion scope".to_string()),
+            Containment::Inside | Containment::Skipped => None,
+        }
+    };
+    report(classification.status, classification.deletable, reason)
+}
+
+/// ディレクトリの代表ステータス
+///
+/// 削除不可のエントリがあればその最も重いもの、全て Ignored なら Ignored、それ以外は Clean。
+fn directory_status(
+    checker: &GitChecker,
+    dir: &Path,
+    cache: &HashMap<Vec<u8>, FileStatus>,
+) -> Result<FileStatus, SafeRmError> {
+    let entries = checker.collect_directory_statuses_with_cache(dir, cache)?;
+
+    if let Some(blocker) = entries
+        .iter()
+        .filter(|(path, status)| !checker.is_path_deletable(path, *status))
+        .map(|(_, status)| *status)
+        .max()
+    {
+        return Ok(blocker);
+    }
+
+    if !entries.is_empty() && entries.iter().all(|(_, s)| *s == FileStatus::Ignored) {
+        Ok(FileStatus::Ignored)
+    } else {
+        Ok(FileStatus::Clean)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_display_deletable() {
+        let report = PathReport {
+            path: PathBuf::from("src/main.rs"),
+            status: Some(FileStatus::Clean),
+            deletable: true,
+            reason: None,
+        };
+        assert_eq!(report.to_string(), "Clean yes src/main.rs");
+    }
+
+    #[test]
+    fn test_display_with_reason() {
+        let report = PathReport {
+            path: PathBuf::from("/etc/passwd"),
+            status: None,
+            deletable: false,
+            reason: Some("outside project".to_string()),
+        };
+        assert_eq!(report.to_string(), "- no /etc/passwd (outside project)");
+    }
+
+    #[test]
+    fn test_evaluate_outside_project() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let root = temp_dir.path().canonicalize().unwrap();
+        let config = Config::default();
+        let cache = HashMap::new();
+        let ctx = StatusContext {
+            cwd: &root,
+            project_root: &root,
+            git_checker: None,
+            status_cache: &cache,
+            config: &config,
+            config_files: &[],
+        };
+
+        let report = evaluate(Path::new("../outside.txt"), &ctx);
+        assert!(!report.deletable);
+        assert_eq!(report.reason.as_deref(), Some("outside project"));
+    }
+
+    #[test]
+    fn test_evaluate_not_in_repo_is_deletable() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let root = temp_dir.path().canonicalize().unwrap();
+        std::fs::write(root.join("a.txt"), "a").unwrap();
+        let config = Config::default();
+        let cache = HashMap::new();
+        let ctx = StatusContext {
+            cwd: &root,
+            project_root: &root,
+            git_checker: None,
+            status_cache: &cache,
+            config: &config,
+            config_files: &[],
+        };
+
+        let report = evaluate(Path::new("a.txt"), &ctx);
+        assert_eq!(report.status, Some(FileStatus::NotInRepo));
+        assert!(report.deletable);
+    }
+
+    /// 一時ディレクトリ（canonicalize 済み）に `a.txt` を作成
+    fn project_with_file() -> (tempfile::TempDir, PathBuf) {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let root = temp_dir.path().canonicalize().unwrap();
+        std::fs::write(root.join("a.txt"), "a").unwrap();
+        (temp_dir, root)
+    }
+
+    fn git(root: &Path, args: &[&str]) {
+        std::process::Command::new("git")
+            .args(args)
+            .current_dir(root)
+            .output()
+            .unwrap();
+    }
+
+    #[test]
+    fn test_classify_not_in_repo() {
+        let (_temp_dir, root) = project_with_file();
+
+        let result = classify(Path::new("a.txt"), &root, &Config::default()).unwrap();
+        assert_eq!(result.path, root.join("a.txt"));
+        assert_eq!(result.containment, Containment::Inside);
+        assert_eq!(result.status, Some(FileStatus::NotInRepo));
+        assert!(result.deletable);
+    }
+
+    #[test]
+    fn test_classify_outside_project_and_deletion_scope() {
+        let (_temp_dir, root) = project_with_file();
+        let project = root.join("project");
+        std::fs::create_dir_all(project.join("build")).unwrap();
+
+        let result = classify(Path::new("../a.txt"), &project, &Config::default()).unwrap();
+        assert_eq!(result.containment, Containment::OutsideProject);
+        assert!(!result.deletable);
+
+        let mut config = Config::default();
+        config.deletion_scope = vec!["build".to_string()];
+        std::fs::write(project.join("b.txt"), "b").unwrap();
+        let result = classify(Path::new("b.txt"), &project, &config).unwrap();
+        assert_eq!(result.containment, Containment::OutsideDeletionScope);
+        assert!(!result.deletable);
+    }
+
+    #[test]
+    fn test_classify_protected_and_allowed() {
+        let (_temp_dir, root) = project_with_file();
+        std::fs::write(root.join("key.pem"), "secret").unwrap();
+        let mut config = Config::default();
+        config.protected_patterns = vec!["*.pem".to_string()];
+        config.allowed_paths = vec![crate::config::AllowedPathEntry {
+            path: root.join("cache").to_string_lossy().to_string(),
+            recursive: true,
+            glob: None,
+            follow_symlinks: true,
+        }];
+        config.resolve_protected_patterns();
+        config.resolve_allowed_paths(None);
+        let project = root.join("project");
+        std::fs::create_dir_all(&project).unwrap();
+        std::fs::create_dir_all(root.join("cache")).unwrap();
+        std::fs::write(root.join("cache").join("c.bin"), "c").unwrap();
+
+        let result = classify(Path::new("key.pem"), &root, &config).unwrap();
+        assert_eq!(result.protected_by.as_deref(), Some("*.pem"));
+        assert_eq!(result.containment, Containment::Skipped);
+        assert!(!result.deletable);
+
+        // allowed_paths はプロジェクト外でも包含検証をバイパスして許可
+        let result = classify(&root.join("cache").join("c.bin"), &project, &config).unwrap();
+        assert!(result.allowed_by.is_some());
+        assert_eq!(result.containment, Containment::Skipped);
+        assert!(result.deletable);
+
+        // denied_paths は allowed_paths より優先
+        config.denied_paths = vec![root.join("cache").to_string_lossy().to_string()];
+        let result = classify(&root.join("cache").join("c.bin"), &project, &config).unwrap();
+        assert!(result.denied_by.is_some());
+        assert!(result.allowed_by.is_none());
+        assert!(!result.deletable);
+    }
+
+    #[test]
+    fn test_classify_not_found_and_empty_path_are_errors() {
+        let (_temp_dir, root) = project_with_file();
+
+        assert!(matches!(
+            classify(Path::new("missing.txt"), &root, &Config::default()),
+            Err(SafeRmError::NotFound(_))
+        ));
+        assert!(matches!(
+            classify(Path::new(""), &root, &Config::default()),
+            Err(SafeRmError::InvalidPath { .. })
+        ));
+    }
+
+    #[test]
+    fn test_classify_applies_additional_safety_checks() {
+        let (_temp_dir, root) = project_with_file();
+        std::fs::create_dir_all(root.join("vendor").join("lib").join(".git")).unwrap();
+        std::fs::write(root.join("safe-rm.toml"), "").unwrap();
+        let config = Config::default();
+        let cache = HashMap::new();
+        let config_files = vec![root.join("safe-rm.toml")];
+        let ctx = StatusContext {
+            cwd: &root,
+            project_root: &root,
+            git_checker: None,
+            status_cache: &cache,
+            config: &config,
+            config_files: &config_files,
+        };
+
+        let blocked_by =
+            |path: &str, ctx: &StatusContext| classify_in(Path::new(path), ctx).unwrap().blocked_by;
+        assert_eq!(
+            blocked_by("vendor", &ctx).as_deref(),
+            Some("nested_repository")
+        );
+        assert_eq!(
+            blocked_by(".", &ctx).as_deref(),
+            Some("refuse_cwd_deletion")
+        );
+        assert_eq!(
+            blocked_by("safe-rm.toml", &ctx).as_deref(),
+            Some("refuse_self_config")
+        );
+        assert_eq!(blocked_by("a.txt", &ctx), None);
+
+        let mut recent = Config::default();
+        recent.min_age_seconds = Some(3600);
+        let ctx = StatusContext {
+            config: &recent,
+            ..ctx
+        };
+        let result = classify_in(Path::new("a.txt"), &ctx).unwrap();
+        assert_eq!(result.blocked_by.as_deref(), Some("too_recent"));
+        assert!(!result.deletable);
+        assert_eq!(
+            evaluate(Path::new("a.txt"), &ctx).reason.as_deref(),
+            Some("too recent")
+        );
+    }
+
+    #[test]
+    fn test_classify_runs_pre_delete_hook() {
+        let (_temp_dir, root) = project_with_file();
+        let mut config = Config::default();
+        config.pre_delete_hook = Some("/nonexistent/hook".to_string());
+
+        let result = classify(Path::new("a.txt"), &root, &config).unwrap();
+        assert_eq!(result.blocked_by.as_deref(), Some("hook_rejected"));
+        assert_eq!(result.status, Some(FileStatus::NotInRepo));
+        assert!(!result.deletable);
+    }
+
+    #[test]
+    fn test_classify_git_status_in_strict_mode() {
+        let (_temp_dir, root) = project_with_file();
+        git(&root, &["init"]);
+        git(&root, &["config", "user.email", "test@test.com"]);
+        git(&root, &["config", "user.name", "Test User"]);
+        git(&root, &["add", "a.txt"]);
+        git(&root, &["commit", "-m", "Add a.txt"]);
+        std::fs::write(root.join("new.txt"), "new").unwrap();
+        let mut config = Config::default();
+        config.allow_project_deletion = false;
+
+        let clean = classify(Path::new("a.txt"), &root, &config).unwrap();
+        assert_eq!(clean.status, Some(FileStatus::Clean));
+        assert!(clean.deletable);
+
+        let untracked = classify(Path::new("new.txt"), &root, &config).unwrap();
+        assert_eq!(untracked.status, Some(FileStatus::Untracked));
+        assert!(!untracked.deletable);
+    }
+}
@@ -0,0 +1,347 @@
+//! safe-rm の削除サマリー
+//!
+//! 削除対象をファイル種別（拡張子）ごとに集計し、人間向けの内訳を生成する。
+//! ドライランでは削除対象のファイル数と合計サイズも集計する。
+
+use crate::exclude::{self, ExcludeSet};
+use std::collections::BTreeMap;
+use std::fmt;
+use std::path::Path;
+
+/// 拡張子ごとの集計結果
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct TypeSummary {
+    /// 拡張子（ドットなし）→ ファイル数
+    pub extensions: BTreeMap<String, usize>,
+    /// 拡張子なしファイル数
+    pub no_extension: usize,
+    /// ディレクトリ数（対象ディレクトリ自体を含む）
+    pub directories: usize,
+}
+
+impl TypeSummary {
+    /// 空の集計を作成
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// パスを集計に追加
+    ///
+    /// ディレクトリは `symlink_metadata()` ベースで走査し、symlink は辿らずに
+    /// リンク自体を1ファイルとして数える。集計は表示専用のため、
+    /// 読み取れないエントリはスキップする（安全チェックは別途 fail-closed）。
+    /// `RemovalTotal::add_path` と同じく、実際には残るエントリ（`--exclude` / `--older-than` /
+    /// `--one-file-system` / `--depth` / `--partial-dir`）と、それを含むディレクトリは数えない。
+    pub fn add_path(&mut self, path: &Path, excludes: &ExcludeSet) {
+        let root_device = excludes.root_device(path).unwrap_or(None);
+        self.add_entry(path, Path::new(""), excludes, root_device);
+    }
+
+    /// エントリを集計に追加し、エントリ全体が削除されるかを返す
+    fn add_entry(
+        &mut self,
+        path: &Path,
+        relative: &Path,
+        excludes: &ExcludeSet,
+        root_device: Option<u64>,
+    ) -> bool {
+        let Ok(metadata) = std::fs::symlink_metadata(path) else {
+            return false;
+        };
+
+        if metadata.is_dir() {
+            let mut removed = true;
+            if let Ok(entries) = std::fs::read_dir(path) {
+                for entry in entries.flatten() {
+                    let entry_relative = relative.join(entry.file_name());
+                    let retained = excludes.is_kept(&entry_relative)
+                        || excludes.is_excluded(&entry_relative)
+                        || excludes.is_beyond_depth(&entry_relative)
+                        || exclude::is_other_device(root_device, &entry.path()).unwrap_or(true);
+                    if retained
+                        || !self.add_entry(&entry.path(), &entry_relative, excludes, root_device)
+                    {
+                        removed = false;
+                    }
+                }
+            }
+            if removed {
+                self.directories += 1;
+            }
+            removed
+        } else if excludes.is_newer(path).unwrap_or(true) {
+            false
+        } else {
+            self.add_file(path);
+            true
+        }
+    }
+
+    /// 単一ファイルを拡張子で分類して追加
+    fn add_file(&mut self, path: &Path) {
+        match path.extension() {
+            Some(ext) => {
+                *self
+                    .extensions
+                    .entry(ext.to_string_lossy().into_owned())
+                    .or_insert(0) += 1;
+            }
+            None => self.no_extension += 1,
+        }
+    }
+
+    /// ファイル総数
+    pub fn total_files(&self) -> usize {
+        self.extensions.values().sum::<usize>() + self.no_extension
+    }
+
+    /// 何も集計されていないか
+    pub fn is_empty(&self) -> bool {
+        self.total_files() == 0 && self.directories == 0
+    }
+}
+
+/// ドライランで削除対象と判定したファイル数と合計サイズ
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct RemovalTotal {
+    /// ファイル数（ディレクトリ以外。シンボリックリンクはリンク自体を1件）
+    pub files: usize,
+    /// 合計バイト数（シンボリックリンクはリンク自体のサイズ）
+    pub bytes: u64,
+}
+
+impl RemovalTotal {
+    /// 空の集計を作成
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// パスを集計に追加
+    ///
+    /// `TypeSummary::add_path` と同じく symlink は辿らず、読み取れないエントリはスキップする。
+    /// ディレクトリ配下は `--exclude` / `--older-than` / `--one-file-system` で残るエントリを数えない。
+    pub fn add_path(&mut self, path: &Path, excludes: &ExcludeSet) {
+        let root_device = excludes.root_device(path).unwrap_or(None);
+        self.add_entry(path, Path::new(""), excludes, root_device);
+    }
+
+    fn add_entry(
+        &mut self,
+        path: &Path,
+        relative: &Path,
+        excludes: &ExcludeSet,
+        root_device: Option<u64>,
+    ) {
+        let Ok(metadata) = std::fs::symlink_metadata(path) else {
+            return;
+        };
+
+        if metadata.is_dir() {
+            if let Ok(entries) = std::fs::read_dir(path) {
+                for entry in entries.flatten() {
+                    let entry_relative = relative.join(entry.file_name());
+                    if !excludes.is_kept(&entry_relative)
+                        && !excludes.is_excluded(&entry_relative)
+                        && !excludes.is_beyond_depth(&entry_relative)
+                        && !exclude::is_other_device(root_device, &entry.path()).unwrap_or(true)
+                    {
+                        self.add_entry(&entry.path(), &entry_relative, excludes, root_device);
+                    }
+                }
+            }
+        } else if !excludes.is_newer(path).unwrap_or(true) {
+            self.files += 1;
+            self.bytes += metadata.len();
+        }
+    }
+}
+
+impl fmt::Display for RemovalTotal {
+    /// 例: `1234 files (512.0 MiB)`
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} ({})",
+            plural(self.files, "file", "files"),
+            format_bytes(self.bytes)
+        )
+    }
+}
+
+/// バイト数を 1024 単位で表示（例: `512 B`, `1.5 KiB`）
+fn format_bytes(bytes: u64) -> String {
+    const UNITS: [&str; 5] = ["KiB", "MiB", "GiB", "TiB", "PiB"];
+    if bytes < 1024 {
+        return format!("{} B", bytes);
+    }
+    let mut value = bytes as f64 / 1024.0;
+    let mut unit = 0;
+    while value >= 1024.0 && unit < UNITS.len() - 1 {
+        value /= 1024.0;
+        unit += 1;
+    }
+    format!("{:.1} {}", value, UNITS[unit])
+}
+
+/// 単数/複数形を切り替え
+fn plural(count: usize, singular: &str, plural: &str) -> String {
+    if count == 1 {
+        format!("{} {}", count, singular)
+    } else {
+        format!("{} {}", count, plural)
+    }
+}
+
+impl fmt::Display for TypeSummary {
+    /// 例: `42 .o files, 10 .log files, 3 directories`
+    ///
+    /// 件数の多い順（同数は拡張子名順）に並べる。
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut by_count: Vec<(&String, &usize)> = self.extensions.iter().collect();
+        by_count.sort_by(|a, b| b.1.cmp(a.1).then_with(|| a.0.cmp(b.0)));
+
+        let mut parts: Vec<String> = by_count
+            .into_iter()
+            .map(|(ext, &count)| {
+                plural(count, &format!(".{} file", ext), &format!(".{} files", ext))
+            })
+            .collect();
+        if self.no_extension > 0 {
+            parts.push(plural(
+                self.no_extension,
+                "file without extension",
+                "files without extension",
+            ));
+        }
+        if self.directories > 0 {
+            parts.push(plural(self.directories, "directory", "directories"));
+        }
+
+        if parts.is_empty() {
+            write!(f, "nothing")
+        } else {
+            write!(f, "{}", parts.join(", "))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use std::path::PathBuf;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_summary_mixed_tree() {
+        let temp_dir = TempDir::new().unwrap();
+        let root = temp_dir.path().join("build");
+        fs::create_dir_all(root.join("obj").join("nested")).unwrap();
+        fs::create_dir_all(root.join("logs")).unwrap();
+        fs::write(root.join("obj").join("a.o"), "").unwrap();
+        fs::write(root.join("obj").join("b.o"), "").unwrap();
+        fs::write(root.join("obj").join("nested").join("c.o"), "").unwrap();
+        fs::write(root.join("logs").join("run.log"), "").unwrap();
+        fs::write(root.join("Makefile"), "").unwrap();
+
+        let mut summary = TypeSummary::new();
+        summary.add_path(&root, &ExcludeSet::new(&[]).unwrap());
+
+        assert_eq!(summary.extensions.get("o"), Some(&3));
+        assert_eq!(summary.extensions.get("log"), Some(&1));
+        assert_eq!(summary.no_extension, 1);
+        // build, obj, obj/nested, logs
+        assert_eq!(summary.directories, 4);
+        assert_eq!(summary.total_files(), 5);
+        assert_eq!(
+            summary.to_string(),
+            "3 .o files, 1 .log file, 1 file without extension, 4 directories"
+        );
+    }
+
+    #[test]
+    fn test_summary_single_file() {
+        let temp_dir = TempDir::new().unwrap();
+        let file = temp_dir.path().join("debug.log");
+        fs::write(&file, "").unwrap();
+
+        let mut summary = TypeSummary::new();
+        summary.add_path(&file, &ExcludeSet::new(&[]).unwrap());
+
+        assert_eq!(summary.to_string(), "1 .log file");
+        assert_eq!(summary.directories, 0);
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn test_summary_does_not_follow_directory_symlink() {
+        let temp_dir = TempDir::new().unwrap();
+        let target = temp_dir.path().join("target");
+        fs::create_dir(&target).unwrap();
+        fs::write(target.join("inner.txt"), "").unwrap();
+
+        let root = temp_dir.path().join("root");
+        fs::create_dir(&root).unwrap();
+        std::os::unix::fs::symlink(&target, root.join("link")).unwrap();
+
+        let mut summary = TypeSummary::new();
+        summary.add_path(&root, &ExcludeSet::new(&[]).unwrap());
+
+        // link 自体は拡張子なしファイルとして数え、内部の inner.txt は数えない
+        assert_eq!(summary.no_extension, 1);
+        assert!(summary.extensions.is_empty());
+        assert_eq!(summary.directories, 1);
+    }
+
+    #[test]
+    fn test_removal_total_counts_bytes_and_skips_excluded() {
+        let temp_dir = TempDir::new().unwrap();
+        let root = temp_dir.path().join("build");
+        fs::create_dir_all(root.join("obj")).unwrap();
+        fs::write(root.join("obj").join("a.o"), vec![0u8; 1000]).unwrap();
+        fs::write(root.join("obj").join("b.o"), vec![0u8; 1048]).unwrap();
+        fs::write(root.join("keep.cfg"), vec![0u8; 4096]).unwrap();
+
+        let mut total = RemovalTotal::new();
+        total.add_path(&root, &ExcludeSet::new(&["*.cfg".to_string()]).unwrap());
+
+        assert_eq!(total.files, 2);
+        assert_eq!(total.bytes, 2048);
+        assert_eq!(total.to_string(), "2 files (2.0 KiB)");
+    }
+
+    #[test]
+    fn test_summary_skips_excluded_and_kept_entries() {
+        let temp_dir = TempDir::new().unwrap();
+        let root = temp_dir.path().join("build");
+        fs::create_dir_all(root.join("obj")).unwrap();
+        fs::create_dir_all(root.join("src")).unwrap();
+        fs::write(root.join("obj").join("a.o"), "").unwrap();
+        fs::write(root.join("keep.cfg"), "").unwrap();
+        fs::write(root.join("src").join("dirty.rs"), "").unwrap();
+
+        let excludes = ExcludeSet::new(&["*.cfg".to_string()])
+            .unwrap()
+            .keep_paths(vec![PathBuf::from("src/dirty.rs")]);
+        let mut summary = TypeSummary::new();
+        summary.add_path(&root, &excludes);
+
+        // build と src は残るエントリを含むため削除されない
+        assert_eq!(summary.to_string(), "1 .o file, 1 directory");
+    }
+
+    #[test]
+    fn test_format_bytes_units() {
+        assert_eq!(format_bytes(0), "0 B");
+        assert_eq!(format_bytes(1023), "1023 B");
+        assert_eq!(format_bytes(1536), "1.5 KiB");
+        assert_eq!(format_bytes(512 * 1024 * 1024), "512.0 MiB");
+    }
+
+    #[test]
+    fn test_summary_empty_display() {
+        let summary = TypeSummary::new();
+        assert!(summary.is_empty());
+        assert_eq!(summary.to_string(), "nothing");
+    }
+}
@@ -0,0 +1,127 @@
+//! safe-rm の処理時間の内訳表示（`--trace`）
+//!
+//! 大きなリポジトリで遅い原因（無視ファイルの走査かファイルシステムの削除か）を調べるため、
+//! リポジトリのオープン、Git ステータスの一括取得、パス毎のチェックと削除の所要時間を
+//! stderr へ出力する。`--format json` では1行1オブジェクトの `{"type":"trace",...}` とし、
+//! stdout の結果を汚さない。
+
+use crate::cli::OutputFormat;
+use std::cell::Cell;
+use std::path::Path;
+use std::time::Duration;
+
+/// 処理時間の出力
+pub struct Trace {
+    format: OutputFormat,
+    /// 処理中のパスの削除に要した時間（`path` で出力してリセット）
+    removal: Cell<Duration>,
+}
+
+impl Trace {
+    pub fn new(format: OutputFormat) -> Self {
+        Self {
+            format,
+            removal: Cell::new(Duration::ZERO),
+        }
+    }
+
+    /// 実行全体の処理（`open_repository` / `git_status` / `total`）の所要時間を出力
+    pub fn phase(&self, phase: &str, elapsed: Duration) {
+        eprintln!("{}", phase_line(self.format, phase, elapsed));
+    }
+
+    /// 処理中のパスの削除時間を加算（`--backup` では退避の時間）
+    pub fn add_removal(&self, elapsed: Duration) {
+        self.removal.set(self.removal.get() + elapsed);
+    }
+
+    /// 1パスの所要時間を、チェックと削除に分けて出力
+    ///
+    /// `elapsed` はそのパスの処理全体の時間。削除以外の時間をチェックの時間とする。
+    pub fn path(&self, path: &Path, elapsed: Duration) {
+        let removal = self.removal.take();
+        let check = elapsed.saturating_sub(removal);
+        eprintln!("{}", path_line(self.format, path, check, removal));
+    }
+}
+
+/// ミリ秒（小数3桁）
+fn millis(duration: Duration) -> f64 {
+    (duration.as_secs_f64() * 1_000_000.0).round() / 1000.0
+}
+
+fn phase_line(format: OutputFormat, phase: &str, elapsed: Duration) -> String {
+    match format {
+        OutputFormat::Human => {
+            format!("safe-rm: trace: {}: {:.3}ms", phase, millis(elapsed))
+        }
+        OutputFormat::Json => serde_json::json!({
+            "type": "trace",
+            "phase": phase,
+            "ms": millis(elapsed),
+        })
+        .to_string(),
+    }
+}
+
+fn path_line(format: OutputFormat, path: &Path, check: Duration, removal: Duration) -> String {
+    match format {
+        OutputFormat::Human => format!(
+            "safe-rm: trace: path {}: check {:.3}ms, delete {:.3}ms",
+            path.display(),
+            millis(check),
+            millis(removal)
+        ),
+        OutputFormat::Json => serde_json::json!({
+            "type": "trace",
+            "phase": "path",
+            "path": path,
+            "check_ms": millis(check),
+            "delete_ms": millis(removal),
+        })
+        .to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_phase_line_formats() {
+        let elapsed = Duration::from_micros(12_345);
+        assert_eq!(
+            phase_line(OutputFormat::Human, "git_status", elapsed),
+            "safe-rm: trace: git_status: 12.345ms"
+        );
+        let json: serde_json::Value =
+            serde_json::from_str(&phase_line(OutputFormat::Json, "git_status", elapsed)).unwrap();
+        assert_eq!(json["type"], "trace");
+        assert_eq!(json["phase"], "git_status");
+        assert_eq!(json["ms"], 12.345);
+    }
+
+    #[test]
+    fn test_path_line_splits_check_and_delete() {
+        let line = path_line(
+            OutputFormat::Human,
+            Path::new("build"),
+            Duration::from_millis(2),
+            Duration::from_millis(5),
+        );
+        assert_eq!(
+            line,
+            "safe-rm: trace: path build: check 2.000ms, delete 5.000ms"
+        );
+    }
+
+    #[test]
+    fn test_removal_is_reset_per_path() {
+        let trace = Trace::new(OutputFormat::Human);
+        trace.add_removal(Duration::from_millis(3));
+        trace.add_removal(Duration::from_millis(4));
+        assert_eq!(trace.removal.get(), Duration::from_millis(7));
+        trace.path(Path::new("a"), Duration::from_millis(10));
+        assert_eq!(trace.removal.get(), Duration::ZERO);
+    }
+}
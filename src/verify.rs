@@ -0,0 +1,400 @@
+//! safe-rm の環境診断
+//!
+//! `safe-rm verify` で設定ファイル・Git リポジトリ・libgit2・allowed_paths の状態を確認する。
+//! `safe-rm config` 用の実効設定の表示もここで組み立てる。
+
+use crate::config::Config;
+use crate::git_checker::GitChecker;
+use std::fmt;
+use std::path::Path;
+
+/// 診断項目の結果
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CheckLevel {
+    /// 問題なし
+    Ok,
+    /// 情報（失敗扱いにしない）
+    Info,
+    /// 失敗（終了コード 1）
+    Fail,
+}
+
+/// 1つの診断項目
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Check {
+    pub level: CheckLevel,
+    pub name: String,
+    pub detail: String,
+}
+
+impl Check {
+    fn new(level: CheckLevel, name: &str, detail: impl Into<String>) -> Self {
+        Self {
+            level,
+            name: name.to_string(),
+            detail: detail.into(),
+        }
+    }
+}
+
+impl fmt::Display for Check {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let label = match self.level {
+            CheckLevel::Ok => "ok  ",
+            CheckLevel::Info => "info",
+            CheckLevel::Fail => "FAIL",
+        };
+        write!(f, "{} {}: {}", label, self.name, self.detail)
+    }
+}
+
+/// 全ての診断を実行
+///
+/// `config_path` は `Config::config_path()` の結果、`cwd` はリポジトリ検出の起点。
+pub fn run_checks(config_path: Option<&Path>, cwd: &Path) -> Vec<Check> {
+    let mut checks = Vec::new();
+
+    // 設定ファイル
+    let config = match config_path {
+        None => {
+            checks.push(Check::new(
+                CheckLevel::Info,
+                "config",
+                "cannot determine config path (using defaults)",
+            ));
+            Config::default()
+        }
+        Some(path) if !path.exists() => {
+            checks.push(Check::new(
+                CheckLevel::Info,
+                "config",
+                format!("{} not found (using defaults)", path.display()),
+            ));
+            Config::default()
+        }
+        Some(path) => match Config::try_load_from_path(path) {
+            Ok(config) => {
+                checks.push(Check::new(
+                    CheckLevel::Ok,
+                    "config",
+                    format!("{} (valid)", path.display()),
+                ));
+                config
+            }
+            Err(e) => {
+                checks.push(Check::new(CheckLevel::Fail, "config", e));
+                Config::default()
+            }
+        },
+    };
+
+    let mode = if config.allow_project_deletion {
+        "default (allow_project_deletion = true)"
+    } else {
+        "strict (allow_project_deletion = false)"
+    };
+    checks.push(Check::new(CheckLevel::Info, "mode", mode));
+
+    // libgit2 とリポジトリ検出
+    let (major, minor, rev) = git2::Version::get().libgit2_version();
+    checks.push(Check::new(
+        CheckLevel::Ok,
+        "libgit2",
+        format!("{}.{}.{}", major, minor, rev),
+    ));
+    checks.push(match GitChecker::try_open(cwd) {
+        Ok(Some(checker)) => match checker.workdir() {
+            Some(root) => Check::new(CheckLevel::Ok, "git", format!("root {}", root.display())),
+            None => Check::new(CheckLevel::Info, "git", "bare repository (no workdir)"),
+        },
+        Ok(None) => Check::new(
+            CheckLevel::Info,
+            "git",
+            format!(
+                "no repository at {} (cwd is the project root)",
+                cwd.display()
+            ),
+        ),
+        // strict モードでは実行時に fail-closed になるため失敗扱い
+        Err(e) if !config.allow_project_deletion => {
+            Check::new(CheckLevel::Fail, "git", e.to_string())
+        }
+        Err(e) => Check::new(CheckLevel::Info, "git", e.to_string()),
+    });
+
+    // allowed_paths
+    for entry in &config.allowed_paths {
+        if let Some(glob) = &entry.glob {
+            checks.push(Check::new(
+                CheckLevel::Ok,
+                &format!("allowed_path glob {}", glob),
+                "glob pattern (matched against the target path)",
+            ));
+            continue;
+        }
+        let expanded = Config::expand_tilde(&entry.path);
+        let name = format!("allowed_path {}", entry.path);
+        checks.push(match std::fs::canonicalize(&expanded) {
+            Ok(resolved) => Check::new(
+                CheckLevel::Ok,
+                &name,
+                format!(
+                    "{} ({})",
+                    resolved.display(),
+                    if entry.recursive {
+                        "recursive"
+                    } else {
+                        "direct children"
+                    }
+                ),
+            ),
+            Err(e) => Check::new(
+                CheckLevel::Fail,
+                &name,
+                format!("cannot resolve {}: {}", expanded.display(), e),
+            ),
+        });
+    }
+
+    checks
+}
+
+/// `safe-rm config` 用に実効設定を表示用の行として組み立てる
+///
+/// 戻り値の bool は、設定ファイルが存在するのに読み込めなかった（デフォルトに
+/// フォールバックした）場合に true。
+pub fn describe_config(config_path: Option<&Path>) -> (Vec<String>, bool) {
+    let mut lines = vec![match config_path {
+        Some(path) => format!("config: {}", path.display()),
+        None => "config: (cannot determine path)".to_string(),
+    }];
+    let (config, failed) = match load_config(config_path) {
+        LoadedConfig::Parsed(config) => {
+            lines.push("parsed: yes".to_string());
+            (*config, false)
+        }
+        LoadedConfig::Default(reason) => {
+            lines.push(format!("parsed: no ({})", reason));
+            (Config::default(), false)
+        }
+        LoadedConfig::Failed(e) => {
+            lines.push(format!("parsed: no ({}; using defaults)", e));
+            (Config::default(), true)
+        }
+    };
+
+    lines.push(format!(
+        "allow_project_deletion: {}",
+        config.allow_project_deletion
+    ));
+    if config.allowed_paths.is_empty() {
+        lines.push("allowed_paths: (none)".to_string());
+    } else {
+        lines.push("allowed_paths:".to_string());
+        lines.extend(
+            allowed_path_lines(&config)
+                .into_iter()
+                .map(|line| format!("  {}", line)),
+        );
+    }
+
+    (lines, failed)
+}
+
+/// `safe-rm config --list-allowed` 用に allowed_paths の各エントリを1行ずつ組み立てる
+///
+/// 設定ファイルが存在しない場合は空。存在するのに読み込めなかった場合はそのエラー。
+pub fn list_allowed_paths(config_path: Option<&Path>) -> Result<Vec<String>, String> {
+    match load_config(config_path) {
+        LoadedConfig::Parsed(config) => Ok(allowed_path_lines(&config)),
+        LoadedConfig::Default(_) => Ok(Vec::new()),
+        LoadedConfig::Failed(e) => Err(e),
+    }
+}
+
+/// 表示用に読み込んだ設定
+enum LoadedConfig {
+    Parsed(Box<Config>),
+    /// 設定ファイルがないためデフォルト（理由）
+    Default(&'static str),
+    /// 設定ファイルが存在するのに読み込めなかった（エラー）
+    Failed(String),
+}
+
+fn load_config(config_path: Option<&Path>) -> LoadedConfig {
+    match config_path {
+        None => LoadedConfig::Default("using defaults"),
+        Some(path) if !path.exists() => LoadedConfig::Default("file not found, using defaults"),
+        Some(path) => match Config::try_load_from_path(path) {
+            Ok(config) => LoadedConfig::Parsed(Box::new(config)),
+            Err(e) => LoadedConfig::Failed(e),
+        },
+    }
+}
+
+/// allowed_paths の各エントリの記述（例: `~/tmp -> /home/u/tmp (recursive, exists)`）
+fn allowed_path_lines(config: &Config) -> Vec<String> {
+    config
+        .resolved_allowed_paths()
+        .map(|(entry, resolved)| {
+            if let Some(glob) = &entry.glob {
+                return format!("{} (glob)", glob);
+            }
+            format!(
+                "{} -> {} ({}, {})",
+                entry.path,
+                resolved.display(),
+                if entry.recursive {
+                    "recursive"
+                } else {
+                    "direct children"
+                },
+                if resolved.exists() {
+                    "exists"
+                } else {
+                    "missing"
+                }
+            )
+        })
+        .collect()
+}
+
+/// 失敗項目があるか
+pub fn has_failures(checks: &[Check]) -> bool {
+    checks.iter().any(|c| c.level == CheckLevel::Fail)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    #[test]
+    fn test_missing_config_is_not_failure() {
+        let tmp_dir = tempfile::tempdir().unwrap();
+        let checks = run_checks(Some(&tmp_dir.path().join("none.toml")), tmp_dir.path());
+        assert!(!has_failures(&checks));
+        assert_eq!(checks[0].level, CheckLevel::Info);
+    }
+
+    #[test]
+    fn test_invalid_config_fails() {
+        let tmp_dir = tempfile::tempdir().unwrap();
+        let config_path = tmp_dir.path().join("config.toml");
+        fs::write(&config_path, "allow_project_deletion = \"yes\"").unwrap();
+
+        let checks = run_checks(Some(&config_path), tmp_dir.path());
+        assert!(has_failures(&checks));
+        assert_eq!(checks[0].level, CheckLevel::Fail);
+    }
+
+    #[test]
+    fn test_unresolvable_allowed_path_fails() {
+        let tmp_dir = tempfile::tempdir().unwrap();
+        let config_path = tmp_dir.path().join("config.toml");
+        fs::write(
+            &config_path,
+            format!(
+                "[[allowed_paths]]\npath = \"{}\"\n",
+                tmp_dir.path().join("missing").display()
+            ),
+        )
+        .unwrap();
+
+        let checks = run_checks(Some(&config_path), tmp_dir.path());
+        let allowed = checks
+            .iter()
+            .find(|c| c.name.starts_with("allowed_path"))
+            .unwrap();
+        assert_eq!(allowed.level, CheckLevel::Fail);
+    }
+
+    #[test]
+    fn test_describe_config_lists_allowed_paths() {
+        let tmp_dir = tempfile::tempdir().unwrap();
+        let existing = tmp_dir.path().canonicalize().unwrap();
+        let config_path = tmp_dir.path().join("config.toml");
+        fs::write(
+            &config_path,
+            format!(
+                "allow_project_deletion = false\n\n[[allowed_paths]]\npath = \"{}\"\nrecursive = true\n\n[[allowed_paths]]\npath = \"{}\"\n",
+                existing.display(),
+                existing.join("missing").display()
+            ),
+        )
+        .unwrap();
+
+        let (lines, failed) = describe_config(Some(&config_path));
+        assert!(!failed);
+        assert!(lines.contains(&"parsed: yes".to_string()));
+        assert!(lines.contains(&"allow_project_deletion: false".to_string()));
+        assert!(
+            lines.iter().any(|l| l.ends_with("(recursive, exists)")
+                && l.contains(&existing.display().to_string()))
+        );
+        assert!(
+            lines
+                .iter()
+                .any(|l| l.ends_with("(direct children, missing)"))
+        );
+    }
+
+    #[test]
+    fn test_list_allowed_paths() {
+        let tmp_dir = tempfile::tempdir().unwrap();
+        let existing = tmp_dir.path().canonicalize().unwrap();
+        let config_path = tmp_dir.path().join("config.toml");
+        fs::write(
+            &config_path,
+            format!(
+                "[[allowed_paths]]\npath = \"{}\"\nrecursive = true\n",
+                existing.display()
+            ),
+        )
+        .unwrap();
+
+        let lines = list_allowed_paths(Some(&config_path)).unwrap();
+        assert_eq!(
+            lines,
+            vec![format!(
+                "{} -> {} (recursive, exists)",
+                existing.display(),
+                existing.display()
+            )]
+        );
+
+        assert!(
+            list_allowed_paths(Some(&tmp_dir.path().join("none.toml")))
+                .unwrap()
+                .is_empty()
+        );
+        fs::write(&config_path, "allowed_paths = 1\n").unwrap();
+        assert!(list_allowed_paths(Some(&config_path)).is_err());
+    }
+
+    #[test]
+    fn test_describe_config_parse_failure() {
+        let tmp_dir = tempfile::tempdir().unwrap();
+        let config_path = tmp_dir.path().join("config.toml");
+        fs::write(&config_path, "allow_project_deletion = \"yes\"").unwrap();
+
+        let (lines, failed) = describe_config(Some(&config_path));
+        assert!(failed);
+        assert!(lines[1].starts_with("parsed: no (config parse error"));
+        assert!(lines.contains(&"allow_project_deletion: true".to_string()));
+    }
+
+    #[test]
+    fn test_describe_config_missing_file_is_not_failure() {
+        let tmp_dir = tempfile::tempdir().unwrap();
+        let (lines, failed) = describe_config(Some(&tmp_dir.path().join("none.toml")));
+        assert!(!failed);
+        assert!(lines.contains(&"allowed_paths: (none)".to_string()));
+    }
+
+    #[test]
+    fn test_check_display() {
+        let check = Check::new(CheckLevel::Fail, "config", "bad");
+        assert_eq!(check.to_string(), "FAIL config: bad");
+    }
+}
@@ -297,6 +297,47 @@ mod allow_flow_tests {
         assert!(!abs_path.exists(), "File should be deleted");
     }
 
+    #[test]
+    fn test_cwd_boundary_blocks_sibling_directory() {
+        let temp_dir = create_test_repo();
+        let repo_path = temp_dir.path().canonicalize().unwrap();
+        let frontend = repo_path.join("frontend");
+        fs::create_dir(&frontend).unwrap();
+        commit_file(&repo_path, "backend/file.txt", "backend content");
+        commit_file(&repo_path, "frontend/clean.txt", "clean");
+        commit_file(&repo_path, "frontend/modified.txt", "original");
+        fs::write(frontend.join("modified.txt"), "changed").unwrap();
+        let config = tempfile::NamedTempFile::new().unwrap();
+        fs::write(
+            config.path(),
+            "boundary = \"cwd\"\nallow_project_deletion = false\n",
+        )
+        .unwrap();
+
+        let abs_path = repo_path.join("backend").join("file.txt");
+        let (exit_code, _, stderr) = run_safe_rm_with_config(
+            &[abs_path.to_str().unwrap()],
+            &frontend,
+            Some(config.path()),
+        );
+        assert_eq!(exit_code, 2, "stderr: {}", stderr);
+        assert!(
+            abs_path.exists(),
+            "Sibling directory must be outside the cwd boundary"
+        );
+
+        // 境界内では Git ステータスのチェックが引き続き適用される
+        let (exit_code, stdout, stderr) = run_safe_rm_with_config(
+            &["clean.txt", "modified.txt"],
+            &frontend,
+            Some(config.path()),
+        );
+        assert_eq!(exit_code, 2, "stderr: {}", stderr);
+        assert!(stdout.contains("removed: clean.txt"), "stdout: {}", stdout);
+        assert!(stderr.contains("Modified"), "stderr: {}", stderr);
+        assert!(frontend.join("modified.txt").exists());
+    }
+
     #[test]
     fn test_absolute_path_within_same_repo() {
         // リポジトリルートのファイルをサブディレクトリからフルパスで削除
@@ -367,6 +408,25 @@ mod block_flow_tests {
         config
     }
 
+    #[test]
+    fn test_modified_file_blocked_from_deep_subdirectory() {
+        let temp_dir = create_test_repo();
+        let repo_path = temp_dir.path().canonicalize().unwrap();
+        let config = create_strict_config();
+
+        // リポジトリルートではなく深いサブディレクトリから実行してもリポジトリを検出する
+        commit_file(&repo_path, "a/b/c/modified.txt", "original");
+        let deep_dir = repo_path.join("a").join("b").join("c");
+        fs::write(deep_dir.join("modified.txt"), "modified content").unwrap();
+
+        let (exit_code, _, stderr) =
+            run_safe_rm_with_config(&["modified.txt"], &deep_dir, Some(config.path()));
+
+        assert_eq!(exit_code, 2, "stderr: {}", stderr);
+        assert!(stderr.contains("Modified"), "stderr: {}", stderr);
+        assert!(deep_dir.join("modified.txt").exists());
+    }
+
     #[test]
     fn test_modified_file_blocked() {
         let temp_dir = create_test_repo();
@@ -570,6 +630,33 @@ mod block_flow_tests {
             stderr
         );
     }
+
+    #[test]
+    fn test_dir_flag_removes_empty_directory() {
+        let temp_dir = create_test_repo();
+        let repo_path = temp_dir.path().canonicalize().unwrap();
+        fs::create_dir(repo_path.join("emptydir")).unwrap();
+
+        let (exit_code, stdout, stderr) = run_safe_rm(&["-d", "emptydir"], &repo_path);
+
+        assert_eq!(exit_code, 0, "stderr: {}", stderr);
+        assert!(stdout.contains("removed:"));
+        assert!(!repo_path.join("emptydir").exists());
+    }
+
+    #[test]
+    fn test_dir_flag_rejects_non_empty_directory() {
+        let temp_dir = create_test_repo();
+        let repo_path = temp_dir.path().canonicalize().unwrap();
+        fs::create_dir(repo_path.join("fulldir")).unwrap();
+        fs::write(repo_path.join("fulldir").join("a.txt"), "a").unwrap();
+
+        let (exit_code, _, stderr) = run_safe_rm(&["-d", "fulldir"], &repo_path);
+
+        assert_eq!(exit_code, 1);
+        assert!(stderr.contains("Directory not empty"), "stderr: {}", stderr);
+        assert!(repo_path.join("fulldir").join("a.txt").exists());
+    }
 }
 
 // =============================================================================
@@ -800,6 +887,32 @@ mod edge_case_tests {
             "Version should show program name"
         );
     }
+
+    #[test]
+    fn test_empty_path_argument_rejected() {
+        let temp_dir = create_test_repo();
+        let repo_path = temp_dir.path().canonicalize().unwrap();
+        commit_file(&repo_path, "keep.txt", "keep");
+
+        // 空文字列はテンプレート展開バグ等で混入し得る。cwd に解決させてはならない
+        let (exit_code, _, stderr) = run_safe_rm(&["-rf", ""], &repo_path);
+
+        assert_eq!(
+            exit_code, 2,
+            "Empty path should be rejected. stderr: {}",
+            stderr
+        );
+        assert!(
+            stderr.contains("無効なパス"),
+            "Error should indicate invalid path: {}",
+            stderr
+        );
+        assert!(repo_path.exists(), "Project root must survive");
+        assert!(
+            repo_path.join("keep.txt").exists(),
+            "Files in cwd must survive"
+        );
+    }
 }
 
 // =============================================================================
@@ -816,6 +929,165 @@ mod strict_mode_allow_tests {
         config
     }
 
+    #[test]
+    fn test_strict_mode_blocks_modified_tracked_file_matching_gitignore() {
+        let temp_dir = create_test_repo();
+        let repo_path = temp_dir.path().canonicalize().unwrap();
+        let config = create_strict_config();
+        commit_file(&repo_path, "config.local", "original");
+        commit_file(&repo_path, ".gitignore", "config.local\n");
+        fs::write(repo_path.join("config.local"), "changed").unwrap();
+
+        for args in [&[][..], &["--recheck"][..]] {
+            let mut args = args.to_vec();
+            args.push("config.local");
+            let (exit_code, _, stderr) =
+                run_safe_rm_with_config(&args, &repo_path, Some(config.path()));
+            assert_eq!(exit_code, 2, "args: {:?}, stderr: {}", args, stderr);
+            assert!(stderr.contains("Modified"), "stderr: {}", stderr);
+            assert!(repo_path.join("config.local").exists());
+        }
+
+        let (_, stdout, _) =
+            run_safe_rm_with_config(&["status", "config.local"], &repo_path, Some(config.path()));
+        assert!(stdout.starts_with("Modified no"), "stdout: {}", stdout);
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn test_strict_mode_blocks_untracked_non_utf8_file() {
+        use std::os::unix::ffi::OsStrExt;
+
+        let temp_dir = create_test_repo();
+        let repo_path = temp_dir.path().canonicalize().unwrap();
+        let config = create_strict_config();
+        commit_file(&repo_path, "clean.txt", "clean");
+        let name = std::ffi::OsStr::from_bytes(b"bad\xff.txt");
+        // 非 UTF-8 のファイル名を拒否するファイルシステム（APFS など）では検証できない
+        if fs::write(repo_path.join(name), "new").is_err() {
+            return;
+        }
+
+        for args in [&[][..], &["--recheck"][..]] {
+            let output = Command::new(get_binary_path())
+                .args(args)
+                .arg(name)
+                .current_dir(&repo_path)
+                .env("SAFE_RM_CONFIG", config.path())
+                .output()
+                .unwrap();
+            let stderr = String::from_utf8_lossy(&output.stderr);
+            assert_eq!(output.status.code(), Some(2), "stderr: {}", stderr);
+            assert!(stderr.contains("Untracked"), "stderr: {}", stderr);
+            assert!(repo_path.join(name).exists());
+        }
+    }
+
+    #[test]
+    fn test_strict_mode_recheck_allows_clean_and_blocks_dirty() {
+        let temp_dir = create_test_repo();
+        let repo_path = temp_dir.path().canonicalize().unwrap();
+        let config = create_strict_config();
+
+        commit_file(&repo_path, "clean.txt", "clean");
+        commit_file(&repo_path, "modified.txt", "original");
+        fs::write(repo_path.join("modified.txt"), "changed").unwrap();
+
+        let (exit_code, stdout, stderr) = run_safe_rm_with_config(
+            &["--recheck", "clean.txt", "modified.txt"],
+            &repo_path,
+            Some(config.path()),
+        );
+
+        assert_eq!(exit_code, 2, "stderr: {}", stderr);
+        assert!(stdout.contains("removed: clean.txt"), "stdout: {}", stdout);
+        assert!(!repo_path.join("clean.txt").exists());
+        assert!(
+            repo_path.join("modified.txt").exists(),
+            "Modified file must be blocked with --recheck"
+        );
+    }
+
+    #[test]
+    fn test_strict_mode_dry_run_attributes_directory_blockers() {
+        let temp_dir = create_test_repo();
+        let repo_path = temp_dir.path().canonicalize().unwrap();
+        let config = create_strict_config();
+
+        commit_file(&repo_path, ".gitignore", "*.log\n");
+        commit_file(&repo_path, "pkg/clean.txt", "clean");
+        commit_file(&repo_path, "pkg/dirty.txt", "original");
+        fs::write(repo_path.join("pkg").join("dirty.txt"), "changed").unwrap();
+        fs::write(repo_path.join("pkg").join("build.log"), "log").unwrap();
+
+        let (exit_code, stdout, stderr) =
+            run_safe_rm_with_config(&["-rn", "pkg"], &repo_path, Some(config.path()));
+
+        assert_eq!(exit_code, 2, "stderr: {}", stderr);
+        assert!(
+            stdout.contains("blocker: pkg/dirty.txt (Modified)"),
+            "Dirty file should be attributed as blocker: {}",
+            stdout
+        );
+        assert!(
+            stdout.contains("blocked by directory: pkg/clean.txt (Clean, but pkg has blockers)"),
+            "Clean sibling should be attributed to the directory: {}",
+            stdout
+        );
+        assert!(
+            stdout.contains("blocked by directory: pkg/build.log (Ignored, but pkg has blockers)"),
+            "Ignored sibling should be labeled with its own status: {}",
+            stdout
+        );
+        assert!(!stdout.contains("blocker: pkg/clean.txt"));
+
+        // 個別指定すれば clean ファイルは削除可能
+        let (exit_code, _, stderr) =
+            run_safe_rm_with_config(&["pkg/clean.txt"], &repo_path, Some(config.path()));
+        assert_eq!(exit_code, 0, "stderr: {}", stderr);
+    }
+
+    #[test]
+    fn test_strict_mode_list_dirty_reports_every_offender() {
+        let temp_dir = create_test_repo();
+        let repo_path = temp_dir.path().canonicalize().unwrap();
+        let config = create_strict_config();
+
+        commit_file(&repo_path, "pkg/clean.txt", "clean");
+        commit_file(&repo_path, "pkg/dirty.txt", "original");
+        fs::write(repo_path.join("pkg").join("dirty.txt"), "changed").unwrap();
+        fs::write(repo_path.join("pkg").join("new.txt"), "new").unwrap();
+
+        // 既定では最初のブロッカーのみ
+        let (exit_code, _, stderr) =
+            run_safe_rm_with_config(&["-r", "pkg"], &repo_path, Some(config.path()));
+        assert_eq!(exit_code, 2, "stderr: {}", stderr);
+        assert_eq!(
+            stderr.contains("dirty.txt") as u8 + stderr.contains("new.txt") as u8,
+            1,
+            "stderr: {}",
+            stderr
+        );
+
+        for args in [
+            &["--list-dirty", "-r", "pkg"][..],
+            &["--list-dirty", "--recheck", "-r", "pkg"][..],
+        ] {
+            let (exit_code, _, stderr) =
+                run_safe_rm_with_config(args, &repo_path, Some(config.path()));
+            assert_eq!(exit_code, 2, "stderr: {}", stderr);
+            assert!(stderr.contains("（2 件）"), "stderr: {}", stderr);
+            assert!(
+                stderr.contains("dirty.txt (Modified)"),
+                "stderr: {}",
+                stderr
+            );
+            assert!(stderr.contains("new.txt (Untracked)"), "stderr: {}", stderr);
+            assert!(!stderr.contains("clean.txt"), "stderr: {}", stderr);
+            assert!(repo_path.join("pkg").join("clean.txt").exists());
+        }
+    }
+
     #[test]
     fn test_strict_mode_allows_clean_file() {
         let temp_dir = create_test_repo();
@@ -879,6 +1151,82 @@ mod strict_mode_allow_tests {
         );
     }
 
+    #[test]
+    fn test_strict_mode_single_ignored_directory_skips_repo_scan() {
+        let temp_dir = create_test_repo();
+        let repo_path = temp_dir.path().canonicalize().unwrap();
+        let config = create_strict_config();
+
+        fs::write(repo_path.join(".gitignore"), "node_modules/\n").unwrap();
+        Command::new("git")
+            .args(["add", ".gitignore"])
+            .current_dir(&repo_path)
+            .output()
+            .unwrap();
+        Command::new("git")
+            .args(["commit", "-m", "Add gitignore"])
+            .current_dir(&repo_path)
+            .output()
+            .unwrap();
+
+        // 無視ディレクトリと、走査対象になるはずの未追跡ツリー
+        let pkg = repo_path.join("node_modules").join("pkg");
+        fs::create_dir_all(&pkg).unwrap();
+        fs::write(pkg.join("index.js"), "x").unwrap();
+        let untracked = repo_path.join("scratch").join("deep");
+        fs::create_dir_all(&untracked).unwrap();
+        for i in 0..50 {
+            fs::write(untracked.join(format!("f{}.txt", i)), "x").unwrap();
+        }
+
+        let (exit_code, _, stderr) =
+            run_safe_rm_with_config(&["-r", "node_modules"], &repo_path, Some(config.path()));
+
+        assert_eq!(exit_code, 0, "stderr: {}", stderr);
+        assert!(!repo_path.join("node_modules").exists());
+        assert!(untracked.join("f0.txt").exists());
+    }
+
+    #[test]
+    fn test_strict_mode_deep_ignored_directories_skip_large_repo_scan() {
+        let temp_dir = create_test_repo();
+        let repo_path = temp_dir.path().canonicalize().unwrap();
+        let config = create_strict_config();
+        commit_file(&repo_path, ".gitignore", "target/\n");
+
+        // 深い階層の無視ディレクトリ2つと、全体走査なら列挙される大量の無視・未追跡ファイル
+        let targets = [
+            repo_path.join("target"),
+            repo_path.join("crates").join("core").join("target"),
+        ];
+        for target in &targets {
+            let deps = target.join("debug").join("deps");
+            fs::create_dir_all(&deps).unwrap();
+            for i in 0..2000 {
+                fs::write(deps.join(format!("lib{}.rlib", i)), "x").unwrap();
+            }
+        }
+        let scratch = repo_path.join("scratch");
+        fs::create_dir(&scratch).unwrap();
+        for i in 0..2000 {
+            fs::write(scratch.join(format!("f{}.txt", i)), "x").unwrap();
+        }
+
+        let start = std::time::Instant::now();
+        let (exit_code, _, stderr) = run_safe_rm_with_config(
+            &["-r", "target", "crates/core/target"],
+            &repo_path,
+            Some(config.path()),
+        );
+        let elapsed = start.elapsed();
+
+        assert_eq!(exit_code, 0, "stderr: {}", stderr);
+        assert!(targets.iter().all(|target| !target.exists()));
+        assert!(scratch.join("f0.txt").exists());
+        // 計測値は参考（CI の揺らぎを考慮し、極端な劣化のみ検出）
+        assert!(elapsed.as_secs() < 30, "took {:?}", elapsed);
+    }
+
     #[test]
     fn test_strict_mode_allows_ignored_directory_recursive() {
         let temp_dir = create_test_repo();
@@ -1042,31 +1390,83 @@ recursive = true
             stderr
         );
         assert!(
-            stdout.contains("removed:") && stdout.contains("allowed by config"),
-            "Should show removed message with config annotation: {}",
+            stdout.contains("removed:")
+                && stdout.contains(&format!(
+                    "(allowed by config: {} recursive)",
+                    outside_path.display()
+                )),
+            "Should show removed message with matched allowed_paths entry: {}",
             stdout
         );
         assert!(!outside_file.exists(), "File should be deleted");
     }
+
+    #[test]
+    fn test_env_config_list_later_file_takes_precedence() {
+        let temp_dir = create_test_repo();
+        let repo_path = temp_dir.path().canonicalize().unwrap();
+        fs::write(repo_path.join("untracked.txt"), "content").unwrap();
+
+        let config_dir = TempDir::new().unwrap();
+        let strict = config_dir.path().join("base.toml");
+        let relaxed = config_dir.path().join("user.toml");
+        fs::write(&strict, "allow_project_deletion = false\n").unwrap();
+        fs::write(&relaxed, "allow_project_deletion = true\n").unwrap();
+
+        // 基本ポリシー（strict）を後ろに置くと untracked はブロック
+        let list = std::env::join_paths([&relaxed, &strict]).unwrap();
+        let (exit_code, _, _) = run_safe_rm_with_config(
+            &["untracked.txt"],
+            &repo_path,
+            Some(std::path::Path::new(&list)),
+        );
+        assert_eq!(exit_code, 2, "Later strict config should win");
+        assert!(repo_path.join("untracked.txt").exists());
+
+        // ユーザー設定を後ろに置くと上書きされて削除できる
+        let list = std::env::join_paths([&strict, &relaxed]).unwrap();
+        let (exit_code, stdout, stderr) = run_safe_rm_with_config(
+            &["untracked.txt"],
+            &repo_path,
+            Some(std::path::Path::new(&list)),
+        );
+        assert_eq!(exit_code, 0, "Later relaxed config should win: {}", stderr);
+        assert!(stdout.contains("removed:"));
+        assert!(!repo_path.join("untracked.txt").exists());
+    }
 }
 
 // =============================================================================
-// allowed_paths のエラーハンドリングテスト
+// --config フラグのテスト
 // =============================================================================
 
-mod allowed_paths_error_tests {
+mod config_flag_tests {
     use super::*;
 
     #[test]
-    fn test_allowed_paths_nonexistent_with_force() {
-        let project_dir = create_test_repo();
-        let project_path = project_dir.path().canonicalize().unwrap();
+    fn test_relative_allowed_path_in_project_config_matches_project_file() {
+        let temp_dir = create_test_repo();
+        let repo_path = temp_dir.path().canonicalize().unwrap();
+        fs::create_dir(repo_path.join("scratch")).unwrap();
+        commit_file(&repo_path, "scratch/notes.txt", "v1");
+        fs::write(repo_path.join("scratch").join("notes.txt"), "v2").unwrap();
+        fs::write(
+            repo_path.join(".safe-rm.toml"),
+            "allow_project_deletion = false\n\n[[allowed_paths]]\npath = \"scratch\"\nrecursive = true\n",
+        )
+        .unwrap();
 
-        // 存在しないパスを許可する設定
-        let allowed_dir = TempDir::new().unwrap();
-        let allowed_path = allowed_dir.path().canonicalize().unwrap();
-        let nonexistent_file = allowed_path.join("nonexistent.txt");
+        // サブディレクトリから実行しても設定ファイルの場所を基準に解決
+        let sub = repo_path.join("scratch");
+        let (exit_code, stdout, stderr) =
+            run_safe_rm(&["--config", "../.safe-rm.toml", "notes.txt"], &sub);
+        assert_eq!(exit_code, 0, "stderr: {}", stderr);
+        assert!(stdout.contains("allowed by config"), "stdout: {}", stdout);
+        assert!(!sub.join("notes.txt").exists());
+    }
 
+    /// allowed_paths にプロジェクト外ディレクトリを許可する設定ファイルを作成
+    fn allowed_paths_config(allowed: &std::path::Path) -> tempfile::NamedTempFile {
         let config = tempfile::NamedTempFile::new().unwrap();
         let config_content = format!(
             r#"
@@ -1074,54 +1474,171 @@ mod allowed_paths_error_tests {
 path = "{}"
 recursive = true
 "#,
-            allowed_path.display()
+            allowed.display()
         );
         fs::write(config.path(), config_content).unwrap();
+        config
+    }
 
-        // -f フラグ付きで存在しないファイルを削除 → 成功（無視）
-        let (exit_code, _, _) = run_safe_rm_with_config(
-            &["-f", nonexistent_file.to_str().unwrap()],
+    #[test]
+    fn test_config_flag_applies_allowed_paths() {
+        let outside_dir = TempDir::new().unwrap();
+        let outside_path = outside_dir.path().canonicalize().unwrap();
+        let outside_file = outside_path.join("allowed_file.txt");
+        fs::write(&outside_file, "content").unwrap();
+        let config = allowed_paths_config(&outside_path);
+
+        let project_dir = create_test_repo();
+        let project_path = project_dir.path().canonicalize().unwrap();
+
+        let (exit_code, stdout, stderr) = run_safe_rm(
+            &[
+                "--config",
+                config.path().to_str().unwrap(),
+                outside_file.to_str().unwrap(),
+            ],
             &project_path,
-            Some(config.path()),
         );
 
         assert_eq!(
             exit_code, 0,
-            "-f flag should ignore nonexistent file in allowed_paths"
+            "File in allowed_paths should be deletable. stderr: {}",
+            stderr
+        );
+        assert!(
+            stdout.contains("allowed by config"),
+            "Should show removed message with config annotation: {}",
+            stdout
         );
+        assert!(!outside_file.exists(), "File should be deleted");
     }
 
     #[test]
-    fn test_allowed_paths_nonexistent_without_force() {
-        let project_dir = create_test_repo();
-        let project_path = project_dir.path().canonicalize().unwrap();
+    fn test_config_flag_nonexistent_path_fallback() {
+        let temp_dir = create_test_repo();
+        let repo_path = temp_dir.path().canonicalize().unwrap();
+        commit_file(&repo_path, "clean.txt", "clean content");
 
-        // 存在しないパスを許可する設定
-        let allowed_dir = TempDir::new().unwrap();
-        let allowed_path = allowed_dir.path().canonicalize().unwrap();
-        let nonexistent_file = allowed_path.join("nonexistent.txt");
+        // 存在しない設定ファイルを指定 → デフォルト設定にフォールバック
+        let (exit_code, stdout, _) = run_safe_rm(
+            &["--config", "/nonexistent/config.toml", "clean.txt"],
+            &repo_path,
+        );
 
-        let config = tempfile::NamedTempFile::new().unwrap();
-        let config_content = format!(
-            r#"
-[[allowed_paths]]
-path = "{}"
-recursive = true
-"#,
-            allowed_path.display()
+        assert_eq!(
+            exit_code, 0,
+            "Should fallback to default config and succeed"
         );
-        fs::write(config.path(), config_content).unwrap();
+        assert!(stdout.contains("removed:"), "Should show removed message");
+    }
 
-        // -f なしで存在しないファイルを削除 → 失敗
+    #[test]
+    fn test_config_flag_takes_precedence_over_env() {
+        let outside_dir = TempDir::new().unwrap();
+        let outside_path = outside_dir.path().canonicalize().unwrap();
+        let outside_file = outside_path.join("allowed_file.txt");
+        fs::write(&outside_file, "content").unwrap();
+        let config = allowed_paths_config(&outside_path);
+
+        // 環境変数側は何も許可しない設定
+        let env_config = tempfile::NamedTempFile::new().unwrap();
+        fs::write(env_config.path(), "").unwrap();
+
+        let project_dir = create_test_repo();
+        let project_path = project_dir.path().canonicalize().unwrap();
+
+        // --config の設定が SAFE_RM_CONFIG より優先される
         let (exit_code, _, stderr) = run_safe_rm_with_config(
-            &[nonexistent_file.to_str().unwrap()],
+            &[
+                "--config",
+                config.path().to_str().unwrap(),
+                outside_file.to_str().unwrap(),
+            ],
             &project_path,
-            Some(config.path()),
+            Some(env_config.path()),
         );
 
-        assert_eq!(exit_code, 1, "Should fail for nonexistent file without -f");
-        assert!(
-            stderr.contains("No such file"),
+        assert_eq!(
+            exit_code, 0,
+            "--config should override SAFE_RM_CONFIG. stderr: {}",
+            stderr
+        );
+        assert!(!outside_file.exists(), "File should be deleted");
+    }
+}
+
+// =============================================================================
+// allowed_paths のエラーハンドリングテスト
+// =============================================================================
+
+mod allowed_paths_error_tests {
+    use super::*;
+
+    #[test]
+    fn test_allowed_paths_nonexistent_with_force() {
+        let project_dir = create_test_repo();
+        let project_path = project_dir.path().canonicalize().unwrap();
+
+        // 存在しないパスを許可する設定
+        let allowed_dir = TempDir::new().unwrap();
+        let allowed_path = allowed_dir.path().canonicalize().unwrap();
+        let nonexistent_file = allowed_path.join("nonexistent.txt");
+
+        let config = tempfile::NamedTempFile::new().unwrap();
+        let config_content = format!(
+            r#"
+[[allowed_paths]]
+path = "{}"
+recursive = true
+"#,
+            allowed_path.display()
+        );
+        fs::write(config.path(), config_content).unwrap();
+
+        // -f フラグ付きで存在しないファイルを削除 → 成功（無視）
+        let (exit_code, _, _) = run_safe_rm_with_config(
+            &["-f", nonexistent_file.to_str().unwrap()],
+            &project_path,
+            Some(config.path()),
+        );
+
+        assert_eq!(
+            exit_code, 0,
+            "-f flag should ignore nonexistent file in allowed_paths"
+        );
+    }
+
+    #[test]
+    fn test_allowed_paths_nonexistent_without_force() {
+        let project_dir = create_test_repo();
+        let project_path = project_dir.path().canonicalize().unwrap();
+
+        // 存在しないパスを許可する設定
+        let allowed_dir = TempDir::new().unwrap();
+        let allowed_path = allowed_dir.path().canonicalize().unwrap();
+        let nonexistent_file = allowed_path.join("nonexistent.txt");
+
+        let config = tempfile::NamedTempFile::new().unwrap();
+        let config_content = format!(
+            r#"
+[[allowed_paths]]
+path = "{}"
+recursive = true
+"#,
+            allowed_path.display()
+        );
+        fs::write(config.path(), config_content).unwrap();
+
+        // -f なしで存在しないファイルを削除 → 失敗
+        let (exit_code, _, stderr) = run_safe_rm_with_config(
+            &[nonexistent_file.to_str().unwrap()],
+            &project_path,
+            Some(config.path()),
+        );
+
+        assert_eq!(exit_code, 1, "Should fail for nonexistent file without -f");
+        assert!(
+            stderr.contains("No such file"),
             "Error should mention file not found: {}",
             stderr
         );
@@ -1207,6 +1724,59 @@ recursive = true
         );
         assert!(!outside_file.exists(), "File should be deleted");
     }
+
+    #[test]
+    fn test_missing_allowed_path_warns_with_verbose() {
+        let project_dir = create_test_repo();
+        let project_path = project_dir.path().canonicalize().unwrap();
+        fs::write(project_path.join("file.txt"), "content").unwrap();
+
+        let missing = project_path.join("no-such-dir");
+        let config = tempfile::NamedTempFile::new().unwrap();
+        fs::write(
+            config.path(),
+            format!("[[allowed_paths]]\npath = \"{}\"\n", missing.display()),
+        )
+        .unwrap();
+
+        let (exit_code, _, stderr) = run_safe_rm_with_config(
+            &["--verbose", "file.txt"],
+            &project_path,
+            Some(config.path()),
+        );
+
+        // 警告のみで処理は継続する
+        assert_eq!(exit_code, 0, "stderr: {}", stderr);
+        assert!(
+            stderr.contains("safe-rm: warning: allowed_paths entry does not exist"),
+            "stderr: {}",
+            stderr
+        );
+        assert!(stderr.contains(&missing.display().to_string()));
+    }
+
+    #[test]
+    fn test_missing_allowed_path_silent_without_verbose() {
+        let project_dir = create_test_repo();
+        let project_path = project_dir.path().canonicalize().unwrap();
+        fs::write(project_path.join("file.txt"), "content").unwrap();
+
+        let config = tempfile::NamedTempFile::new().unwrap();
+        fs::write(
+            config.path(),
+            format!(
+                "[[allowed_paths]]\npath = \"{}\"\n",
+                project_path.join("no-such-dir").display()
+            ),
+        )
+        .unwrap();
+
+        let (exit_code, _, stderr) =
+            run_safe_rm_with_config(&["file.txt"], &project_path, Some(config.path()));
+
+        assert_eq!(exit_code, 0);
+        assert!(!stderr.contains("warning"), "stderr: {}", stderr);
+    }
 }
 
 // =============================================================================
@@ -1285,6 +1855,82 @@ mod init_tests {
     }
 }
 
+// =============================================================================
+// reset サブコマンドのテスト
+// =============================================================================
+
+mod reset_tests {
+    use super::*;
+
+    /// init でテンプレートを生成した設定ファイルのパス
+    fn init_config(temp_dir: &TempDir) -> std::path::PathBuf {
+        let config_path = temp_dir.path().join("safe-rm").join("config.toml");
+        let (exit_code, _, stderr) =
+            run_safe_rm_with_config(&["init"], temp_dir.path(), Some(&config_path));
+        assert_eq!(exit_code, 0, "stderr: {}", stderr);
+        config_path
+    }
+
+    #[test]
+    fn test_reset_removes_unmodified_template() {
+        let temp_dir = TempDir::new().unwrap();
+        let config_path = init_config(&temp_dir);
+
+        let (exit_code, stdout, stderr) =
+            run_safe_rm_with_config(&["reset", "--yes"], temp_dir.path(), Some(&config_path));
+
+        assert_eq!(exit_code, 0, "stderr: {}", stderr);
+        assert!(stdout.contains("Removed config file"), "stdout: {}", stdout);
+        assert!(!config_path.exists());
+    }
+
+    #[test]
+    fn test_reset_refuses_modified_config_without_force() {
+        let temp_dir = TempDir::new().unwrap();
+        let config_path = init_config(&temp_dir);
+        let mut content = fs::read_to_string(&config_path).unwrap();
+        content.push_str("\nmax_paths = 10\n");
+        fs::write(&config_path, &content).unwrap();
+
+        let (exit_code, _, stderr) =
+            run_safe_rm_with_config(&["reset", "--yes"], temp_dir.path(), Some(&config_path));
+        assert_eq!(exit_code, 1, "stderr: {}", stderr);
+        assert!(stderr.contains("--force"), "stderr: {}", stderr);
+        assert_eq!(fs::read_to_string(&config_path).unwrap(), content);
+
+        // 非対話では --force だけでは編集済みの設定を消せない
+        let (exit_code, _, stderr) = run_safe_rm_with_config(
+            &["reset", "--yes", "--force"],
+            temp_dir.path(),
+            Some(&config_path),
+        );
+        assert_eq!(exit_code, 1, "stderr: {}", stderr);
+        assert!(stderr.contains("--allow-self-config"), "stderr: {}", stderr);
+        assert_eq!(fs::read_to_string(&config_path).unwrap(), content);
+
+        let (exit_code, _, stderr) = run_safe_rm_with_config(
+            &["reset", "--yes", "--force", "--allow-self-config"],
+            temp_dir.path(),
+            Some(&config_path),
+        );
+        assert_eq!(exit_code, 0, "stderr: {}", stderr);
+        assert!(!config_path.exists());
+    }
+
+    #[test]
+    fn test_reset_without_yes_requires_terminal() {
+        let temp_dir = TempDir::new().unwrap();
+        let config_path = init_config(&temp_dir);
+
+        let (exit_code, _, stderr) =
+            run_safe_rm_with_config(&["reset"], temp_dir.path(), Some(&config_path));
+
+        assert_eq!(exit_code, 1, "stderr: {}", stderr);
+        assert!(stderr.contains("--yes"), "stderr: {}", stderr);
+        assert!(config_path.exists());
+    }
+}
+
 // =============================================================================
 // allowed_paths の非再帰設定テスト
 // =============================================================================
@@ -1382,6 +2028,48 @@ recursive = false
 mod dry_run_tests {
     use super::*;
 
+    #[test]
+    fn test_dry_run_by_type_summary() {
+        let temp_dir = create_test_repo();
+        let repo_path = temp_dir.path().canonicalize().unwrap();
+
+        let build = repo_path.join("build");
+        fs::create_dir_all(build.join("obj")).unwrap();
+        fs::write(build.join("obj").join("a.o"), "").unwrap();
+        fs::write(build.join("obj").join("b.o"), "").unwrap();
+        fs::write(build.join("run.log"), "").unwrap();
+
+        let (exit_code, stdout, stderr) = run_safe_rm(&["-rn", "--by-type", "build"], &repo_path);
+
+        assert_eq!(exit_code, 0, "stderr: {}", stderr);
+        assert!(
+            stdout.contains("summary: 2 .o files, 1 .log file, 2 directories"),
+            "Should show grouped summary: {}",
+            stdout
+        );
+        assert!(build.exists(), "Directory should NOT be deleted in dry run");
+    }
+
+    #[test]
+    fn test_by_type_summary_after_delete() {
+        let temp_dir = create_test_repo();
+        let repo_path = temp_dir.path().canonicalize().unwrap();
+
+        let cache = repo_path.join("cache");
+        fs::create_dir_all(&cache).unwrap();
+        fs::write(cache.join("x.tmp"), "").unwrap();
+
+        let (exit_code, stdout, stderr) = run_safe_rm(&["-r", "--by-type", "cache"], &repo_path);
+
+        assert_eq!(exit_code, 0, "stderr: {}", stderr);
+        assert!(
+            stdout.contains("summary: 1 .tmp file, 1 directory"),
+            "Summary must be computed before deletion: {}",
+            stdout
+        );
+        assert!(!cache.exists());
+    }
+
     #[test]
     fn test_dry_run_with_directory() {
         let temp_dir = create_test_repo();
@@ -1545,6 +2233,28 @@ mod symlink_tests {
         );
     }
 
+    #[test]
+    fn test_dot_dot_through_symlink_cannot_escape_project() {
+        let temp_dir = create_test_repo();
+        let repo_path = temp_dir.path().canonicalize().unwrap();
+        commit_file(&repo_path, "init.txt", "init");
+
+        // link/../secret は字句的には repo/secret だが、実際は outside/secret を指す
+        let outside_dir = TempDir::new().unwrap();
+        fs::create_dir(outside_dir.path().join("sub")).unwrap();
+        let secret = outside_dir.path().join("secret");
+        fs::write(&secret, "secret").unwrap();
+        std::os::unix::fs::symlink(outside_dir.path().join("sub"), repo_path.join("link")).unwrap();
+
+        let (exit_code, _, stderr) = run_safe_rm(&["link/../secret"], &repo_path);
+
+        assert_eq!(exit_code, 2, "stderr: {}", stderr);
+        assert!(
+            secret.exists(),
+            "File outside the project must not be deleted"
+        );
+    }
+
     #[test]
     fn test_strict_mode_directory_symlink_does_not_traverse_target() {
         use std::os::unix::fs::PermissionsExt;
@@ -1685,6 +2395,51 @@ mod symlink_tests {
             "Untracked symlink should NOT be deleted"
         );
     }
+
+    #[test]
+    fn test_dangling_symlink_deletable_without_force() {
+        let temp_dir = create_test_repo();
+        let repo_path = temp_dir.path().canonicalize().unwrap();
+        commit_file(&repo_path, "init.txt", "init");
+
+        // リンク先が存在しないシンボリックリンク（exists() は false になる）
+        let link_path = repo_path.join("dangling");
+        std::os::unix::fs::symlink("missing.txt", &link_path).unwrap();
+        assert!(!link_path.exists());
+
+        let (exit_code, stdout, stderr) = run_safe_rm(&["dangling"], &repo_path);
+
+        assert_eq!(exit_code, 0, "stderr: {}", stderr);
+        assert!(stdout.contains("removed: dangling"), "stdout: {}", stdout);
+        assert!(!stderr.contains("No such file"), "stderr: {}", stderr);
+        assert!(link_path.symlink_metadata().is_err());
+    }
+
+    #[test]
+    fn test_strict_mode_committed_dangling_symlink_deletable() {
+        let temp_dir = create_test_repo();
+        let repo_path = temp_dir.path().canonicalize().unwrap();
+        let link_path = repo_path.join("dangling");
+        std::os::unix::fs::symlink("missing.txt", &link_path).unwrap();
+        Command::new("git")
+            .args(["add", "dangling"])
+            .current_dir(&repo_path)
+            .output()
+            .unwrap();
+        Command::new("git")
+            .args(["commit", "-m", "Add dangling symlink"])
+            .current_dir(&repo_path)
+            .output()
+            .unwrap();
+        let config = tempfile::NamedTempFile::new().unwrap();
+        fs::write(config.path(), "allow_project_deletion = false\n").unwrap();
+
+        let (exit_code, _, stderr) =
+            run_safe_rm_with_config(&["dangling"], &repo_path, Some(config.path()));
+
+        assert_eq!(exit_code, 0, "stderr: {}", stderr);
+        assert!(link_path.symlink_metadata().is_err());
+    }
 }
 
 // =============================================================================
@@ -1821,8 +2576,9 @@ mod partial_failure_tests {
             stderr
         );
         assert!(
-            stderr.contains("file(s) removed"),
-            "Should show PartialFailure message. stderr: {}",
+            stderr
+                .contains("0 file(s) removed, 3 failed (0 blocked, 3 not found, 0 other error(s))"),
+            "Should show PartialFailure breakdown. stderr: {}",
             stderr
         );
     }
@@ -1852,6 +2608,39 @@ mod partial_failure_tests {
             "Should report missing file. stderr: {}",
             stderr
         );
+        assert!(
+            stderr.contains("1 file(s) removed, 1 failed (0 blocked, 1 not found"),
+            "stderr: {}",
+            stderr
+        );
+    }
+
+    #[test]
+    fn test_partial_failure_mix_blocked_and_not_found_keeps_breakdown() {
+        let temp_dir = create_test_repo();
+        let repo_path = temp_dir.path().canonicalize().unwrap();
+        let config = tempfile::NamedTempFile::new().unwrap();
+        fs::write(config.path(), "allow_project_deletion = false\n").unwrap();
+
+        commit_file(&repo_path, "clean.txt", "content");
+        fs::write(repo_path.join("untracked.txt"), "new").unwrap();
+
+        let (exit_code, _, stderr) = run_safe_rm_with_config(
+            &["clean.txt", "untracked.txt", "missing.txt"],
+            &repo_path,
+            Some(config.path()),
+        );
+
+        // ブロックを含むため終了コード 2、ただし NotFound の件数も失わない
+        assert_eq!(exit_code, 2, "stderr: {}", stderr);
+        assert!(
+            stderr
+                .contains("1 file(s) removed, 2 failed (1 blocked, 1 not found, 0 other error(s))"),
+            "stderr: {}",
+            stderr
+        );
+        assert!(!repo_path.join("clean.txt").exists());
+        assert!(repo_path.join("untracked.txt").exists());
     }
 }
 
@@ -1863,6 +2652,37 @@ mod partial_failure_tests {
 mod dirty_symlink_tests {
     use super::*;
 
+    #[test]
+    fn test_strict_mode_symlink_cycle_terminates_and_keeps_link_targets() {
+        let temp_dir = create_test_repo();
+        let repo_path = temp_dir.path().canonicalize().unwrap();
+        let config = tempfile::NamedTempFile::new().unwrap();
+        fs::write(config.path(), "allow_project_deletion = false\n").unwrap();
+
+        commit_file(&repo_path, "keep.txt", "keep");
+        let dir = repo_path.join("cycle");
+        fs::create_dir(&dir).unwrap();
+        std::os::unix::fs::symlink("..", dir.join("up")).unwrap();
+        std::os::unix::fs::symlink(".", dir.join("self")).unwrap();
+        Command::new("git")
+            .args(["add", "-A"])
+            .current_dir(&repo_path)
+            .output()
+            .unwrap();
+        Command::new("git")
+            .args(["commit", "-m", "Add cycle"])
+            .current_dir(&repo_path)
+            .output()
+            .unwrap();
+
+        let (exit_code, _, stderr) =
+            run_safe_rm_with_config(&["-r", "cycle"], &repo_path, Some(config.path()));
+
+        assert_eq!(exit_code, 0, "stderr: {}", stderr);
+        assert!(!dir.exists());
+        assert!(repo_path.join("keep.txt").exists());
+    }
+
     #[test]
     fn test_strict_mode_untracked_symlink_blocked() {
         let temp_dir = create_test_repo();
@@ -2008,3 +2828,4446 @@ mod special_filename_tests {
         );
     }
 }
+
+// =============================================================================
+// --files-from のテスト
+// =============================================================================
+
+mod files_from_tests {
+    use super::*;
+
+    #[test]
+    fn test_files_from_combined_with_positional_paths() {
+        let temp_dir = create_test_repo();
+        let repo_path = temp_dir.path().canonicalize().unwrap();
+
+        commit_file(&repo_path, "a.txt", "a");
+        commit_file(&repo_path, "b.txt", "b");
+        commit_file(&repo_path, "c.txt", "c");
+        commit_file(&repo_path, "keep.txt", "keep");
+        fs::write(
+            repo_path.join("cleanup.list"),
+            "# generated cleanup list\na.txt\n\nb.txt\n# keep.txt\n",
+        )
+        .unwrap();
+
+        let (exit_code, stdout, stderr) =
+            run_safe_rm(&["--files-from", "cleanup.list", "c.txt"], &repo_path);
+
+        assert_eq!(exit_code, 0, "stderr: {}", stderr);
+        assert!(stdout.contains("removed: a.txt"), "stdout: {}", stdout);
+        assert!(!repo_path.join("a.txt").exists());
+        assert!(!repo_path.join("b.txt").exists());
+        assert!(!repo_path.join("c.txt").exists());
+        assert!(
+            repo_path.join("keep.txt").exists(),
+            "Commented-out entry must not be deleted"
+        );
+    }
+
+    #[test]
+    fn test_files_from_entries_still_checked() {
+        let temp_dir = create_test_repo();
+        let repo_path = temp_dir.path().canonicalize().unwrap();
+        commit_file(&repo_path, "init.txt", "init");
+        fs::write(repo_path.join("cleanup.list"), "/etc/passwd\n").unwrap();
+
+        let (exit_code, _, stderr) = run_safe_rm(&["--files-from", "cleanup.list"], &repo_path);
+
+        assert_eq!(
+            exit_code, 2,
+            "Listed paths go through containment check. stderr: {}",
+            stderr
+        );
+    }
+
+    #[test]
+    fn test_files_from_relative_list_outside_project_blocked() {
+        let temp_dir = create_test_repo();
+        let repo_path = temp_dir.path().canonicalize().unwrap();
+        commit_file(&repo_path, "init.txt", "init");
+
+        let (exit_code, _, stderr) = run_safe_rm(&["--files-from", "../outside.list"], &repo_path);
+
+        assert_eq!(
+            exit_code, 2,
+            "Relative list file outside project should be blocked. stderr: {}",
+            stderr
+        );
+    }
+}
+
+// =============================================================================
+// --color のテスト
+// =============================================================================
+
+mod color_tests {
+    use super::*;
+
+    /// 環境変数を指定して safe-rm を実行
+    fn run_with_env(args: &[&str], cwd: &std::path::Path, env: &[(&str, &str)]) -> (i32, String) {
+        let mut cmd = Command::new(get_binary_path());
+        cmd.args(args).current_dir(cwd).env_remove("NO_COLOR");
+        for (key, value) in env {
+            cmd.env(key, value);
+        }
+        let output = cmd.output().expect("Failed to execute safe-rm");
+        (
+            output.status.code().unwrap_or(-1),
+            String::from_utf8_lossy(&output.stdout).to_string(),
+        )
+    }
+
+    #[test]
+    fn test_color_always_colors_removed() {
+        let temp_dir = create_test_repo();
+        let repo_path = temp_dir.path().canonicalize().unwrap();
+        commit_file(&repo_path, "a.txt", "a");
+
+        let (exit_code, stdout) = run_with_env(&["--color", "always", "a.txt"], &repo_path, &[]);
+
+        assert_eq!(exit_code, 0);
+        assert!(
+            stdout.contains("\x1b[32mremoved:\x1b[0m a.txt"),
+            "removed: should be green: {:?}",
+            stdout
+        );
+    }
+
+    #[test]
+    fn test_color_always_colors_dry_run_yellow() {
+        let temp_dir = create_test_repo();
+        let repo_path = temp_dir.path().canonicalize().unwrap();
+        commit_file(&repo_path, "a.txt", "a");
+
+        let (_, stdout) = run_with_env(&["--color=always", "-n", "a.txt"], &repo_path, &[]);
+
+        assert!(
+            stdout.contains("\x1b[33mwould remove:\x1b[0m a.txt"),
+            "would remove: should be yellow: {:?}",
+            stdout
+        );
+    }
+
+    #[test]
+    fn test_color_auto_is_plain_when_piped() {
+        let temp_dir = create_test_repo();
+        let repo_path = temp_dir.path().canonicalize().unwrap();
+        commit_file(&repo_path, "a.txt", "a");
+
+        let (_, stdout) = run_with_env(&["a.txt"], &repo_path, &[]);
+
+        assert!(!stdout.contains('\x1b'), "Piped output must be plain");
+    }
+
+    #[test]
+    fn test_no_color_env_overrides_always() {
+        let temp_dir = create_test_repo();
+        let repo_path = temp_dir.path().canonicalize().unwrap();
+        commit_file(&repo_path, "a.txt", "a");
+
+        let (_, stdout) = run_with_env(
+            &["--color", "always", "a.txt"],
+            &repo_path,
+            &[("NO_COLOR", "1")],
+        );
+
+        assert!(!stdout.contains('\x1b'), "NO_COLOR must disable colors");
+        assert!(stdout.contains("removed: a.txt"));
+    }
+}
+
+// =============================================================================
+// --per-repo（複数リポジトリのバッチ）のテスト
+// =============================================================================
+
+mod per_repo_tests {
+    use super::*;
+
+    /// allow_project_deletion = false の設定ファイルを作成
+    fn create_strict_config() -> tempfile::NamedTempFile {
+        let config = tempfile::NamedTempFile::new().unwrap();
+        fs::write(config.path(), "allow_project_deletion = false\n").unwrap();
+        config
+    }
+
+    #[test]
+    fn test_per_repo_checks_each_path_against_its_own_repo() {
+        let repo_a = create_test_repo();
+        let repo_b = create_test_repo();
+        let path_a = repo_a.path().canonicalize().unwrap();
+        let path_b = repo_b.path().canonicalize().unwrap();
+        let config = create_strict_config();
+
+        commit_file(&path_a, "a.txt", "a");
+        commit_file(&path_b, "b.txt", "b");
+        commit_file(&path_b, "dirty.txt", "original");
+        fs::write(path_b.join("dirty.txt"), "changed").unwrap();
+
+        let b_clean = path_b.join("b.txt");
+        let b_dirty = path_b.join("dirty.txt");
+        let (exit_code, stdout, stderr) = run_safe_rm_with_config(
+            &[
+                "--per-repo",
+                "a.txt",
+                b_clean.to_str().unwrap(),
+                b_dirty.to_str().unwrap(),
+            ],
+            &path_a,
+            Some(config.path()),
+        );
+
+        assert_eq!(exit_code, 2, "stdout: {} stderr: {}", stdout, stderr);
+        assert!(!path_a.join("a.txt").exists(), "a.txt should be deleted");
+        assert!(!b_clean.exists(), "Clean file in repo B should be deleted");
+        assert!(
+            b_dirty.exists(),
+            "Dirty file in repo B must be blocked by repo B's status"
+        );
+        assert!(
+            stderr.contains("Modified"),
+            "Should report dirty status: {}",
+            stderr
+        );
+    }
+
+    #[test]
+    fn test_without_per_repo_other_repo_is_outside_project() {
+        let repo_a = create_test_repo();
+        let repo_b = create_test_repo();
+        let path_a = repo_a.path().canonicalize().unwrap();
+        let path_b = repo_b.path().canonicalize().unwrap();
+
+        commit_file(&path_a, "a.txt", "a");
+        commit_file(&path_b, "b.txt", "b");
+
+        let b_clean = path_b.join("b.txt");
+        let (exit_code, _, _) = run_safe_rm(&[b_clean.to_str().unwrap()], &path_a);
+
+        assert_eq!(exit_code, 2, "Other repo is outside the cwd project");
+        assert!(b_clean.exists());
+    }
+
+    #[test]
+    fn test_per_repo_does_not_allow_deleting_repo_root_itself() {
+        let repo_a = create_test_repo();
+        let repo_b = create_test_repo();
+        let path_a = repo_a.path().canonicalize().unwrap();
+        let path_b = repo_b.path().canonicalize().unwrap();
+        commit_file(&path_a, "a.txt", "a");
+        commit_file(&path_b, "b.txt", "b");
+
+        // repo B のルート自体は repo B の「中」ではなく、親ディレクトリ側で判定される
+        let (exit_code, _, _) =
+            run_safe_rm(&["--per-repo", "-r", path_b.to_str().unwrap()], &path_a);
+
+        assert_eq!(exit_code, 2, "Repo root of another repo must stay blocked");
+        assert!(path_b.join("b.txt").exists());
+    }
+
+    #[test]
+    fn test_per_repo_keeps_cwd_boundary() {
+        let temp_dir = create_test_repo();
+        let repo_path = temp_dir.path().canonicalize().unwrap();
+        commit_file(&repo_path, "f.txt", "f");
+        commit_file(&repo_path, "sub/g.txt", "g");
+        let config = tempfile::NamedTempFile::new().unwrap();
+        fs::write(config.path(), "boundary = \"cwd\"\n").unwrap();
+
+        let (exit_code, _, stderr) = run_safe_rm_with_config(
+            &["--per-repo", "../f.txt"],
+            &repo_path.join("sub"),
+            Some(config.path()),
+        );
+        assert_eq!(exit_code, 2, "stderr: {}", stderr);
+        assert!(
+            repo_path.join("f.txt").exists(),
+            "--per-repo must not widen the cwd boundary to the repo root"
+        );
+
+        let (exit_code, _, stderr) = run_safe_rm_with_config(
+            &["--per-repo", "g.txt"],
+            &repo_path.join("sub"),
+            Some(config.path()),
+        );
+        assert_eq!(exit_code, 0, "stderr: {}", stderr);
+        assert!(!repo_path.join("sub").join("g.txt").exists());
+    }
+}
+
+// =============================================================================
+// 監査ログのテスト
+// =============================================================================
+
+mod audit_log_tests {
+    use super::*;
+
+    #[test]
+    fn test_audit_log_records_removed_and_blocked() {
+        let temp_dir = create_test_repo();
+        let repo_path = temp_dir.path().canonicalize().unwrap();
+        let config_dir = TempDir::new().unwrap();
+        let log_path = config_dir.path().join("audit.log");
+        let config_path = config_dir.path().join("config.toml");
+        fs::write(
+            &config_path,
+            format!(
+                "allow_project_deletion = false\naudit_log = \"{}\"\n",
+                log_path.display()
+            ),
+        )
+        .unwrap();
+
+        commit_file(&repo_path, "clean.txt", "clean");
+        commit_file(&repo_path, "dirty.txt", "original");
+        fs::write(repo_path.join("dirty.txt"), "modified").unwrap();
+
+        let (exit_code, _, _) =
+            run_safe_rm_with_config(&["clean.txt", "dirty.txt"], &repo_path, Some(&config_path));
+        assert_eq!(exit_code, 2);
+
+        let content = fs::read_to_string(&log_path).unwrap();
+        let lines: Vec<&str> = content.lines().collect();
+        assert_eq!(lines.len(), 2, "one line per path: {content}");
+
+        let removed: Vec<&str> = lines[0].split('\t').collect();
+        assert_eq!(removed[1], "removed");
+        assert_eq!(removed[2], repo_path.join("clean.txt").to_string_lossy());
+        assert_eq!(removed[3], "Clean");
+        assert_eq!(removed[5], repo_path.to_string_lossy());
+
+        let blocked: Vec<&str> = lines[1].split('\t').collect();
+        assert_eq!(blocked[1], "blocked");
+        assert_eq!(blocked[3], "Modified");
+        assert_ne!(blocked[4], "-", "blocked entry should carry a reason");
+    }
+
+    #[test]
+    fn test_audit_log_flag_appends_dry_run() {
+        let temp_dir = TempDir::new().unwrap();
+        let dir = temp_dir.path().canonicalize().unwrap();
+        let log_dir = TempDir::new().unwrap();
+        let log_path = log_dir.path().join("audit.log");
+        fs::write(&log_path, "existing\n").unwrap();
+        fs::write(dir.join("a.txt"), "a").unwrap();
+
+        let (exit_code, _, _) = run_safe_rm(
+            &["-n", "--audit-log", log_path.to_str().unwrap(), "a.txt"],
+            &dir,
+        );
+        assert_eq!(exit_code, 0);
+        assert!(dir.join("a.txt").exists());
+
+        let content = fs::read_to_string(&log_path).unwrap();
+        let lines: Vec<&str> = content.lines().collect();
+        assert_eq!(lines[0], "existing", "existing lines must be preserved");
+        assert!(lines[1].contains("\twould_remove\t"));
+    }
+
+    #[test]
+    fn test_audit_log_unwritable_aborts_before_deletion() {
+        let temp_dir = TempDir::new().unwrap();
+        let dir = temp_dir.path().canonicalize().unwrap();
+        fs::write(dir.join("a.txt"), "a").unwrap();
+        let log_path = dir.join("missing").join("audit.log");
+
+        let (exit_code, _, _) =
+            run_safe_rm(&["--audit-log", log_path.to_str().unwrap(), "a.txt"], &dir);
+        assert_eq!(exit_code, 1);
+        assert!(dir.join("a.txt").exists());
+    }
+}
+
+// =============================================================================
+// Git 初期化失敗時の縮退テスト
+// =============================================================================
+
+mod git_unavailable_tests {
+    use super::*;
+
+    /// 破損した `.git` ファイルを持つディレクトリを作成
+    fn create_corrupt_repo() -> TempDir {
+        let temp_dir = TempDir::new().unwrap();
+        fs::write(temp_dir.path().join(".git"), "garbage").unwrap();
+        fs::write(temp_dir.path().join("file.txt"), "content").unwrap();
+        temp_dir
+    }
+
+    #[test]
+    fn test_corrupt_git_degrades_with_warning_in_default_mode() {
+        let temp_dir = create_corrupt_repo();
+        let dir = temp_dir.path().canonicalize().unwrap();
+
+        let (exit_code, _, stderr) = run_safe_rm(&["file.txt"], &dir);
+        assert_eq!(exit_code, 0, "stderr: {stderr}");
+        assert!(stderr.contains("warning"));
+        assert!(!dir.join("file.txt").exists());
+    }
+
+    #[test]
+    fn test_corrupt_git_warning_is_json_under_json_format() {
+        let temp_dir = create_corrupt_repo();
+        let dir = temp_dir.path().canonicalize().unwrap();
+
+        let (exit_code, _, stderr) = run_safe_rm(&["--format", "json", "file.txt"], &dir);
+        assert_eq!(exit_code, 0, "stderr: {stderr}");
+        let warning: serde_json::Value = serde_json::from_str(stderr.trim()).unwrap();
+        assert_eq!(warning["type"], "warning");
+        assert_eq!(warning["path"], dir.to_str().unwrap());
+        assert!(
+            warning["message"]
+                .as_str()
+                .unwrap()
+                .contains("continuing without Git checks")
+        );
+        assert!(!dir.join("file.txt").exists());
+    }
+
+    #[test]
+    fn test_corrupt_git_fails_closed_with_strict_paths() {
+        let temp_dir = create_corrupt_repo();
+        let dir = temp_dir.path().canonicalize().unwrap();
+        fs::create_dir(dir.join("src")).unwrap();
+        fs::write(dir.join("src").join("a"), "a").unwrap();
+        let config = tempfile::NamedTempFile::new().unwrap();
+        fs::write(config.path(), "strict_paths = [\"src\"]\n").unwrap();
+
+        let (exit_code, _, stderr) = run_safe_rm_with_config(&["src/a"], &dir, Some(config.path()));
+        assert_eq!(exit_code, 2, "stderr: {stderr}");
+        assert!(stderr.contains("Git リポジトリを開けません"));
+        assert!(dir.join("src").join("a").exists());
+    }
+
+    #[test]
+    fn test_corrupt_head_fails_closed_in_strict_mode() {
+        let temp_dir = create_test_repo();
+        let repo_path = temp_dir.path().canonicalize().unwrap();
+        commit_file(&repo_path, "tracked.txt", "original");
+        fs::write(repo_path.join("tracked.txt"), "changed").unwrap();
+        fs::write(repo_path.join(".git").join("HEAD"), "garbage").unwrap();
+        let config = tempfile::NamedTempFile::new().unwrap();
+        fs::write(config.path(), "allow_project_deletion = false\n").unwrap();
+
+        let (exit_code, _, stderr) =
+            run_safe_rm_with_config(&["tracked.txt"], &repo_path, Some(config.path()));
+        assert_eq!(exit_code, 2, "stderr: {stderr}");
+        assert!(stderr.contains("Git リポジトリを開けません"));
+        assert!(repo_path.join("tracked.txt").exists());
+    }
+
+    #[test]
+    fn test_corrupt_git_fails_closed_in_strict_mode() {
+        let temp_dir = create_corrupt_repo();
+        let dir = temp_dir.path().canonicalize().unwrap();
+        let config = tempfile::NamedTempFile::new().unwrap();
+        fs::write(config.path(), "allow_project_deletion = false\n").unwrap();
+
+        let (exit_code, _, stderr) =
+            run_safe_rm_with_config(&["file.txt"], &dir, Some(config.path()));
+        assert_eq!(exit_code, 2, "stderr: {stderr}");
+        assert!(stderr.contains("Git リポジトリを開けません"));
+        assert!(dir.join("file.txt").exists());
+    }
+}
+
+// =============================================================================
+// バックアップ（--backup）のテスト
+// =============================================================================
+
+mod backup_tests {
+    use super::*;
+
+    /// 実行ごとのバックアップディレクトリを取得（1回の実行で1つ）
+    fn single_run_dir(backup_root: &std::path::Path) -> std::path::PathBuf {
+        let runs: Vec<_> = fs::read_dir(backup_root)
+            .unwrap()
+            .map(|e| e.unwrap().path())
+            .collect();
+        assert_eq!(runs.len(), 1, "expected exactly one run dir: {runs:?}");
+        runs.into_iter().next().unwrap()
+    }
+
+    #[test]
+    fn test_verify_backup_reports_success_after_check() {
+        let temp_dir = TempDir::new().unwrap();
+        let dir = temp_dir.path().canonicalize().unwrap();
+        let backup_root = TempDir::new().unwrap();
+        fs::create_dir_all(dir.join("sub")).unwrap();
+        fs::write(dir.join("sub").join("b.txt"), "b").unwrap();
+
+        let (exit_code, stdout, stderr) = run_safe_rm(
+            &[
+                "-r",
+                "--backup",
+                backup_root.path().to_str().unwrap(),
+                "--verify-backup",
+                "sub",
+            ],
+            &dir,
+        );
+        assert_eq!(exit_code, 0, "stderr: {stderr}");
+        assert!(stdout.contains("removed: sub"));
+        let run_dir = single_run_dir(backup_root.path());
+        assert!(
+            run_dir
+                .join("files")
+                .join(dir.strip_prefix("/").unwrap())
+                .join("sub")
+                .join("b.txt")
+                .exists()
+        );
+
+        // --backup なしでは指定できない
+        let (exit_code, _, _) = run_safe_rm(&["--verify-backup", "a.txt"], &dir);
+        assert_eq!(exit_code, 2);
+    }
+
+    #[test]
+    fn test_backup_manifest_maps_each_file() {
+        let temp_dir = TempDir::new().unwrap();
+        let dir = temp_dir.path().canonicalize().unwrap();
+        let backup_root = TempDir::new().unwrap();
+        fs::write(dir.join("a.txt"), "a").unwrap();
+        fs::create_dir_all(dir.join("sub")).unwrap();
+        fs::write(dir.join("sub").join("b.txt"), "b").unwrap();
+
+        let (exit_code, stdout, stderr) = run_safe_rm(
+            &[
+                "-r",
+                "--backup",
+                backup_root.path().to_str().unwrap(),
+                "a.txt",
+                "sub",
+            ],
+            &dir,
+        );
+        assert_eq!(exit_code, 0, "stderr: {stderr}");
+        assert!(stdout.contains("restore with"));
+        assert!(!dir.join("a.txt").exists());
+        assert!(!dir.join("sub").exists());
+
+        let run_dir = single_run_dir(backup_root.path());
+        let manifest: serde_json::Value =
+            serde_json::from_str(&fs::read_to_string(run_dir.join("manifest.json")).unwrap())
+                .unwrap();
+        let entries = manifest["entries"].as_array().unwrap();
+        assert_eq!(entries.len(), 2);
+
+        for (entry, original) in entries.iter().zip([dir.join("a.txt"), dir.join("sub")]) {
+            assert_eq!(entry["original"], original.to_str().unwrap());
+            let backup = std::path::PathBuf::from(entry["backup"].as_str().unwrap());
+            assert!(backup.starts_with(&run_dir));
+            assert!(fs::symlink_metadata(&backup).is_ok(), "missing {backup:?}");
+        }
+        assert_eq!(
+            fs::read_to_string(
+                std::path::PathBuf::from(entries[1]["backup"].as_str().unwrap()).join("b.txt")
+            )
+            .unwrap(),
+            "b"
+        );
+    }
+
+    #[test]
+    fn test_backup_restore_script_restores_files() {
+        let temp_dir = TempDir::new().unwrap();
+        let dir = temp_dir.path().canonicalize().unwrap();
+        let backup_root = TempDir::new().unwrap();
+        fs::write(dir.join("a.txt"), "a").unwrap();
+
+        let (exit_code, _, _) = run_safe_rm(
+            &["--backup", backup_root.path().to_str().unwrap(), "a.txt"],
+            &dir,
+        );
+        assert_eq!(exit_code, 0);
+        assert!(!dir.join("a.txt").exists());
+
+        let run_dir = single_run_dir(backup_root.path());
+        let status = Command::new("sh")
+            .arg(run_dir.join("restore.sh"))
+            .status()
+            .unwrap();
+        assert!(status.success());
+        assert_eq!(fs::read_to_string(dir.join("a.txt")).unwrap(), "a");
+    }
+
+    #[test]
+    fn test_backup_dry_run_creates_nothing() {
+        let temp_dir = TempDir::new().unwrap();
+        let dir = temp_dir.path().canonicalize().unwrap();
+        let backup_root = TempDir::new().unwrap();
+        fs::write(dir.join("a.txt"), "a").unwrap();
+
+        let (exit_code, _, _) = run_safe_rm(
+            &[
+                "-n",
+                "--backup",
+                backup_root.path().to_str().unwrap(),
+                "a.txt",
+            ],
+            &dir,
+        );
+        assert_eq!(exit_code, 0);
+        assert!(dir.join("a.txt").exists());
+        assert_eq!(fs::read_dir(backup_root.path()).unwrap().count(), 0);
+    }
+}
+
+// =============================================================================
+// status サブコマンドのテスト
+// =============================================================================
+
+mod status_subcommand_tests {
+    use super::*;
+
+    /// allow_project_deletion = false の設定ファイルを作成
+    fn create_strict_config() -> tempfile::NamedTempFile {
+        let config = tempfile::NamedTempFile::new().unwrap();
+        fs::write(config.path(), "allow_project_deletion = false\n").unwrap();
+        config
+    }
+
+    #[test]
+    fn test_status_prints_verdict_without_deleting() {
+        let temp_dir = create_test_repo();
+        let repo_path = temp_dir.path().canonicalize().unwrap();
+        let config = create_strict_config();
+        commit_file(&repo_path, "clean.txt", "clean");
+        fs::write(repo_path.join("new.txt"), "new").unwrap();
+
+        let (exit_code, stdout, _) = run_safe_rm_with_config(
+            &["status", "clean.txt", "new.txt"],
+            &repo_path,
+            Some(config.path()),
+        );
+        assert_eq!(exit_code, 0);
+        let lines: Vec<&str> = stdout.lines().collect();
+        assert_eq!(lines, vec!["Clean yes clean.txt", "Untracked no new.txt"]);
+        assert!(repo_path.join("clean.txt").exists());
+        assert!(repo_path.join("new.txt").exists());
+    }
+
+    #[test]
+    fn test_status_default_mode_reports_ignored_and_untracked_files() {
+        // デフォルトモードでは無視ファイルや未追跡ディレクトリ配下を事前取得しない
+        let temp_dir = create_test_repo();
+        let repo_path = temp_dir.path().canonicalize().unwrap();
+        commit_file(&repo_path, ".gitignore", "node_modules/\n");
+        fs::create_dir_all(repo_path.join("node_modules").join("pkg")).unwrap();
+        fs::write(repo_path.join("node_modules").join("pkg").join("a.js"), "x").unwrap();
+        fs::create_dir_all(repo_path.join("new_dir")).unwrap();
+        fs::write(repo_path.join("new_dir").join("b.txt"), "x").unwrap();
+
+        let (exit_code, stdout, _) = run_safe_rm(
+            &["status", "node_modules/pkg/a.js", "new_dir/b.txt"],
+            &repo_path,
+        );
+        assert_eq!(exit_code, 0);
+        let lines: Vec<&str> = stdout.lines().collect();
+        assert_eq!(
+            lines,
+            vec![
+                "Ignored yes node_modules/pkg/a.js",
+                "Untracked yes new_dir/b.txt"
+            ]
+        );
+
+        let (exit_code, _, _) = run_safe_rm(&["-r", "node_modules"], &repo_path);
+        assert_eq!(exit_code, 0);
+        assert!(!repo_path.join("node_modules").exists());
+    }
+
+    #[test]
+    fn test_status_json_format() {
+        let temp_dir = create_test_repo();
+        let repo_path = temp_dir.path().canonicalize().unwrap();
+        let config = create_strict_config();
+        commit_file(&repo_path, "clean.txt", "clean");
+
+        let (exit_code, stdout, _) = run_safe_rm_with_config(
+            &["status", "--format", "json", "clean.txt", "/etc/passwd"],
+            &repo_path,
+            Some(config.path()),
+        );
+        assert_eq!(exit_code, 0);
+        let reports: serde_json::Value = serde_json::from_str(&stdout).unwrap();
+        assert_eq!(reports[0]["path"], "clean.txt");
+        assert_eq!(reports[0]["status"], "Clean");
+        assert_eq!(reports[0]["deletable"], true);
+        assert_eq!(reports[1]["status"], serde_json::Value::Null);
+        assert_eq!(reports[1]["deletable"], false);
+        assert_eq!(reports[1]["reason"], "outside project");
+    }
+
+    #[test]
+    fn test_config_warning_is_json_under_json_format() {
+        let temp_dir = create_test_repo();
+        let repo_path = temp_dir.path().canonicalize().unwrap();
+        let config = tempfile::NamedTempFile::new().unwrap();
+        fs::write(config.path(), "allow_project_deletion = \"yes\"\n").unwrap();
+        commit_file(&repo_path, "clean.txt", "clean");
+
+        let (exit_code, stdout, stderr) = run_safe_rm_with_config(
+            &["status", "--format", "json", "clean.txt"],
+            &repo_path,
+            Some(config.path()),
+        );
+        assert_eq!(exit_code, 0);
+        assert!(serde_json::from_str::<serde_json::Value>(&stdout).is_ok());
+
+        let warning: serde_json::Value = serde_json::from_str(stderr.trim()).unwrap();
+        assert_eq!(warning["type"], "warning");
+        assert_eq!(warning["path"], config.path().to_str().unwrap());
+        assert!(
+            warning["message"]
+                .as_str()
+                .unwrap()
+                .contains("config parse error")
+        );
+    }
+
+    #[test]
+    fn test_errors_include_stable_code_in_json_format() {
+        let temp_dir = create_test_repo();
+        let repo_path = temp_dir.path().canonicalize().unwrap();
+
+        let (exit_code, _, stderr) = run_safe_rm(
+            &["--format", "json", "missing.txt", "/etc/passwd"],
+            &repo_path,
+        );
+        assert_eq!(exit_code, 2);
+        let errors: Vec<serde_json::Value> = stderr
+            .lines()
+            .map(|line| serde_json::from_str(line).unwrap())
+            .collect();
+        assert_eq!(errors[0]["type"], "error");
+        assert_eq!(errors[0]["code"], "not_found");
+        assert_eq!(errors[0]["path"], "missing.txt");
+        assert_eq!(errors[1]["code"], "outside_project");
+        assert_eq!(errors[1]["exit_code"], 2);
+        // 最後の行は実行全体の結果（パスなし）
+        let last = errors.last().unwrap();
+        assert_eq!(last["code"], "partial_failure");
+        assert_eq!(last["path"], serde_json::Value::Null);
+    }
+
+    #[test]
+    fn test_config_warning_is_plain_text_by_default() {
+        let temp_dir = create_test_repo();
+        let repo_path = temp_dir.path().canonicalize().unwrap();
+        let config = tempfile::NamedTempFile::new().unwrap();
+        fs::write(config.path(), "allow_project_deletion = \"yes\"\n").unwrap();
+        commit_file(&repo_path, "clean.txt", "clean");
+
+        let (_, _, stderr) =
+            run_safe_rm_with_config(&["status", "clean.txt"], &repo_path, Some(config.path()));
+        assert!(stderr.starts_with("safe-rm: warning: config parse error"));
+    }
+}
+
+// =============================================================================
+// always_deletable_extensions のテスト
+// =============================================================================
+
+mod always_deletable_extensions_tests {
+    use super::*;
+
+    #[test]
+    fn test_log_deletable_in_strict_mode_while_rs_blocked() {
+        let temp_dir = create_test_repo();
+        let repo_path = temp_dir.path().canonicalize().unwrap();
+        let config = tempfile::NamedTempFile::new().unwrap();
+        fs::write(
+            config.path(),
+            "allow_project_deletion = false\nalways_deletable_extensions = [\"log\", \"tmp\"]\n",
+        )
+        .unwrap();
+        commit_file(&repo_path, "README.md", "readme");
+        fs::write(repo_path.join("debug.log"), "untracked log").unwrap();
+        fs::write(repo_path.join("new.rs"), "fn main() {}").unwrap();
+
+        let (exit_code, _, _) =
+            run_safe_rm_with_config(&["debug.log"], &repo_path, Some(config.path()));
+        assert_eq!(exit_code, 0);
+        assert!(!repo_path.join("debug.log").exists());
+
+        let (exit_code, _, _) =
+            run_safe_rm_with_config(&["new.rs"], &repo_path, Some(config.path()));
+        assert_eq!(exit_code, 2);
+        assert!(repo_path.join("new.rs").exists());
+    }
+
+    #[test]
+    fn test_safe_extensions_alias_in_strict_mode() {
+        let temp_dir = create_test_repo();
+        let repo_path = temp_dir.path().canonicalize().unwrap();
+        let config = tempfile::NamedTempFile::new().unwrap();
+        fs::write(
+            config.path(),
+            "allow_project_deletion = false\nsafe_extensions = [\".log\"]\n",
+        )
+        .unwrap();
+        commit_file(&repo_path, "README.md", "readme");
+        fs::write(repo_path.join("build.log"), "untracked log").unwrap();
+        fs::write(repo_path.join("lib.rs"), "pub fn f() {}").unwrap();
+
+        let (exit_code, _, _) =
+            run_safe_rm_with_config(&["build.log", "lib.rs"], &repo_path, Some(config.path()));
+        assert_eq!(exit_code, 2);
+        assert!(!repo_path.join("build.log").exists());
+        assert!(repo_path.join("lib.rs").exists());
+    }
+
+    #[test]
+    fn test_always_deletable_extension_still_requires_containment() {
+        let temp_dir = create_test_repo();
+        let repo_path = temp_dir.path().canonicalize().unwrap();
+        let outside = TempDir::new().unwrap();
+        let outside_log = outside.path().join("other.log");
+        fs::write(&outside_log, "log").unwrap();
+        let config = tempfile::NamedTempFile::new().unwrap();
+        fs::write(
+            config.path(),
+            "allow_project_deletion = false\nalways_deletable_extensions = [\"log\"]\n",
+        )
+        .unwrap();
+
+        let (exit_code, _, _) = run_safe_rm_with_config(
+            &[outside_log.to_str().unwrap()],
+            &repo_path,
+            Some(config.path()),
+        );
+        assert_eq!(exit_code, 2);
+        assert!(outside_log.exists());
+    }
+}
+
+// =============================================================================
+// protected_patterns のテスト
+// =============================================================================
+
+mod protected_patterns_tests {
+    use super::*;
+
+    fn create_config(content: &str) -> tempfile::NamedTempFile {
+        let config = tempfile::NamedTempFile::new().unwrap();
+        fs::write(config.path(), content).unwrap();
+        config
+    }
+
+    #[test]
+    fn test_protected_file_blocked_in_permissive_mode() {
+        let temp_dir = create_test_repo();
+        let repo_path = temp_dir.path().canonicalize().unwrap();
+        let config = create_config("protected_patterns = [\"*.pem\", \".env*\"]\n");
+        fs::write(repo_path.join(".env.local"), "SECRET=1").unwrap();
+        fs::write(repo_path.join("notes.txt"), "notes").unwrap();
+
+        let (exit_code, _, stderr) = run_safe_rm_with_config(
+            &[".env.local", "notes.txt"],
+            &repo_path,
+            Some(config.path()),
+        );
+        assert_eq!(exit_code, 2);
+        assert!(stderr.contains(".env*"));
+        assert!(repo_path.join(".env.local").exists());
+        assert!(!repo_path.join("notes.txt").exists());
+    }
+
+    #[test]
+    fn test_directory_containing_protected_file_blocked() {
+        let temp_dir = create_test_repo();
+        let repo_path = temp_dir.path().canonicalize().unwrap();
+        let config = create_config("protected_patterns = [\"**/secrets/**\"]\n");
+        fs::create_dir_all(repo_path.join("app").join("secrets")).unwrap();
+        fs::write(repo_path.join("app").join("secrets").join("key"), "k").unwrap();
+
+        let (exit_code, _, _) =
+            run_safe_rm_with_config(&["-r", "app"], &repo_path, Some(config.path()));
+        assert_eq!(exit_code, 2);
+        assert!(repo_path.join("app").join("secrets").join("key").exists());
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn test_protected_file_blocked_through_symlinked_parent() {
+        let temp_dir = create_test_repo();
+        let repo_path = temp_dir.path().canonicalize().unwrap();
+        let config = create_config("protected_patterns = [\"secrets/**\"]\n");
+        fs::create_dir_all(repo_path.join("secrets")).unwrap();
+        fs::write(repo_path.join("secrets").join("key"), "k").unwrap();
+        std::os::unix::fs::symlink("secrets", repo_path.join("alias")).unwrap();
+
+        let (exit_code, _, stderr) =
+            run_safe_rm_with_config(&["alias/key"], &repo_path, Some(config.path()));
+        assert_eq!(exit_code, 2, "stderr: {}", stderr);
+        assert!(stderr.contains("secrets/**"), "stderr: {}", stderr);
+        assert!(repo_path.join("secrets").join("key").exists());
+
+        // 末尾の `/` はリンク先を指すため、リンク先として照合する
+        let (exit_code, _, stderr) =
+            run_safe_rm_with_config(&["-r", "alias/"], &repo_path, Some(config.path()));
+        assert_eq!(exit_code, 2, "stderr: {}", stderr);
+        assert!(stderr.contains("secrets/**"), "stderr: {}", stderr);
+        assert!(repo_path.join("secrets").join("key").exists());
+    }
+
+    #[test]
+    fn test_protected_pattern_overrides_allowed_paths() {
+        let temp_dir = TempDir::new().unwrap();
+        let cwd = temp_dir.path().canonicalize().unwrap();
+        let allowed = TempDir::new().unwrap();
+        let allowed_dir = allowed.path().canonicalize().unwrap();
+        fs::write(allowed_dir.join("server.pem"), "k").unwrap();
+        let config = create_config(&format!(
+            "protected_patterns = [\"*.pem\"]\n\n[[allowed_paths]]\npath = \"{}\"\nrecursive = true\n",
+            allowed_dir.display()
+        ));
+
+        let (exit_code, _, _) = run_safe_rm_with_config(
+            &[allowed_dir.join("server.pem").to_str().unwrap()],
+            &cwd,
+            Some(config.path()),
+        );
+        assert_eq!(exit_code, 2);
+        assert!(allowed_dir.join("server.pem").exists());
+    }
+}
+
+// =============================================================================
+// verify サブコマンドのテスト
+// =============================================================================
+
+mod verify_subcommand_tests {
+    use super::*;
+
+    #[test]
+    fn test_verify_passes_in_clean_environment() {
+        let temp_dir = create_test_repo();
+        let repo_path = temp_dir.path().canonicalize().unwrap();
+        let allowed = TempDir::new().unwrap();
+        let config = tempfile::NamedTempFile::new().unwrap();
+        fs::write(
+            config.path(),
+            format!(
+                "[[allowed_paths]]\npath = \"{}\"\nrecursive = true\n",
+                allowed.path().display()
+            ),
+        )
+        .unwrap();
+
+        let (exit_code, stdout, _) =
+            run_safe_rm_with_config(&["verify"], &repo_path, Some(config.path()));
+        assert_eq!(exit_code, 0, "stdout: {stdout}");
+        assert!(stdout.contains("ok   config:"));
+        assert!(stdout.contains(&format!("root {}", repo_path.display())));
+        assert!(stdout.contains("libgit2:"));
+        assert!(!stdout.contains("FAIL"));
+    }
+
+    #[test]
+    fn test_verify_fails_on_bad_config() {
+        let temp_dir = TempDir::new().unwrap();
+        let config = tempfile::NamedTempFile::new().unwrap();
+        fs::write(config.path(), "allowed_paths = 42\n").unwrap();
+
+        let (exit_code, stdout, _) =
+            run_safe_rm_with_config(&["verify"], temp_dir.path(), Some(config.path()));
+        assert_eq!(exit_code, 1);
+        assert!(stdout.contains("FAIL config:"));
+    }
+}
+
+// =============================================================================
+// --root のテスト
+// =============================================================================
+
+mod root_override_tests {
+    use super::*;
+
+    #[test]
+    fn test_root_narrows_boundary_to_subtree() {
+        let temp_dir = create_test_repo();
+        let repo_path = temp_dir.path().canonicalize().unwrap();
+        fs::create_dir_all(repo_path.join("pkg").join("sub")).unwrap();
+        fs::write(repo_path.join("pkg").join("sub").join("a.txt"), "a").unwrap();
+        fs::write(repo_path.join("top.txt"), "top").unwrap();
+        let cwd = repo_path.join("pkg").join("sub");
+
+        let (exit_code, _, _) = run_safe_rm(&["--root", "..", "../../top.txt"], &cwd);
+        assert_eq!(exit_code, 2, "paths above --root must be blocked");
+        assert!(repo_path.join("top.txt").exists());
+
+        let (exit_code, _, _) = run_safe_rm(&["--root", "..", "a.txt"], &cwd);
+        assert_eq!(exit_code, 0);
+        assert!(!cwd.join("a.txt").exists());
+    }
+
+    #[test]
+    fn test_root_outside_git_repo_sets_hard_boundary() {
+        let temp_dir = TempDir::new().unwrap();
+        let base = temp_dir.path().canonicalize().unwrap();
+        fs::create_dir_all(base.join("work")).unwrap();
+        fs::write(base.join("work").join("a.txt"), "a").unwrap();
+        fs::write(base.join("sibling.txt"), "s").unwrap();
+
+        let (exit_code, _, _) = run_safe_rm(&["--root", ".", "../sibling.txt"], &base.join("work"));
+        assert_eq!(exit_code, 2);
+        assert!(base.join("sibling.txt").exists());
+    }
+
+    #[test]
+    fn test_root_must_be_ancestor_of_cwd() {
+        let temp_dir = TempDir::new().unwrap();
+        let base = temp_dir.path().canonicalize().unwrap();
+        fs::create_dir_all(base.join("a")).unwrap();
+        fs::create_dir_all(base.join("b")).unwrap();
+        fs::write(base.join("b").join("f.txt"), "f").unwrap();
+
+        let (exit_code, _, stderr) = run_safe_rm(
+            &["--root", base.join("b").to_str().unwrap(), "../b/f.txt"],
+            &base.join("a"),
+        );
+        assert_eq!(exit_code, 2);
+        assert!(stderr.contains("ancestors"));
+        assert!(base.join("b").join("f.txt").exists());
+    }
+}
+
+mod deletion_scope_tests {
+    use super::*;
+
+    fn create_config(content: &str) -> tempfile::NamedTempFile {
+        let config = tempfile::NamedTempFile::new().unwrap();
+        fs::write(config.path(), content).unwrap();
+        config
+    }
+
+    #[test]
+    fn test_deletion_allowed_in_scope_and_blocked_outside() {
+        let temp_dir = create_test_repo();
+        let repo_path = temp_dir.path().canonicalize().unwrap();
+        let config = create_config("deletion_scope = \"tmp\"\n");
+        fs::create_dir_all(repo_path.join("tmp")).unwrap();
+        fs::create_dir_all(repo_path.join("src")).unwrap();
+        fs::write(repo_path.join("tmp").join("scratch.txt"), "x").unwrap();
+        fs::write(repo_path.join("src").join("lib.rs"), "x").unwrap();
+
+        let (exit_code, _, _) =
+            run_safe_rm_with_config(&["tmp/scratch.txt"], &repo_path, Some(config.path()));
+        assert_eq!(exit_code, 0);
+        assert!(!repo_path.join("tmp").join("scratch.txt").exists());
+
+        let (exit_code, _, stderr) =
+            run_safe_rm_with_config(&["src/lib.rs"], &repo_path, Some(config.path()));
+        assert_eq!(exit_code, 2);
+        assert!(stderr.contains("削除スコープ外"));
+        assert!(repo_path.join("src").join("lib.rs").exists());
+    }
+
+    #[test]
+    fn test_multiple_scopes() {
+        let temp_dir = create_test_repo();
+        let repo_path = temp_dir.path().canonicalize().unwrap();
+        let config = create_config("deletion_scope = [\"tmp\", \"build\"]\n");
+        fs::create_dir_all(repo_path.join("build").join("out")).unwrap();
+        fs::write(repo_path.join("build").join("out").join("a.o"), "x").unwrap();
+        fs::write(repo_path.join("README.txt"), "x").unwrap();
+
+        let (exit_code, _, _) = run_safe_rm_with_config(
+            &["-r", "build/out", "README.txt"],
+            &repo_path,
+            Some(config.path()),
+        );
+        assert_eq!(exit_code, 2);
+        assert!(!repo_path.join("build").join("out").exists());
+        assert!(repo_path.join("README.txt").exists());
+    }
+}
+
+// =============================================================================
+// --check-open のテスト（/proc を使用するため Linux のみ）
+// =============================================================================
+
+#[cfg(target_os = "linux")]
+mod check_open_tests {
+    use super::*;
+    use std::process::{Child, Stdio};
+
+    /// `path` を標準入力として開いたまま待機する子プロセス
+    fn hold_open(path: &std::path::Path) -> Child {
+        Command::new("sleep")
+            .arg("30")
+            .stdin(Stdio::from(fs::File::open(path).unwrap()))
+            .spawn()
+            .unwrap()
+    }
+
+    #[test]
+    fn test_check_open_block_refuses_held_file() {
+        let temp_dir = create_test_repo();
+        let repo_path = temp_dir.path().canonicalize().unwrap();
+        fs::write(repo_path.join("app.log"), "log").unwrap();
+        let mut child = hold_open(&repo_path.join("app.log"));
+
+        let (exit_code, _, stderr) = run_safe_rm(&["--check-open=block", "app.log"], &repo_path);
+        child.kill().unwrap();
+        child.wait().unwrap();
+
+        assert_eq!(exit_code, 2, "stderr: {}", stderr);
+        assert!(stderr.contains(&format!("PID: {}", child.id())));
+        assert!(repo_path.join("app.log").exists());
+    }
+
+    #[test]
+    fn test_check_open_warn_deletes_held_file() {
+        let temp_dir = create_test_repo();
+        let repo_path = temp_dir.path().canonicalize().unwrap();
+        fs::write(repo_path.join("app.log"), "log").unwrap();
+        let mut child = hold_open(&repo_path.join("app.log"));
+
+        let (exit_code, _, stderr) = run_safe_rm(&["--check-open", "app.log"], &repo_path);
+        child.kill().unwrap();
+        child.wait().unwrap();
+
+        assert_eq!(exit_code, 0, "stderr: {}", stderr);
+        assert!(stderr.contains("is open by process"));
+        assert!(!repo_path.join("app.log").exists());
+    }
+
+    #[test]
+    fn test_check_open_warn_is_json_under_json_format() {
+        let temp_dir = create_test_repo();
+        let repo_path = temp_dir.path().canonicalize().unwrap();
+        fs::write(repo_path.join("app.log"), "log").unwrap();
+        let mut child = hold_open(&repo_path.join("app.log"));
+
+        let (exit_code, _, stderr) =
+            run_safe_rm(&["--format", "json", "--check-open", "app.log"], &repo_path);
+        child.kill().unwrap();
+        child.wait().unwrap();
+
+        assert_eq!(exit_code, 0, "stderr: {}", stderr);
+        let warning: serde_json::Value = serde_json::from_str(stderr.trim()).unwrap();
+        assert_eq!(warning["type"], "warning");
+        assert_eq!(warning["path"], repo_path.join("app.log").to_str().unwrap());
+        assert!(
+            warning["message"]
+                .as_str()
+                .unwrap()
+                .contains(&format!("is open by process {}", child.id()))
+        );
+    }
+
+    #[test]
+    fn test_check_open_ignores_files_not_held() {
+        let temp_dir = create_test_repo();
+        let repo_path = temp_dir.path().canonicalize().unwrap();
+        fs::write(repo_path.join("free.txt"), "x").unwrap();
+
+        let (exit_code, _, stderr) = run_safe_rm(&["--check-open=block", "free.txt"], &repo_path);
+        assert_eq!(exit_code, 0, "stderr: {}", stderr);
+        assert!(!repo_path.join("free.txt").exists());
+    }
+}
+
+// =============================================================================
+// deletable（ステータスごとの削除可否）のテスト
+// =============================================================================
+
+mod deletable_policy_tests {
+    use super::*;
+
+    /// 外側のリポジトリから見て Git 管理外となるネストしたリポジトリ内のファイルを作成
+    fn create_nested_repo_file(repo_path: &std::path::Path) {
+        commit_file(repo_path, "init.txt", "init");
+        let nested = repo_path.join("vendor");
+        fs::create_dir(&nested).unwrap();
+        Command::new("git")
+            .args(["init"])
+            .current_dir(&nested)
+            .output()
+            .unwrap();
+        fs::write(nested.join("lib.txt"), "x").unwrap();
+    }
+
+    #[test]
+    fn test_not_in_repo_deletable_by_default() {
+        let temp_dir = create_test_repo();
+        let repo_path = temp_dir.path().canonicalize().unwrap();
+        create_nested_repo_file(&repo_path);
+        let config = tempfile::NamedTempFile::new().unwrap();
+        fs::write(config.path(), "allow_project_deletion = false\n").unwrap();
+
+        let (exit_code, _, stderr) =
+            run_safe_rm_with_config(&["vendor/lib.txt"], &repo_path, Some(config.path()));
+        assert_eq!(exit_code, 0, "stderr: {}", stderr);
+        assert!(!repo_path.join("vendor").join("lib.txt").exists());
+    }
+
+    #[test]
+    fn test_not_in_repo_blocked_when_disabled() {
+        let temp_dir = create_test_repo();
+        let repo_path = temp_dir.path().canonicalize().unwrap();
+        create_nested_repo_file(&repo_path);
+        let config = tempfile::NamedTempFile::new().unwrap();
+        fs::write(
+            config.path(),
+            "allow_project_deletion = false\n\n[deletable]\nnot_in_repo = false\n",
+        )
+        .unwrap();
+
+        let (exit_code, _, stderr) =
+            run_safe_rm_with_config(&["vendor/lib.txt"], &repo_path, Some(config.path()));
+        assert_eq!(exit_code, 2, "stderr: {}", stderr);
+        assert!(stderr.contains("NotInRepo"), "stderr: {}", stderr);
+        assert!(repo_path.join("vendor").join("lib.txt").exists());
+    }
+
+    #[test]
+    fn test_not_in_repo_blocked_outside_any_repository() {
+        let temp_dir = TempDir::new().unwrap();
+        let cwd = temp_dir.path().canonicalize().unwrap();
+        fs::write(cwd.join("plain.txt"), "x").unwrap();
+        let config = tempfile::NamedTempFile::new().unwrap();
+        fs::write(
+            config.path(),
+            "allow_project_deletion = false\n\n[deletable]\nnot_in_repo = false\n",
+        )
+        .unwrap();
+
+        let (exit_code, _, _) = run_safe_rm_with_config(&["plain.txt"], &cwd, Some(config.path()));
+        assert_eq!(exit_code, 2);
+        assert!(cwd.join("plain.txt").exists());
+    }
+}
+
+// =============================================================================
+// config サブコマンドのテスト
+// =============================================================================
+
+mod config_subcommand_tests {
+    use super::*;
+
+    #[test]
+    fn test_config_prints_effective_settings() {
+        let temp_dir = TempDir::new().unwrap();
+        let allowed = TempDir::new().unwrap();
+        let allowed_path = allowed.path().canonicalize().unwrap();
+        let config = tempfile::NamedTempFile::new().unwrap();
+        fs::write(
+            config.path(),
+            format!(
+                "allow_project_deletion = false\n\n[[allowed_paths]]\npath = \"{}\"\n",
+                allowed_path.display()
+            ),
+        )
+        .unwrap();
+
+        let (exit_code, stdout, _) =
+            run_safe_rm_with_config(&["config"], temp_dir.path(), Some(config.path()));
+        assert_eq!(exit_code, 0, "stdout: {stdout}");
+        assert!(stdout.contains(&format!("config: {}", config.path().display())));
+        assert!(stdout.contains("parsed: yes"));
+        assert!(stdout.contains("allow_project_deletion: false"));
+        assert!(stdout.contains(&format!(
+            "-> {} (direct children, exists)",
+            allowed_path.display()
+        )));
+    }
+
+    #[test]
+    fn test_config_discovered_under_xdg_config_home() {
+        let temp_dir = TempDir::new().unwrap();
+        let xdg = TempDir::new().unwrap();
+        let config_path = xdg.path().join("safe-rm").join("config.toml");
+        fs::create_dir_all(config_path.parent().unwrap()).unwrap();
+        fs::write(&config_path, "allow_project_deletion = false\n").unwrap();
+
+        let output = Command::new(get_binary_path())
+            .arg("config")
+            .current_dir(temp_dir.path())
+            .env_remove("SAFE_RM_CONFIG")
+            .env("XDG_CONFIG_HOME", xdg.path())
+            .output()
+            .unwrap();
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        assert_eq!(output.status.code(), Some(0), "stdout: {stdout}");
+        assert!(stdout.contains(&format!("config: {}", config_path.display())));
+        assert!(stdout.contains("allow_project_deletion: false"));
+    }
+
+    #[test]
+    fn test_config_list_allowed_prints_only_allowlist() {
+        let temp_dir = TempDir::new().unwrap();
+        let allowed = TempDir::new().unwrap();
+        let allowed_path = allowed.path().canonicalize().unwrap();
+        let config = tempfile::NamedTempFile::new().unwrap();
+        fs::write(
+            config.path(),
+            format!(
+                "allow_project_deletion = false\n\n[[allowed_paths]]\npath = \"{}\"\nrecursive = true\n\n[[allowed_paths]]\npath = \"{}\"\n",
+                allowed_path.display(),
+                allowed_path.join("gone").display()
+            ),
+        )
+        .unwrap();
+
+        let (exit_code, stdout, _) = run_safe_rm_with_config(
+            &["config", "--list-allowed"],
+            temp_dir.path(),
+            Some(config.path()),
+        );
+        assert_eq!(exit_code, 0, "stdout: {stdout}");
+        let lines: Vec<&str> = stdout.lines().collect();
+        assert_eq!(lines.len(), 2, "stdout: {stdout}");
+        assert!(lines[0].ends_with(&format!(
+            "-> {} (recursive, exists)",
+            allowed_path.display()
+        )));
+        assert!(lines[1].ends_with("(direct children, missing)"));
+        assert!(!stdout.contains("allow_project_deletion"));
+    }
+
+    #[test]
+    fn test_config_list_allowed_fails_when_file_does_not_parse() {
+        let temp_dir = TempDir::new().unwrap();
+        let config = tempfile::NamedTempFile::new().unwrap();
+        fs::write(config.path(), "allowed_paths = 1\n").unwrap();
+
+        let (exit_code, stdout, stderr) = run_safe_rm_with_config(
+            &["config", "--list-allowed"],
+            temp_dir.path(),
+            Some(config.path()),
+        );
+        assert_eq!(exit_code, 1);
+        assert!(stdout.is_empty());
+        assert!(stderr.contains("config parse error"), "stderr: {stderr}");
+    }
+
+    #[test]
+    fn test_config_fails_when_file_does_not_parse() {
+        let temp_dir = TempDir::new().unwrap();
+        let config = tempfile::NamedTempFile::new().unwrap();
+        fs::write(config.path(), "allowed_paths = 1\n").unwrap();
+
+        let (exit_code, stdout, _) =
+            run_safe_rm_with_config(&["config"], temp_dir.path(), Some(config.path()));
+        assert_eq!(exit_code, 1);
+        assert!(stdout.contains("parsed: no (config parse error"));
+    }
+}
+
+// =============================================================================
+// skip-worktree / assume-unchanged（sparse-checkout）のテスト
+// =============================================================================
+
+mod skip_worktree_tests {
+    use super::*;
+
+    fn strict_config() -> tempfile::NamedTempFile {
+        let config = tempfile::NamedTempFile::new().unwrap();
+        fs::write(config.path(), "allow_project_deletion = false\n").unwrap();
+        config
+    }
+
+    fn skip_worktree(repo_path: &std::path::Path, file: &str) {
+        Command::new("git")
+            .args(["update-index", "--skip-worktree", file])
+            .current_dir(repo_path)
+            .output()
+            .unwrap();
+    }
+
+    #[test]
+    fn test_unmodified_skip_worktree_file_is_deletable() {
+        let temp_dir = create_test_repo();
+        let repo_path = temp_dir.path().canonicalize().unwrap();
+        commit_file(&repo_path, "sparse.txt", "content");
+        skip_worktree(&repo_path, "sparse.txt");
+        let config = strict_config();
+
+        let (exit_code, _, stderr) =
+            run_safe_rm_with_config(&["sparse.txt"], &repo_path, Some(config.path()));
+        assert_eq!(exit_code, 0, "stderr: {}", stderr);
+        assert!(!repo_path.join("sparse.txt").exists());
+    }
+
+    #[test]
+    fn test_skip_worktree_file_with_local_edits_is_blocked() {
+        let temp_dir = create_test_repo();
+        let repo_path = temp_dir.path().canonicalize().unwrap();
+        commit_file(&repo_path, "sparse.txt", "content");
+        skip_worktree(&repo_path, "sparse.txt");
+        fs::write(repo_path.join("sparse.txt"), "local edits").unwrap();
+        let config = strict_config();
+
+        let (exit_code, _, stderr) =
+            run_safe_rm_with_config(&["sparse.txt"], &repo_path, Some(config.path()));
+        assert_eq!(exit_code, 2);
+        assert!(stderr.contains("Modified"), "stderr: {}", stderr);
+        assert!(repo_path.join("sparse.txt").exists());
+    }
+}
+
+// =============================================================================
+// max_paths / --max-paths のテスト
+// =============================================================================
+
+mod max_paths_tests {
+    use super::*;
+
+    fn create_files(repo_path: &std::path::Path, names: &[&str]) {
+        for name in names {
+            fs::write(repo_path.join(name), "x").unwrap();
+        }
+    }
+
+    #[test]
+    fn test_max_paths_flag_aborts_before_deleting() {
+        let temp_dir = create_test_repo();
+        let repo_path = temp_dir.path().canonicalize().unwrap();
+        create_files(&repo_path, &["a.txt", "b.txt", "c.txt"]);
+
+        let (exit_code, stdout, stderr) =
+            run_safe_rm(&["--max-paths", "2", "a.txt", "b.txt", "c.txt"], &repo_path);
+        assert_eq!(exit_code, 2);
+        assert!(stderr.contains("Limit: 2"), "stderr: {}", stderr);
+        assert!(!stdout.contains("removed:"));
+        for name in ["a.txt", "b.txt", "c.txt"] {
+            assert!(repo_path.join(name).exists(), "{} should remain", name);
+        }
+
+        let (exit_code, _, _) =
+            run_safe_rm(&["--max-paths", "3", "a.txt", "b.txt", "c.txt"], &repo_path);
+        assert_eq!(exit_code, 0);
+        assert!(!repo_path.join("a.txt").exists());
+    }
+
+    #[test]
+    fn test_max_paths_config_counts_files_from_entries() {
+        let temp_dir = create_test_repo();
+        let repo_path = temp_dir.path().canonicalize().unwrap();
+        create_files(&repo_path, &["a.txt", "b.txt"]);
+        let list = repo_path.join("list.txt");
+        fs::write(&list, "a.txt\nb.txt\n").unwrap();
+        let config = tempfile::NamedTempFile::new().unwrap();
+        fs::write(config.path(), "max_paths = 1\n").unwrap();
+
+        let (exit_code, _, stderr) = run_safe_rm_with_config(
+            &["--files-from", list.to_str().unwrap()],
+            &repo_path,
+            Some(config.path()),
+        );
+        assert_eq!(exit_code, 2);
+        assert!(stderr.contains("Count: 2"), "stderr: {}", stderr);
+        assert!(repo_path.join("a.txt").exists());
+        assert!(repo_path.join("b.txt").exists());
+
+        // --max-paths は設定より優先
+        let (exit_code, _, stderr) = run_safe_rm_with_config(
+            &["--max-paths", "2", "--files-from", list.to_str().unwrap()],
+            &repo_path,
+            Some(config.path()),
+        );
+        assert_eq!(exit_code, 0, "stderr: {}", stderr);
+        assert!(!repo_path.join("a.txt").exists());
+    }
+}
+
+mod git_env_tests {
+    use super::*;
+
+    /// `.git` を別名のディレクトリへ移したリポジトリ（GIT_DIR / GIT_WORK_TREE なしでは検出できない）
+    fn create_detached_repo() -> (TempDir, std::path::PathBuf, std::path::PathBuf) {
+        let temp_dir = create_test_repo();
+        let work_tree = temp_dir.path().canonicalize().unwrap();
+        commit_file(&work_tree, "clean.txt", "clean");
+        commit_file(&work_tree, "modified.txt", "original");
+        fs::write(work_tree.join("modified.txt"), "modified").unwrap();
+
+        let git_dir = work_tree.join("store.git");
+        fs::rename(work_tree.join(".git"), &git_dir).unwrap();
+        (temp_dir, work_tree, git_dir)
+    }
+
+    fn run_with_git_env(
+        args: &[&str],
+        work_tree: &std::path::Path,
+        git_dir: &std::path::Path,
+        config: &std::path::Path,
+    ) -> (i32, String, String) {
+        let output = Command::new(get_binary_path())
+            .args(args)
+            .current_dir(work_tree)
+            .env("SAFE_RM_CONFIG", config)
+            .env("GIT_DIR", git_dir)
+            .env("GIT_WORK_TREE", work_tree)
+            .output()
+            .unwrap();
+        (
+            output.status.code().unwrap_or(-1),
+            String::from_utf8_lossy(&output.stdout).to_string(),
+            String::from_utf8_lossy(&output.stderr).to_string(),
+        )
+    }
+
+    fn strict_config() -> tempfile::NamedTempFile {
+        let config = tempfile::NamedTempFile::new().unwrap();
+        fs::write(config.path(), "allow_project_deletion = false\n").unwrap();
+        config
+    }
+
+    #[test]
+    fn test_git_dir_and_work_tree_env_are_honored() {
+        let (_temp_dir, work_tree, git_dir) = create_detached_repo();
+        let config = strict_config();
+
+        let (exit_code, _, stderr) =
+            run_with_git_env(&["modified.txt"], &work_tree, &git_dir, config.path());
+        assert_eq!(exit_code, 2, "stderr: {}", stderr);
+        assert!(stderr.contains("Modified"), "stderr: {}", stderr);
+        assert!(work_tree.join("modified.txt").exists());
+
+        let (exit_code, _, stderr) =
+            run_with_git_env(&["clean.txt"], &work_tree, &git_dir, config.path());
+        assert_eq!(exit_code, 0, "stderr: {}", stderr);
+        assert!(!work_tree.join("clean.txt").exists());
+    }
+
+    #[test]
+    fn test_invalid_git_dir_env_fails_closed_in_strict_mode() {
+        let temp_dir = TempDir::new().unwrap();
+        let work_tree = temp_dir.path().canonicalize().unwrap();
+        fs::write(work_tree.join("a.txt"), "a").unwrap();
+        let config = strict_config();
+
+        let (exit_code, _, _) = run_with_git_env(
+            &["a.txt"],
+            &work_tree,
+            &work_tree.join("missing.git"),
+            config.path(),
+        );
+        assert_eq!(exit_code, 2);
+        assert!(work_tree.join("a.txt").exists());
+    }
+}
+
+mod cwd_deletion_tests {
+    use super::*;
+
+    #[test]
+    fn test_dot_is_refused() {
+        let temp_dir = create_test_repo();
+        let repo_path = temp_dir.path().canonicalize().unwrap();
+        commit_file(&repo_path, "a.txt", "a");
+
+        let (exit_code, _, stderr) = run_safe_rm(&["-r", "."], &repo_path);
+        assert_eq!(exit_code, 2, "stderr: {}", stderr);
+        assert!(stderr.contains("Cwd:"), "stderr: {}", stderr);
+        assert!(repo_path.join("a.txt").exists());
+    }
+
+    #[test]
+    fn test_parent_of_cwd_is_refused() {
+        let temp_dir = create_test_repo();
+        let repo_path = temp_dir.path().canonicalize().unwrap();
+        commit_file(&repo_path, "sub/inner/a.txt", "a");
+        let cwd = repo_path.join("sub").join("inner");
+
+        for target in ["..", "../../sub"] {
+            let (exit_code, _, stderr) = run_safe_rm(&["-r", target], &cwd);
+            assert_eq!(exit_code, 2, "{}: {}", target, stderr);
+        }
+        assert!(cwd.join("a.txt").exists());
+    }
+
+    #[test]
+    fn test_sibling_directory_is_not_refused() {
+        let temp_dir = create_test_repo();
+        let repo_path = temp_dir.path().canonicalize().unwrap();
+        commit_file(&repo_path, "sub/a.txt", "a");
+        commit_file(&repo_path, "other/b.txt", "b");
+
+        let (exit_code, _, stderr) = run_safe_rm(&["-r", "../other"], &repo_path.join("sub"));
+        assert_eq!(exit_code, 0, "stderr: {}", stderr);
+        assert!(!repo_path.join("other").exists());
+    }
+
+    #[test]
+    fn test_force_allows_deleting_cwd() {
+        let temp_dir = create_test_repo();
+        let repo_path = temp_dir.path().canonicalize().unwrap();
+        commit_file(&repo_path, "sub/a.txt", "a");
+
+        // `.` 自体は OS が rmdir を拒否するため絶対パスで指定
+        let sub = repo_path.join("sub");
+        let (exit_code, _, stderr) = run_safe_rm(&["-rf", sub.to_str().unwrap()], &sub);
+        assert_eq!(exit_code, 0, "stderr: {}", stderr);
+        assert!(!repo_path.join("sub").exists());
+    }
+}
+
+mod print0_tests {
+    use super::*;
+
+    #[test]
+    fn test_print0_outputs_nul_separated_removed_paths() {
+        let temp_dir = create_test_repo();
+        let repo_path = temp_dir.path().canonicalize().unwrap();
+        fs::write(repo_path.join("a.txt"), "a").unwrap();
+        fs::write(repo_path.join("b c.txt"), "b").unwrap();
+
+        let (exit_code, stdout, stderr) =
+            run_safe_rm(&["--print0", "a.txt", "/etc/passwd", "b c.txt"], &repo_path);
+        assert_eq!(exit_code, 2, "stderr: {}", stderr);
+        // 削除したパスのみを NUL 終端で出力（ブロックは stderr）
+        assert_eq!(stdout.as_bytes(), b"a.txt\0b c.txt\0");
+        assert!(stderr.contains("/etc/passwd"));
+        assert!(!repo_path.join("a.txt").exists());
+        assert!(!repo_path.join("b c.txt").exists());
+    }
+
+    #[test]
+    fn test_print0_dry_run_omits_total() {
+        let temp_dir = create_test_repo();
+        let repo_path = temp_dir.path().canonicalize().unwrap();
+        fs::write(repo_path.join("a.txt"), "a").unwrap();
+
+        let (exit_code, stdout, _) = run_safe_rm(&["-n", "--print0", "a.txt"], &repo_path);
+        assert_eq!(exit_code, 0);
+        assert_eq!(stdout.as_bytes(), b"a.txt\0");
+        assert!(repo_path.join("a.txt").exists());
+    }
+
+    #[test]
+    fn test_print0_conflicts_with_json_format() {
+        let temp_dir = create_test_repo();
+        let repo_path = temp_dir.path().canonicalize().unwrap();
+        fs::write(repo_path.join("a.txt"), "a").unwrap();
+
+        let (exit_code, stdout, stderr) =
+            run_safe_rm(&["--print0", "--format", "json", "a.txt"], &repo_path);
+        assert_eq!(exit_code, 2);
+        assert!(stdout.is_empty());
+        assert!(stderr.contains("--print0 cannot be used with --format json"));
+        assert!(repo_path.join("a.txt").exists());
+    }
+}
+
+mod emit_script_tests {
+    use super::*;
+
+    #[test]
+    fn test_emit_script_quotes_paths_and_runs() {
+        let temp_dir = create_test_repo();
+        let repo_path = temp_dir.path().canonicalize().unwrap();
+        fs::write(repo_path.join("it's a file.txt"), "a").unwrap();
+        fs::create_dir_all(repo_path.join("build dir").join("out")).unwrap();
+        fs::write(repo_path.join("build dir").join("out").join("$x.o"), "o").unwrap();
+
+        let (exit_code, stdout, stderr) = run_safe_rm(
+            &["-n", "-r", "--emit-script", "it's a file.txt", "build dir"],
+            &repo_path,
+        );
+        assert_eq!(exit_code, 0, "stderr: {}", stderr);
+        assert!(stdout.starts_with("#!/bin/sh\n"));
+        let file = repo_path.join("it's a file.txt");
+        assert!(stdout.contains(&format!(
+            "rm -- '{}'",
+            file.to_str().unwrap().replace('\'', "'\\''")
+        )));
+        assert!(stdout.contains(&format!(
+            "rm -r -- '{}'",
+            repo_path.join("build dir").to_str().unwrap()
+        )));
+        assert!(!stdout.contains("would remove:"));
+        assert!(stdout.contains("# total: would remove 2 files"));
+        // ドライランなので何も削除しない
+        assert!(file.exists());
+
+        // 出力したスクリプトをそのまま実行できる
+        let script = repo_path.join("cleanup.sh");
+        fs::write(&script, &stdout).unwrap();
+        let status = Command::new("sh").arg(&script).status().unwrap();
+        assert!(status.success());
+        assert!(!file.exists());
+        assert!(!repo_path.join("build dir").exists());
+    }
+
+    #[test]
+    fn test_emit_script_blocked_paths_are_stderr_comments() {
+        let temp_dir = create_test_repo();
+        let repo_path = temp_dir.path().canonicalize().unwrap();
+        fs::write(repo_path.join("a.txt"), "a").unwrap();
+
+        let (exit_code, stdout, stderr) =
+            run_safe_rm(&["-n", "--emit-script", "a.txt", "/etc/passwd"], &repo_path);
+        assert_eq!(exit_code, 2);
+        assert!(!stdout.contains("passwd"), "stdout: {}", stdout);
+        assert!(stderr.starts_with("# /etc/passwd: "), "stderr: {}", stderr);
+        assert!(
+            stderr
+                .lines()
+                .take_while(|line| !line.starts_with("safe-rm:"))
+                .all(|line| line.starts_with("# ")),
+            "stderr: {}",
+            stderr
+        );
+    }
+
+    #[test]
+    fn test_emit_script_requires_dry_run() {
+        let temp_dir = create_test_repo();
+        let repo_path = temp_dir.path().canonicalize().unwrap();
+        fs::write(repo_path.join("a.txt"), "a").unwrap();
+
+        let (exit_code, _, _) = run_safe_rm(&["--emit-script", "a.txt"], &repo_path);
+        assert_eq!(exit_code, 2);
+        assert!(repo_path.join("a.txt").exists());
+    }
+}
+
+/// 読み取り専用の親ディレクトリ内のファイル削除（root 実行など権限チェックが効かない環境ではスキップ）
+#[cfg(unix)]
+mod permission_denied_tests {
+    use super::*;
+    use std::os::unix::fs::PermissionsExt;
+
+    /// `locked/a.txt` を作り `locked` を読み取り専用にする。書き込めてしまう環境では None
+    fn create_locked_file(repo_path: &std::path::Path) -> Option<std::path::PathBuf> {
+        let locked = repo_path.join("locked");
+        fs::create_dir(&locked).unwrap();
+        fs::write(locked.join("a.txt"), "a").unwrap();
+        fs::set_permissions(&locked, fs::Permissions::from_mode(0o555)).unwrap();
+        if fs::write(locked.join("probe"), "p").is_ok() {
+            fs::set_permissions(&locked, fs::Permissions::from_mode(0o755)).unwrap();
+            eprintln!("skipping: read-only directories are writable in this environment");
+            return None;
+        }
+        Some(locked)
+    }
+
+    #[test]
+    fn test_permission_denied_has_distinct_code() {
+        let temp_dir = create_test_repo();
+        let repo_path = temp_dir.path().canonicalize().unwrap();
+        let Some(locked) = create_locked_file(&repo_path) else {
+            return;
+        };
+
+        let (exit_code, _, stderr) = run_safe_rm(&["--format", "json", "locked/a.txt"], &repo_path);
+        fs::set_permissions(&locked, fs::Permissions::from_mode(0o755)).unwrap();
+
+        assert_eq!(exit_code, 1, "stderr: {}", stderr);
+        let error: serde_json::Value =
+            serde_json::from_str(stderr.lines().next().unwrap()).unwrap();
+        assert_eq!(error["code"], "permission_denied");
+        assert_eq!(error["exit_code"], 1);
+        assert!(locked.join("a.txt").exists());
+    }
+
+    #[test]
+    fn test_permission_denied_message() {
+        let temp_dir = create_test_repo();
+        let repo_path = temp_dir.path().canonicalize().unwrap();
+        let Some(locked) = create_locked_file(&repo_path) else {
+            return;
+        };
+
+        let (exit_code, _, stderr) = run_safe_rm(&["locked/a.txt"], &repo_path);
+        fs::set_permissions(&locked, fs::Permissions::from_mode(0o755)).unwrap();
+
+        assert_eq!(exit_code, 1);
+        assert!(stderr.contains("Permission denied"), "stderr: {}", stderr);
+        assert!(!stderr.contains("No such file"));
+    }
+}
+
+mod depth_tests {
+    use super::*;
+
+    /// build/top.o（深さ1）, build/a/mid.o（深さ2）, build/a/b/deep.o（深さ3）
+    fn create_nested_build(repo_path: &std::path::Path) {
+        let build = repo_path.join("build");
+        fs::create_dir_all(build.join("a").join("b")).unwrap();
+        fs::write(build.join("top.o"), "t").unwrap();
+        fs::write(build.join("a").join("mid.o"), "m").unwrap();
+        fs::write(build.join("a").join("b").join("deep.o"), "d").unwrap();
+    }
+
+    #[test]
+    fn test_depth_keeps_deeper_entries_and_their_parents() {
+        let temp_dir = create_test_repo();
+        let repo_path = temp_dir.path().canonicalize().unwrap();
+        create_nested_build(&repo_path);
+
+        let (exit_code, stdout, stderr) = run_safe_rm(&["-r", "--depth", "2", "build"], &repo_path);
+        assert_eq!(exit_code, 0, "stderr: {}", stderr);
+        assert!(
+            stdout.contains("skipped: build/a/b/deep.o (beyond --depth)"),
+            "stdout: {}",
+            stdout
+        );
+        let build = repo_path.join("build");
+        assert!(!build.join("top.o").exists());
+        assert!(!build.join("a").join("mid.o").exists());
+        assert!(build.join("a").join("b").join("deep.o").exists());
+        // 残したエントリを含むディレクトリ自体は削除しない
+        assert!(build.join("a").is_dir());
+        assert!(build.is_dir());
+    }
+
+    #[test]
+    fn test_depth_covering_tree_removes_everything() {
+        let temp_dir = create_test_repo();
+        let repo_path = temp_dir.path().canonicalize().unwrap();
+        create_nested_build(&repo_path);
+
+        let (exit_code, stdout, stderr) = run_safe_rm(&["-r", "--depth", "3", "build"], &repo_path);
+        assert_eq!(exit_code, 0, "stderr: {}", stderr);
+        assert!(!stdout.contains("skipped"));
+        assert!(!repo_path.join("build").exists());
+    }
+
+    #[test]
+    fn test_depth_dry_run_keeps_everything() {
+        let temp_dir = create_test_repo();
+        let repo_path = temp_dir.path().canonicalize().unwrap();
+        create_nested_build(&repo_path);
+
+        let (exit_code, _, stderr) =
+            run_safe_rm(&["-n", "-r", "--depth", "1", "build"], &repo_path);
+        assert_eq!(exit_code, 0, "stderr: {}", stderr);
+        assert!(repo_path.join("build").join("top.o").exists());
+    }
+
+    #[test]
+    fn test_depth_requires_recursive() {
+        let temp_dir = create_test_repo();
+        let repo_path = temp_dir.path().canonicalize().unwrap();
+        fs::write(repo_path.join("a.txt"), "a").unwrap();
+
+        let (exit_code, _, _) = run_safe_rm(&["--depth", "1", "a.txt"], &repo_path);
+        assert_eq!(exit_code, 2);
+        assert!(repo_path.join("a.txt").exists());
+    }
+}
+
+mod one_file_system_tests {
+    use super::*;
+
+    #[test]
+    fn test_one_file_system_same_filesystem_removes_tree() {
+        let temp_dir = create_test_repo();
+        let repo_path = temp_dir.path().canonicalize().unwrap();
+        fs::create_dir_all(repo_path.join("build").join("out")).unwrap();
+        fs::write(repo_path.join("build").join("out").join("a.o"), "o").unwrap();
+
+        let (exit_code, stdout, stderr) =
+            run_safe_rm(&["-r", "--one-file-system", "build"], &repo_path);
+        assert_eq!(exit_code, 0, "stderr: {}", stderr);
+        assert!(!stdout.contains("skipped"));
+        assert!(!repo_path.join("build").exists());
+    }
+
+    /// マウントポイントを残して削除する（tmpfs をマウントできる root 環境でのみ検証）
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn test_one_file_system_skips_mount_point() {
+        let temp_dir = create_test_repo();
+        let repo_path = temp_dir.path().canonicalize().unwrap();
+        let mount_point = repo_path.join("build").join("mnt");
+        fs::create_dir_all(&mount_point).unwrap();
+        fs::write(repo_path.join("build").join("a.o"), "o").unwrap();
+
+        let mounted = Command::new("mount")
+            .args(["-t", "tmpfs", "none"])
+            .arg(&mount_point)
+            .output()
+            .is_ok_and(|output| output.status.success());
+        if !mounted {
+            eprintln!("skipping: cannot mount tmpfs in this environment");
+            return;
+        }
+        fs::write(mount_point.join("volume.txt"), "v").unwrap();
+
+        let (exit_code, stdout, stderr) =
+            run_safe_rm(&["-r", "--one-file-system", "build"], &repo_path);
+        let volume_survived = mount_point.join("volume.txt").exists();
+        Command::new("umount").arg(&mount_point).output().unwrap();
+
+        assert_eq!(exit_code, 0, "stderr: {}", stderr);
+        assert!(
+            stdout.contains("skipped: build/mnt (on another file system)"),
+            "stdout: {}",
+            stdout
+        );
+        assert!(volume_survived);
+        assert!(!repo_path.join("build").join("a.o").exists());
+        assert!(mount_point.exists());
+    }
+}
+
+mod contents_only_tests {
+    use super::*;
+
+    fn create_strict_config() -> tempfile::NamedTempFile {
+        let config = tempfile::NamedTempFile::new().unwrap();
+        fs::write(config.path(), "allow_project_deletion = false\n").unwrap();
+        config
+    }
+
+    #[test]
+    fn test_contents_only_keeps_directory() {
+        let temp_dir = create_test_repo();
+        let repo_path = temp_dir.path().canonicalize().unwrap();
+        let config = create_strict_config();
+        fs::write(repo_path.join(".gitignore"), "cache/tmp/\n").unwrap();
+        commit_file(&repo_path, "cache/clean.txt", "clean");
+        fs::create_dir_all(repo_path.join("cache").join("tmp")).unwrap();
+        fs::write(repo_path.join("cache").join("tmp").join("x.bin"), "x").unwrap();
+
+        let (exit_code, stdout, stderr) = run_safe_rm_with_config(
+            &["--contents-only", "-r", "cache"],
+            &repo_path,
+            Some(config.path()),
+        );
+        assert_eq!(exit_code, 0, "stderr: {}", stderr);
+        assert!(
+            stdout.contains("removed: cache/clean.txt"),
+            "stdout: {}",
+            stdout
+        );
+        assert!(stdout.contains("removed: cache/tmp"), "stdout: {}", stdout);
+        assert!(repo_path.join("cache").is_dir());
+        assert_eq!(fs::read_dir(repo_path.join("cache")).unwrap().count(), 0);
+
+        // 空になったディレクトリに再度実行しても何もしない
+        let (exit_code, _, _) = run_safe_rm_with_config(
+            &["--contents-only", "-r", "cache"],
+            &repo_path,
+            Some(config.path()),
+        );
+        assert_eq!(exit_code, 0);
+        assert!(repo_path.join("cache").is_dir());
+    }
+
+    #[test]
+    fn test_contents_only_dirty_child_blocks_in_strict_mode() {
+        let temp_dir = create_test_repo();
+        let repo_path = temp_dir.path().canonicalize().unwrap();
+        let config = create_strict_config();
+        commit_file(&repo_path, "cache/clean.txt", "clean");
+        commit_file(&repo_path, "cache/dirty.txt", "original");
+        fs::write(repo_path.join("cache").join("dirty.txt"), "modified").unwrap();
+
+        let (exit_code, _, stderr) = run_safe_rm_with_config(
+            &["--contents-only", "-r", "cache"],
+            &repo_path,
+            Some(config.path()),
+        );
+        assert_eq!(exit_code, 2);
+        assert!(stderr.contains("dirty.txt"), "stderr: {}", stderr);
+        assert!(!repo_path.join("cache").join("clean.txt").exists());
+        assert!(repo_path.join("cache").join("dirty.txt").exists());
+    }
+
+    #[test]
+    fn test_contents_only_rejects_non_directory_and_outside_project() {
+        let temp_dir = create_test_repo();
+        let repo_path = temp_dir.path().canonicalize().unwrap();
+        fs::write(repo_path.join("a.txt"), "a").unwrap();
+        fs::create_dir_all(repo_path.join("cache")).unwrap();
+        fs::write(repo_path.join("cache").join("b.txt"), "b").unwrap();
+
+        let (exit_code, _, stderr) =
+            run_safe_rm(&["--contents-only", "cache", "a.txt"], &repo_path);
+        assert_eq!(exit_code, 2);
+        assert!(stderr.contains("not a directory"), "stderr: {}", stderr);
+        assert!(repo_path.join("a.txt").exists());
+        assert!(repo_path.join("cache").join("b.txt").exists());
+
+        let outside = tempfile::tempdir().unwrap();
+        fs::write(outside.path().join("c.txt"), "c").unwrap();
+        let (exit_code, _, stderr) = run_safe_rm(
+            &["--contents-only", outside.path().to_str().unwrap()],
+            &repo_path,
+        );
+        assert_eq!(exit_code, 2);
+        assert!(!stderr.contains("c.txt"), "stderr: {}", stderr);
+        assert!(outside.path().join("c.txt").exists());
+    }
+}
+
+mod exit_codes_tests {
+    use super::*;
+
+    fn custom_codes_config() -> tempfile::NamedTempFile {
+        let config = tempfile::NamedTempFile::new().unwrap();
+        fs::write(
+            config.path(),
+            "allow_project_deletion = false\n\n[exit_codes]\nsecurity_block = 20\nfile_error = 10\npartial_failure = 30\n",
+        )
+        .unwrap();
+        config
+    }
+
+    #[test]
+    fn test_custom_exit_codes_per_category() {
+        let temp_dir = create_test_repo();
+        let repo_path = temp_dir.path().canonicalize().unwrap();
+        let config = custom_codes_config();
+        commit_file(&repo_path, "dirty.txt", "original");
+        fs::write(repo_path.join("dirty.txt"), "modified").unwrap();
+        commit_file(&repo_path, "clean.txt", "clean");
+
+        let (exit_code, _, _) =
+            run_safe_rm_with_config(&["dirty.txt"], &repo_path, Some(config.path()));
+        assert_eq!(exit_code, 20);
+
+        let (exit_code, _, _) =
+            run_safe_rm_with_config(&["missing.txt"], &repo_path, Some(config.path()));
+        assert_eq!(exit_code, 10);
+
+        let (exit_code, _, _) = run_safe_rm_with_config(
+            &["clean.txt", "missing.txt"],
+            &repo_path,
+            Some(config.path()),
+        );
+        assert_eq!(exit_code, 30);
+        assert!(!repo_path.join("clean.txt").exists());
+    }
+
+    #[test]
+    fn test_default_exit_codes_unchanged() {
+        let temp_dir = create_test_repo();
+        let repo_path = temp_dir.path().canonicalize().unwrap();
+
+        let (exit_code, _, _) = run_safe_rm(&["/etc/passwd"], &repo_path);
+        assert_eq!(exit_code, 2);
+        let (exit_code, _, _) = run_safe_rm(&["missing.txt"], &repo_path);
+        assert_eq!(exit_code, 1);
+    }
+}
+
+mod older_than_tests {
+    use super::*;
+    use std::time::{Duration, SystemTime};
+
+    /// ファイルの更新時刻を `days` 日前に設定
+    fn age_days(path: &std::path::Path, days: u64) {
+        let file = fs::File::options().write(true).open(path).unwrap();
+        file.set_modified(SystemTime::now() - Duration::from_secs(days * 86_400))
+            .unwrap();
+    }
+
+    #[test]
+    fn test_older_than_skips_newer_files_silently() {
+        let temp_dir = create_test_repo();
+        let repo_path = temp_dir.path().canonicalize().unwrap();
+        fs::write(repo_path.join("old.log"), "old").unwrap();
+        fs::write(repo_path.join("new.log"), "new").unwrap();
+        age_days(&repo_path.join("old.log"), 10);
+
+        let (exit_code, stdout, stderr) =
+            run_safe_rm(&["--older-than", "7d", "old.log", "new.log"], &repo_path);
+        assert_eq!(exit_code, 0, "stderr: {}", stderr);
+        assert!(stdout.contains("removed: old.log"));
+        assert!(!stdout.contains("new.log"));
+        assert!(stderr.is_empty(), "stderr: {}", stderr);
+        assert!(!repo_path.join("old.log").exists());
+        assert!(repo_path.join("new.log").exists());
+    }
+
+    #[test]
+    fn test_older_than_recursive_keeps_directories_with_newer_entries() {
+        let temp_dir = create_test_repo();
+        let repo_path = temp_dir.path().canonicalize().unwrap();
+        let cache = repo_path.join("cache");
+        fs::create_dir_all(cache.join("stale")).unwrap();
+        fs::create_dir_all(cache.join("mixed")).unwrap();
+        for name in ["stale/a.bin", "mixed/b.bin", "mixed/c.bin"] {
+            fs::write(cache.join(name), "x").unwrap();
+        }
+        age_days(&cache.join("stale").join("a.bin"), 30);
+        age_days(&cache.join("mixed").join("b.bin"), 30);
+
+        let (exit_code, stdout, _) =
+            run_safe_rm(&["-n", "-r", "--older-than", "1w", "cache"], &repo_path);
+        assert_eq!(exit_code, 0);
+        assert!(
+            stdout.contains("total: would remove 2 files"),
+            "stdout: {}",
+            stdout
+        );
+
+        let (exit_code, _, stderr) =
+            run_safe_rm(&["-r", "--older-than", "1w", "cache"], &repo_path);
+        assert_eq!(exit_code, 0, "stderr: {}", stderr);
+        assert!(!cache.join("stale").exists());
+        assert!(!cache.join("mixed").join("b.bin").exists());
+        assert!(cache.join("mixed").join("c.bin").exists());
+    }
+
+    #[test]
+    fn test_older_than_keeps_git_protection() {
+        let temp_dir = create_test_repo();
+        let repo_path = temp_dir.path().canonicalize().unwrap();
+        let config = tempfile::NamedTempFile::new().unwrap();
+        fs::write(config.path(), "allow_project_deletion = false\n").unwrap();
+        commit_file(&repo_path, "tracked.log", "original");
+        fs::write(repo_path.join("tracked.log"), "modified").unwrap();
+        age_days(&repo_path.join("tracked.log"), 30);
+
+        let (exit_code, _, _) = run_safe_rm_with_config(
+            &["--older-than", "7d", "tracked.log"],
+            &repo_path,
+            Some(config.path()),
+        );
+        assert_eq!(exit_code, 2);
+        assert!(repo_path.join("tracked.log").exists());
+    }
+
+    #[test]
+    fn test_invalid_older_than_is_rejected() {
+        let temp_dir = create_test_repo();
+        let repo_path = temp_dir.path().canonicalize().unwrap();
+        fs::write(repo_path.join("a.log"), "a").unwrap();
+
+        let (exit_code, _, _) = run_safe_rm(&["--older-than", "7y", "a.log"], &repo_path);
+        assert_ne!(exit_code, 0);
+        assert!(repo_path.join("a.log").exists());
+    }
+}
+
+#[cfg(unix)]
+mod special_file_tests {
+    use super::*;
+
+    fn mkfifo(path: &std::path::Path) {
+        let status = std::process::Command::new("mkfifo")
+            .arg(path)
+            .status()
+            .unwrap();
+        assert!(status.success());
+    }
+
+    #[test]
+    fn test_fifo_is_blocked_by_default() {
+        let temp_dir = create_test_repo();
+        let repo_path = temp_dir.path().canonicalize().unwrap();
+        mkfifo(&repo_path.join("pipe"));
+
+        let (exit_code, _, stderr) = run_safe_rm(&["pipe"], &repo_path);
+        assert_eq!(exit_code, 2);
+        assert!(
+            stderr.contains("特殊ファイル（fifo）"),
+            "stderr: {}",
+            stderr
+        );
+        assert!(repo_path.join("pipe").exists());
+
+        let (exit_code, _, stderr) = run_safe_rm(&["-f", "pipe"], &repo_path);
+        assert_eq!(exit_code, 0, "stderr: {}", stderr);
+        assert!(!repo_path.join("pipe").exists());
+    }
+
+    #[test]
+    fn test_fifo_allowed_by_config() {
+        let temp_dir = create_test_repo();
+        let repo_path = temp_dir.path().canonicalize().unwrap();
+        mkfifo(&repo_path.join("pipe"));
+
+        let config = tempfile::NamedTempFile::new().unwrap();
+        fs::write(config.path(), "allow_special_files = true\n").unwrap();
+        let (exit_code, _, stderr) =
+            run_safe_rm_with_config(&["pipe"], &repo_path, Some(config.path()));
+        assert_eq!(exit_code, 0, "stderr: {}", stderr);
+        assert!(!repo_path.join("pipe").exists());
+    }
+}
+
+mod min_age_tests {
+    use super::*;
+    use std::time::{Duration, SystemTime};
+
+    /// ファイルの更新時刻を1日前に設定
+    fn make_old(path: &std::path::Path) {
+        let file = fs::File::options().write(true).open(path).unwrap();
+        file.set_modified(SystemTime::now() - Duration::from_secs(86_400))
+            .unwrap();
+    }
+
+    #[test]
+    fn test_min_age_blocks_recent_file_and_allows_old_one() {
+        let temp_dir = create_test_repo();
+        let repo_path = temp_dir.path().canonicalize().unwrap();
+        fs::write(repo_path.join("fresh.txt"), "new").unwrap();
+        fs::write(repo_path.join("old.txt"), "old").unwrap();
+        make_old(&repo_path.join("old.txt"));
+
+        let (exit_code, _, stderr) = run_safe_rm(&["--min-age", "600", "fresh.txt"], &repo_path);
+        assert_eq!(exit_code, 2);
+        assert!(stderr.contains("最近変更された"), "stderr: {}", stderr);
+        assert!(repo_path.join("fresh.txt").exists());
+
+        let (exit_code, _, stderr) = run_safe_rm(&["--min-age", "600", "old.txt"], &repo_path);
+        assert_eq!(exit_code, 0, "stderr: {}", stderr);
+        assert!(!repo_path.join("old.txt").exists());
+    }
+
+    #[test]
+    fn test_min_age_config_and_force_override() {
+        let temp_dir = create_test_repo();
+        let repo_path = temp_dir.path().canonicalize().unwrap();
+        fs::write(repo_path.join("fresh.txt"), "new").unwrap();
+        let config = tempfile::NamedTempFile::new().unwrap();
+        fs::write(config.path(), "min_age_seconds = 600\n").unwrap();
+
+        let (exit_code, _, _) =
+            run_safe_rm_with_config(&["fresh.txt"], &repo_path, Some(config.path()));
+        assert_eq!(exit_code, 2);
+        assert!(repo_path.join("fresh.txt").exists());
+
+        let (exit_code, _, stderr) =
+            run_safe_rm_with_config(&["-f", "fresh.txt"], &repo_path, Some(config.path()));
+        assert_eq!(exit_code, 0, "stderr: {}", stderr);
+        assert!(!repo_path.join("fresh.txt").exists());
+    }
+}
+
+mod generated_files_tests {
+    use super::*;
+
+    #[test]
+    fn test_generated_file_deletable_in_strict_mode_when_enabled() {
+        let temp_dir = create_test_repo();
+        let repo_path = temp_dir.path().canonicalize().unwrap();
+        commit_file(&repo_path, ".gitattributes", "*.pb.go linguist-generated\n");
+        commit_file(&repo_path, "api.pb.go", "v1");
+        fs::write(repo_path.join("api.pb.go"), "v2").unwrap();
+
+        let config = tempfile::NamedTempFile::new().unwrap();
+        fs::write(config.path(), "allow_project_deletion = false\n").unwrap();
+        let (exit_code, _, _) =
+            run_safe_rm_with_config(&["api.pb.go"], &repo_path, Some(config.path()));
+        assert_eq!(exit_code, 2);
+        assert!(repo_path.join("api.pb.go").exists());
+
+        fs::write(
+            config.path(),
+            "allow_project_deletion = false\ntreat_generated_as_deletable = true\n",
+        )
+        .unwrap();
+        let (exit_code, _, stderr) =
+            run_safe_rm_with_config(&["api.pb.go"], &repo_path, Some(config.path()));
+        assert_eq!(exit_code, 0, "stderr: {}", stderr);
+        assert!(!repo_path.join("api.pb.go").exists());
+    }
+}
+
+mod confirm_tests {
+    use super::*;
+
+    fn create_build_dir(repo_path: &std::path::Path) {
+        fs::create_dir(repo_path.join("build")).unwrap();
+        for i in 0..5 {
+            fs::write(repo_path.join("build").join(format!("{}.o", i)), "o").unwrap();
+        }
+    }
+
+    #[test]
+    fn test_threshold_blocks_non_interactive_run_without_yes() {
+        let temp_dir = create_test_repo();
+        let repo_path = temp_dir.path().canonicalize().unwrap();
+        create_build_dir(&repo_path);
+        let config = tempfile::NamedTempFile::new().unwrap();
+        fs::write(config.path(), "confirm_threshold = 3\n").unwrap();
+
+        // テストの子プロセスの stdin は端末ではないため確認できずブロック
+        let (exit_code, _, stderr) =
+            run_safe_rm_with_config(&["-r", "build"], &repo_path, Some(config.path()));
+        assert_eq!(exit_code, 2);
+        assert!(stderr.contains("5 件のファイル"), "stderr: {}", stderr);
+        assert!(repo_path.join("build").join("0.o").exists());
+
+        let (exit_code, _, stderr) =
+            run_safe_rm_with_config(&["-r", "--yes", "build"], &repo_path, Some(config.path()));
+        assert_eq!(exit_code, 0, "stderr: {}", stderr);
+        assert!(!repo_path.join("build").exists());
+    }
+
+    #[test]
+    fn test_below_threshold_and_dry_run_do_not_ask() {
+        let temp_dir = create_test_repo();
+        let repo_path = temp_dir.path().canonicalize().unwrap();
+        create_build_dir(&repo_path);
+        let config = tempfile::NamedTempFile::new().unwrap();
+        fs::write(config.path(), "confirm_threshold = 3\n").unwrap();
+
+        let (exit_code, _, _) =
+            run_safe_rm_with_config(&["-r", "-n", "build"], &repo_path, Some(config.path()));
+        assert_eq!(exit_code, 0);
+
+        let (exit_code, _, stderr) =
+            run_safe_rm_with_config(&["build/0.o", "build/1.o"], &repo_path, Some(config.path()));
+        assert_eq!(exit_code, 0, "stderr: {}", stderr);
+        assert!(!repo_path.join("build").join("0.o").exists());
+    }
+
+    #[test]
+    fn test_yes_skips_prompt_but_keeps_safety_checks() {
+        let temp_dir = create_test_repo();
+        let repo_path = temp_dir.path().canonicalize().unwrap();
+        commit_file(&repo_path, "clean.txt", "clean");
+        commit_file(&repo_path, "modified.txt", "original");
+        fs::write(repo_path.join("modified.txt"), "changed").unwrap();
+        fs::write(repo_path.join("key.pem"), "secret").unwrap();
+        let config = tempfile::NamedTempFile::new().unwrap();
+        fs::write(
+            config.path(),
+            "allow_project_deletion = false\nconfirm_threshold = 1\nprotected_patterns = [\"*.pem\"]\n",
+        )
+        .unwrap();
+
+        let (exit_code, stdout, stderr) = run_safe_rm_with_config(
+            &["--yes", "clean.txt", "modified.txt", "key.pem"],
+            &repo_path,
+            Some(config.path()),
+        );
+
+        // 確認は省かれて clean なファイルは削除されるが、ブロックは --yes で覆らない
+        assert_eq!(exit_code, 2, "stderr: {}", stderr);
+        assert!(!stderr.contains("件のファイル"), "stderr: {}", stderr);
+        assert!(stdout.contains("removed: clean.txt"), "stdout: {}", stdout);
+        assert!(stderr.contains("Modified"), "stderr: {}", stderr);
+        assert!(repo_path.join("modified.txt").exists());
+        assert!(repo_path.join("key.pem").exists());
+    }
+
+    /// 標準入力に `input` を与えて safe-rm を実行
+    fn run_with_stdin(args: &[&str], cwd: &std::path::Path, input: &str) -> (i32, String, String) {
+        use std::io::Write;
+        use std::process::Stdio;
+
+        let mut child = Command::new(get_binary_path())
+            .args(args)
+            .current_dir(cwd)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn()
+            .unwrap();
+        // 確認が不要な実行は入力を読まずに終了するため、書き込みエラーは無視
+        let _ = child.stdin.take().unwrap().write_all(input.as_bytes());
+        let output = child.wait_with_output().unwrap();
+        (
+            output.status.code().unwrap_or(-1),
+            String::from_utf8_lossy(&output.stdout).to_string(),
+            String::from_utf8_lossy(&output.stderr).to_string(),
+        )
+    }
+
+    #[test]
+    fn test_interactive_once_prompts_before_recursive_delete() {
+        let temp_dir = create_test_repo();
+        let repo_path = temp_dir.path().canonicalize().unwrap();
+        create_build_dir(&repo_path);
+
+        // 拒否すると何も削除しない
+        let (exit_code, _, stderr) = run_with_stdin(&["-I", "-r", "build"], &repo_path, "n\n");
+        assert_eq!(exit_code, 1, "stderr: {}", stderr);
+        assert!(stderr.contains("Proceed? [y/N]"), "stderr: {}", stderr);
+        assert!(repo_path.join("build").join("0.o").exists());
+
+        let (exit_code, stdout, stderr) = run_with_stdin(&["-I", "-r", "build"], &repo_path, "y\n");
+        assert_eq!(exit_code, 0, "stderr: {}", stderr);
+        assert!(stdout.contains("removed: build"), "stdout: {}", stdout);
+        assert!(!repo_path.join("build").exists());
+    }
+
+    #[test]
+    fn test_interactive_once_prompts_only_above_three_paths() {
+        let temp_dir = create_test_repo();
+        let repo_path = temp_dir.path().canonicalize().unwrap();
+        create_build_dir(&repo_path);
+
+        let (exit_code, _, stderr) = run_with_stdin(
+            &["-I", "build/0.o", "build/1.o", "build/2.o", "build/3.o"],
+            &repo_path,
+            "",
+        );
+        assert_eq!(exit_code, 1, "stderr: {}", stderr);
+        assert!(repo_path.join("build").join("0.o").exists());
+
+        // 3件までは確認しない（入力がなくても削除される）
+        let (exit_code, _, stderr) = run_with_stdin(
+            &["-I", "build/0.o", "build/1.o", "build/2.o"],
+            &repo_path,
+            "",
+        );
+        assert_eq!(exit_code, 0, "stderr: {}", stderr);
+        assert!(!stderr.contains("Proceed?"), "stderr: {}", stderr);
+        assert!(!repo_path.join("build").join("0.o").exists());
+
+        // --yes は確認に答えるだけ
+        let (exit_code, _, stderr) =
+            run_with_stdin(&["-I", "--yes", "-r", "build"], &repo_path, "");
+        assert_eq!(exit_code, 0, "stderr: {}", stderr);
+        assert!(!repo_path.join("build").exists());
+    }
+
+    #[test]
+    fn test_confirm_flag_asks_regardless_of_count() {
+        let temp_dir = create_test_repo();
+        let repo_path = temp_dir.path().canonicalize().unwrap();
+        fs::write(repo_path.join("a.txt"), "a").unwrap();
+
+        let (exit_code, _, _) = run_safe_rm(&["--confirm", "a.txt"], &repo_path);
+        assert_eq!(exit_code, 2);
+        assert!(repo_path.join("a.txt").exists());
+    }
+}
+
+mod relative_to_tests {
+    use super::*;
+
+    #[test]
+    fn test_relative_to_renders_paths_from_base() {
+        let temp_dir = create_test_repo();
+        let repo_path = temp_dir.path().canonicalize().unwrap();
+        fs::create_dir(repo_path.join("sub")).unwrap();
+        fs::write(repo_path.join("a.txt"), "a").unwrap();
+        fs::write(repo_path.join("sub").join("b.txt"), "b").unwrap();
+        let sub = repo_path.join("sub");
+        let repo = repo_path.to_str().unwrap();
+
+        let (exit_code, stdout, _) = run_safe_rm(
+            &[&format!("--relative-to={}", repo), "../a.txt", "b.txt"],
+            &sub,
+        );
+        assert_eq!(exit_code, 0);
+        assert!(stdout.contains("removed: a.txt\n"), "stdout: {}", stdout);
+        assert!(
+            stdout.contains("removed: sub/b.txt\n"),
+            "stdout: {}",
+            stdout
+        );
+    }
+
+    #[test]
+    fn test_relative_to_defaults_to_cwd_and_falls_back_to_absolute() {
+        let temp_dir = create_test_repo();
+        let repo_path = temp_dir.path().canonicalize().unwrap();
+        fs::create_dir(repo_path.join("sub")).unwrap();
+        fs::write(repo_path.join("a.txt"), "a").unwrap();
+        fs::write(repo_path.join("sub").join("b.txt"), "b").unwrap();
+        let sub = repo_path.join("sub");
+        let absolute_b = sub.join("b.txt");
+
+        let (exit_code, stdout, _) = run_safe_rm(
+            &[
+                "-n",
+                "../a.txt",
+                absolute_b.to_str().unwrap(),
+                "--relative-to",
+            ],
+            &sub,
+        );
+        assert_eq!(exit_code, 0);
+        assert!(
+            stdout.contains(&format!(
+                "would remove: {}\n",
+                repo_path.join("a.txt").display()
+            )),
+            "stdout: {}",
+            stdout
+        );
+        assert!(
+            stdout.contains("would remove: b.txt\n"),
+            "stdout: {}",
+            stdout
+        );
+    }
+
+    #[test]
+    fn test_json_error_keeps_raw_and_resolved_paths() {
+        let temp_dir = create_test_repo();
+        let repo_path = temp_dir.path().canonicalize().unwrap();
+        fs::create_dir(repo_path.join("sub")).unwrap();
+        let sub = repo_path.join("sub");
+
+        let (_, _, stderr) = run_safe_rm(
+            &["--format", "json", "--relative-to=..", "missing.txt"],
+            &sub,
+        );
+        let error: serde_json::Value =
+            serde_json::from_str(stderr.lines().next().unwrap()).unwrap();
+        assert_eq!(error["path"], "missing.txt");
+        assert_eq!(error["resolved"], sub.join("missing.txt").to_str().unwrap());
+    }
+}
+
+mod strict_paths_tests {
+    use super::*;
+
+    #[test]
+    fn test_strict_paths_block_modified_file_in_default_mode() {
+        let temp_dir = create_test_repo();
+        let repo_path = temp_dir.path().canonicalize().unwrap();
+        fs::create_dir(repo_path.join("src")).unwrap();
+        commit_file(&repo_path, "src/lib.rs", "v1");
+        commit_file(&repo_path, "notes.txt", "v1");
+        fs::write(repo_path.join("src").join("lib.rs"), "v2").unwrap();
+        fs::write(repo_path.join("notes.txt"), "v2").unwrap();
+        let config = tempfile::NamedTempFile::new().unwrap();
+        fs::write(config.path(), "strict_paths = [\"src\"]\n").unwrap();
+
+        let (exit_code, _, stderr) =
+            run_safe_rm_with_config(&["src/lib.rs"], &repo_path, Some(config.path()));
+        assert_eq!(exit_code, 2);
+        assert!(stderr.contains("Status: Modified"), "stderr: {}", stderr);
+        assert!(repo_path.join("src").join("lib.rs").exists());
+
+        // strict_paths の外は allow_project_deletion に従い削除可能
+        let (exit_code, _, stderr) =
+            run_safe_rm_with_config(&["notes.txt"], &repo_path, Some(config.path()));
+        assert_eq!(exit_code, 0, "stderr: {}", stderr);
+        assert!(!repo_path.join("notes.txt").exists());
+    }
+
+    #[test]
+    fn test_strict_paths_apply_to_parent_directory() {
+        let temp_dir = create_test_repo();
+        let repo_path = temp_dir.path().canonicalize().unwrap();
+        fs::create_dir_all(repo_path.join("db").join("migrations")).unwrap();
+        commit_file(&repo_path, "db/migrations/001.sql", "v1");
+        fs::write(
+            repo_path.join("db").join("migrations").join("002.sql"),
+            "new",
+        )
+        .unwrap();
+        let config = tempfile::NamedTempFile::new().unwrap();
+        fs::write(config.path(), "strict_paths = \"db/migrations\"\n").unwrap();
+
+        let (exit_code, _, _) =
+            run_safe_rm_with_config(&["-r", "db"], &repo_path, Some(config.path()));
+        assert_eq!(exit_code, 2);
+        assert!(
+            repo_path
+                .join("db")
+                .join("migrations")
+                .join("002.sql")
+                .exists()
+        );
+    }
+}
+
+mod fail_fast_tests {
+    use super::*;
+
+    fn create_files(repo_path: &std::path::Path) {
+        fs::write(repo_path.join("a.txt"), "a").unwrap();
+        fs::write(repo_path.join("c.txt"), "c").unwrap();
+    }
+
+    #[test]
+    fn test_keep_going_is_default_and_processes_all_paths() {
+        let temp_dir = create_test_repo();
+        let repo_path = temp_dir.path().canonicalize().unwrap();
+        create_files(&repo_path);
+
+        let (exit_code, _, stderr) = run_safe_rm(&["a.txt", "/etc/passwd", "c.txt"], &repo_path);
+        assert_eq!(exit_code, 2);
+        assert!(!stderr.contains("not processed"));
+        assert!(!repo_path.join("a.txt").exists());
+        assert!(!repo_path.join("c.txt").exists());
+    }
+
+    #[test]
+    fn test_fail_fast_stops_at_first_error() {
+        let temp_dir = create_test_repo();
+        let repo_path = temp_dir.path().canonicalize().unwrap();
+        create_files(&repo_path);
+
+        let (exit_code, stdout, stderr) = run_safe_rm(
+            &["--fail-fast", "a.txt", "/etc/passwd", "c.txt"],
+            &repo_path,
+        );
+        assert_eq!(exit_code, 2);
+        assert!(stdout.contains("removed: a.txt"));
+        assert!(
+            stderr.contains("1 path(s) not processed"),
+            "stderr: {}",
+            stderr
+        );
+        assert!(!repo_path.join("a.txt").exists());
+        assert!(repo_path.join("c.txt").exists());
+
+        // 後から指定した --keep-going が優先
+        let (exit_code, _, _) = run_safe_rm(
+            &["--fail-fast", "--keep-going", "missing.txt", "c.txt"],
+            &repo_path,
+        );
+        assert_eq!(exit_code, 1);
+        assert!(!repo_path.join("c.txt").exists());
+    }
+
+    #[test]
+    fn test_delete_order_smallest_first_with_fail_fast() {
+        let temp_dir = create_test_repo();
+        let repo_path = temp_dir.path().canonicalize().unwrap();
+        fs::write(repo_path.join("big.bin"), vec![0u8; 4096]).unwrap();
+        fs::write(repo_path.join("small.txt"), "s").unwrap();
+        fs::write(repo_path.join("key.pem"), vec![0u8; 100]).unwrap();
+        let config = tempfile::NamedTempFile::new().unwrap();
+        fs::write(
+            config.path(),
+            "delete_order = \"smallest_first\"\nprotected_patterns = [\"*.pem\"]\n",
+        )
+        .unwrap();
+
+        let (exit_code, stdout, _) = run_safe_rm_with_config(
+            &["-n", "big.bin", "small.txt"],
+            &repo_path,
+            Some(config.path()),
+        );
+        assert_eq!(exit_code, 0);
+        let small = stdout.find("would remove: small.txt").unwrap();
+        let big = stdout.find("would remove: big.bin").unwrap();
+        assert!(small < big, "stdout: {}", stdout);
+
+        // 中間サイズのブロックで中断しても、小さいファイルは削除済みで大きいファイルは残る
+        let (exit_code, _, _) = run_safe_rm_with_config(
+            &["--fail-fast", "big.bin", "key.pem", "small.txt"],
+            &repo_path,
+            Some(config.path()),
+        );
+        assert_eq!(exit_code, 2);
+        assert!(!repo_path.join("small.txt").exists());
+        assert!(repo_path.join("big.bin").exists());
+    }
+}
+
+#[cfg(unix)]
+mod pre_delete_hook_tests {
+    use super::*;
+    use std::os::unix::fs::PermissionsExt;
+
+    #[test]
+    fn test_hook_rejects_one_file() {
+        let temp_dir = create_test_repo();
+        let repo_path = temp_dir.path().canonicalize().unwrap();
+        fs::write(repo_path.join("locked.txt"), "l").unwrap();
+        fs::write(repo_path.join("free.txt"), "f").unwrap();
+
+        let hook_dir = tempfile::tempdir().unwrap();
+        let hook = hook_dir.path().join("veto.sh");
+        fs::write(
+            &hook,
+            "#!/bin/sh\ncase \"$1\" in\n  */locked.txt) echo 'file is locked' >&2; exit 1 ;;\nesac\n",
+        )
+        .unwrap();
+        fs::set_permissions(&hook, fs::Permissions::from_mode(0o755)).unwrap();
+        let config = tempfile::NamedTempFile::new().unwrap();
+        fs::write(
+            config.path(),
+            format!("pre_delete_hook = \"{}\"\n", hook.display()),
+        )
+        .unwrap();
+
+        let (exit_code, stdout, stderr) =
+            run_safe_rm_with_config(&["locked.txt", "free.txt"], &repo_path, Some(config.path()));
+        assert_eq!(exit_code, 2);
+        assert!(stderr.contains("file is locked"), "stderr: {}", stderr);
+        assert!(stdout.contains("removed: free.txt"));
+        assert!(repo_path.join("locked.txt").exists());
+        assert!(!repo_path.join("free.txt").exists());
+    }
+}
+
+// =============================================================================
+// --report のテスト
+// =============================================================================
+
+mod report_tests {
+    use super::*;
+
+    fn read_report(path: &std::path::Path) -> Vec<serde_json::Value> {
+        fs::read_to_string(path)
+            .unwrap()
+            .lines()
+            .map(|line| serde_json::from_str(line).unwrap())
+            .collect()
+    }
+
+    #[test]
+    fn test_report_is_written_on_partial_failure() {
+        let temp_dir = create_test_repo();
+        let repo_path = temp_dir.path().canonicalize().unwrap();
+        fs::write(repo_path.join("a.txt"), "a").unwrap();
+        let report_dir = tempfile::tempdir().unwrap();
+        let report_path = report_dir.path().join("report.jsonl");
+
+        let (exit_code, stdout, _) = run_safe_rm(
+            &[
+                "--report",
+                report_path.to_str().unwrap(),
+                "a.txt",
+                "missing.txt",
+                "/etc/passwd",
+            ],
+            &repo_path,
+        );
+        assert_eq!(exit_code, 2);
+        // 端末には通常の人間向け出力が残る
+        assert!(stdout.contains("removed: a.txt"));
+
+        let lines = read_report(&report_path);
+        assert_eq!(lines.len(), 4);
+        assert_eq!(lines[0]["type"], "removed");
+        assert_eq!(lines[0]["path"], "a.txt");
+        assert_eq!(
+            lines[0]["resolved"],
+            repo_path.join("a.txt").to_str().unwrap()
+        );
+        assert_eq!(lines[1]["code"], "not_found");
+        assert_eq!(lines[2]["exit_code"], 2);
+        assert_eq!(lines[3]["type"], "summary");
+        assert_eq!(lines[3]["removed"], 1);
+        assert_eq!(lines[3]["blocked"], 1);
+        assert_eq!(lines[3]["not_found"], 1);
+        assert_eq!(lines[3]["exit_code"], 2);
+    }
+
+    #[test]
+    fn test_report_records_dry_run_and_skipped_paths() {
+        let temp_dir = create_test_repo();
+        let repo_path = temp_dir.path().canonicalize().unwrap();
+        fs::write(repo_path.join("a.txt"), "a").unwrap();
+        let report_path = repo_path.join("report.jsonl");
+
+        let (exit_code, _, _) = run_safe_rm(
+            &[
+                "-n",
+                "-f",
+                "--report",
+                "report.jsonl",
+                "a.txt",
+                "missing.txt",
+            ],
+            &repo_path,
+        );
+        assert_eq!(exit_code, 0);
+        assert!(repo_path.join("a.txt").exists());
+
+        let lines = read_report(&report_path);
+        assert_eq!(lines[0]["type"], "would_remove");
+        assert_eq!(lines[1]["type"], "skipped");
+        assert_eq!(lines[2]["dry_run"], true);
+        assert_eq!(lines[2]["exit_code"], 0);
+    }
+
+    #[test]
+    fn test_unwritable_report_fails_successful_run() {
+        let temp_dir = create_test_repo();
+        let repo_path = temp_dir.path().canonicalize().unwrap();
+        fs::write(repo_path.join("a.txt"), "a").unwrap();
+
+        let (exit_code, _, stderr) = run_safe_rm(
+            &["--report", "no-such-dir/report.jsonl", "a.txt"],
+            &repo_path,
+        );
+        assert_eq!(exit_code, 1);
+        assert!(stderr.contains("cannot write report"), "stderr: {}", stderr);
+    }
+}
+
+// =============================================================================
+// --since <ref> のテスト
+// =============================================================================
+
+mod since_tests {
+    use super::*;
+
+    /// `v1` タグ以降に `changed.txt` を更新し `added.txt` を追加したリポジトリ
+    fn create_tagged_repo() -> TempDir {
+        let temp_dir = create_test_repo();
+        let repo_path = temp_dir.path();
+        commit_file(repo_path, "same.txt", "same");
+        commit_file(repo_path, "changed.txt", "v1");
+        Command::new("git")
+            .args(["tag", "v1"])
+            .current_dir(repo_path)
+            .output()
+            .unwrap();
+        commit_file(repo_path, "changed.txt", "v2");
+        commit_file(repo_path, "pkg/added.txt", "added");
+        temp_dir
+    }
+
+    #[test]
+    fn test_since_deletes_files_identical_to_ref() {
+        let temp_dir = create_tagged_repo();
+        let repo_path = temp_dir.path().canonicalize().unwrap();
+
+        let (exit_code, _, stderr) = run_safe_rm(&["--since", "v1", "same.txt"], &repo_path);
+        assert_eq!(exit_code, 0, "stderr: {}", stderr);
+        assert!(!repo_path.join("same.txt").exists());
+    }
+
+    #[test]
+    fn test_since_blocks_files_changed_or_added_after_ref() {
+        let temp_dir = create_tagged_repo();
+        let repo_path = temp_dir.path().canonicalize().unwrap();
+
+        let (exit_code, _, stderr) =
+            run_safe_rm(&["--since", "v1", "-r", "changed.txt", "pkg"], &repo_path);
+        assert_eq!(exit_code, 2, "stderr: {}", stderr);
+        assert!(stderr.contains("ChangedSinceRef"), "stderr: {}", stderr);
+        assert!(repo_path.join("changed.txt").exists());
+        assert!(repo_path.join("pkg").join("added.txt").exists());
+
+        // --since なしなら HEAD と一致するため削除できる
+        let (exit_code, _, stderr) = run_safe_rm(&["changed.txt"], &repo_path);
+        assert_eq!(exit_code, 0, "stderr: {}", stderr);
+    }
+
+    #[test]
+    fn test_since_composes_with_dirty_check() {
+        let temp_dir = create_tagged_repo();
+        let repo_path = temp_dir.path().canonicalize().unwrap();
+        let config = tempfile::NamedTempFile::new().unwrap();
+        fs::write(config.path(), "allow_project_deletion = false\n").unwrap();
+        // タグと同じ内容だが HEAD からは変更あり → 既存の Git チェックでブロック
+        fs::write(repo_path.join("changed.txt"), "v1").unwrap();
+
+        let (exit_code, _, stderr) = run_safe_rm_with_config(
+            &["--since", "v1", "changed.txt"],
+            &repo_path,
+            Some(config.path()),
+        );
+        assert_eq!(exit_code, 2, "stderr: {}", stderr);
+        assert!(stderr.contains("Modified"), "stderr: {}", stderr);
+        assert!(repo_path.join("changed.txt").exists());
+    }
+
+    #[test]
+    fn test_since_unknown_ref_is_rejected() {
+        let temp_dir = create_tagged_repo();
+        let repo_path = temp_dir.path().canonicalize().unwrap();
+
+        let (exit_code, _, stderr) =
+            run_safe_rm(&["--since", "no-such-ref", "same.txt"], &repo_path);
+        assert_eq!(exit_code, 2, "stderr: {}", stderr);
+        assert!(stderr.contains("no-such-ref"), "stderr: {}", stderr);
+        assert!(repo_path.join("same.txt").exists());
+    }
+}
+
+// =============================================================================
+// マージコンフリクト中のファイルのテスト
+// =============================================================================
+
+mod conflict_tests {
+    use super::*;
+
+    fn git(repo_path: &std::path::Path, args: &[&str]) {
+        Command::new("git")
+            .args(args)
+            .current_dir(repo_path)
+            .output()
+            .unwrap();
+    }
+
+    /// `dir/conflict.txt` がコンフリクトした状態のリポジトリを作成
+    fn create_conflicted_repo() -> TempDir {
+        let temp_dir = create_test_repo();
+        let repo_path = temp_dir.path();
+        fs::create_dir_all(repo_path.join("dir")).unwrap();
+        commit_file(repo_path, "dir/conflict.txt", "base");
+        commit_file(repo_path, "dir/clean.txt", "clean");
+
+        git(repo_path, &["checkout", "-b", "other"]);
+        commit_file(repo_path, "dir/conflict.txt", "theirs");
+        git(repo_path, &["checkout", "-"]);
+        commit_file(repo_path, "dir/conflict.txt", "ours");
+        git(repo_path, &["merge", "other"]);
+        temp_dir
+    }
+
+    #[test]
+    fn test_conflicted_file_blocked_in_default_mode() {
+        let temp_dir = create_conflicted_repo();
+        let repo_path = temp_dir.path().canonicalize().unwrap();
+
+        let (exit_code, _, stderr) = run_safe_rm(&["dir/conflict.txt"], &repo_path);
+        assert_eq!(exit_code, 2, "stderr: {}", stderr);
+        assert!(stderr.contains("Conflicted"), "stderr: {}", stderr);
+        assert!(repo_path.join("dir").join("conflict.txt").exists());
+
+        // コンフリクトしていないファイルは従来どおり削除可能
+        let (exit_code, _, _) = run_safe_rm(&["dir/clean.txt"], &repo_path);
+        assert_eq!(exit_code, 0);
+    }
+
+    #[test]
+    fn test_directory_with_conflicted_file_blocked() {
+        let temp_dir = create_conflicted_repo();
+        let repo_path = temp_dir.path().canonicalize().unwrap();
+
+        let (exit_code, _, stderr) = run_safe_rm(&["-r", "dir"], &repo_path);
+        assert_eq!(exit_code, 2, "stderr: {}", stderr);
+        assert!(stderr.contains("conflict.txt"), "stderr: {}", stderr);
+        assert!(repo_path.join("dir").join("conflict.txt").exists());
+    }
+
+    #[test]
+    fn test_status_reports_conflicted() {
+        let temp_dir = create_conflicted_repo();
+        let repo_path = temp_dir.path().canonicalize().unwrap();
+
+        let (exit_code, stdout, _) = run_safe_rm(&["status", "dir/conflict.txt"], &repo_path);
+        assert_eq!(exit_code, 0);
+        assert!(stdout.starts_with("Conflicted no "), "stdout: {}", stdout);
+    }
+}
+
+// =============================================================================
+// --exclude のテスト
+// =============================================================================
+
+mod exclude_tests {
+    use super::*;
+
+    fn create_tree(repo_path: &std::path::Path) {
+        let build = repo_path.join("build");
+        fs::create_dir_all(build.join("cache").join("keep")).unwrap();
+        fs::create_dir_all(build.join("out")).unwrap();
+        fs::write(build.join("cache").join("keep").join("a.bin"), "a").unwrap();
+        fs::write(build.join("cache").join("important.txt"), "i").unwrap();
+        fs::write(build.join("cache").join("tmp.txt"), "t").unwrap();
+        fs::write(build.join("out").join("app"), "x").unwrap();
+    }
+
+    #[test]
+    fn test_dry_run_total_respects_excludes() {
+        let temp_dir = create_test_repo();
+        let repo_path = temp_dir.path().canonicalize().unwrap();
+        create_tree(&repo_path);
+        fs::write(repo_path.join("big.log"), vec![b'x'; 2048]).unwrap();
+
+        let (exit_code, stdout, _) = run_safe_rm(
+            &["-n", "-r", "--exclude", "important.txt", "build", "big.log"],
+            &repo_path,
+        );
+        assert_eq!(exit_code, 0);
+        // a.bin, tmp.txt, app（各1バイト）と big.log
+        assert!(
+            stdout.ends_with("total: would remove 4 files (2.0 KiB)\n"),
+            "stdout: {}",
+            stdout
+        );
+        assert!(repo_path.join("big.log").exists());
+    }
+
+    #[test]
+    fn test_exclude_nested_file() {
+        let temp_dir = create_test_repo();
+        let repo_path = temp_dir.path().canonicalize().unwrap();
+        create_tree(&repo_path);
+
+        let (exit_code, stdout, stderr) =
+            run_safe_rm(&["-r", "--exclude", "important.txt", "build"], &repo_path);
+        assert_eq!(exit_code, 0, "stderr: {}", stderr);
+        assert!(
+            stdout.contains("skipped: build/cache/important.txt (excluded)"),
+            "stdout: {}",
+            stdout
+        );
+        let build = repo_path.join("build");
+        assert!(build.join("cache").join("important.txt").exists());
+        assert!(!build.join("cache").join("tmp.txt").exists());
+        assert!(!build.join("cache").join("keep").exists());
+        assert!(!build.join("out").exists());
+    }
+
+    #[test]
+    fn test_exclude_nested_directory() {
+        let temp_dir = create_test_repo();
+        let repo_path = temp_dir.path().canonicalize().unwrap();
+        create_tree(&repo_path);
+
+        let (exit_code, stdout, stderr) = run_safe_rm(
+            &["-r", "--exclude", "cache/keep", "--exclude", "out", "build"],
+            &repo_path,
+        );
+        assert_eq!(exit_code, 0, "stderr: {}", stderr);
+        assert!(stdout.contains("skipped: build/cache/keep (excluded)"));
+        let build = repo_path.join("build");
+        assert!(build.join("cache").join("keep").join("a.bin").exists());
+        assert!(build.join("out").join("app").exists());
+        assert!(!build.join("cache").join("tmp.txt").exists());
+        assert!(!build.join("cache").join("important.txt").exists());
+    }
+
+    #[test]
+    fn test_exclude_invalid_pattern_blocks_before_deleting() {
+        let temp_dir = create_test_repo();
+        let repo_path = temp_dir.path().canonicalize().unwrap();
+        create_tree(&repo_path);
+
+        let (exit_code, _, stderr) = run_safe_rm(&["-r", "--exclude", "[", "build"], &repo_path);
+        assert_eq!(exit_code, 2);
+        assert!(
+            stderr.contains("invalid exclude pattern"),
+            "stderr: {}",
+            stderr
+        );
+        assert!(repo_path.join("build").join("out").join("app").exists());
+    }
+}
+
+// =============================================================================
+// --progress のテスト
+// =============================================================================
+
+mod progress_tests {
+    use super::*;
+
+    fn create_tree(repo_path: &std::path::Path) {
+        let build = repo_path.join("build");
+        fs::create_dir_all(build.join("nested")).unwrap();
+        fs::write(build.join("a.o"), "a").unwrap();
+        fs::write(build.join("nested").join("b.o"), "b").unwrap();
+        fs::write(build.join("nested").join("c.o"), "c").unwrap();
+    }
+
+    #[test]
+    fn test_progress_reports_removed_count() {
+        let temp_dir = create_test_repo();
+        let repo_path = temp_dir.path().canonicalize().unwrap();
+        create_tree(&repo_path);
+
+        let (exit_code, stdout, stderr) = run_safe_rm(&["-r", "--progress", "build"], &repo_path);
+        assert_eq!(exit_code, 0, "stderr: {}", stderr);
+        assert!(
+            stderr.contains("safe-rm: progress: build: 3 file(s) removed"),
+            "stderr: {}",
+            stderr
+        );
+        assert!(!stdout.contains("progress"), "stdout: {}", stdout);
+        assert!(!repo_path.join("build").exists());
+    }
+
+    #[test]
+    fn test_progress_json_lines_on_stderr() {
+        let temp_dir = create_test_repo();
+        let repo_path = temp_dir.path().canonicalize().unwrap();
+        create_tree(&repo_path);
+
+        let (exit_code, _, stderr) = run_safe_rm(
+            &["-r", "--progress", "--format", "json", "build"],
+            &repo_path,
+        );
+        assert_eq!(exit_code, 0, "stderr: {}", stderr);
+        let last: serde_json::Value = serde_json::from_str(stderr.lines().last().unwrap()).unwrap();
+        assert_eq!(last["type"], "progress");
+        assert_eq!(last["removed"], 3);
+    }
+}
+
+// =============================================================================
+// --no-git のテスト
+// =============================================================================
+
+mod no_git_tests {
+    use super::*;
+
+    fn strict_config() -> tempfile::NamedTempFile {
+        let config = tempfile::NamedTempFile::new().unwrap();
+        fs::write(config.path(), "allow_project_deletion = false\n").unwrap();
+        config
+    }
+
+    #[test]
+    fn test_no_git_skips_dirty_file_checks() {
+        let temp_dir = create_test_repo();
+        let repo_path = temp_dir.path().canonicalize().unwrap();
+        fs::write(repo_path.join("untracked.txt"), "x").unwrap();
+        let config = strict_config();
+
+        let (exit_code, _, _) =
+            run_safe_rm_with_config(&["untracked.txt"], &repo_path, Some(config.path()));
+        assert_eq!(exit_code, 2, "strict mode should block untracked files");
+
+        let (exit_code, _, stderr) = run_safe_rm_with_config(
+            &["--no-git", "untracked.txt"],
+            &repo_path,
+            Some(config.path()),
+        );
+        assert_eq!(exit_code, 0, "stderr: {}", stderr);
+        assert!(!repo_path.join("untracked.txt").exists());
+    }
+
+    #[test]
+    fn test_no_git_still_blocks_outside_project() {
+        let temp_dir = create_test_repo();
+        let repo_path = temp_dir.path().canonicalize().unwrap();
+        let outside_dir = TempDir::new().unwrap();
+        let outside_file = outside_dir.path().canonicalize().unwrap().join("file.txt");
+        fs::write(&outside_file, "x").unwrap();
+        let config = strict_config();
+
+        let (exit_code, _, stderr) = run_safe_rm_with_config(
+            &["--no-git", outside_file.to_str().unwrap()],
+            &repo_path,
+            Some(config.path()),
+        );
+        assert_eq!(exit_code, 2);
+        assert!(stderr.contains("プロジェクト外"), "stderr: {}", stderr);
+        assert!(outside_file.exists());
+    }
+
+    #[test]
+    fn test_no_git_uses_cwd_as_boundary() {
+        let temp_dir = create_test_repo();
+        let repo_path = temp_dir.path().canonicalize().unwrap();
+        fs::create_dir_all(repo_path.join("sub")).unwrap();
+        commit_file(&repo_path, "top.txt", "x");
+
+        // リポジトリを開かないため、リポジトリルートではなく cwd が境界になる
+        let (exit_code, _, _) = run_safe_rm(&["--no-git", "../top.txt"], &repo_path.join("sub"));
+        assert_eq!(exit_code, 2);
+        assert!(repo_path.join("top.txt").exists());
+    }
+}
+
+// =============================================================================
+// ネストしたリポジトリのテスト
+// =============================================================================
+
+mod nested_repository_tests {
+    use super::*;
+
+    /// `vendor/lib` にネストしたリポジトリを作成
+    fn create_nested_repo(repo_path: &std::path::Path) {
+        let nested = repo_path.join("vendor").join("lib");
+        fs::create_dir_all(&nested).unwrap();
+        Command::new("git")
+            .args(["init"])
+            .current_dir(&nested)
+            .output()
+            .unwrap();
+        fs::write(nested.join("lib.rs"), "x").unwrap();
+    }
+
+    #[test]
+    fn test_recursive_delete_blocked_by_nested_repo() {
+        let temp_dir = create_test_repo();
+        let repo_path = temp_dir.path().canonicalize().unwrap();
+        create_nested_repo(&repo_path);
+
+        let (exit_code, _, stderr) = run_safe_rm(&["-r", "vendor"], &repo_path);
+        assert_eq!(exit_code, 2);
+        assert!(
+            stderr.contains("--allow-nested-repos"),
+            "stderr: {}",
+            stderr
+        );
+        assert!(repo_path.join("vendor").join("lib").join(".git").exists());
+
+        // ドライランでも同様にブロック
+        let (exit_code, _, _) = run_safe_rm(&["-r", "-n", "vendor"], &repo_path);
+        assert_eq!(exit_code, 2);
+    }
+
+    #[test]
+    fn test_allow_nested_repos_flag() {
+        let temp_dir = create_test_repo();
+        let repo_path = temp_dir.path().canonicalize().unwrap();
+        create_nested_repo(&repo_path);
+
+        let (exit_code, _, stderr) =
+            run_safe_rm(&["-r", "--allow-nested-repos", "vendor"], &repo_path);
+        assert_eq!(exit_code, 0, "stderr: {}", stderr);
+        assert!(!repo_path.join("vendor").exists());
+    }
+}
+
+// =============================================================================
+// use_lock のテスト
+// =============================================================================
+
+#[cfg(unix)]
+mod use_lock_tests {
+    use super::*;
+    use safe_rm::lock::RepoLock;
+    use std::os::unix::fs::PermissionsExt;
+    use std::time::Duration;
+
+    #[test]
+    fn test_concurrent_runs_do_not_interleave() {
+        let temp_dir = create_test_repo();
+        let repo_path = temp_dir.path().canonicalize().unwrap();
+        fs::write(repo_path.join("a.txt"), "a").unwrap();
+        fs::write(repo_path.join("b.txt"), "b").unwrap();
+
+        // 削除前フックで開始・終了を記録し、その間に待機して重なりやすくする
+        let hook_dir = tempfile::tempdir().unwrap();
+        let log = hook_dir.path().join("hook.log");
+        let hook = hook_dir.path().join("record.sh");
+        fs::write(
+            &hook,
+            format!(
+                "#!/bin/sh\necho \"start $(basename \"$1\")\" >> '{log}'\nsleep 0.3\necho \"end $(basename \"$1\")\" >> '{log}'\n",
+                log = log.display()
+            ),
+        )
+        .unwrap();
+        fs::set_permissions(&hook, fs::Permissions::from_mode(0o755)).unwrap();
+        let config = tempfile::NamedTempFile::new().unwrap();
+        fs::write(
+            config.path(),
+            format!(
+                "use_lock = true\npre_delete_hook = \"{}\"\n",
+                hook.display()
+            ),
+        )
+        .unwrap();
+
+        let runs: Vec<_> = ["a.txt", "b.txt"]
+            .into_iter()
+            .map(|file| {
+                let repo_path = repo_path.clone();
+                let config_path = config.path().to_path_buf();
+                std::thread::spawn(move || {
+                    run_safe_rm_with_config(&[file], &repo_path, Some(&config_path))
+                })
+            })
+            .collect();
+        for run in runs {
+            let (exit_code, _, stderr) = run.join().unwrap();
+            assert_eq!(exit_code, 0, "stderr: {}", stderr);
+        }
+
+        let lines: Vec<String> = fs::read_to_string(&log)
+            .unwrap()
+            .lines()
+            .map(str::to_string)
+            .collect();
+        assert_eq!(lines.len(), 4, "log: {:?}", lines);
+        // 一方の実行が終わってから他方が始まる
+        for pair in lines.chunks(2) {
+            let name = pair[0].strip_prefix("start ").unwrap();
+            assert_eq!(pair[1], format!("end {}", name), "log: {:?}", lines);
+        }
+        assert!(!repo_path.join("a.txt").exists());
+        assert!(!repo_path.join("b.txt").exists());
+    }
+
+    #[test]
+    fn test_held_lock_blocks_deletion_after_timeout() {
+        let temp_dir = create_test_repo();
+        let repo_path = temp_dir.path().canonicalize().unwrap();
+        fs::write(repo_path.join("a.txt"), "a").unwrap();
+        let config = tempfile::NamedTempFile::new().unwrap();
+        fs::write(config.path(), "use_lock = true\nlock_timeout_ms = 100\n").unwrap();
+
+        let _held = RepoLock::acquire(&repo_path.join(".git"), Duration::ZERO).unwrap();
+        let (exit_code, _, stderr) = run_safe_rm_with_config(
+            &["--format", "json", "a.txt"],
+            &repo_path,
+            Some(config.path()),
+        );
+        assert_eq!(exit_code, 1, "stderr: {}", stderr);
+        let error: serde_json::Value =
+            serde_json::from_str(stderr.lines().next().unwrap()).unwrap();
+        assert_eq!(error["code"], "lock_timeout");
+        assert!(repo_path.join("a.txt").exists());
+    }
+
+    #[test]
+    fn test_lock_not_used_by_default() {
+        let temp_dir = create_test_repo();
+        let repo_path = temp_dir.path().canonicalize().unwrap();
+        fs::write(repo_path.join("a.txt"), "a").unwrap();
+
+        let _held = RepoLock::acquire(&repo_path.join(".git"), Duration::ZERO).unwrap();
+        let (exit_code, _, stderr) = run_safe_rm(&["a.txt"], &repo_path);
+        assert_eq!(exit_code, 0, "stderr: {}", stderr);
+        assert!(!repo_path.join("a.txt").exists());
+    }
+}
+
+// =============================================================================
+// --pathspec のテスト
+// =============================================================================
+
+mod pathspec_tests {
+    use super::*;
+
+    fn create_strict_config() -> tempfile::NamedTempFile {
+        let config = tempfile::NamedTempFile::new().unwrap();
+        fs::write(config.path(), "allow_project_deletion = false\n").unwrap();
+        config
+    }
+
+    #[test]
+    fn test_glob_pathspec_deletes_matching_files_through_checks() {
+        let temp_dir = create_test_repo();
+        let repo_path = temp_dir.path().canonicalize().unwrap();
+        let config = create_strict_config();
+        commit_file(&repo_path, "a.tmp", "a");
+        commit_file(&repo_path, "sub/b.tmp", "b");
+        commit_file(&repo_path, "sub/deep/c.tmp", "c");
+        commit_file(&repo_path, "dirty.tmp", "original");
+        commit_file(&repo_path, "keep.txt", "k");
+        fs::write(repo_path.join("dirty.tmp"), "modified").unwrap();
+
+        let (exit_code, stdout, stderr) = run_safe_rm_with_config(
+            &["--pathspec", ":(glob)**/*.tmp"],
+            &repo_path,
+            Some(config.path()),
+        );
+        // 変更のあるファイルは通常どおりブロック
+        assert_eq!(exit_code, 2, "stderr: {}", stderr);
+        assert!(stderr.contains("dirty.tmp"), "stderr: {}", stderr);
+        assert!(repo_path.join("dirty.tmp").exists());
+        for file in ["a.tmp", "sub/b.tmp", "sub/deep/c.tmp"] {
+            assert!(
+                stdout.contains(&format!("removed: {}", file)),
+                "stdout: {}",
+                stdout
+            );
+            assert!(!repo_path.join(file).exists(), "{} should be removed", file);
+        }
+        assert!(repo_path.join("keep.txt").exists());
+    }
+
+    #[test]
+    fn test_glob_magic_star_does_not_cross_directories() {
+        let temp_dir = create_test_repo();
+        let repo_path = temp_dir.path().canonicalize().unwrap();
+        commit_file(&repo_path, "a.tmp", "a");
+        commit_file(&repo_path, "sub/b.tmp", "b");
+
+        let (exit_code, _, stderr) = run_safe_rm(&["--pathspec", ":(glob)*.tmp"], &repo_path);
+        assert_eq!(exit_code, 0, "stderr: {}", stderr);
+        assert!(!repo_path.join("a.tmp").exists());
+        assert!(repo_path.join("sub").join("b.tmp").exists());
+    }
+
+    #[test]
+    fn test_pathspec_is_relative_to_cwd_unless_top() {
+        let temp_dir = create_test_repo();
+        let repo_path = temp_dir.path().canonicalize().unwrap();
+        commit_file(&repo_path, "sub/a.log", "a");
+        commit_file(&repo_path, "logs/b.log", "b");
+        commit_file(&repo_path, "c.log", "c");
+
+        let sub = repo_path.join("sub");
+        let (exit_code, stdout, stderr) = run_safe_rm(&["--pathspec", "*.log", ":/logs"], &sub);
+        assert_eq!(exit_code, 0, "stderr: {}", stderr);
+        assert!(stdout.contains("removed: a.log"), "stdout: {}", stdout);
+        assert!(!sub.join("a.log").exists());
+        assert!(!repo_path.join("logs").join("b.log").exists());
+        assert!(repo_path.join("c.log").exists());
+    }
+
+    #[test]
+    fn test_unmatched_pathspec_is_not_found() {
+        let temp_dir = create_test_repo();
+        let repo_path = temp_dir.path().canonicalize().unwrap();
+
+        let (exit_code, _, stderr) = run_safe_rm(&["--pathspec", ":(glob)*.none"], &repo_path);
+        assert_eq!(exit_code, 1);
+        assert!(stderr.contains("No such file"), "stderr: {}", stderr);
+
+        let (exit_code, _, _) = run_safe_rm(&["-f", "--pathspec", ":(glob)*.none"], &repo_path);
+        assert_eq!(exit_code, 0);
+    }
+
+    #[test]
+    fn test_pathspec_rejects_unsupported_magic_before_deleting() {
+        let temp_dir = create_test_repo();
+        let repo_path = temp_dir.path().canonicalize().unwrap();
+        commit_file(&repo_path, "a.tmp", "a");
+
+        let (exit_code, _, stderr) =
+            run_safe_rm(&["--pathspec", "a.tmp", ":(exclude)b.tmp"], &repo_path);
+        assert_eq!(exit_code, 2);
+        assert!(
+            stderr.contains("unsupported pathspec magic"),
+            "stderr: {}",
+            stderr
+        );
+        assert!(repo_path.join("a.tmp").exists());
+    }
+
+    #[test]
+    fn test_pathspec_outside_repository_is_error() {
+        let temp_dir = TempDir::new().unwrap();
+        fs::write(temp_dir.path().join("a.tmp"), "a").unwrap();
+
+        let (exit_code, _, stderr) = run_safe_rm(&["--pathspec", "*.tmp"], temp_dir.path());
+        assert_eq!(exit_code, 2);
+        assert!(
+            stderr.contains("requires a Git repository"),
+            "stderr: {}",
+            stderr
+        );
+        assert!(temp_dir.path().join("a.tmp").exists());
+    }
+}
+
+// =============================================================================
+// SAFE_RM_DISABLE（緊急用の安全チェック無効化）のテスト
+// =============================================================================
+
+mod break_glass_tests {
+    use super::*;
+
+    fn run_with_env(
+        args: &[&str],
+        cwd: &std::path::Path,
+        disable: Option<&str>,
+        config_path: &std::path::Path,
+    ) -> (i32, String, String) {
+        let mut cmd = Command::new(get_binary_path());
+        cmd.args(args)
+            .current_dir(cwd)
+            .env("SAFE_RM_CONFIG", config_path)
+            .env_remove("SAFE_RM_DISABLE");
+        if let Some(value) = disable {
+            cmd.env("SAFE_RM_DISABLE", value);
+        }
+        let output = cmd.output().unwrap();
+        (
+            output.status.code().unwrap_or(-1),
+            String::from_utf8_lossy(&output.stdout).to_string(),
+            String::from_utf8_lossy(&output.stderr).to_string(),
+        )
+    }
+
+    /// strict 設定のリポジトリ（変更ありの tracked.txt）と、その外側の outside.txt
+    fn setup() -> (TempDir, std::path::PathBuf, tempfile::NamedTempFile) {
+        let outer = TempDir::new().unwrap();
+        let repo_path = outer.path().canonicalize().unwrap().join("repo");
+        fs::create_dir(&repo_path).unwrap();
+        Command::new("git")
+            .args(["init", "-q"])
+            .current_dir(&repo_path)
+            .output()
+            .unwrap();
+        Command::new("git")
+            .args(["config", "user.email", "test@test.com"])
+            .current_dir(&repo_path)
+            .output()
+            .unwrap();
+        Command::new("git")
+            .args(["config", "user.name", "Test User"])
+            .current_dir(&repo_path)
+            .output()
+            .unwrap();
+        commit_file(&repo_path, "tracked.txt", "original");
+        fs::write(repo_path.join("tracked.txt"), "modified").unwrap();
+        fs::write(outer.path().join("outside.txt"), "o").unwrap();
+
+        let config = tempfile::NamedTempFile::new().unwrap();
+        fs::write(config.path(), "allow_project_deletion = false\n").unwrap();
+        (outer, repo_path, config)
+    }
+
+    #[test]
+    fn test_env_and_flag_together_disable_checks_with_warning() {
+        let (outer, repo_path, config) = setup();
+
+        let (exit_code, _, stderr) = run_with_env(
+            &["--i-understand-the-risk", "tracked.txt", "../outside.txt"],
+            &repo_path,
+            Some("1"),
+            config.path(),
+        );
+        assert_eq!(exit_code, 0, "stderr: {}", stderr);
+        assert!(stderr.contains("WARNING"), "stderr: {}", stderr);
+        assert!(stderr.contains("DISABLED"), "stderr: {}", stderr);
+        assert!(!repo_path.join("tracked.txt").exists());
+        assert!(!outer.path().join("outside.txt").exists());
+    }
+
+    #[test]
+    fn test_env_alone_keeps_safety_checks() {
+        let (outer, repo_path, config) = setup();
+
+        let (exit_code, _, stderr) = run_with_env(
+            &["tracked.txt", "../outside.txt"],
+            &repo_path,
+            Some("1"),
+            config.path(),
+        );
+        assert_eq!(exit_code, 2);
+        assert!(!stderr.contains("DISABLED"));
+        assert!(repo_path.join("tracked.txt").exists());
+        assert!(outer.path().join("outside.txt").exists());
+    }
+
+    #[test]
+    fn test_flag_alone_keeps_safety_checks() {
+        let (outer, repo_path, config) = setup();
+
+        for disable in [None, Some("0"), Some("true")] {
+            let (exit_code, _, stderr) = run_with_env(
+                &["--i-understand-the-risk", "tracked.txt", "../outside.txt"],
+                &repo_path,
+                disable,
+                config.path(),
+            );
+            assert_eq!(exit_code, 2, "SAFE_RM_DISABLE={:?}", disable);
+            assert!(!stderr.contains("DISABLED"));
+            assert!(repo_path.join("tracked.txt").exists());
+            assert!(outer.path().join("outside.txt").exists());
+        }
+    }
+
+    #[test]
+    fn test_break_glass_still_refuses_file_system_root() {
+        let (_outer, repo_path, config) = setup();
+
+        let (exit_code, _, stderr) = run_with_env(
+            &["-n", "-rf", "--i-understand-the-risk", "/"],
+            &repo_path,
+            Some("1"),
+            config.path(),
+        );
+        assert_eq!(exit_code, 2);
+        assert!(
+            stderr.contains("refusing to remove the file system root"),
+            "stderr: {}",
+            stderr
+        );
+    }
+}
+
+#[cfg(unix)]
+mod directory_read_policy_tests {
+    use super::*;
+    use std::os::unix::fs::PermissionsExt;
+
+    fn create_config(policy: &str) -> tempfile::NamedTempFile {
+        let config = tempfile::NamedTempFile::new().unwrap();
+        fs::write(
+            config.path(),
+            format!(
+                "allow_project_deletion = false\ndirectory_read_policy = \"{}\"\n",
+                policy
+            ),
+        )
+        .unwrap();
+        config
+    }
+
+    /// コミット済みの `cache/ok.txt` と一覧できない `cache/locked` を作る。一覧できてしまう環境では None
+    fn create_unreadable_dir(repo_path: &std::path::Path) -> Option<std::path::PathBuf> {
+        commit_file(repo_path, "cache/ok.txt", "ok");
+        let locked = repo_path.join("cache").join("locked");
+        fs::create_dir_all(&locked).unwrap();
+        fs::write(locked.join("secret.txt"), "s").unwrap();
+        fs::set_permissions(&locked, fs::Permissions::from_mode(0o000)).unwrap();
+        if fs::read_dir(&locked).is_ok() {
+            fs::set_permissions(&locked, fs::Permissions::from_mode(0o755)).unwrap();
+            eprintln!("skipping: unreadable directories are readable in this environment");
+            return None;
+        }
+        Some(locked)
+    }
+
+    #[test]
+    fn test_fail_closed_blocks_unreadable_directory() {
+        let temp_dir = create_test_repo();
+        let repo_path = temp_dir.path().canonicalize().unwrap();
+        let Some(locked) = create_unreadable_dir(&repo_path) else {
+            return;
+        };
+        let config = create_config("fail_closed");
+
+        let (exit_code, _, stderr) = run_safe_rm_with_config(
+            &["--format", "json", "-r", "cache"],
+            &repo_path,
+            Some(config.path()),
+        );
+        fs::set_permissions(&locked, fs::Permissions::from_mode(0o755)).unwrap();
+
+        assert_eq!(exit_code, 2, "stderr: {}", stderr);
+        assert!(
+            stderr.contains("directory_read_error"),
+            "stderr: {}",
+            stderr
+        );
+        assert!(repo_path.join("cache").join("ok.txt").exists());
+    }
+
+    #[test]
+    fn test_skip_unreadable_warns_and_does_not_block() {
+        let temp_dir = create_test_repo();
+        let repo_path = temp_dir.path().canonicalize().unwrap();
+        let Some(locked) = create_unreadable_dir(&repo_path) else {
+            return;
+        };
+        let config = create_config("skip_unreadable");
+
+        let (exit_code, stdout, stderr) = run_safe_rm_with_config(
+            &["--dry-run", "-r", "cache"],
+            &repo_path,
+            Some(config.path()),
+        );
+        fs::set_permissions(&locked, fs::Permissions::from_mode(0o755)).unwrap();
+
+        assert_eq!(exit_code, 0, "stdout: {}\nstderr: {}", stdout, stderr);
+        assert!(
+            stderr.contains("skipped unreadable directory")
+                && stderr.contains(&locked.display().to_string()),
+            "stderr: {}",
+            stderr
+        );
+        assert_eq!(stderr.matches("skipped unreadable directory").count(), 1);
+        assert!(repo_path.join("cache").join("ok.txt").exists());
+    }
+
+    #[test]
+    fn test_skip_unreadable_still_checks_readable_files() {
+        let temp_dir = create_test_repo();
+        let repo_path = temp_dir.path().canonicalize().unwrap();
+        commit_file(&repo_path, "cache/tracked.txt", "v1");
+        fs::write(repo_path.join("cache").join("tracked.txt"), "v2").unwrap();
+        let Some(locked) = create_unreadable_dir(&repo_path) else {
+            return;
+        };
+        let config = create_config("skip_unreadable");
+
+        let (exit_code, _, stderr) = run_safe_rm_with_config(
+            &["--format", "json", "-r", "cache"],
+            &repo_path,
+            Some(config.path()),
+        );
+        fs::set_permissions(&locked, fs::Permissions::from_mode(0o755)).unwrap();
+
+        assert_eq!(exit_code, 2, "stderr: {}", stderr);
+        assert!(
+            stderr.contains("\"type\":\"warning\""),
+            "stderr: {}",
+            stderr
+        );
+        assert!(repo_path.join("cache").join("tracked.txt").exists());
+    }
+}
+
+mod trace_tests {
+    use super::*;
+
+    #[test]
+    fn test_trace_reports_phases_and_paths() {
+        let temp_dir = create_test_repo();
+        let repo_path = temp_dir.path().canonicalize().unwrap();
+        fs::write(repo_path.join("a.log"), "a").unwrap();
+
+        let (exit_code, stdout, stderr) = run_safe_rm(&["--trace", "a.log"], &repo_path);
+        assert_eq!(exit_code, 0, "stderr: {}", stderr);
+        for phase in [
+            "open_repository",
+            "git_status",
+            "path a.log: check",
+            "total",
+        ] {
+            assert!(
+                stderr.contains(&format!("safe-rm: trace: {}", phase)),
+                "stderr: {}",
+                stderr
+            );
+        }
+        assert!(!stdout.contains("trace"), "stdout: {}", stdout);
+        assert!(!repo_path.join("a.log").exists());
+    }
+
+    #[test]
+    fn test_trace_json_lines_on_stderr() {
+        let temp_dir = create_test_repo();
+        let repo_path = temp_dir.path().canonicalize().unwrap();
+        fs::write(repo_path.join("a.log"), "a").unwrap();
+
+        let (exit_code, stdout, stderr) =
+            run_safe_rm(&["--trace", "--format", "json", "a.log"], &repo_path);
+        assert_eq!(exit_code, 0, "stderr: {}", stderr);
+        assert!(!stdout.contains("trace"), "stdout: {}", stdout);
+        let lines: Vec<serde_json::Value> = stderr
+            .lines()
+            .map(|line| serde_json::from_str(line).unwrap())
+            .collect();
+        assert!(lines.iter().all(|line| line["type"] == "trace"));
+        let path = lines.iter().find(|line| line["phase"] == "path").unwrap();
+        assert_eq!(path["path"], "a.log");
+        assert!(path["check_ms"].is_number() && path["delete_ms"].is_number());
+    }
+
+    #[test]
+    fn test_no_trace_by_default() {
+        let temp_dir = create_test_repo();
+        let repo_path = temp_dir.path().canonicalize().unwrap();
+        fs::write(repo_path.join("a.log"), "a").unwrap();
+
+        let (exit_code, _, stderr) = run_safe_rm(&["a.log"], &repo_path);
+        assert_eq!(exit_code, 0);
+        assert!(!stderr.contains("trace"), "stderr: {}", stderr);
+    }
+}
+
+mod allowed_paths_glob_tests {
+    use super::*;
+
+    fn create_glob_config(glob: &str) -> tempfile::NamedTempFile {
+        let config = tempfile::NamedTempFile::new().unwrap();
+        fs::write(
+            config.path(),
+            format!(
+                "allow_project_deletion = false\n\n[[allowed_paths]]\nglob = \"{}\"\n",
+                glob
+            ),
+        )
+        .unwrap();
+        config
+    }
+
+    #[test]
+    fn test_glob_allows_matching_untracked_file() {
+        let temp_dir = create_test_repo();
+        let repo_path = temp_dir.path().canonicalize().unwrap();
+        commit_file(&repo_path, "init.txt", "init");
+        fs::create_dir_all(repo_path.join("logs").join("app")).unwrap();
+        let log = repo_path.join("logs").join("app").join("run.log");
+        fs::write(&log, "log").unwrap();
+        let config = create_glob_config("**/*.log");
+
+        let (exit_code, stdout, stderr) =
+            run_safe_rm_with_config(&["logs/app/run.log"], &repo_path, Some(config.path()));
+
+        assert_eq!(exit_code, 0, "stderr: {}", stderr);
+        assert!(
+            stdout.contains("(allowed by config: glob **/*.log)"),
+            "stdout: {}",
+            stdout
+        );
+        assert!(!log.exists());
+    }
+
+    #[test]
+    fn test_glob_does_not_allow_other_files() {
+        let temp_dir = create_test_repo();
+        let repo_path = temp_dir.path().canonicalize().unwrap();
+        commit_file(&repo_path, "init.txt", "init");
+        fs::write(repo_path.join("notes.txt"), "notes").unwrap();
+        let config = create_glob_config("**/*.log");
+
+        let (exit_code, _, stderr) =
+            run_safe_rm_with_config(&["notes.txt"], &repo_path, Some(config.path()));
+
+        assert_eq!(exit_code, 2, "stderr: {}", stderr);
+        assert!(repo_path.join("notes.txt").exists());
+    }
+
+    #[test]
+    fn test_entry_with_path_and_glob_is_rejected() {
+        let temp_dir = create_test_repo();
+        let repo_path = temp_dir.path().canonicalize().unwrap();
+        let config = tempfile::NamedTempFile::new().unwrap();
+        fs::write(
+            config.path(),
+            "[[allowed_paths]]\npath = \"/tmp\"\nglob = \"*.log\"\n",
+        )
+        .unwrap();
+
+        let (_, stdout, stderr) = run_safe_rm_with_config(
+            &["config", "--list-allowed"],
+            &repo_path,
+            Some(config.path()),
+        );
+        assert!(
+            stderr.contains("exactly one of `path` or `glob`"),
+            "stdout: {}\nstderr: {}",
+            stdout,
+            stderr
+        );
+    }
+}
+
+mod allow_paths_override_git_tests {
+    use super::*;
+
+    /// 厳格モードでリポジトリ全体を allowed_paths に含める設定
+    fn create_config(repo_path: &std::path::Path, extra: &str) -> tempfile::NamedTempFile {
+        let config = tempfile::NamedTempFile::new().unwrap();
+        fs::write(
+            config.path(),
+            format!(
+                "allow_project_deletion = false\n{}\n[[allowed_paths]]\npath = \"{}\"\nrecursive = true\n",
+                extra,
+                repo_path.display()
+            ),
+        )
+        .unwrap();
+        config
+    }
+
+    fn create_dirty_file(repo_path: &std::path::Path) -> std::path::PathBuf {
+        commit_file(repo_path, "dirty.txt", "v1");
+        let dirty = repo_path.join("dirty.txt");
+        fs::write(&dirty, "v2").unwrap();
+        dirty
+    }
+
+    #[test]
+    fn test_repo_wide_allowed_path_bypasses_git_and_warns() {
+        let temp_dir = create_test_repo();
+        let repo_path = temp_dir.path().canonicalize().unwrap();
+        let dirty = create_dirty_file(&repo_path);
+        let config = create_config(&repo_path, "");
+
+        let (exit_code, stdout, stderr) =
+            run_safe_rm_with_config(&["dirty.txt"], &repo_path, Some(config.path()));
+
+        assert_eq!(exit_code, 0, "stderr: {}", stderr);
+        assert!(stdout.contains("allowed by config"), "stdout: {}", stdout);
+        assert!(
+            stderr.contains("covers the entire project"),
+            "stderr: {}",
+            stderr
+        );
+        assert!(!dirty.exists());
+    }
+
+    #[test]
+    fn test_override_disabled_keeps_git_checks_inside_project() {
+        let temp_dir = create_test_repo();
+        let repo_path = temp_dir.path().canonicalize().unwrap();
+        let dirty = create_dirty_file(&repo_path);
+        let config = create_config(&repo_path, "allow_paths_override_git = false");
+
+        let (exit_code, _, stderr) =
+            run_safe_rm_with_config(&["dirty.txt"], &repo_path, Some(config.path()));
+
+        assert_eq!(exit_code, 2, "stderr: {}", stderr);
+        assert!(
+            !stderr.contains("covers the entire project"),
+            "stderr: {}",
+            stderr
+        );
+        assert!(dirty.exists());
+    }
+
+    #[test]
+    fn test_override_disabled_still_allows_paths_outside_project() {
+        let temp_dir = create_test_repo();
+        let repo_path = temp_dir.path().canonicalize().unwrap();
+        commit_file(&repo_path, "init.txt", "init");
+        let outside_dir = TempDir::new().unwrap();
+        let outside_path = outside_dir.path().canonicalize().unwrap();
+        let outside_file = outside_path.join("scratch.txt");
+        fs::write(&outside_file, "scratch").unwrap();
+        let config = tempfile::NamedTempFile::new().unwrap();
+        fs::write(
+            config.path(),
+            format!(
+                "allow_project_deletion = false\nallow_paths_override_git = false\n\n[[allowed_paths]]\npath = \"{}\"\nrecursive = true\n",
+                outside_path.display()
+            ),
+        )
+        .unwrap();
+
+        let (exit_code, stdout, stderr) = run_safe_rm_with_config(
+            &[outside_file.to_str().unwrap()],
+            &repo_path,
+            Some(config.path()),
+        );
+
+        assert_eq!(exit_code, 0, "stderr: {}", stderr);
+        assert!(stdout.contains("allowed by config"), "stdout: {}", stdout);
+        assert!(!outside_file.exists());
+    }
+}
+
+mod count_tests {
+    use super::*;
+
+    fn strict_config() -> tempfile::NamedTempFile {
+        let config = tempfile::NamedTempFile::new().unwrap();
+        fs::write(config.path(), "allow_project_deletion = false\n").unwrap();
+        config
+    }
+
+    #[test]
+    fn test_count_prints_only_eligible_file_count() {
+        let temp_dir = create_test_repo();
+        let repo_path = temp_dir.path();
+        commit_file(repo_path, "a.txt", "a");
+        commit_file(repo_path, "b.txt", "b");
+        commit_file(repo_path, "dir/one.txt", "1");
+        commit_file(repo_path, "dir/sub/two.txt", "2");
+
+        let (exit_code, stdout, _) =
+            run_safe_rm(&["--count", "-r", "a.txt", "b.txt", "dir"], repo_path);
+
+        assert_eq!(exit_code, 0);
+        assert_eq!(stdout, "4\n");
+        // 何も削除しない
+        assert!(repo_path.join("a.txt").exists());
+        assert!(repo_path.join("dir/sub/two.txt").exists());
+    }
+
+    #[test]
+    fn test_count_reports_blocked_paths_to_stderr_without_counting() {
+        let temp_dir = create_test_repo();
+        let repo_path = temp_dir.path();
+        commit_file(repo_path, "a.txt", "a");
+        commit_file(repo_path, "dirty.txt", "v1");
+        fs::write(repo_path.join("dirty.txt"), "v2").unwrap();
+
+        let config = strict_config();
+
+        let (exit_code, stdout, stderr) = run_safe_rm_with_config(
+            &["--count", "a.txt", "dirty.txt", "/etc/passwd"],
+            repo_path,
+            Some(config.path()),
+        );
+
+        assert_eq!(exit_code, 0);
+        assert_eq!(stdout, "1\n");
+        assert!(stderr.contains("dirty.txt"), "stderr: {}", stderr);
+        assert!(stderr.contains("/etc/passwd"), "stderr: {}", stderr);
+        assert!(repo_path.join("dirty.txt").exists());
+    }
+}
+
+mod trailing_slash_tests {
+    use super::*;
+
+    #[test]
+    fn test_trailing_slash_on_file_is_not_a_directory() {
+        let temp_dir = create_test_repo();
+        let repo_path = temp_dir.path();
+        commit_file(repo_path, "foo", "file");
+
+        let (exit_code, _, stderr) = run_safe_rm(&["-r", "foo/"], repo_path);
+
+        assert_eq!(exit_code, 1, "stderr: {}", stderr);
+        assert!(stderr.contains("Not a directory"), "stderr: {}", stderr);
+        assert!(repo_path.join("foo").exists());
+    }
+
+    #[test]
+    fn test_trailing_slash_on_file_json_code() {
+        let temp_dir = create_test_repo();
+        let repo_path = temp_dir.path();
+        commit_file(repo_path, "foo", "file");
+
+        let (exit_code, _, stderr) = run_safe_rm(&["--format", "json", "foo/"], repo_path);
+
+        assert_eq!(exit_code, 1, "stderr: {}", stderr);
+        assert!(
+            stderr.contains(r#""code":"not_a_directory""#),
+            "stderr: {}",
+            stderr
+        );
+        assert!(repo_path.join("foo").exists());
+    }
+
+    #[test]
+    fn test_trailing_slash_on_directory_follows_directory_rules() {
+        let temp_dir = create_test_repo();
+        let repo_path = temp_dir.path();
+        commit_file(repo_path, "foo/a.txt", "a");
+
+        // -r なしは通常どおり IsDirectory
+        let (exit_code, _, stderr) = run_safe_rm(&["foo/"], repo_path);
+        assert_eq!(exit_code, 1, "stderr: {}", stderr);
+        assert!(stderr.contains("Is a directory"), "stderr: {}", stderr);
+
+        let (exit_code, _, stderr) = run_safe_rm(&["-r", "foo/"], repo_path);
+        assert_eq!(exit_code, 0, "stderr: {}", stderr);
+        assert!(!repo_path.join("foo").exists());
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn test_trailing_slash_on_directory_symlink_is_refused() {
+        let temp_dir = create_test_repo();
+        let repo_path = temp_dir.path();
+        commit_file(repo_path, "target/a.txt", "a");
+        std::os::unix::fs::symlink("target", repo_path.join("alias")).unwrap();
+
+        let (exit_code, stdout, stderr) = run_safe_rm(&["-r", "alias/"], repo_path);
+        assert_eq!(exit_code, 2, "stderr: {}", stderr);
+        assert!(!stdout.contains("removed"), "stdout: {}", stdout);
+        assert!(repo_path.join("target/a.txt").exists());
+        assert!(repo_path.join("alias").exists());
+
+        // `/` なしならリンク自体を削除し、リンク先は残る
+        let (exit_code, _, stderr) = run_safe_rm(&["alias"], repo_path);
+        assert_eq!(exit_code, 0, "stderr: {}", stderr);
+        assert!(!repo_path.join("alias").exists());
+        assert!(repo_path.join("target/a.txt").exists());
+    }
+}
+
+mod self_config_tests {
+    use super::*;
+
+    /// リポジトリ内の設定ディレクトリに設定ファイルを置く
+    fn write_config(repo_path: &std::path::Path) -> std::path::PathBuf {
+        let config = repo_path.join("conf/safe-rm.toml");
+        commit_file(
+            repo_path,
+            "conf/safe-rm.toml",
+            "allow_project_deletion = true\n",
+        );
+        config
+    }
+
+    #[test]
+    fn test_own_config_file_is_refused() {
+        let temp_dir = create_test_repo();
+        let repo_path = temp_dir.path();
+        let config = write_config(repo_path);
+
+        let (exit_code, _, stderr) =
+            run_safe_rm_with_config(&["conf/safe-rm.toml"], repo_path, Some(config.as_path()));
+        assert_eq!(exit_code, 2, "stderr: {}", stderr);
+        assert!(stderr.contains("--allow-self-config"), "stderr: {}", stderr);
+        assert!(config.exists());
+
+        // 設定ファイルを含むディレクトリも同様
+        let (exit_code, _, stderr) =
+            run_safe_rm_with_config(&["-r", "conf"], repo_path, Some(config.as_path()));
+        assert_eq!(exit_code, 2, "stderr: {}", stderr);
+        assert!(config.exists());
+
+        // -f だけでは許可しない
+        let (exit_code, _, stderr) = run_safe_rm_with_config(
+            &["-f", "conf/safe-rm.toml"],
+            repo_path,
+            Some(config.as_path()),
+        );
+        assert_eq!(exit_code, 2, "stderr: {}", stderr);
+        assert!(config.exists());
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn test_every_loaded_config_layer_is_refused() {
+        let temp_dir = create_test_repo();
+        let repo_path = temp_dir.path();
+        let config = write_config(repo_path);
+        commit_file(repo_path, "conf/base.toml", "");
+        let base = repo_path.join("conf/base.toml");
+        let layers = format!("{}:{}", base.display(), config.display());
+
+        let (exit_code, _, stderr) = run_safe_rm_with_config(
+            &["conf/base.toml"],
+            repo_path,
+            Some(std::path::Path::new(&layers)),
+        );
+        assert_eq!(exit_code, 2, "stderr: {}", stderr);
+        assert!(base.exists());
+    }
+
+    #[test]
+    fn test_config_flag_file_is_refused() {
+        let temp_dir = create_test_repo();
+        let repo_path = temp_dir.path();
+        let config = write_config(repo_path);
+
+        let (exit_code, _, stderr) = run_safe_rm(
+            &["--config", "conf/safe-rm.toml", "conf/safe-rm.toml"],
+            repo_path,
+        );
+        assert_eq!(exit_code, 2, "stderr: {}", stderr);
+        assert!(stderr.contains("--allow-self-config"), "stderr: {}", stderr);
+        assert!(config.exists());
+    }
+
+    #[test]
+    fn test_own_config_file_with_force_and_flag_is_removed() {
+        let temp_dir = create_test_repo();
+        let repo_path = temp_dir.path();
+        let config = write_config(repo_path);
+
+        let (exit_code, _, stderr) = run_safe_rm_with_config(
+            &["-f", "--allow-self-config", "conf/safe-rm.toml"],
+            repo_path,
+            Some(config.as_path()),
+        );
+        assert_eq!(exit_code, 0, "stderr: {}", stderr);
+        assert!(!config.exists());
+    }
+}
+
+mod blocked_summary_tests {
+    use super::*;
+
+    fn write_config(content: &str) -> tempfile::NamedTempFile {
+        let config = tempfile::NamedTempFile::new().unwrap();
+        fs::write(config.path(), content).unwrap();
+        config
+    }
+
+    /// 未コミット変更のファイル2件とクリーンなファイル1件
+    fn setup(repo_path: &std::path::Path) {
+        commit_file(repo_path, "clean.txt", "clean");
+        commit_file(repo_path, "a.txt", "v1");
+        commit_file(repo_path, "b.txt", "v1");
+        fs::write(repo_path.join("a.txt"), "v2").unwrap();
+        fs::write(repo_path.join("b.txt"), "v2").unwrap();
+    }
+
+    #[test]
+    fn test_strict_mode_prints_breakdown() {
+        let temp_dir = create_test_repo();
+        let repo_path = temp_dir.path();
+        setup(repo_path);
+        let config = write_config("allow_project_deletion = false\n");
+
+        let (exit_code, _, stderr) = run_safe_rm_with_config(
+            &["clean.txt", "a.txt", "b.txt", "/etc/passwd"],
+            repo_path,
+            Some(config.path()),
+        );
+
+        assert_eq!(exit_code, 2, "stderr: {}", stderr);
+        assert!(
+            stderr.contains("safe-rm: blocked by reason: dirty_files=2, outside_project=1"),
+            "stderr: {}",
+            stderr
+        );
+        assert!(!repo_path.join("clean.txt").exists());
+    }
+
+    #[test]
+    fn test_default_mode_prints_breakdown_only_with_flag() {
+        let temp_dir = create_test_repo();
+        let repo_path = temp_dir.path();
+        commit_file(repo_path, "a.txt", "a");
+        let config = write_config("allow_project_deletion = true\n");
+
+        let (exit_code, _, stderr) =
+            run_safe_rm_with_config(&["a.txt", "/etc/passwd"], repo_path, Some(config.path()));
+        assert_eq!(exit_code, 2, "stderr: {}", stderr);
+        assert!(!stderr.contains("blocked by reason"), "stderr: {}", stderr);
+
+        let (exit_code, _, stderr) = run_safe_rm_with_config(
+            &["--summary", "/etc/passwd", "/etc/hosts"],
+            repo_path,
+            Some(config.path()),
+        );
+        assert_eq!(exit_code, 2, "stderr: {}", stderr);
+        assert!(
+            stderr.contains("safe-rm: blocked by reason: outside_project=2"),
+            "stderr: {}",
+            stderr
+        );
+    }
+
+    #[test]
+    fn test_breakdown_json() {
+        let temp_dir = create_test_repo();
+        let repo_path = temp_dir.path();
+        setup(repo_path);
+        let config = write_config("allow_project_deletion = false\n");
+
+        let (_, _, stderr) = run_safe_rm_with_config(
+            &["--format", "json", "a.txt", "b.txt"],
+            repo_path,
+            Some(config.path()),
+        );
+
+        assert!(
+            stderr.contains(r#"{"blocked":{"dirty_files":2},"type":"blocked_summary"}"#),
+            "stderr: {}",
+            stderr
+        );
+    }
+
+    #[test]
+    fn test_no_breakdown_without_blocks() {
+        let temp_dir = create_test_repo();
+        let repo_path = temp_dir.path();
+        commit_file(repo_path, "a.txt", "a");
+        let config = write_config("allow_project_deletion = false\n");
+
+        let (exit_code, _, stderr) =
+            run_safe_rm_with_config(&["--summary", "a.txt"], repo_path, Some(config.path()));
+        assert_eq!(exit_code, 0, "stderr: {}", stderr);
+        assert!(!stderr.contains("blocked by reason"), "stderr: {}", stderr);
+    }
+}
+
+mod partial_dir_tests {
+    use super::*;
+
+    fn strict_config() -> tempfile::NamedTempFile {
+        let config = tempfile::NamedTempFile::new().unwrap();
+        fs::write(config.path(), "allow_project_deletion = false\n").unwrap();
+        config
+    }
+
+    /// クリーンなファイル2件、変更済みファイル1件、未追跡ファイル1件を含む dir
+    fn setup(repo_path: &std::path::Path) {
+        commit_file(repo_path, "dir/clean.txt", "clean");
+        commit_file(repo_path, "dir/sub/ok.txt", "ok");
+        commit_file(repo_path, "dir/sub/dirty.txt", "v1");
+        commit_file(repo_path, "dir/other/a.txt", "a");
+        fs::write(repo_path.join("dir/sub/dirty.txt"), "v2").unwrap();
+        fs::write(repo_path.join("dir/other/new.txt"), "new").unwrap();
+    }
+
+    #[test]
+    fn test_partial_dir_removes_clean_subset_and_keeps_dirty() {
+        let temp_dir = create_test_repo();
+        let repo_path = temp_dir.path();
+        setup(repo_path);
+        let config = strict_config();
+
+        let (exit_code, stdout, stderr) = run_safe_rm_with_config(
+            &["-r", "--partial-dir", "dir"],
+            repo_path,
+            Some(config.path()),
+        );
+
+        assert_eq!(exit_code, 0, "stderr: {}", stderr);
+        assert!(
+            stdout.contains("skipped: dir/sub/dirty.txt (Modified)"),
+            "stdout: {}",
+            stdout
+        );
+        assert!(
+            stdout.contains("skipped: dir/other/new.txt (Untracked)"),
+            "stdout: {}",
+            stdout
+        );
+        assert!(repo_path.join("dir/sub/dirty.txt").exists());
+        assert!(repo_path.join("dir/other/new.txt").exists());
+        assert!(!repo_path.join("dir/clean.txt").exists());
+        assert!(!repo_path.join("dir/sub/ok.txt").exists());
+        assert!(!repo_path.join("dir/other/a.txt").exists());
+    }
+
+    #[test]
+    fn test_without_partial_dir_whole_directory_is_blocked() {
+        let temp_dir = create_test_repo();
+        let repo_path = temp_dir.path();
+        setup(repo_path);
+        let config = strict_config();
+
+        let (exit_code, _, stderr) =
+            run_safe_rm_with_config(&["-r", "dir"], repo_path, Some(config.path()));
+
+        assert_eq!(exit_code, 2, "stderr: {}", stderr);
+        assert!(repo_path.join("dir/clean.txt").exists());
+    }
+
+    #[test]
+    fn test_partial_dir_dry_run_counts_only_eligible_files() {
+        let temp_dir = create_test_repo();
+        let repo_path = temp_dir.path();
+        setup(repo_path);
+        let config = strict_config();
+
+        let (exit_code, stdout, stderr) = run_safe_rm_with_config(
+            &["-n", "-r", "--partial-dir", "dir"],
+            repo_path,
+            Some(config.path()),
+        );
+        assert_eq!(exit_code, 0, "stderr: {}", stderr);
+        assert!(stdout.contains("would remove: dir"), "stdout: {}", stdout);
+        assert!(
+            stdout.contains("total: would remove 3 files"),
+            "stdout: {}",
+            stdout
+        );
+        assert!(repo_path.join("dir/clean.txt").exists());
+
+        let (exit_code, stdout, _) = run_safe_rm_with_config(
+            &["--count", "-r", "--partial-dir", "dir"],
+            repo_path,
+            Some(config.path()),
+        );
+        assert_eq!(exit_code, 0);
+        assert_eq!(stdout, "3\n");
+    }
+
+    #[test]
+    fn test_partial_dir_requires_recursive() {
+        let temp_dir = create_test_repo();
+        let repo_path = temp_dir.path();
+
+        let (exit_code, _, _) = run_safe_rm(&["--partial-dir", "dir"], repo_path);
+        assert_eq!(exit_code, 2);
+    }
+}
+
+mod policy_precedence_tests {
+    use super::*;
+
+    fn write_config(content: &str) -> tempfile::NamedTempFile {
+        let config = tempfile::NamedTempFile::new().unwrap();
+        fs::write(config.path(), content).unwrap();
+        config
+    }
+
+    #[test]
+    fn test_protected_beats_allowed_path() {
+        let temp_dir = create_test_repo();
+        let repo_path = temp_dir.path();
+        fs::create_dir_all(repo_path.join("cache")).unwrap();
+        fs::write(repo_path.join("cache/key.pem"), "secret").unwrap();
+        fs::write(repo_path.join("cache/data.bin"), "data").unwrap();
+        let config = write_config(&format!(
+            "protected_patterns = [\"*.pem\"]\n[[allowed_paths]]\npath = \"{}\"\n",
+            repo_path.join("cache").display()
+        ));
+
+        let (exit_code, _, stderr) =
+            run_safe_rm_with_config(&["cache/key.pem"], repo_path, Some(config.path()));
+        assert_eq!(exit_code, 2, "stderr: {}", stderr);
+        assert!(stderr.contains("*.pem"), "stderr: {}", stderr);
+        assert!(repo_path.join("cache/key.pem").exists());
+
+        // 保護対象を含まない許可パスは従来どおり削除できる
+        let (exit_code, _, stderr) =
+            run_safe_rm_with_config(&["cache/data.bin"], repo_path, Some(config.path()));
+        assert_eq!(exit_code, 0, "stderr: {}", stderr);
+        assert!(!repo_path.join("cache/data.bin").exists());
+    }
+
+    #[test]
+    fn test_denied_path_beats_allowed_path() {
+        let temp_dir = create_test_repo();
+        let repo_path = temp_dir.path();
+        fs::create_dir_all(repo_path.join("cache/keep")).unwrap();
+        fs::write(repo_path.join("cache/keep/a.bin"), "a").unwrap();
+        let config = write_config(&format!(
+            "denied_paths = [\"cache/keep\"]\n[[allowed_paths]]\npath = \"{}\"\n",
+            repo_path.join("cache").display()
+        ));
+
+        let (exit_code, _, stderr) = run_safe_rm_with_config(
+            &["--format", "json", "cache/keep/a.bin"],
+            repo_path,
+            Some(config.path()),
+        );
+        assert_eq!(exit_code, 2, "stderr: {}", stderr);
+        assert!(
+            stderr.contains(r#""code":"denied_path""#),
+            "stderr: {}",
+            stderr
+        );
+
+        // 拒否パスを含むディレクトリも削除できない
+        let (exit_code, _, stderr) =
+            run_safe_rm_with_config(&["-r", "cache"], repo_path, Some(config.path()));
+        assert_eq!(exit_code, 2, "stderr: {}", stderr);
+        assert!(repo_path.join("cache/keep/a.bin").exists());
+    }
+
+    #[test]
+    fn test_denied_path_in_default_mode() {
+        let temp_dir = create_test_repo();
+        let repo_path = temp_dir.path();
+        commit_file(repo_path, "vendor/lib.rs", "lib");
+        commit_file(repo_path, "src/main.rs", "main");
+        let config = write_config("denied_paths = \"vendor\"\n");
+
+        let (exit_code, _, stderr) =
+            run_safe_rm_with_config(&["vendor/lib.rs"], repo_path, Some(config.path()));
+        assert_eq!(exit_code, 2, "stderr: {}", stderr);
+        assert!(stderr.contains("Denied: vendor"), "stderr: {}", stderr);
+        assert!(repo_path.join("vendor/lib.rs").exists());
+
+        let (exit_code, _, stderr) =
+            run_safe_rm_with_config(&["src/main.rs"], repo_path, Some(config.path()));
+        assert_eq!(exit_code, 0, "stderr: {}", stderr);
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn test_denied_path_blocked_through_symlinked_parent() {
+        let temp_dir = create_test_repo();
+        let repo_path = temp_dir.path();
+        fs::create_dir_all(repo_path.join("secrets")).unwrap();
+        fs::write(repo_path.join("secrets/key"), "secret").unwrap();
+        std::os::unix::fs::symlink("secrets", repo_path.join("alias")).unwrap();
+        let config = write_config("denied_paths = [\"secrets\"]\n");
+
+        let (exit_code, _, stderr) =
+            run_safe_rm_with_config(&["alias/key"], repo_path, Some(config.path()));
+        assert_eq!(exit_code, 2, "stderr: {}", stderr);
+        assert!(stderr.contains("Denied: secrets"), "stderr: {}", stderr);
+        assert!(repo_path.join("secrets/key").exists());
+
+        // 末尾の `/` はリンク先を指すため、リンク先として照合する
+        let (exit_code, _, stderr) =
+            run_safe_rm_with_config(&["-r", "alias/"], repo_path, Some(config.path()));
+        assert_eq!(exit_code, 2, "stderr: {}", stderr);
+        assert!(stderr.contains("Denied: secrets"), "stderr: {}", stderr);
+        assert!(repo_path.join("secrets/key").exists());
+
+        // リンク自体の削除はリンク先に影響しないため拒否しない
+        let (exit_code, _, stderr) =
+            run_safe_rm_with_config(&["alias"], repo_path, Some(config.path()));
+        assert_eq!(exit_code, 0, "stderr: {}", stderr);
+        assert!(repo_path.join("secrets/key").exists());
+    }
+}
+
+mod version_tests {
+    use super::*;
+
+    #[test]
+    fn test_version_json_shape() {
+        let temp_dir = TempDir::new().unwrap();
+
+        let (exit_code, stdout, stderr) = run_safe_rm(&["version", "--json"], temp_dir.path());
+
+        assert_eq!(exit_code, 0, "stderr: {}", stderr);
+        assert_eq!(stdout.lines().count(), 1, "stdout: {}", stdout);
+        let info: serde_json::Value = serde_json::from_str(&stdout).unwrap();
+        assert_eq!(info["version"], env!("CARGO_PKG_VERSION"));
+        assert!(info["git2"].as_str().is_some_and(|v| !v.is_empty()));
+        assert!(
+            info["rustc"]
+                .as_str()
+                .is_some_and(|v| v.chars().next().is_some_and(|c| c.is_ascii_digit())),
+            "rustc: {}",
+            info["rustc"]
+        );
+        let features = info["features"].as_array().unwrap();
+        assert_eq!(
+            features.iter().any(|f| f == "logging"),
+            cfg!(feature = "logging")
+        );
+    }
+
+    #[test]
+    fn test_version_human_output() {
+        let temp_dir = TempDir::new().unwrap();
+
+        let (exit_code, stdout, _) = run_safe_rm(&["version"], temp_dir.path());
+
+        assert_eq!(exit_code, 0);
+        assert!(
+            stdout.starts_with(&format!("safe-rm {}\n", env!("CARGO_PKG_VERSION"))),
+            "stdout: {}",
+            stdout
+        );
+        assert!(stdout.contains("libgit2: "), "stdout: {}", stdout);
+        assert!(stdout.contains("rustc: "), "stdout: {}", stdout);
+    }
+}
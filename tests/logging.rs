@@ -0,0 +1,98 @@
+//! `logging` フィーチャの記録（`cargo test --features logging`）
+//!
+//! ロガーはプロセス全体で1つのため、このファイルは統合テストとは別バイナリにしている。
+
+#![cfg(feature = "logging")]
+
+use safe_rm::config::Config;
+use safe_rm::{DeleteOptions, safe_delete};
+use std::fs;
+use std::process::Command;
+use std::sync::Mutex;
+
+/// 受け取ったレコードを `LEVEL target: message` の形式で保持するテスト用ロガー
+struct Recorder(Mutex<Vec<String>>);
+
+impl log::Log for Recorder {
+    fn enabled(&self, _: &log::Metadata) -> bool {
+        true
+    }
+
+    fn log(&self, record: &log::Record) {
+        self.0.lock().unwrap().push(format!(
+            "{} {}: {}",
+            record.level(),
+            record.target(),
+            record.args()
+        ));
+    }
+
+    fn flush(&self) {}
+}
+
+static RECORDER: Recorder = Recorder(Mutex::new(Vec::new()));
+
+fn git(repo_path: &std::path::Path, args: &[&str]) {
+    let output = Command::new("git")
+        .args(args)
+        .current_dir(repo_path)
+        .output()
+        .unwrap();
+    assert!(output.status.success(), "git {:?}", args);
+}
+
+#[test]
+fn test_key_decisions_are_logged() {
+    log::set_logger(&RECORDER).unwrap();
+    log::set_max_level(log::LevelFilter::Trace);
+
+    let temp_dir = tempfile::tempdir().unwrap();
+    let repo_path = temp_dir.path().canonicalize().unwrap();
+    git(&repo_path, &["init", "-q"]);
+    git(&repo_path, &["config", "user.email", "test@example.com"]);
+    git(&repo_path, &["config", "user.name", "Test"]);
+    fs::write(repo_path.join("clean.txt"), "clean").unwrap();
+    fs::write(repo_path.join("dirty.txt"), "v1").unwrap();
+    git(&repo_path, &["add", "."]);
+    git(&repo_path, &["commit", "-q", "-m", "init"]);
+    fs::write(repo_path.join("dirty.txt"), "v2").unwrap();
+
+    let mut config = Config::default();
+    config.allow_project_deletion = false;
+    let report = safe_delete(
+        &repo_path,
+        &["clean.txt".into(), "dirty.txt".into()],
+        &config,
+        &DeleteOptions::default(),
+    );
+    assert_eq!(report.deleted.len(), 1);
+    assert_eq!(report.blocked.len(), 1);
+
+    let records = RECORDER.0.lock().unwrap();
+    let has = |expected: &str| records.iter().any(|record| record.starts_with(expected));
+    assert!(
+        has("DEBUG safe_rm::git_checker: repository discovered from"),
+        "{:#?}",
+        records
+    );
+    assert!(
+        has("DEBUG safe_rm::git_checker: status cache built:"),
+        "{:#?}",
+        records
+    );
+    assert!(
+        has("TRACE safe_rm::git_checker: status cache miss:"),
+        "{:#?}",
+        records
+    );
+    assert!(
+        has("DEBUG safe_rm::delete: clean.txt: deleted"),
+        "{:#?}",
+        records
+    );
+    assert!(
+        has("DEBUG safe_rm::delete: dirty.txt: blocked (dirty_files)"),
+        "{:#?}",
+        records
+    );
+}